@@ -0,0 +1,361 @@
+# German texts of Sand Drop Clicker
+# each line is a `key = text` pair, `{ $name }` is replaced with a value
+
+## Options window
+options-title = Optionen
+options-settings = ⚙ Einstellungen
+options-guide = Sandführer
+options-history = Verlauf
+options-hint = Klicke auf den Knopf, um Geld zu verdienen!
+hardcore-badge = ☠ Hardcore
+ng-plus = Neues Spiel+ { $level }
+ng-plus-tooltip = Beginne die Verbesserungen und das Geld von vorn. Der Sand bringt 4x so viel ein, und die Verbesserungen kosten 5x so viel.
+ng-plus-started = Neues Spiel+ { $level } gestartet!
+sandbox-badge = 🎨 Sandkasten
+options-convert = Verkaufen ({ $key })
+container-full-hint = Behälter voll, drücke { $key }, um deinen Sand zu verkaufen
+options-money = Geld: { $money }$
+contents-title = Behälterinhalt
+contents-empty = Der Behälter ist leer.
+contents-total = Summe: { $money }$
+column-sand = Sand
+column-count = Anzahl
+column-value = Wert
+column-subtotal = Zwischensumme
+column-in-container = Im Behälter
+column-dropped = Fallen gelassen
+column-sold = Verkauft
+upgrades-none = Noch keine Verbesserungen verfügbar. Klick weiter!
+upgrades-available = Verfügbare Verbesserungen:
+upgrades-unlocked = Neue Verbesserung verfügbar: { $upgrade }!
+upgrade-button = { $upgrade } ({ $level }): { $cost }$
+upgrade-button-max = { $upgrade } ({ $level }): (MAXIMALSTUFE)
+upgrade-cost = Kosten: { $cost }$
+upgrade-eta = noch etwa { $time }
+upgrade-payback = Hat sich bei den aktuellen Raten in etwa { $time } bezahlt gemacht
+upgrade-payback-unknown = Verdiene etwas Geld, um zu sehen, wann es sich bezahlt macht
+upgrade-cant-afford = Nicht genug Geld für { $upgrade } ({ $cost }$)
+upgrade-shortcuts = Shift+Klick: { $count } für { $cost }$ kaufen, Strg+Klick: so viele wie möglich
+upgrade-bought = { $count } Stufen { $upgrade } gekauft
+upgrade-bought-partial = { $count } von { $wanted } Stufen { $upgrade } gekauft
+themes-title = Designs
+themes-use = { $theme } verwenden
+themes-buy = { $theme } kaufen: { $cost }$
+
+## Upgrades
+upgrade-bigger-container = Größerer Behälter
+upgrade-bigger-container-button = Größeren Behälter kaufen
+upgrade-bigger-container-desc = Damit wird dein Behälter größer:
+upgrade-particle-tier = Sandqualität
+upgrade-particle-tier-button = Sandqualität verbessern
+upgrade-particle-tier-desc = Damit fällt mit etwas Glück besserer Sand:
+upgrade-auto-clicker = Autoklicker
+upgrade-auto-clicker-button = Autoklicker kaufen
+upgrade-auto-clicker-desc = Damit fällt der Sand von selbst:
+upgrade-more-particles = Mehr Körner
+upgrade-more-particles-button = Mehr Körner kaufen
+upgrade-more-particles-desc = Damit fällt mehr Sand pro Klick:
+upgrade-sculptor = Formwerkzeug
+upgrade-sculptor-button = Formwerkzeug verbessern
+upgrade-sculptor-desc = Damit schiebst (Shift) und ziehst (Strg) du mehr Sand:
+effect-container = Behälter
+effect-best-sand = Bester Sand
+effect-none = keiner
+effect-autoclicker = Autoklicker
+effect-off = aus
+effect-every = alle { $seconds }s
+effect-grains = Körner pro Klick
+effect-sculpt-radius = Formradius
+effect-summary = { $effect }: { $current } → { $next }
+effect-summary-max = { $effect }: { $current } (max)
+category-production = Produktion
+category-economy = Wirtschaft
+category-container = Behälter
+
+## Pause menu and quit dialog
+pause-title = Pausiert
+pause-resume = Weiter
+pause-settings = Einstellungen
+pause-challenges = Herausforderungen
+pause-blitz = Blitz
+pause-blitz-tooltip = Verdiene in 5 Minuten so viel wie möglich mit günstigeren Upgrades, das Hauptspiel bleibt unberührt
+pause-daily = Tageslauf
+pause-daily-tooltip = Spiele 10 Minuten nach den Regeln des Tages, die für alle gleich sind, die heute spielen
+pause-save = Speichern
+pause-quit = Beenden
+pause-saved = Spiel gespeichert
+pause-save-failed = Das Spiel konnte nicht gespeichert werden: { $error }
+quit-title = Beenden?
+quit-unsold = Du hast { $amount } unverkaufte Körner im Wert von { $money }$
+quit-sell = Verkaufen & Beenden
+quit-quit = Beenden
+quit-cancel = Abbrechen
+
+## Settings window
+settings-title = Einstellungen
+settings-display = Anzeige
+settings-player-info = Spielerinfo ({ $key })
+settings-trails = Bewegungsspuren
+settings-colorblind = Farbenblinde Palette
+settings-floating-text = Schwebender Geldtext
+settings-full-numbers = Ganze Zahlen anzeigen
+settings-gauge = Füllstandsanzeige
+settings-screen-shake = Bildschirmwackeln
+settings-flash = Bildschirmblitz
+settings-muted = Ganzen Ton aus ({ $key })
+settings-music-muted = Musik aus
+settings-music-volume = Musiklautstärke
+settings-interface = Oberfläche
+settings-language = Sprache
+settings-ui-scale = Oberflächengröße
+settings-scale-grains = Neue Körner skalieren
+settings-speedrun = Speedrun-Timer
+settings-speedrun-hint = Miss das Spiel in Echtzeit mit Zwischenzeiten, beim Einschalten beginnt ein neuer Lauf
+settings-background = Im Hintergrund
+background-pause = Pausieren
+background-pause-hint = Das Spiel hält an, bis das Fenster zurück ist
+background-idle = Leerlauf
+background-idle-hint = Der Autoklicker verdient weiter Geld, aber es fällt kein Sand
+background-run = Weiterlaufen
+background-run-hint = Der Sand fällt weiter, ein minimiertes Fenster läuft nur im Leerlauf
+settings-fps-cap = Bildratenlimit
+settings-fps-unlimited = Unbegrenzt
+settings-fps = { $fps } FPS
+settings-max-grains = Limit aktiver Körner
+settings-max-grains-unlimited = Unbegrenzt
+settings-max-grains-hint = Über dem Limit werden die ältesten liegenden Körner unten im Haufen zusammengepresst. Sie füllen weiter den Behälter und werden normal verkauft.
+settings-theme = Design
+settings-time-of-day = Tageszeit
+settings-time-cycle = Zyklus
+settings-controls = Steuerung
+settings-press-key = Drücke eine Taste...
+settings-conflict = ⚠ Konflikt
+settings-conflict-hint = Eine andere Aktion nutzt dieselbe Taste.
+settings-default-keys = Standardtasten wiederherstellen
+settings-mouse = Maustasten
+settings-mouse-hint = Eine Taste lässt immer Sand fallen.
+settings-swap-buttons = Linke und rechte Taste tauschen
+mouse-left = Linke Taste
+mouse-right = Rechte Taste
+mouse-middle = Mittlere Taste
+mouse-other = Andere Taste
+mouse-action-drop = Sand fallen lassen
+mouse-action-vacuum = Aufsaugen
+mouse-action-pan = Kamera verschieben
+mouse-action-nothing = Nichts
+settings-reset = Fortschritt zurücksetzen
+settings-reset-hint = Gib RESET ein, um dein Geld, deinen Sand und deine Verbesserungen zu löschen.
+settings-reset-button = Fortschritt zurücksetzen
+settings-reset-profile = Neues Spiel:
+key-none = Keine
+
+## Other windows
+guide-title = Sandführer
+stats-title = Statistiken
+stats-time-played = Spielzeit
+stats-clicks = Klicks
+stats-money-earned = Verdientes Geld
+stats-money-spent = Ausgegebenes Geld
+stats-grains-clicked = Geklickte Körner
+stats-grains-autoclicked = Automatische Körner
+stats-best-sale = Bester Verkauf
+stats-average-sale = Durchschnittlicher Verkauf
+stats-average-fill = Durchschnittliche Füllung beim Verkauf
+stats-decayed = Durch Zerfall verlorene Körner
+stats-last-sales = Letzte Verkäufe
+stats-no-sales = Noch keine Verkäufe
+stats-new-record = Neuer Rekordverkauf: { $money }$!
+stats-last-minute = Letzte Minute
+stats-per-minute = { $money }$/min
+income-per-second = ≈ { $money }$/s (letzte Minute)
+grain-value = ≈ { $money }$ pro fallendem Korn
+grain-value-tooltip = Was ein Korn mit dem freigeschalteten Sand im Schnitt einbringt, ohne die Combo
+stats-dropped = { $sand } fallen gelassen
+stats-sold = { $sand } verkauft
+stats-session = Diese Sitzung
+stats-lifetime = Gesamt
+stats-session-reset = Sitzung zurücksetzen
+stats-session-reset-tooltip = Zähle diese Sitzung ab null, die Gesamtstatistik und Erfolge bleiben erhalten
+stats-blitz-scores = Blitz-Bestenliste
+stats-daily = Tagesläufe
+stats-income-graph = Geld pro Minute, letzte 10 Minuten
+stats-graph-point = { $time }: { $money }$/min
+stats-graph-paused = Pausiert
+stats-heatmap = Klick-Heatmap zeigen
+stats-heatmap-reset = Heatmap zurücksetzen
+stats-export = Statistik exportieren
+stats-export-tooltip = Speichere eine Tabelle mit Geld, Körnern, Verkäufen und Verbesserungen für jede gespielte Minute
+stats-exported = Statistik nach { $path } exportiert
+stats-export-failed = Die Statistik konnte nicht exportiert werden: { $error }
+achievements-title = Erfolge
+achievements-earned-at = Erreicht nach { $time } Spielzeit
+achievements-earned = Erfolg erreicht: { $achievement }
+history-title = Verlauf
+history-empty = Noch nichts gekauft oder verkauft.
+history-clear = Leeren
+history-purchase = { $upgrade } Stufe { $level } für { $cost }$ gekauft
+history-sale = Sand für { $money }$ verkauft
+notifications-more = +{ $count } weitere
+
+## Overlays and messages
+low-gravity = NIEDRIGE SCHWERKRAFT! { $seconds }s
+player-info = Spielzeit: { $time }\nKlicks: { $clicks }
+player-ng-plus = Neues Spiel+: { $level }
+audio-muted = Ton: aus
+audio-on = Ton: an, Musik auf { $volume }%
+screenshot-saved = { $path } gespeichert
+screenshot-dir-failed = { $dir } konnte nicht erstellt werden: { $error }
+screenshot-capture-failed = Der Bildschirm konnte nicht aufgenommen werden: { $error }
+screenshot-save-failed = Der Screenshot konnte nicht gespeichert werden: { $error }
+screenshot-failed = Der Screenshot konnte nicht gespeichert werden
+save-load-failed = Der Spielstand konnte nicht gelesen werden, ein neues Spiel beginnt: { $error }
+settings-save-failed = Die Einstellungen konnten nicht gespeichert werden: { $error }
+settings-load-failed = Die Einstellungen konnten nicht gelesen werden, die Standardwerte werden verwendet: { $error }
+fullscreen-failed = Der Vollbildmodus konnte nicht aktiviert werden: { $error }
+combo = Kombo x{ $count } (+{ $bonus }%)
+
+## Tutorial
+tutorial-title = Willkommen!
+tutorial-drop-sand = Klicke irgendwo in das Spielfeld, um Sand fallen zu lassen.
+tutorial-convert = Verkaufe deinen Sand mit dem Verkaufen-Knopf im Optionen-Fenster.
+tutorial-upgrade = Kaufe deine erste Verbesserung, sobald du sie dir leisten kannst.
+tutorial-info = Tipp: Drücke { $key }, um deine Spielzeit und Klicks zu sehen.
+tutorial-skip = Tutorial überspringen
+
+## Replays
+replay-title = WIEDERGABE
+replay-progress = { $time } von { $total }
+replay-matched = Die Wiedergabe endete genauso wie die Aufnahme.
+replay-diverged = Die Wiedergabe endete anders als die Aufnahme!
+
+## Developer console
+console-title = Konsole
+
+## Speedrun
+speedrun-title = Speedrun
+speedrun-split = { $split } in { $time }
+speedrun-split-delta = { $split } in { $time } ({ $delta })
+speedrun-skipped = Vor diesem Lauf erreicht
+speedrun-best = Persönliche Bestzeit
+split-first-quartz = Erster Quarz
+split-first-autoclicker = Erster AutoClicker
+split-millionaire = 1M$ verdient
+split-all-maxed = Alle Verbesserungen maximal
+
+## Achievements
+achievement-first-grain = Erstes Korn
+achievement-first-grain-desc = Lass dein erstes Sandkorn fallen.
+achievement-busy-fingers = Fleißige Finger
+achievement-busy-fingers-desc = Klicke 1000 Mal.
+achievement-pocket-money = Taschengeld
+achievement-pocket-money-desc = Verdiene insgesamt 1000$.
+achievement-millionaire = Millionär
+achievement-millionaire-desc = Verdiene insgesamt 1000000$.
+achievement-full-house = Volles Haus
+achievement-full-house-desc = Fülle den Behälter bis zum Rand.
+achievement-automation = Automatisierung
+achievement-automation-desc = Kaufe einen Autoklicker.
+achievement-diamond-hands = Diamanthände
+achievement-diamond-hands-desc = Lass ein Korn Diamantsand fallen.
+achievement-gold-rush = Goldrausch
+achievement-gold-rush-desc = Verkaufe 100 Körner Goldsand.
+achievement-record-breaker = Rekordbrecher
+achievement-record-breaker-desc = Übertriff deinen besten Verkauf 10 Mal.
+achievement-dedicated = Ausdauernd
+achievement-dedicated-desc = Spiele eine Stunde lang.
+
+## Sand particles
+sand-sand = Sand
+sand-quartz = Quarz
+sand-shell = Muschel
+sand-coral = Koralle
+sand-pinksand = Rosa Sand
+sand-volcanic = Vulkansand
+sand-glauconite = Glaukonit
+sand-gemstones = Edelsteine
+sand-iron = Eisen
+sand-starsand = Sternsand
+sand-gold = Gold
+sand-diamond = Diamant
+
+## Themes and times of day
+theme-classic = Klassisch
+theme-beach = Strand
+theme-night-sky = Nachthimmel
+theme-volcanic = Vulkan
+time-dawn = Morgengrauen
+time-day = Tag
+time-dusk = Abenddämmerung
+time-night = Nacht
+
+## Keyboard actions
+action-drop = Sand fallen lassen
+action-convert = Verkaufen
+action-toggle-info = Spielerinfo
+action-toggle-stats = Statistiken
+action-toggle-achievements = Erfolge
+action-toggle-settings = Einstellungen
+action-toggle-options = Optionen
+action-toggle-debug = Debug-Anzeige
+action-quit = Beenden
+action-pause = Pause
+action-screenshot = Screenshot
+action-reset-camera = Kamera zurücksetzen
+action-fullscreen = Vollbild
+action-toggle-mute = Ton aus/an
+
+challenges-title = Herausforderungen
+challenges-intro = Spiel einen Lauf mit geänderten Regeln. Das Hauptspiel wartet, bis die Herausforderung endet, und jede geschaffte Herausforderung lässt allen Sand für { $percent }% mehr verkaufen.
+challenge-no-autoclicker = Ohne Autoklicker
+challenge-no-autoclicker-desc = Das Autoklicker-Upgrade wird nie angeboten, jedes Korn fällt von Hand
+challenge-tiny-container = Winziger Behälter
+challenge-tiny-container-desc = Der Behälter fasst 25 Körner, egal wie viele Upgrades gekauft werden
+challenge-expensive-taste = Teurer Geschmack
+challenge-expensive-taste-desc = Jedes Upgrade kostet dreimal so viel
+challenge-goal = Ziel: { $goal } verdienen
+challenge-start = Starten
+challenge-give-up = Aufgeben
+challenge-started = Herausforderung gestartet: { $challenge }
+challenge-given-up = Herausforderung aufgegeben, zurück zum Hauptspiel
+challenge-complete-title = Herausforderung geschafft!
+challenge-complete-time = Zeit: { $time }
+challenge-complete-clicks = Klicks: { $clicks }
+challenge-complete-money = Verdientes Geld: { $money }
+challenge-complete-reward = Aller Sand verkauft sich jetzt für { $percent }% mehr
+challenge-complete-close = Zurück zum Hauptspiel
+blitz-title = Blitz
+blitz-started = Blitz gestartet, verdiene so viel wie möglich, bevor die Zeit abläuft
+blitz-earned = Verdient: { $money }$
+blitz-give-up = Aufgeben
+blitz-given-up = Blitz aufgegeben, zurück zum Hauptspiel
+blitz-over-title = Die Zeit ist um!
+blitz-over-money = Du hast { $money }$ verdient
+blitz-over-rank = Neuer Rekord, Platz { $rank }
+blitz-over-unranked = Nicht genug für die Bestenliste
+blitz-over-close = Zurück zum Hauptspiel
+blitz-no-scores = Noch kein Blitz gespielt
+daily-started = Tageslauf gestartet: { $rules }
+daily-rules-smaller = { $particle } ist { $boost }x so viel wert, Behälter { $percent }% kleiner
+daily-rules-larger = { $particle } ist { $boost }x so viel wert, Behälter { $percent }% größer
+daily-over-best = Dein bester Tageslauf des Tages
+daily-over-not-best = Dein Bestwert des Tages bleibt { $best }
+daily-mon = Mo
+daily-tue = Di
+daily-wed = Mi
+daily-thu = Do
+daily-fri = Fr
+daily-sat = Sa
+daily-sun = So
+
+profile-normal = Normal
+profile-normal-desc = Das normale Spiel.
+profile-hardcore = Hardcore
+profile-hardcore-desc = Liegender Sand verblasst und geht nach einer Minute am Boden verloren, also verkaufe ihn rechtzeitig. Hardcore-Fortschritt bleibt vom normalen Fortschritt getrennt.
+profile-sandbox = Sandkasten
+profile-sandbox-desc = Male mit Sand: unendlich Geld, alle Verbesserungen und Werkzeuge für das Sandteilchen, die Schwerkraft und die Korngröße. Keine Erfolge, Bestenlisten, Herausforderungen oder Tagesläufe.
+sandbox-title = Sandkasten
+sandbox-palette = Sand zum Fallenlassen:
+sandbox-random = Zufällig
+sandbox-gravity = Schwerkraft
+sandbox-grain-size = Korngröße
+sandbox-defaults = Standard
@@ -0,0 +1,361 @@
+# English texts of Sand Drop Clicker
+# each line is a `key = text` pair, `{ $name }` is replaced with a value
+
+## Options window
+options-title = Options
+options-settings = ⚙ Settings
+options-guide = Sand Guide
+options-history = History
+options-hint = Click the button to earn money!
+hardcore-badge = ☠ Hardcore
+ng-plus = New Game+ { $level }
+ng-plus-tooltip = Start the upgrades and the money over. The sand sells for 4x as much, and the upgrades cost 5x as much.
+ng-plus-started = Started New Game+ { $level }!
+sandbox-badge = 🎨 Sandbox
+options-convert = Convert ({ $key })
+container-full-hint = Container full, press { $key } to sell your sand
+options-money = Money: { $money }$
+contents-title = Container Contents
+contents-empty = The container is empty.
+contents-total = Total: { $money }$
+column-sand = Sand
+column-count = Count
+column-value = Value
+column-subtotal = Subtotal
+column-in-container = In container
+column-dropped = Dropped
+column-sold = Sold
+upgrades-none = No upgrades available yet. Keep clicking!
+upgrades-available = Available Upgrades:
+upgrades-unlocked = New upgrade available: { $upgrade }!
+upgrade-button = { $upgrade } ({ $level }): { $cost }$
+upgrade-button-max = { $upgrade } ({ $level }): (MAX LEVEL)
+upgrade-cost = Cost: { $cost }$
+upgrade-eta = about { $time } to go
+upgrade-payback = Pays for itself in about { $time } at the current rates
+upgrade-payback-unknown = Earn some money to see when it pays for itself
+upgrade-cant-afford = Not enough money for { $upgrade } ({ $cost }$)
+upgrade-shortcuts = Shift+click: buy { $count } for { $cost }$, Ctrl+click: buy max
+upgrade-bought = Bought { $count } levels of { $upgrade }
+upgrade-bought-partial = Bought { $count } of { $wanted } levels of { $upgrade }
+themes-title = Themes
+themes-use = Use { $theme }
+themes-buy = Buy { $theme }: { $cost }$
+
+## Upgrades
+upgrade-bigger-container = Bigger Container
+upgrade-bigger-container-button = Buy Bigger Container
+upgrade-bigger-container-desc = This will increase your container size:
+upgrade-particle-tier = Sand Quality
+upgrade-particle-tier-button = Improve Sand Quality
+upgrade-particle-tier-desc = This will allow you a chances to drop better sand:
+upgrade-auto-clicker = Auto Clicker
+upgrade-auto-clicker-button = Buy Auto Clicker
+upgrade-auto-clicker-desc = This will drop sand for you:
+upgrade-more-particles = More Particles
+upgrade-more-particles-button = Buy More Particles
+upgrade-more-particles-desc = This will allow you to drop more sand per click:
+upgrade-sculptor = Sculpting Tool
+upgrade-sculptor-button = Improve Sculpting Tool
+upgrade-sculptor-desc = This will let you push (Shift) and pull (Ctrl) more sand:
+effect-container = Container
+effect-best-sand = Best sand
+effect-none = none
+effect-autoclicker = Autoclicker
+effect-off = off
+effect-every = every { $seconds }s
+effect-grains = Grains per click
+effect-sculpt-radius = Sculpting radius
+effect-summary = { $effect }: { $current } → { $next }
+effect-summary-max = { $effect }: { $current } (max)
+category-production = Production
+category-economy = Economy
+category-container = Container
+
+## Pause menu and quit dialog
+pause-title = Paused
+pause-resume = Resume
+pause-settings = Settings
+pause-challenges = Challenges
+pause-blitz = Blitz
+pause-blitz-tooltip = Earn as much as you can in 5 minutes with cheaper upgrades, the main game waits untouched
+pause-daily = Daily Run
+pause-daily-tooltip = Play 10 minutes under the rules of today, the same for everyone playing today
+pause-save = Save
+pause-quit = Quit
+pause-saved = Game saved
+pause-save-failed = Couldn't save the game: { $error }
+quit-title = Quit?
+quit-unsold = You have { $amount } unsold grains worth { $money }$
+quit-sell = Sell & Quit
+quit-quit = Quit
+quit-cancel = Cancel
+
+## Settings window
+settings-title = Settings
+settings-display = Display
+settings-player-info = Player info ({ $key })
+settings-trails = Motion trails
+settings-colorblind = Colorblind palette
+settings-floating-text = Floating money text
+settings-full-numbers = Show full numbers
+settings-gauge = Container gauge
+settings-screen-shake = Screen shake
+settings-flash = Screen flash
+settings-muted = Mute all sounds ({ $key })
+settings-music-muted = Mute music
+settings-music-volume = Music volume
+settings-interface = Interface
+settings-language = Language
+settings-ui-scale = UI scale
+settings-scale-grains = Scale new grains
+settings-speedrun = Speedrun timer
+settings-speedrun-hint = Time the game in real time with splits, turning it on starts a new run
+settings-background = In the background
+background-pause = Pause
+background-pause-hint = The game stops until the window is back
+background-idle = Idle
+background-idle-hint = The autoclicker keeps earning money, but no sand falls
+background-run = Keep running
+background-run-hint = The sand keeps falling, a minimized window only idles
+settings-fps-cap = Frame rate cap
+settings-fps-unlimited = Unlimited
+settings-fps = { $fps } FPS
+settings-max-grains = Active grain limit
+settings-max-grains-unlimited = Unlimited
+settings-max-grains-hint = Past the limit, the oldest settled grains are packed into the bottom of the pile. They still fill the container and sell as usual.
+settings-theme = Theme
+settings-time-of-day = Time of day
+settings-time-cycle = Cycle
+settings-controls = Controls
+settings-press-key = Press a key...
+settings-conflict = ⚠ Conflict
+settings-conflict-hint = Another action uses the same key.
+settings-default-keys = Restore default keys
+settings-mouse = Mouse Buttons
+settings-mouse-hint = One button always drops sand.
+settings-swap-buttons = Swap left and right buttons
+mouse-left = Left button
+mouse-right = Right button
+mouse-middle = Middle button
+mouse-other = Other button
+mouse-action-drop = Drop sand
+mouse-action-vacuum = Vacuum
+mouse-action-pan = Pan camera
+mouse-action-nothing = Nothing
+settings-reset = Reset Progress
+settings-reset-hint = Type RESET to delete your money, sand, and upgrades.
+settings-reset-button = Reset progress
+settings-reset-profile = New game:
+key-none = None
+
+## Other windows
+guide-title = Sand Guide
+stats-title = Statistics
+stats-time-played = Time played
+stats-clicks = Clicks
+stats-money-earned = Money earned
+stats-money-spent = Money spent
+stats-grains-clicked = Grains clicked
+stats-grains-autoclicked = Grains autoclicked
+stats-best-sale = Best conversion
+stats-average-sale = Average conversion
+stats-average-fill = Average fill at conversion
+stats-decayed = Grains lost to decay
+stats-last-sales = Last conversions
+stats-no-sales = No conversions yet
+stats-new-record = New best conversion: { $money }$!
+stats-last-minute = Last minute
+stats-per-minute = { $money }$/min
+income-per-second = ≈ { $money }$/s (last minute)
+grain-value = ≈ { $money }$ per dropped grain
+grain-value-tooltip = What a grain of the unlocked sand sells for on average, not counting the combo
+stats-dropped = { $sand } dropped
+stats-sold = { $sand } sold
+stats-session = This session
+stats-lifetime = Lifetime
+stats-session-reset = Reset session stats
+stats-session-reset-tooltip = Start counting this session from zero, the lifetime stats and achievements are kept
+stats-blitz-scores = Blitz high scores
+stats-daily = Daily runs
+stats-income-graph = Money per minute, last 10 minutes
+stats-graph-point = { $time }: { $money }$/min
+stats-graph-paused = Paused
+stats-heatmap = Show click heatmap
+stats-heatmap-reset = Reset heatmap
+stats-export = Export Stats
+stats-export-tooltip = Save a spreadsheet of your money, grains, conversions, and upgrades for every minute played
+stats-exported = Exported the statistics to { $path }
+stats-export-failed = Couldn't export the statistics: { $error }
+achievements-title = Achievements
+achievements-earned-at = Earned at { $time } played
+achievements-earned = Achievement earned: { $achievement }
+history-title = History
+history-empty = Nothing bought or sold yet.
+history-clear = Clear
+history-purchase = Bought { $upgrade } level { $level } for { $cost }$
+history-sale = Converted sand for { $money }$
+notifications-more = +{ $count } more
+
+## Overlays and messages
+low-gravity = LOW GRAVITY! { $seconds }s
+player-info = Total Time: { $time }\nTotal Clicks: { $clicks }
+player-ng-plus = New Game+: { $level }
+audio-muted = Sound: muted
+audio-on = Sound: on, music at { $volume }%
+screenshot-saved = Saved { $path }
+screenshot-dir-failed = Couldn't create { $dir }: { $error }
+screenshot-capture-failed = Couldn't capture the screen: { $error }
+screenshot-save-failed = Couldn't save the screenshot: { $error }
+screenshot-failed = Couldn't save the screenshot
+save-load-failed = Couldn't read the save file, starting a new game: { $error }
+settings-save-failed = Couldn't save the settings: { $error }
+settings-load-failed = Couldn't read the settings, using the defaults: { $error }
+fullscreen-failed = Couldn't switch to fullscreen: { $error }
+combo = Combo x{ $count } (+{ $bonus }%)
+
+## Tutorial
+tutorial-title = Welcome!
+tutorial-drop-sand = Click anywhere in the play area to drop sand.
+tutorial-convert = Sell your sand with the Convert button in the Options window.
+tutorial-upgrade = Buy your first upgrade as soon as you can afford it.
+tutorial-info = Tip: press { $key } to see your play time and clicks.
+tutorial-skip = Skip tutorial
+
+## Replays
+replay-title = REPLAY
+replay-progress = { $time } of { $total }
+replay-matched = The replay ended the same way it was recorded.
+replay-diverged = The replay ended differently than it was recorded!
+
+## Developer console
+console-title = Console
+
+## Speedrun
+speedrun-title = Speedrun
+speedrun-split = { $split } in { $time }
+speedrun-split-delta = { $split } in { $time } ({ $delta })
+speedrun-skipped = Reached before this run started
+speedrun-best = Personal best
+split-first-quartz = First Quartz
+split-first-autoclicker = First AutoClicker
+split-millionaire = 1M$ earned
+split-all-maxed = All upgrades maxed
+
+## Achievements
+achievement-first-grain = First Grain
+achievement-first-grain-desc = Drop your first grain of sand.
+achievement-busy-fingers = Busy Fingers
+achievement-busy-fingers-desc = Click 1000 times.
+achievement-pocket-money = Pocket Money
+achievement-pocket-money-desc = Earn 1000$ in total.
+achievement-millionaire = Millionaire
+achievement-millionaire-desc = Earn 1000000$ in total.
+achievement-full-house = Full House
+achievement-full-house-desc = Fill the container to the brim.
+achievement-automation = Automation
+achievement-automation-desc = Buy an auto clicker.
+achievement-diamond-hands = Diamond Hands
+achievement-diamond-hands-desc = Drop a grain of diamond sand.
+achievement-gold-rush = Gold Rush
+achievement-gold-rush-desc = Sell 100 grains of gold sand.
+achievement-record-breaker = Record Breaker
+achievement-record-breaker-desc = Beat your best conversion 10 times.
+achievement-dedicated = Dedicated
+achievement-dedicated-desc = Play for an hour.
+
+## Sand particles
+sand-sand = Sand
+sand-quartz = Quartz
+sand-shell = Shell
+sand-coral = Coral
+sand-pinksand = Pink Sand
+sand-volcanic = Volcanic
+sand-glauconite = Glauconite
+sand-gemstones = Gemstones
+sand-iron = Iron
+sand-starsand = Star Sand
+sand-gold = Gold
+sand-diamond = Diamond
+
+## Themes and times of day
+theme-classic = Classic
+theme-beach = Beach
+theme-night-sky = Night Sky
+theme-volcanic = Volcanic
+time-dawn = Dawn
+time-day = Day
+time-dusk = Dusk
+time-night = Night
+
+## Keyboard actions
+action-drop = Drop sand
+action-convert = Convert
+action-toggle-info = Player info
+action-toggle-stats = Statistics
+action-toggle-achievements = Achievements
+action-toggle-settings = Settings
+action-toggle-options = Options
+action-toggle-debug = Debug overlay
+action-quit = Quit
+action-pause = Pause
+action-screenshot = Screenshot
+action-reset-camera = Reset camera
+action-fullscreen = Fullscreen
+action-toggle-mute = Mute all sounds
+
+challenges-title = Challenges
+challenges-intro = Play a run with modified rules. The main game waits until the challenge ends, and each completed challenge makes all sand sell for { $percent }% more.
+challenge-no-autoclicker = No Autoclicker
+challenge-no-autoclicker-desc = The AutoClicker upgrade is never offered, every grain is dropped by hand
+challenge-tiny-container = Tiny Container
+challenge-tiny-container-desc = The container holds 25 grains, however many upgrades are bought
+challenge-expensive-taste = Expensive Taste
+challenge-expensive-taste-desc = Every upgrade costs three times as much
+challenge-goal = Goal: earn { $goal }
+challenge-start = Start
+challenge-give-up = Give up
+challenge-started = Challenge started: { $challenge }
+challenge-given-up = Gave up the challenge, back to the main game
+challenge-complete-title = Challenge complete!
+challenge-complete-time = Time: { $time }
+challenge-complete-clicks = Clicks: { $clicks }
+challenge-complete-money = Money earned: { $money }
+challenge-complete-reward = All sand now sells for { $percent }% more
+challenge-complete-close = Back to the main game
+blitz-title = Blitz
+blitz-started = Blitz started, earn as much as you can before the time runs out
+blitz-earned = Earned: { $money }$
+blitz-give-up = Give up
+blitz-given-up = Gave up the blitz, back to the main game
+blitz-over-title = Time's up!
+blitz-over-money = You earned { $money }$
+blitz-over-rank = New high score, place { $rank }
+blitz-over-unranked = Not enough for the high-score table
+blitz-over-close = Back to the main game
+blitz-no-scores = No blitz played yet
+daily-started = Daily run started: { $rules }
+daily-rules-smaller = { $particle } is worth { $boost }x, containers { $percent }% smaller
+daily-rules-larger = { $particle } is worth { $boost }x, containers { $percent }% larger
+daily-over-best = Your best daily run of the day
+daily-over-not-best = Your best of the day stays { $best }
+daily-mon = Mo
+daily-tue = Tu
+daily-wed = We
+daily-thu = Th
+daily-fri = Fr
+daily-sat = Sa
+daily-sun = Su
+
+profile-normal = Normal
+profile-normal-desc = The regular game.
+profile-hardcore = Hardcore
+profile-hardcore-desc = Settled sand fades and is lost after a minute on the ground, so convert it in time. Hardcore progress is kept apart from normal progress.
+profile-sandbox = Sandbox
+profile-sandbox-desc = Paint with sand: infinite money, every upgrade, and tools for the sand particle, the gravity, and the grain size. No achievements, scores, challenges, or daily runs.
+sandbox-title = Sandbox
+sandbox-palette = Sand to drop:
+sandbox-random = Random
+sandbox-gravity = Gravity
+sandbox-grain-size = Grain size
+sandbox-defaults = Defaults
@@ -0,0 +1,143 @@
+//! Benchmarks of the grain physics, the render preparation, and the HUD text
+//! run headless with `cargo bench`, no window or GPU needed
+
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use ggez::graphics::Text;
+use sand_drop_clicker::{
+    FPS, GRAIN_SIZE, GRAVITY, SCREEN_SIZE,
+    currency::Currency,
+    grain::{SpatialGrid, scatter_grains},
+    ui::{HudText, format_money, grain_instances, grown_capacity},
+};
+use std::hint::black_box;
+
+// Amounts of grains each benchmark runs with
+const AMOUNTS: [usize; 3] = [1_000, 10_000, 50_000];
+
+/// benchmarks one physics step of all the grains
+fn grain_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grain_update");
+    for amount in AMOUNTS {
+        group.throughput(Throughput::Elements(amount as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(amount),
+            &amount,
+            |b, &amount| {
+                // fresh grains for every batch, so none of them have settled yet
+                b.iter_batched_ref(
+                    || scatter_grains(amount, GRAIN_SIZE, SCREEN_SIZE),
+                    |grains| {
+                        for grain in grains.iter_mut() {
+                            grain.update(1.0 / FPS as f32, GRAVITY, SCREEN_SIZE);
+                        }
+                    },
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+/// benchmarks filling the instance array with the grains of a frame
+fn instance_array(c: &mut Criterion) {
+    let mut group = c.benchmark_group("instance_array");
+    for amount in AMOUNTS {
+        let grains = scatter_grains(amount, GRAIN_SIZE, SCREEN_SIZE);
+        let mut instances = Vec::with_capacity(amount);
+        group.throughput(Throughput::Elements(amount as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(amount), &grains, |b, grains| {
+            b.iter(|| {
+                instances.clear();
+                instances.extend(grain_instances(grains, true, 0.5, None));
+                black_box(instances.len())
+            });
+        });
+    }
+    group.finish();
+}
+
+/// benchmarks refilling the instance array while the amount of grains
+/// oscillates around its capacity, as it does when grains settle and drop
+fn instance_growth(c: &mut Criterion) {
+    let mut group = c.benchmark_group("instance_growth");
+    for amount in AMOUNTS {
+        let grains = scatter_grains(amount + amount / 10, GRAIN_SIZE, SCREEN_SIZE);
+        group.throughput(Throughput::Elements(amount as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(amount), &grains, |b, grains| {
+            let mut instances = Vec::with_capacity(amount);
+            let mut frame = 0;
+            b.iter(|| {
+                // every other frame has a few more grains than the last
+                frame += 1;
+                let len = if frame % 2 == 0 { grains.len() } else { amount };
+                instances.clear();
+                if let Some(capacity) = grown_capacity(instances.capacity(), len) {
+                    instances.reserve_exact(capacity);
+                }
+                instances.extend(grain_instances(&grains[..len], true, 0.5, None));
+                black_box(instances.len())
+            });
+        });
+    }
+    group.finish();
+}
+
+/// benchmarks rebuilding the spatial grid and looking up the grains around a point
+fn spatial_grid(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spatial_grid");
+    for amount in AMOUNTS {
+        let grains = scatter_grains(amount, GRAIN_SIZE, SCREEN_SIZE);
+        let mut grid = SpatialGrid::new(GRAIN_SIZE * 4.0);
+        group.throughput(Throughput::Elements(amount as u64));
+        group.bench_with_input(BenchmarkId::new("rebuild", amount), &grains, |b, grains| {
+            b.iter(|| grid.rebuild(black_box(grains).iter().enumerate()));
+        });
+        grid.rebuild(grains.iter().enumerate());
+        group.bench_with_input(BenchmarkId::new("query", amount), &grid, |b, grid| {
+            b.iter(|| grid.query(black_box(400.0), black_box(300.0), black_box(40.0)));
+        });
+    }
+    group.finish();
+}
+
+/// benchmarks a second of frames of the game info text, where the money changes once
+/// formatting the text every frame against rebuilding it only when the values change
+fn hud_text(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hud_text");
+    let frames = FPS as u64;
+    group.throughput(Throughput::Elements(frames));
+    let money = |frame: u64| Currency(u128::from(1_234_567 + frame / frames * 1_000));
+    group.bench_function("every_frame", |b| {
+        b.iter(|| {
+            for frame in 0..frames {
+                let text = Text::new(format!("{}/{}\n{}$", 120, 500, format_money(money(frame))));
+                black_box(text);
+            }
+        });
+    });
+    group.bench_function("cached", |b| {
+        let mut hud = HudText::default();
+        b.iter(|| {
+            for frame in 0..frames {
+                let key = (120, 500, money(frame));
+                if !hud.is_current(&key) {
+                    let money = format_money(key.2);
+                    hud.set(key, 16.0, format_args!("{}/{}\n{}$", key.0, key.1, money));
+                }
+                black_box(hud.text());
+            }
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    grain_update,
+    instance_array,
+    instance_growth,
+    spatial_grid,
+    hud_text
+);
+criterion_main!(benches);
@@ -0,0 +1,182 @@
+//! Sound effects and music
+
+use crate::{DUCK_LEVEL, DUCK_RECOVERY};
+use ggez::{
+    Context, GameResult,
+    audio::{self, SoundSource},
+};
+use strum_macros::EnumIter;
+
+// Sound effects, embedded into the game
+pub(crate) const SOUND_DROP: &[u8] = include_bytes!("../assets/sounds/tick.wav");
+
+pub(crate) const SOUND_LAND: &[u8] = include_bytes!("../assets/sounds/land.wav");
+
+pub(crate) const SOUND_SALE: &[u8] = include_bytes!("../assets/sounds/cash.wav");
+
+pub(crate) const SOUND_BUY: &[u8] = include_bytes!("../assets/sounds/click.wav");
+
+pub(crate) const SOUND_POUR: &[u8] = include_bytes!("../assets/sounds/pour.wav");
+
+pub(crate) const MUSIC_CALM: &[u8] = include_bytes!("../assets/sounds/music_calm.wav");
+
+pub(crate) const MUSIC_INTENSE: &[u8] = include_bytes!("../assets/sounds/music_intense.wav");
+
+/// Sound effects of the game
+/// * Drop: A soft tick for sand dropped by hand.
+/// * Land: A patter of grains landing.
+/// * Sale: A cash register for converted sand.
+/// * Buy: A click for a bought upgrade.
+#[derive(Hash, Eq, PartialEq, Debug, EnumIter, Clone, Copy)]
+pub(crate) enum Sound {
+    Drop,
+    Land,
+    Sale,
+    Buy,
+}
+
+/// Structure holding the pre-loaded sound effects
+/// * drop: source of the drop sound
+/// * land: source of the landing sound
+/// * sale: source of the sale sound
+/// * buy: source of the purchase sound
+/// * pour: looping source of the pouring sound, stopped while nothing pours
+pub(crate) struct Sounds {
+    pub(crate) drop: audio::Source,
+    pub(crate) land: audio::Source,
+    pub(crate) sale: audio::Source,
+    pub(crate) buy: audio::Source,
+    pub(crate) pour: audio::Source,
+}
+
+/// Implementation of methods for the Sounds struct
+/// * new: loads the embedded sound effects
+/// * source: returns the source of a sound effect
+/// * stop_all: stops all playing sound effects
+impl Sounds {
+    /// loads the embedded sound effects
+    pub(crate) fn new(ctx: &Context) -> GameResult<Self> {
+        let load =
+            |bytes: &[u8]| audio::Source::from_data(ctx, audio::SoundData::from_bytes(bytes));
+        let mut pour = load(SOUND_POUR)?;
+        pour.set_repeat(true);
+        Ok(Self {
+            drop: load(SOUND_DROP)?,
+            land: load(SOUND_LAND)?,
+            sale: load(SOUND_SALE)?,
+            buy: load(SOUND_BUY)?,
+            pour,
+        })
+    }
+
+    /// returns the source of a sound effect
+    pub(crate) fn source(&mut self, sound: Sound) -> &mut audio::Source {
+        match sound {
+            Sound::Drop => &mut self.drop,
+            Sound::Land => &mut self.land,
+            Sound::Sale => &mut self.sale,
+            Sound::Buy => &mut self.buy,
+        }
+    }
+
+    /// stops all playing sound effects
+    pub(crate) fn stop_all(&mut self, ctx: &Context) {
+        for source in [
+            &mut self.drop,
+            &mut self.land,
+            &mut self.sale,
+            &mut self.buy,
+            &mut self.pour,
+        ] {
+            if !source.stopped() {
+                let _ = source.stop(ctx);
+            }
+        }
+    }
+}
+
+/// Structure holding the looping music tracks
+/// both tracks always play, the crossfade only changes their volume
+/// * calm: the ambient track
+/// * intense: the track for a full container and special events
+pub(crate) struct Music {
+    pub(crate) calm: audio::Source,
+    pub(crate) intense: audio::Source,
+}
+
+/// Implementation of methods for the Music struct
+/// * new: loads the embedded music and starts it silently
+impl Music {
+    /// loads the embedded music and starts it silently
+    pub(crate) fn new(ctx: &Context) -> GameResult<Self> {
+        let start = |bytes: &[u8]| -> GameResult<audio::Source> {
+            let mut source = audio::Source::from_data(ctx, audio::SoundData::from_bytes(bytes))?;
+            source.set_repeat(true);
+            source.set_volume(0.0);
+            source.play(ctx)?;
+            Ok(source)
+        };
+        Ok(Self {
+            calm: start(MUSIC_CALM)?,
+            intense: start(MUSIC_INTENSE)?,
+        })
+    }
+}
+
+/// Envelope that lowers the music for a moment
+/// * gain: multiplier of the music volume, 1 when the music isn't lowered
+#[derive(Debug)]
+pub(crate) struct Ducking {
+    pub(crate) gain: f32,
+}
+
+/// Default ducking, with the music at full volume
+impl Default for Ducking {
+    fn default() -> Self {
+        Self { gain: 1.0 }
+    }
+}
+
+/// Implementation of methods for the Ducking struct
+/// * duck: drops the music to the ducked volume
+/// * update: ramps the music back up to full volume
+impl Ducking {
+    /// drops the music to the ducked volume
+    pub(crate) fn duck(&mut self) {
+        self.gain = self.gain.min(DUCK_LEVEL);
+    }
+
+    /// ramps the music back up to full volume
+    /// the gain ends at exactly 1, so the music returns to the set volume
+    pub(crate) fn update(&mut self, dt: f32) {
+        self.gain = (self.gain + dt * (1.0 - DUCK_LEVEL) / DUCK_RECOVERY).min(1.0);
+    }
+}
+
+/// Tests for the audio
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Ducking tests
+    #[test]
+    fn test_ducking_envelope() {
+        let mut ducking = Ducking::default();
+        ducking.update(1.0);
+        assert_eq!(ducking.gain, 1.0);
+        ducking.duck();
+        assert_eq!(ducking.gain, DUCK_LEVEL);
+        ducking.update(DUCK_RECOVERY / 2.0);
+        assert!((ducking.gain - (1.0 + DUCK_LEVEL) / 2.0).abs() < 1e-6);
+        // ducking again doesn't raise the volume
+        ducking.duck();
+        assert_eq!(ducking.gain, DUCK_LEVEL);
+        // many small frames land exactly on the set volume
+        for _ in 0..100 {
+            ducking.update(1.0 / 60.0);
+        }
+        assert_eq!(ducking.gain, 1.0);
+        let volume = 0.37;
+        assert_eq!(volume * ducking.gain, volume);
+    }
+}
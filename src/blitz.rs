@@ -0,0 +1,230 @@
+//! Timed blitz runs and their high-score table
+
+use crate::{
+    BLITZ_COSTS, BLITZ_SCORES, BLITZ_TIME, DAILY_TIME, challenge::Modifiers, currency::Currency,
+    daily::DailyRules, sim::SimState,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Blitz being played, on a simulation of its own
+/// * main: simulation of the main game, waiting untouched until the blitz ends
+/// * time_left: seconds left on the countdown
+/// * daily: day of the daily run, counted from 1970-01-01, None for a plain blitz
+pub(crate) struct Blitz {
+    pub(crate) main: SimState,
+    pub(crate) time_left: f32,
+    pub(crate) daily: Option<u64>,
+}
+
+/// Implementation of methods for the Blitz struct
+/// * new: starts the countdown of a blitz, with the main game waiting for it
+/// * daily_rules: returns the rules of the daily run, if it is one
+/// * modifiers: returns the rules the blitz is played with
+/// * update: counts down the time left
+impl Blitz {
+    /// starts the countdown of a blitz, with the main game waiting for it
+    /// a daily run takes longer than a plain blitz
+    pub(crate) fn new(main: SimState, daily: Option<u64>) -> Self {
+        Self {
+            main,
+            time_left: if daily.is_some() {
+                DAILY_TIME
+            } else {
+                BLITZ_TIME
+            },
+            daily,
+        }
+    }
+
+    /// returns the rules of the daily run, if it is one
+    pub(crate) fn daily_rules(&self) -> Option<DailyRules> {
+        self.daily
+            .map(|day| DailyRules::for_date(&format_date(day)))
+    }
+
+    /// returns the rules the blitz is played with
+    /// the upgrades of a plain blitz are cheaper, so a few minutes go through more of them,
+    /// and a daily run has the rules of its day
+    pub(crate) fn modifiers(&self) -> Modifiers {
+        match self.daily_rules() {
+            Some(rules) => rules.modifiers(),
+            None => Modifiers {
+                cost_multiplier: BLITZ_COSTS,
+                ..Modifiers::default()
+            },
+        }
+    }
+
+    /// counts down the time left by dt seconds
+    /// returns true once the time is up
+    pub(crate) fn update(&mut self, dt: f32) -> bool {
+        self.time_left = (self.time_left - dt).max(0.0);
+        self.time_left <= 0.0
+    }
+}
+
+/// Score of a finished blitz
+/// * money: money earned in the blitz
+/// * date: day the blitz was played, like 2025-12-09
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BlitzScore {
+    pub(crate) money: Currency,
+    pub(crate) date: String,
+}
+
+/// Result of a finished blitz, shown on the summary screen
+/// * money: money earned in the blitz
+/// * rank: place of the score in the high-score table, None if it didn't make it,
+///   and for a daily run Some(0) if it is the best of its day
+/// * daily: day of the daily run, None for a plain blitz
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct BlitzSummary {
+    pub(crate) money: Currency,
+    pub(crate) rank: Option<usize>,
+    pub(crate) daily: Option<u64>,
+}
+
+/// Best blitz scores of the player, best first
+/// * scores: up to BLITZ_SCORES scores
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct HighScores {
+    pub(crate) scores: Vec<BlitzScore>,
+}
+
+/// Implementation of methods for the HighScores struct
+/// * record: adds a score to the table, if it is good enough
+/// * to_save: returns the scores as save file lines
+/// * load_line: reads a save file line, returns false if it isn't a score
+impl HighScores {
+    /// adds a score to the table, if it is good enough
+    /// returns its place in the table, 0 for the best
+    /// a score that ties an older one goes below it
+    pub(crate) fn record(&mut self, score: BlitzScore) -> Option<usize> {
+        let rank = self
+            .scores
+            .iter()
+            .position(|other| other.money < score.money)
+            .unwrap_or(self.scores.len());
+        if rank >= BLITZ_SCORES {
+            return None;
+        }
+        self.scores.insert(rank, score);
+        self.scores.truncate(BLITZ_SCORES);
+        Some(rank)
+    }
+
+    /// returns the scores as save file lines
+    pub(crate) fn to_save(&self) -> Vec<String> {
+        self.scores
+            .iter()
+            .map(|score| format!("blitz_score={},{}", score.money, score.date))
+            .collect()
+    }
+
+    /// reads a save file line, returns false if it isn't a score
+    pub(crate) fn load_line(&mut self, key: &str, value: &str) -> bool {
+        if key != "blitz_score" {
+            return false;
+        }
+        if let Some((money, date)) = value.split_once(',')
+            && let Ok(money) = money.parse()
+        {
+            self.record(BlitzScore {
+                money,
+                date: date.to_string(),
+            });
+        }
+        true
+    }
+}
+
+/// returns the day of the system clock, counted from 1970-01-01
+pub(crate) fn today() -> u64 {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    secs / 86_400
+}
+
+/// returns the day a number of days after 1970-01-01, like 2025-12-09
+/// the days are counted in eras of 400 years, which always have the same length
+pub(crate) fn format_date(days: u64) -> String {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // the months are counted from March, so the leap day comes last
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!("{}-{:02}-{:02}", year, month, day)
+}
+
+/// Tests for the blitz runs
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Blitz tests
+    #[test]
+    fn test_blitz_countdown() {
+        let mut blitz = Blitz::new(SimState::default(), None);
+        assert!(!blitz.update(BLITZ_TIME - 1.0));
+        assert!(blitz.update(2.0));
+        assert_eq!(blitz.time_left, 0.0);
+        assert!(blitz.modifiers().cost(Currency(100)) < Currency(100));
+        // a daily run plays longer, under the rules of its day
+        let daily = Blitz::new(SimState::default(), Some(20_431));
+        assert_eq!(daily.time_left, DAILY_TIME);
+        assert_eq!(
+            daily.modifiers(),
+            DailyRules::for_date("2025-12-09").modifiers()
+        );
+    }
+
+    // HighScores tests
+    #[test]
+    fn test_high_scores_record() {
+        let mut scores = HighScores::default();
+        let score = |money: u128| BlitzScore {
+            money: Currency(money),
+            date: "2025-12-09".to_string(),
+        };
+        for money in 1..=BLITZ_SCORES as u128 {
+            assert_eq!(scores.record(score(money * 10)), Some(0));
+        }
+        // too low for a full table
+        assert_eq!(scores.record(score(5)), None);
+        // a tie goes below the older score
+        assert_eq!(scores.record(score(90)), Some(2));
+        assert_eq!(scores.scores.len(), BLITZ_SCORES);
+        assert_eq!(scores.scores.last(), Some(&score(20)));
+    }
+    #[test]
+    fn test_high_scores_save_load() {
+        let mut scores = HighScores::default();
+        scores.record(BlitzScore {
+            money: Currency(1234),
+            date: "2025-12-09".to_string(),
+        });
+        let mut loaded = HighScores::default();
+        for line in scores.to_save() {
+            let (key, value) = line.split_once('=').unwrap();
+            assert!(loaded.load_line(key, value));
+        }
+        assert_eq!(loaded, scores);
+        // broken scores are skipped
+        assert!(loaded.load_line("blitz_score", "lots,2025-12-09"));
+        assert!(!loaded.load_line("money", "100"));
+        assert_eq!(loaded, scores);
+    }
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(11_016), "2000-02-29");
+        assert_eq!(format_date(20_431), "2025-12-09");
+    }
+}
@@ -0,0 +1,101 @@
+//! Camera the sand is viewed through
+
+use crate::{MAX_ZOOM, MIN_ZOOM};
+use ggez::graphics::DrawParam;
+
+/// Structure representing the view onto the sand
+/// * x: horizontal screen position of the world origin
+/// * y: vertical screen position of the world origin
+/// * zoom: scale of the world on the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Camera {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) zoom: f32,
+}
+
+/// Default camera showing the world as is
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            zoom: 1.0,
+        }
+    }
+}
+
+/// Implementation of methods for the Camera struct
+/// * to_world: converts a screen position into a world position
+/// * to_screen: converts a world position into a screen position
+/// * pan: moves the view by a screen distance
+/// * zoom_at: zooms the view while keeping a screen position in place
+/// * draw_param: returns the transform for drawing the world
+impl Camera {
+    /// converts a screen position into a world position
+    pub(crate) fn to_world(self, x: f32, y: f32) -> (f32, f32) {
+        ((x - self.x) / self.zoom, (y - self.y) / self.zoom)
+    }
+
+    /// converts a world position into a screen position
+    pub(crate) fn to_screen(self, x: f32, y: f32) -> (f32, f32) {
+        (x * self.zoom + self.x, y * self.zoom + self.y)
+    }
+
+    /// moves the view by a screen distance
+    pub(crate) fn pan(&mut self, dx: f32, dy: f32) {
+        self.x += dx;
+        self.y += dy;
+    }
+
+    /// zooms the view while keeping a screen position in place
+    pub(crate) fn zoom_at(&mut self, x: f32, y: f32, factor: f32) {
+        let (world_x, world_y) = self.to_world(x, y);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.x = x - world_x * self.zoom;
+        self.y = y - world_y * self.zoom;
+    }
+
+    /// returns the transform for drawing the world
+    pub(crate) fn draw_param(&self) -> DrawParam {
+        DrawParam::default()
+            .dest([self.x, self.y])
+            .scale([self.zoom, self.zoom])
+    }
+}
+
+/// Tests for the camera
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Camera tests
+    #[test]
+    fn test_camera_to_screen() {
+        let mut camera = Camera::default();
+        camera.zoom_at(100.0, 50.0, 2.0);
+        camera.pan(10.0, -5.0);
+        let (x, y) = camera.to_world(30.0, 40.0);
+        assert_eq!(camera.to_screen(x, y), (30.0, 40.0));
+    }
+    #[test]
+    fn test_camera_pan() {
+        let mut camera = Camera::default();
+        camera.pan(30.0, -20.0);
+        assert_eq!(camera.to_world(30.0, -20.0), (0.0, 0.0));
+        camera.zoom = 2.0;
+        assert_eq!(camera.to_world(50.0, 0.0), (10.0, 10.0));
+    }
+    #[test]
+    fn test_camera_zoom_at() {
+        let mut camera = Camera::default();
+        let before = camera.to_world(200.0, 300.0);
+        camera.zoom_at(200.0, 300.0, 2.0);
+        // the point under the mouse stays in place
+        assert_eq!(camera.to_world(200.0, 300.0), before);
+        assert_eq!(camera.zoom, 2.0);
+        // the zoom is limited
+        camera.zoom_at(0.0, 0.0, 100.0);
+        assert_eq!(camera.zoom, MAX_ZOOM);
+    }
+}
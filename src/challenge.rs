@@ -0,0 +1,325 @@
+//! Challenge runs with modified rules, and the rules a run is played with
+
+use crate::{
+    CHALLENGE_COSTS, CHALLENGE_REWARD, NG_PLUS_COST, NG_PLUS_VALUE, TINY_CONTAINER,
+    currency::Currency,
+    lang::tr,
+    particle::SandParticle,
+    sim::SimState,
+    stats::Counters,
+    upgrade::{Upgrade, UpgradeEffect},
+};
+use std::collections::{HashMap, HashSet};
+use strum_macros::EnumIter;
+
+/// Enum representing the challenge runs
+/// * NoAutoclicker: The AutoClicker upgrade is never listed.
+/// * TinyContainer: The container holds 25 grains, whatever is bought.
+/// * ExpensiveTaste: Every upgrade costs three times as much.
+#[derive(Hash, Eq, PartialEq, Debug, EnumIter, Clone, Copy)]
+pub(crate) enum Challenge {
+    NoAutoclicker,
+    TinyContainer,
+    ExpensiveTaste,
+}
+
+/// Implementation of methods for the Challenge enum
+/// * name: returns the display name of the challenge
+/// * desc: returns the rules of the challenge
+/// * goal: returns the money to earn to complete the challenge
+/// * reached: checks if the simulation reached the goal of the challenge
+/// * modifiers: returns the rules the challenge is played with
+impl Challenge {
+    /// returns the display name of the challenge
+    pub(crate) fn name(&self) -> &'static str {
+        tr(match self {
+            Challenge::NoAutoclicker => "challenge-no-autoclicker",
+            Challenge::TinyContainer => "challenge-tiny-container",
+            Challenge::ExpensiveTaste => "challenge-expensive-taste",
+        })
+    }
+
+    /// returns the rules of the challenge
+    pub(crate) fn desc(&self) -> &'static str {
+        tr(match self {
+            Challenge::NoAutoclicker => "challenge-no-autoclicker-desc",
+            Challenge::TinyContainer => "challenge-tiny-container-desc",
+            Challenge::ExpensiveTaste => "challenge-expensive-taste-desc",
+        })
+    }
+
+    /// returns the money to earn in the challenge run to complete it
+    pub(crate) fn goal(&self) -> Currency {
+        match self {
+            Challenge::NoAutoclicker => Currency(1_000_000),
+            Challenge::TinyContainer => Currency(250_000),
+            Challenge::ExpensiveTaste => Currency(1_000_000),
+        }
+    }
+
+    /// checks if the simulation reached the goal of the challenge
+    /// only the money earned in the challenge run counts
+    pub(crate) fn reached(&self, sim: &SimState) -> bool {
+        sim.lifetime().money_earned >= self.goal()
+    }
+
+    /// returns the rules the challenge is played with
+    /// the rewards of the completed challenges don't help in a challenge
+    pub(crate) fn modifiers(&self) -> Modifiers {
+        match self {
+            Challenge::NoAutoclicker => Modifiers {
+                hidden: HashSet::from([UpgradeEffect::AutoclickInterval]),
+                ..Modifiers::default()
+            },
+            Challenge::TinyContainer => Modifiers {
+                container_size: Some(TINY_CONTAINER),
+                ..Modifiers::default()
+            },
+            Challenge::ExpensiveTaste => Modifiers {
+                cost_multiplier: CHALLENGE_COSTS,
+                ..Modifiers::default()
+            },
+        }
+    }
+}
+
+/// Challenge being played, next to the progress of the main game
+/// * challenge: challenge being played
+/// * main: progress of the main game in the save file format, restored when the challenge ends
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ChallengeRun {
+    pub(crate) challenge: Challenge,
+    pub(crate) main: String,
+}
+
+/// Result of a completed challenge run, shown on the summary screen
+/// * challenge: challenge that was completed
+/// * counters: what the player did in the challenge run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ChallengeSummary {
+    pub(crate) challenge: Challenge,
+    pub(crate) counters: Counters,
+}
+
+/// Rules a run is played with, consulted by the simulation
+/// * hidden: upgrade effects whose upgrades are never listed or bought
+/// * container_size: fixed size of the container, if any
+/// * cost_multiplier: factor on the cost of every upgrade level
+/// * value_multiplier: factor on the money every grain sells for
+/// * boosts: factor on the money a single sand particle sells for, for the boosted ones
+/// * container_scale: factor on the size of the container
+/// * decay_time: seconds settled grains rest on the ground before they decay, None if they never do
+/// * sandbox: flag for free play, where the money never runs out, every upgrade is listed,
+///   and no achievements are earned
+/// * palette: sand particle every drop is made of, None for random unlocked sand
+/// * gravity_scale: factor on the gravity pulling the grains down
+/// * grain_size: fixed size of new grains, if any
+/// * ng_plus: New Game+ count, each one scaling the values by NG_PLUS_VALUE and the costs by NG_PLUS_COST
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Modifiers {
+    pub(crate) hidden: HashSet<UpgradeEffect>,
+    pub(crate) container_size: Option<u32>,
+    pub(crate) cost_multiplier: f64,
+    pub(crate) value_multiplier: f64,
+    pub(crate) boosts: HashMap<SandParticle, f64>,
+    pub(crate) container_scale: f64,
+    pub(crate) decay_time: Option<f32>,
+    pub(crate) sandbox: bool,
+    pub(crate) palette: Option<SandParticle>,
+    pub(crate) gravity_scale: f32,
+    pub(crate) grain_size: Option<f32>,
+    pub(crate) ng_plus: u32,
+}
+
+/// Default rules, which change nothing
+impl Default for Modifiers {
+    fn default() -> Self {
+        Self {
+            hidden: HashSet::new(),
+            container_size: None,
+            cost_multiplier: 1.0,
+            value_multiplier: 1.0,
+            boosts: HashMap::new(),
+            container_scale: 1.0,
+            decay_time: None,
+            sandbox: false,
+            palette: None,
+            gravity_scale: 1.0,
+            grain_size: None,
+            ng_plus: 0,
+        }
+    }
+}
+
+/// Implementation of methods for the Modifiers struct
+/// * rewards: returns the rules of the main game with the rewards of the completed challenges
+/// * hides: checks if an upgrade is left out of the run
+/// * cost_factor: returns the factor on every cost, with the New Game+ scaling
+/// * value_factor: returns the factor on every sale, with the New Game+ scaling
+/// * cost: applies the cost factor to a cost
+/// * value: applies the value factor to a sale value
+/// * boost: applies the boost of a sand particle to its value
+/// * size: applies the container scale to a container size
+/// * decay: returns how far a settled grain has decayed
+impl Modifiers {
+    /// returns the rules of the main game with the rewards of the completed challenges
+    /// each completed challenge makes the sand sell for CHALLENGE_REWARD more
+    pub(crate) fn rewards(completed: &HashSet<Challenge>) -> Self {
+        Self {
+            value_multiplier: 1.0 + CHALLENGE_REWARD * completed.len() as f64,
+            ..Self::default()
+        }
+    }
+
+    /// checks if the upgrade is left out of the run
+    pub(crate) fn hides(&self, upgrade: Upgrade) -> bool {
+        self.hidden.contains(&upgrade.effect())
+    }
+
+    /// returns the factor on the cost of every upgrade level,
+    /// the cost multiplier of the run times the New Game+ scaling
+    pub(crate) fn cost_factor(&self) -> f64 {
+        self.cost_multiplier * NG_PLUS_COST.powf(self.ng_plus as f64)
+    }
+
+    /// returns the factor on the money every sale earns,
+    /// the value multiplier of the run and its rewards times the New Game+ scaling
+    pub(crate) fn value_factor(&self) -> f64 {
+        self.value_multiplier * NG_PLUS_VALUE.powf(self.ng_plus as f64)
+    }
+
+    /// applies the cost factor to a cost
+    /// an unchanged cost stays exact, as large costs don't fit a float,
+    /// and a cost past the most money there can be stops there
+    pub(crate) fn cost(&self, cost: Currency) -> Currency {
+        let factor = self.cost_factor();
+        if factor == 1.0 {
+            cost
+        } else {
+            Currency::from_f64(cost.as_f64() * factor)
+        }
+    }
+
+    /// applies the value factor to the money a grain sells for
+    pub(crate) fn value(&self, value: Currency) -> Currency {
+        let factor = self.value_factor();
+        if factor == 1.0 {
+            value
+        } else {
+            Currency::from_f64(value.as_f64() * factor)
+        }
+    }
+
+    /// applies the boost of a sand particle to the money one of its grains sells for
+    pub(crate) fn boost(&self, particle: SandParticle, value: Currency) -> Currency {
+        match self.boosts.get(&particle) {
+            Some(boost) => Currency::from_f64(value.as_f64() * boost),
+            None => value,
+        }
+    }
+
+    /// applies the container scale to the size of the container
+    /// the container always holds at least one grain
+    pub(crate) fn size(&self, size: u32) -> u32 {
+        if self.container_scale == 1.0 {
+            size
+        } else {
+            ((size as f64 * self.container_scale).round() as u32).max(1)
+        }
+    }
+
+    /// returns how far a grain that rested on the ground for age seconds has decayed,
+    /// from 0 for fresh sand to 1 for sand about to be lost, always 0 without decay
+    pub(crate) fn decay(&self, age: f32) -> f32 {
+        match self.decay_time {
+            Some(limit) if limit > 0.0 => (age / limit).clamp(0.0, 1.0),
+            Some(_) => 1.0,
+            None => 0.0,
+        }
+    }
+}
+
+/// Tests for the challenges
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    // Challenge tests
+    #[test]
+    fn test_challenge_modifiers() {
+        let rules = Challenge::NoAutoclicker.modifiers();
+        assert!(rules.hides(Upgrade::AUTO_CLICKER));
+        assert!(!rules.hides(Upgrade::BIGGER_CONTAINER));
+        let rules = Challenge::ExpensiveTaste.modifiers();
+        assert_eq!(rules.cost(Currency(50)), Currency(150));
+        assert_eq!(rules.value(Currency(50)), Currency(50));
+        assert_eq!(
+            Challenge::TinyContainer.modifiers().container_size,
+            Some(TINY_CONTAINER)
+        );
+        // every challenge changes the rules
+        assert!(Challenge::iter().all(|challenge| challenge.modifiers() != Modifiers::default()));
+    }
+    #[test]
+    fn test_challenge_rewards() {
+        assert_eq!(Modifiers::rewards(&HashSet::new()), Modifiers::default());
+        let rules = Modifiers::rewards(&Challenge::iter().collect());
+        assert_eq!(
+            rules.value(Currency(100)),
+            Currency::from_f64(100.0 * (1.0 + 3.0 * CHALLENGE_REWARD))
+        );
+        // the exact amounts stay exact without a multiplier
+        assert_eq!(Modifiers::default().cost(Currency::MAX), Currency::MAX);
+        assert_eq!(Modifiers::default().size(u32::MAX), u32::MAX);
+    }
+    #[test]
+    fn test_modifiers_boost_size() {
+        let rules = Modifiers {
+            boosts: HashMap::from([(SandParticle::Iron, 5.0)]),
+            container_scale: 0.5,
+            ..Modifiers::default()
+        };
+        assert_eq!(rules.boost(SandParticle::Iron, Currency(40)), Currency(200));
+        assert_eq!(rules.boost(SandParticle::Sand, Currency(1)), Currency(1));
+        assert_eq!(rules.size(50), 25);
+        assert_eq!(rules.size(1), 1);
+    }
+    #[test]
+    fn test_modifiers_ng_plus() {
+        let rules = Modifiers {
+            ng_plus: 2,
+            cost_multiplier: CHALLENGE_COSTS,
+            value_multiplier: 2.0,
+            ..Modifiers::default()
+        };
+        // the New Game+ scaling composes with the other multipliers
+        assert_eq!(
+            rules.cost(Currency(10)),
+            Currency::from_f64(10.0 * CHALLENGE_COSTS * NG_PLUS_COST * NG_PLUS_COST)
+        );
+        assert_eq!(
+            rules.value(Currency(10)),
+            Currency::from_f64(20.0 * NG_PLUS_VALUE * NG_PLUS_VALUE)
+        );
+        // any amount of New Game+ stops at the most money there can be
+        let rules = Modifiers {
+            ng_plus: u32::MAX,
+            ..Modifiers::default()
+        };
+        assert_eq!(rules.cost(Currency(1)), Currency::MAX);
+        assert_eq!(rules.value(Currency::MAX), Currency::MAX);
+        assert_eq!(rules.cost(Currency::ZERO), Currency::ZERO);
+    }
+    #[test]
+    fn test_modifiers_decay() {
+        assert_eq!(Modifiers::default().decay(1000.0), 0.0);
+        let rules = Modifiers {
+            decay_time: Some(60.0),
+            ..Modifiers::default()
+        };
+        assert_eq!(rules.decay(0.0), 0.0);
+        assert_eq!(rules.decay(30.0), 0.5);
+        assert_eq!(rules.decay(90.0), 1.0);
+    }
+}
@@ -0,0 +1,226 @@
+//! Developer console for testing the game, only enabled with `--dev`
+
+use crate::{
+    CONSOLE_LINES, currency::Currency, game::GameAction, particle::SandParticle,
+    replay::action_text, upgrade::upgrade_table,
+};
+use std::collections::VecDeque;
+use strum::IntoEnumIterator;
+
+// Commands the console understands, printed for help and unknown commands
+pub(crate) const USAGE: &str = "commands: give <money> | set upgrade <upgrade> <level> | spawn <sand> <count> | clear | seed <number> | help";
+
+/// Commands typed into the console
+/// * Help: Print the commands the console understands.
+/// * Run: Apply the game action.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Command {
+    Help,
+    Run(GameAction),
+}
+
+/// reads a console command, or the reason it can't be run
+/// the names of upgrades and sand particles are not case sensitive
+pub(crate) fn parse_command(line: &str) -> Result<Command, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let number = |word: &str| {
+        word.parse::<u128>()
+            .map_err(|_| format!("`{}` isn't a number", word))
+    };
+    Ok(match words[..] {
+        ["help"] => Command::Help,
+        ["give", money] => Command::Run(GameAction::Give {
+            money: Currency(number(money)?),
+        }),
+        ["set", "upgrade", upgrade, level] => {
+            let upgrade = upgrade_table()
+                .iter()
+                .find(|known| format!("{:?}", known).eq_ignore_ascii_case(upgrade))
+                .ok_or_else(|| format!("unknown upgrade `{}`", upgrade))?;
+            Command::Run(GameAction::SetLevel {
+                upgrade,
+                level: number(level)?.min(u32::MAX as u128) as u32,
+            })
+        }
+        ["spawn", particle, amount] => {
+            let particle = SandParticle::iter()
+                .find(|known| format!("{:?}", known).eq_ignore_ascii_case(particle))
+                .ok_or_else(|| format!("unknown sand `{}`", particle))?;
+            Command::Run(GameAction::Spawn {
+                particle,
+                amount: number(amount)?.min(u32::MAX as u128) as u32,
+            })
+        }
+        ["clear"] => Command::Run(GameAction::Clear),
+        ["seed", seed] => {
+            Command::Run(GameAction::Seed(number(seed)?.min(u64::MAX as u128) as u64))
+        }
+        _ => return Err(format!("unknown command `{}`", line.trim())),
+    })
+}
+
+/// Drop-down console for developers
+/// * open: flag to show/hide the console
+/// * input: command being typed
+/// * output: printed lines, oldest first
+/// * history: commands typed so far, oldest first
+/// * browsing: index into the history while going through it with the arrow keys
+#[derive(Debug, Default)]
+pub(crate) struct Console {
+    pub(crate) open: bool,
+    pub(crate) input: String,
+    pub(crate) output: VecDeque<String>,
+    pub(crate) history: Vec<String>,
+    pub(crate) browsing: Option<usize>,
+}
+
+/// Implementation of methods for the Console struct
+/// * print: adds a line to the output, dropping the oldest one when full
+/// * submit: runs the typed command, returns the game action it asks for
+/// * previous: brings back the previous command from the history
+/// * next: brings back the next command from the history
+impl Console {
+    /// adds a line to the output, dropping the oldest one when full
+    pub(crate) fn print(&mut self, line: String) {
+        if self.output.len() >= CONSOLE_LINES {
+            self.output.pop_front();
+        }
+        self.output.push_back(line);
+    }
+
+    /// runs the typed command, returns the game action it asks for
+    /// the command is echoed, and mistakes print the usage
+    pub(crate) fn submit(&mut self) -> Option<GameAction> {
+        let line = std::mem::take(&mut self.input);
+        self.browsing = None;
+        if line.trim().is_empty() {
+            return None;
+        }
+        self.print(format!("> {}", line.trim()));
+        self.history.push(line.trim().to_string());
+        match parse_command(&line) {
+            Ok(Command::Help) => {
+                self.print(USAGE.to_string());
+                None
+            }
+            Ok(Command::Run(action)) => {
+                self.print(format!("ok: {}", action_text(&action)));
+                Some(action)
+            }
+            Err(err) => {
+                self.print(err);
+                self.print(USAGE.to_string());
+                None
+            }
+        }
+    }
+
+    /// brings back the previous command from the history
+    pub(crate) fn previous(&mut self) {
+        let index = match self.browsing {
+            Some(index) => index.saturating_sub(1),
+            None if !self.history.is_empty() => self.history.len() - 1,
+            None => return,
+        };
+        self.browsing = Some(index);
+        self.input = self.history[index].clone();
+    }
+
+    /// brings back the next command from the history
+    /// going past the newest one empties the input
+    pub(crate) fn next(&mut self) {
+        let Some(index) = self.browsing else {
+            return;
+        };
+        if index + 1 < self.history.len() {
+            self.browsing = Some(index + 1);
+            self.input = self.history[index + 1].clone();
+        } else {
+            self.browsing = None;
+            self.input.clear();
+        }
+    }
+}
+
+/// Tests for the developer console
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::upgrade::Upgrade;
+
+    // Command tests
+    #[test]
+    fn test_console_parse_command() {
+        assert_eq!(
+            parse_command("give 100000"),
+            Ok(Command::Run(GameAction::Give {
+                money: Currency(100000)
+            }))
+        );
+        assert_eq!(
+            parse_command("set upgrade autoclicker 10"),
+            Ok(Command::Run(GameAction::SetLevel {
+                upgrade: Upgrade::AUTO_CLICKER,
+                level: 10
+            }))
+        );
+        assert_eq!(
+            parse_command("  spawn DIAMOND 50 "),
+            Ok(Command::Run(GameAction::Spawn {
+                particle: SandParticle::Diamond,
+                amount: 50
+            }))
+        );
+        assert_eq!(parse_command("clear"), Ok(Command::Run(GameAction::Clear)));
+        assert_eq!(
+            parse_command("seed 1234"),
+            Ok(Command::Run(GameAction::Seed(1234)))
+        );
+        assert_eq!(parse_command("help"), Ok(Command::Help));
+        assert_eq!(
+            parse_command("give lots"),
+            Err("`lots` isn't a number".to_string())
+        );
+        assert_eq!(
+            parse_command("spawn mud 5"),
+            Err("unknown sand `mud`".to_string())
+        );
+        assert_eq!(
+            parse_command("fly away"),
+            Err("unknown command `fly away`".to_string())
+        );
+    }
+
+    // Console tests
+    #[test]
+    fn test_console_submit() {
+        let mut console = Console {
+            input: "give 5".to_string(),
+            ..Console::default()
+        };
+        assert_eq!(
+            console.submit(),
+            Some(GameAction::Give { money: Currency(5) })
+        );
+        assert!(console.input.is_empty());
+        // unknown commands print the usage
+        console.input = "dance".to_string();
+        assert_eq!(console.submit(), None);
+        assert_eq!(console.output.back().map(String::as_str), Some(USAGE));
+        assert_eq!(console.history, ["give 5", "dance"]);
+        // the arrow keys go through the history
+        console.previous();
+        assert_eq!(console.input, "dance");
+        console.previous();
+        console.previous();
+        assert_eq!(console.input, "give 5");
+        console.next();
+        console.next();
+        assert!(console.input.is_empty());
+        // the output is bounded
+        for i in 0..CONSOLE_LINES + 5 {
+            console.print(i.to_string());
+        }
+        assert_eq!(console.output.len(), CONSOLE_LINES);
+    }
+}
@@ -0,0 +1,320 @@
+//! Sand in the container: the grains and the counts of each sand particle
+
+use crate::{
+    grain::{Grain, GrainPool},
+    particle::SandParticle,
+};
+use rayon::prelude::*;
+use std::{
+    collections::HashMap,
+    ops::{Index, IndexMut},
+};
+
+/// Sand in the container, kept in one place so the grains and their counts always agree
+/// * grains: pool of the grain instances in the container
+/// * counts: amount of each sand particle in the container, compacted grains included
+/// * compacted: counts of the old settled grains folded out of the pool, by sand particle
+/// * compacted_bonus: extra money the compacted grains sell for, from click combos
+#[derive(Debug, Default)]
+pub(crate) struct Container {
+    grains: GrainPool,
+    counts: HashMap<SandParticle, u32>,
+    compacted: HashMap<SandParticle, u32>,
+    compacted_bonus: f64,
+}
+
+/// Implementation of methods for the Container struct
+/// * add: adds a grain and counts its sand particle
+/// * remove: takes a grain out and uncounts its sand particle
+/// * compact: folds the oldest settled grains out of the pool
+/// * take_all: empties the container, returning the grains that were in the pool
+/// * clear: empties the container
+/// * count: returns the amount of a sand particle in the container
+/// * counts: returns the amount of each sand particle in the container
+/// * amount: returns the amount of grains in the container, compacted ones included
+/// * compacted: returns the counts of the compacted grains
+/// * compacted_amount: returns the amount of compacted grains
+/// * bonus: returns the extra money all the sand sells for
+/// * len: returns the amount of grains in the pool
+/// * is_empty: checks if there is no sand in the container
+/// * pool: returns the pool of the grains
+/// * get: returns the grain in a slot of the pool, if it holds one
+/// * get_mut: returns the grain in a slot of the pool mutably, if it holds one
+/// * iter: returns the grains in the pool
+/// * iter_mut: returns the grains in the pool, mutably
+/// * par_iter_mut: returns the grains in the pool, mutably and split over the threads
+/// * indexed: returns the grains in the pool with their slots
+/// * check: checks that the counts match the grains
+impl Container {
+    /// adds a grain and counts its sand particle
+    /// returns the slot of the grain
+    pub(crate) fn add(&mut self, grain: Grain) -> usize {
+        *self.counts.entry(grain.particle).or_insert(0) += 1;
+        let slot = self.grains.push(grain);
+        self.check();
+        slot
+    }
+
+    /// takes the grain out of the slot and uncounts its sand particle
+    /// returns None if the slot holds no grain
+    pub(crate) fn remove(&mut self, slot: usize) -> Option<Grain> {
+        let grain = self.grains.remove(slot)?;
+        uncount(&mut self.counts, grain.particle, 1);
+        self.check();
+        Some(grain)
+    }
+
+    /// folds the oldest settled grains out of the pool once it holds more than max grains
+    /// the compacted sand keeps its count, so it still fills the container and sells
+    pub(crate) fn compact(&mut self, max: usize) {
+        if max == 0 || self.grains.len() <= max {
+            return;
+        }
+        let excess = self.grains.len() - max;
+        for slot in self.grains.oldest(excess, Grain::is_done) {
+            if let Some(grain) = self.grains.remove(slot) {
+                *self.compacted.entry(grain.particle).or_insert(0) += 1;
+                self.compacted_bonus += grain.bonus;
+            }
+        }
+        self.check();
+    }
+
+    /// empties the container, returning copies of the grains that were in the pool
+    /// the compacted sand has no grains to return
+    pub(crate) fn take_all(&mut self) -> Vec<Grain> {
+        let grains = self.grains.take_all();
+        self.clear();
+        grains
+    }
+
+    /// empties the container, keeping the slots of the pool for reuse
+    pub(crate) fn clear(&mut self) {
+        self.grains.clear();
+        self.counts.clear();
+        self.compacted.clear();
+        self.compacted_bonus = 0.0;
+        self.check();
+    }
+
+    /// returns the amount of the sand particle in the container
+    pub(crate) fn count(&self, particle: SandParticle) -> u32 {
+        self.counts.get(&particle).copied().unwrap_or(0)
+    }
+
+    /// returns the amount of each sand particle in the container
+    /// particles that aren't in the container have no entry
+    pub(crate) fn counts(&self) -> &HashMap<SandParticle, u32> {
+        &self.counts
+    }
+
+    /// returns the amount of grains in the container
+    /// the compacted grains still take up their space
+    pub(crate) fn amount(&self) -> u32 {
+        self.grains.len() as u32 + self.compacted_amount()
+    }
+
+    /// returns the counts of the compacted grains, by sand particle
+    pub(crate) fn compacted(&self) -> &HashMap<SandParticle, u32> {
+        &self.compacted
+    }
+
+    /// returns the amount of compacted grains
+    pub(crate) fn compacted_amount(&self) -> u32 {
+        self.compacted.values().sum()
+    }
+
+    /// returns the extra money the sand in the container sells for, from click combos
+    pub(crate) fn bonus(&self) -> f64 {
+        self.grains.iter().map(|grain| grain.bonus).sum::<f64>() + self.compacted_bonus
+    }
+
+    /// returns the amount of grains in the pool, without the compacted ones
+    pub(crate) fn len(&self) -> usize {
+        self.grains.len()
+    }
+
+    /// checks if there is no sand in the container
+    #[cfg(test)]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// returns the pool of the grains
+    #[cfg(test)]
+    pub(crate) fn pool(&self) -> &GrainPool {
+        &self.grains
+    }
+
+    /// returns the grain in the slot of the pool, None if the slot holds no grain
+    pub(crate) fn get(&self, slot: usize) -> Option<&Grain> {
+        self.grains.get(slot)
+    }
+
+    /// returns the grain in the slot of the pool mutably, None if the slot holds no grain
+    /// the sand particle of the grain must not be changed, it is counted
+    pub(crate) fn get_mut(&mut self, slot: usize) -> Option<&mut Grain> {
+        self.grains.get_mut(slot)
+    }
+
+    /// returns the grains in the pool
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Grain> {
+        self.grains.iter()
+    }
+
+    /// returns the grains in the pool mutably
+    /// the sand particle of a grain must not be changed, it is counted
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut Grain> {
+        self.grains.iter_mut()
+    }
+
+    /// returns the grains in the pool mutably and split over the threads
+    pub(crate) fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = &mut Grain> {
+        self.grains.par_iter_mut()
+    }
+
+    /// returns the grains in the pool with their slots
+    pub(crate) fn indexed(&self) -> impl Iterator<Item = (usize, &Grain)> {
+        self.grains.indexed()
+    }
+
+    /// checks in debug builds that the counts match the grains in the pool and the compacted ones
+    fn check(&self) {
+        debug_assert_eq!(
+            self.counts.values().sum::<u32>(),
+            self.amount(),
+            "the sand particle counts drifted from the grains"
+        );
+        debug_assert!(
+            self.counts.values().all(|&count| count > 0),
+            "a sand particle without grains is still counted"
+        );
+    }
+}
+
+impl Index<usize> for Container {
+    type Output = Grain;
+
+    fn index(&self, slot: usize) -> &Grain {
+        &self.grains[slot]
+    }
+}
+
+impl IndexMut<usize> for Container {
+    fn index_mut(&mut self, slot: usize) -> &mut Grain {
+        &mut self.grains[slot]
+    }
+}
+
+impl FromIterator<Grain> for Container {
+    fn from_iter<I: IntoIterator<Item = Grain>>(grains: I) -> Self {
+        let mut container = Self::default();
+        for grain in grains {
+            container.add(grain);
+        }
+        container
+    }
+}
+
+/// takes an amount of the sand particle off its count
+/// the entry is dropped once no grain of the particle is left
+fn uncount(counts: &mut HashMap<SandParticle, u32>, particle: SandParticle, amount: u32) {
+    if let Some(count) = counts.get_mut(&particle) {
+        *count = count.saturating_sub(amount);
+        if *count == 0 {
+            counts.remove(&particle);
+        }
+    }
+}
+
+/// Tests for the container
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GRAIN_SIZE;
+    use ggez::graphics::Color;
+    use quickcheck::quickcheck;
+
+    /// returns a grain of the sand particle at (x, y)
+    fn grain_of(particle: SandParticle, x: f32, y: f32) -> Grain {
+        let mut grain = Grain::new(x, y, GRAIN_SIZE, Color::WHITE);
+        grain.particle = particle;
+        grain
+    }
+
+    // Container tests
+    #[test]
+    fn test_container_counts() {
+        let mut container = Container::default();
+        let sand = container.add(grain_of(SandParticle::Sand, 0.0, 0.0));
+        container.add(grain_of(SandParticle::Gold, 10.0, 0.0));
+        container.add(grain_of(SandParticle::Gold, 20.0, 0.0));
+        assert_eq!(container.count(SandParticle::Gold), 2);
+        // the last grain of a particle drops its entry
+        assert!(container.remove(sand).is_some());
+        assert!(container.remove(sand).is_none());
+        assert_eq!(container.counts().get(&SandParticle::Sand), None);
+        assert_eq!(container.amount(), 2);
+        assert_eq!(container.take_all().len(), 2);
+        assert!(container.is_empty());
+    }
+    #[test]
+    fn test_container_compact() {
+        let mut container: Container = (0..10)
+            .map(|i| {
+                let mut grain = grain_of(SandParticle::Quartz, i as f32 * 10.0, 0.0);
+                grain.settle(100.0);
+                grain
+            })
+            .collect();
+        container.compact(4);
+        // the compacted grains keep their counts
+        assert_eq!(container.len(), 4);
+        assert_eq!(container.compacted_amount(), 6);
+        assert_eq!(container.count(SandParticle::Quartz), 10);
+        assert_eq!(container.amount(), 10);
+        container.clear();
+        assert_eq!(container.amount(), 0);
+        assert!(container.compacted().is_empty());
+    }
+
+    quickcheck! {
+        fn prop_container_counts_match(ops: Vec<(u8, u8)>) -> bool {
+            let mut container = Container::default();
+            let mut slots = Vec::new();
+            for (op, value) in ops {
+                match op % 5 {
+                    // drops are the most common, so the container fills up
+                    0 | 1 => {
+                        let particle = SandParticle::from_roll(value as u32, u32::MAX);
+                        slots.push(container.add(grain_of(particle, value as f32, 0.0)));
+                    }
+                    2 if !slots.is_empty() => {
+                        let slot = slots.swap_remove(value as usize % slots.len());
+                        container.remove(slot);
+                    }
+                    3 => {
+                        // settled grains, so they can be compacted
+                        for grain in container.iter_mut() {
+                            grain.settle(100.0);
+                        }
+                        container.compact(value as usize % 8);
+                        slots.retain(|&slot| container.pool().alive[slot]);
+                    }
+                    4 if value % 4 == 0 => {
+                        container.take_all();
+                        slots.clear();
+                    }
+                    _ => {}
+                }
+                let counted: u32 = container.counts().values().sum();
+                if counted != container.amount()
+                    || container.counts().values().any(|&count| count == 0)
+                {
+                    return false;
+                }
+            }
+            true
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Money of the game, big enough for the deep late game
+
+use std::{
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign, Div, Mul, Sub, SubAssign},
+    str::FromStr,
+};
+
+/// Amount of money, which never overflows or goes below zero
+/// the arithmetic saturates at zero and at the largest amount
+/// * 0: amount of whole dollars
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Currency(pub u128);
+
+/// Implementation of methods for the Currency struct
+/// * ZERO: no money at all
+/// * MAX: the most money there can be
+/// * from_f64: rounds a fractional amount to whole dollars
+/// * as_f64: returns the amount as a float, for ratios and display
+/// * checked_add: adds, or None past the largest amount
+/// * checked_sub: subtracts, or None if the money doesn't cover it
+/// * div_ceil: divides, rounding up, None when dividing by zero
+impl Currency {
+    /// no money at all
+    pub const ZERO: Currency = Currency(0);
+
+    /// the most money there can be
+    pub const MAX: Currency = Currency(u128::MAX);
+
+    /// rounds a fractional amount to whole dollars
+    /// negative amounts are zero, and amounts past the largest are the largest
+    pub fn from_f64(amount: f64) -> Self {
+        // float to integer casts saturate, and NaN becomes zero
+        Currency(amount.round() as u128)
+    }
+
+    /// returns the amount as a float, for ratios and display
+    pub fn as_f64(self) -> f64 {
+        self.0 as f64
+    }
+
+    /// adds, or None if the sum is past the largest amount
+    pub fn checked_add(self, other: Currency) -> Option<Currency> {
+        self.0.checked_add(other.0).map(Currency)
+    }
+
+    /// subtracts, or None if the money doesn't cover it
+    pub fn checked_sub(self, other: Currency) -> Option<Currency> {
+        self.0.checked_sub(other.0).map(Currency)
+    }
+
+    /// divides, rounding up, None when dividing by zero
+    pub fn div_ceil(self, other: Currency) -> Option<u128> {
+        (other.0 > 0).then(|| self.0.div_ceil(other.0))
+    }
+}
+
+/// Adds money, stopping at the largest amount
+impl Add for Currency {
+    type Output = Currency;
+
+    fn add(self, other: Currency) -> Currency {
+        Currency(self.0.saturating_add(other.0))
+    }
+}
+
+/// Adds money in place, stopping at the largest amount
+impl AddAssign for Currency {
+    fn add_assign(&mut self, other: Currency) {
+        *self = *self + other;
+    }
+}
+
+/// Subtracts money, stopping at zero
+impl Sub for Currency {
+    type Output = Currency;
+
+    fn sub(self, other: Currency) -> Currency {
+        Currency(self.0.saturating_sub(other.0))
+    }
+}
+
+/// Subtracts money in place, stopping at zero
+impl SubAssign for Currency {
+    fn sub_assign(&mut self, other: Currency) {
+        *self = *self - other;
+    }
+}
+
+/// Multiplies money by a count, stopping at the largest amount
+impl Mul<u32> for Currency {
+    type Output = Currency;
+
+    fn mul(self, count: u32) -> Currency {
+        Currency(self.0.saturating_mul(count as u128))
+    }
+}
+
+/// Divides money into equal parts, rounding down
+/// dividing by zero leaves nothing
+impl Div<u64> for Currency {
+    type Output = Currency;
+
+    fn div(self, parts: u64) -> Currency {
+        Currency(self.0.checked_div(parts as u128).unwrap_or(0))
+    }
+}
+
+/// Adds up amounts of money, stopping at the largest amount
+impl Sum for Currency {
+    fn sum<I: Iterator<Item = Currency>>(iter: I) -> Currency {
+        iter.fold(Currency::ZERO, Add::add)
+    }
+}
+
+/// Adds up borrowed amounts of money
+impl<'a> Sum<&'a Currency> for Currency {
+    fn sum<I: Iterator<Item = &'a Currency>>(iter: I) -> Currency {
+        iter.copied().sum()
+    }
+}
+
+/// Shows the exact amount of whole dollars
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Reads the exact amount of whole dollars, as written in the save file
+impl FromStr for Currency {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        text.parse().map(Currency)
+    }
+}
+
+/// Tests for the money arithmetic
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Currency tests
+    #[test]
+    fn test_currency_saturates() {
+        let old_max = Currency(i64::MAX as u128);
+        // the old i64 money wrapped around here
+        assert_eq!(old_max + Currency(1), Currency(i64::MAX as u128 + 1));
+        assert_eq!(old_max * 4, Currency(i64::MAX as u128 * 4));
+        assert_eq!(Currency::MAX + Currency(1), Currency::MAX);
+        assert_eq!(Currency::MAX * 2, Currency::MAX);
+        assert_eq!(Currency(5) - Currency(7), Currency::ZERO);
+        assert_eq!(Currency::MAX.checked_add(Currency(1)), None);
+        assert_eq!(Currency(5).checked_add(Currency(7)), Some(Currency(12)));
+        assert_eq!(Currency(5).checked_sub(Currency(7)), None);
+        assert_eq!(Currency(7).checked_sub(Currency(5)), Some(Currency(2)));
+        let total: Currency = [Currency::MAX, Currency::MAX].iter().sum();
+        assert_eq!(total, Currency::MAX);
+    }
+    #[test]
+    fn test_currency_conversions() {
+        assert_eq!(Currency::from_f64(2.5), Currency(3));
+        assert_eq!(Currency::from_f64(-10.0), Currency::ZERO);
+        assert_eq!(Currency::from_f64(f64::NAN), Currency::ZERO);
+        assert_eq!(Currency::from_f64(f64::INFINITY), Currency::MAX);
+        assert_eq!(Currency(7) / 2, Currency(3));
+        assert_eq!(Currency(7) / 0, Currency::ZERO);
+        assert_eq!(Currency(7).div_ceil(Currency(2)), Some(4));
+        assert_eq!(Currency(7).div_ceil(Currency::ZERO), None);
+        let text = (i64::MAX as u128 * 10).to_string();
+        assert_eq!(
+            text.parse::<Currency>(),
+            Ok(Currency(i64::MAX as u128 * 10))
+        );
+        assert_eq!(Currency(1234).to_string(), "1234");
+        assert!("-5".parse::<Currency>().is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! Daily runs seeded from the date, and the best score of each day
+
+use crate::{
+    DAILY_BOOSTS, DAILY_SCALES, DAILY_WEEKS, blitz::format_date, challenge::Modifiers,
+    currency::Currency, lang::tr_args, particle::SandParticle,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Rules of the daily run of a date, the same for everyone playing that day
+/// * seed: seed of the random numbers, from the date
+/// * boosted: sand particle that is worth more today
+/// * boost: factor on the money the boosted sand particle sells for
+/// * container_scale: factor on the size of the container
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DailyRules {
+    pub(crate) seed: u64,
+    pub(crate) boosted: SandParticle,
+    pub(crate) boost: f64,
+    pub(crate) container_scale: f64,
+}
+
+/// Implementation of methods for the DailyRules struct
+/// * for_date: returns the rules of the daily run of a date
+/// * modifiers: returns the rules the simulation plays the daily run with
+/// * desc: returns the rules for the player to read
+impl DailyRules {
+    /// returns the rules of the daily run of a date
+    /// each rule is picked by its own bits of the seed, so the rules only depend on the date
+    pub(crate) fn for_date(date: &str) -> Self {
+        let seed = daily_seed(date);
+        // the boosted particle is one of the first nine, which a run of ten minutes can reach
+        let tier = (seed % 9) as u32;
+        Self {
+            seed,
+            boosted: SandParticle::from_u32(tier).unwrap_or(SandParticle::Sand),
+            boost: DAILY_BOOSTS[(seed >> 8) as usize % DAILY_BOOSTS.len()],
+            container_scale: DAILY_SCALES[(seed >> 16) as usize % DAILY_SCALES.len()],
+        }
+    }
+
+    /// returns the rules the simulation plays the daily run with
+    pub(crate) fn modifiers(&self) -> Modifiers {
+        Modifiers {
+            boosts: HashMap::from([(self.boosted, self.boost)]),
+            container_scale: self.container_scale,
+            ..Modifiers::default()
+        }
+    }
+
+    /// returns the rules for the player to read, like "Iron is worth 5x, containers 50% smaller"
+    pub(crate) fn desc(&self) -> String {
+        let percent = ((self.container_scale - 1.0).abs() * 100.0).round();
+        let key = if self.container_scale < 1.0 {
+            "daily-rules-smaller"
+        } else {
+            "daily-rules-larger"
+        };
+        tr_args(
+            key,
+            &[
+                ("particle", &self.boosted.name()),
+                ("boost", &self.boost),
+                ("percent", &percent),
+            ],
+        )
+    }
+}
+
+/// returns the seed of the daily run of a date, like 2025-12-09
+/// a 64-bit FNV-1a hash of the date, so every version of the game gets the same seed
+pub(crate) fn daily_seed(date: &str) -> u64 {
+    date.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// returns the day of the week of a day counted from 1970-01-01, 0 for Monday
+pub(crate) fn weekday(day: u64) -> u64 {
+    // 1970-01-01 was a Thursday
+    (day + 3) % 7
+}
+
+/// returns the days of the calendar in the statistics window, Monday to Sunday,
+/// for the last DAILY_WEEKS weeks up to the week of today
+pub(crate) fn calendar(today: u64) -> Vec<u64> {
+    let start = (today - weekday(today)).saturating_sub(7 * (DAILY_WEEKS - 1));
+    (start..start + 7 * DAILY_WEEKS).collect()
+}
+
+/// Best daily run score of each day the player played
+/// * best: best money earned in the daily run of each date
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct DailyScores {
+    pub(crate) best: BTreeMap<String, Currency>,
+}
+
+/// Implementation of methods for the DailyScores struct
+/// * record: keeps a score if it is the best of its date
+/// * get: returns the best score of a day
+/// * to_save: returns the scores as save file lines
+/// * load_line: reads a save file line, returns false if it isn't a score
+impl DailyScores {
+    /// keeps a score if it is the best of its date
+    /// returns true if it is
+    pub(crate) fn record(&mut self, date: &str, money: Currency) -> bool {
+        if self.best.get(date).is_some_and(|best| *best >= money) {
+            return false;
+        }
+        self.best.insert(date.to_string(), money);
+        true
+    }
+
+    /// returns the best score of a day counted from 1970-01-01
+    pub(crate) fn get(&self, day: u64) -> Option<Currency> {
+        self.best.get(&format_date(day)).copied()
+    }
+
+    /// returns the scores as save file lines
+    pub(crate) fn to_save(&self) -> Vec<String> {
+        self.best
+            .iter()
+            .map(|(date, money)| format!("daily.{}={}", date, money))
+            .collect()
+    }
+
+    /// reads a save file line, returns false if it isn't a score
+    pub(crate) fn load_line(&mut self, key: &str, value: &str) -> bool {
+        let Some(date) = key.strip_prefix("daily.") else {
+            return false;
+        };
+        if let Ok(money) = value.parse() {
+            self.record(date, money);
+        }
+        true
+    }
+}
+
+/// Tests for the daily runs
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // DailyRules tests
+    #[test]
+    fn test_daily_seed() {
+        // the seed never changes for a date
+        assert_eq!(daily_seed(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(daily_seed("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(daily_seed("2025-12-09"), daily_seed("2025-12-09"));
+        assert_ne!(daily_seed("2025-12-09"), daily_seed("2025-12-10"));
+    }
+    #[test]
+    fn test_daily_rules() {
+        let rules = DailyRules::for_date("2025-12-09");
+        assert_eq!(rules, DailyRules::for_date("2025-12-09"));
+        assert!(rules.boosted.level() < 9);
+        assert!(DAILY_BOOSTS.contains(&rules.boost));
+        assert!(DAILY_SCALES.contains(&rules.container_scale));
+        let modifiers = rules.modifiers();
+        assert_eq!(modifiers.boosts.get(&rules.boosted), Some(&rules.boost));
+        assert_eq!(modifiers.container_scale, rules.container_scale);
+        // the days don't all play the same
+        let days: Vec<DailyRules> = (20_000..20_030)
+            .map(|day| DailyRules::for_date(&format_date(day)))
+            .collect();
+        assert!(days.iter().any(|day| day.boosted != rules.boosted));
+        assert!(
+            days.iter()
+                .any(|day| day.container_scale != rules.container_scale)
+        );
+    }
+    #[test]
+    fn test_daily_calendar() {
+        // 2025-12-09 was a Tuesday
+        assert_eq!(weekday(20_431), 1);
+        let days = calendar(20_431);
+        assert_eq!(days.len() as u64, 7 * DAILY_WEEKS);
+        assert_eq!(weekday(days[0]), 0);
+        assert_eq!(days[days.len() - 7], 20_430);
+    }
+
+    // DailyScores tests
+    #[test]
+    fn test_daily_scores() {
+        let mut scores = DailyScores::default();
+        assert!(scores.record("2025-12-09", Currency(500)));
+        assert!(!scores.record("2025-12-09", Currency(400)));
+        assert!(scores.record("2025-12-10", Currency(100)));
+        assert_eq!(scores.get(20_431), Some(Currency(500)));
+        let mut loaded = DailyScores::default();
+        for line in scores.to_save() {
+            let (key, value) = line.split_once('=').unwrap();
+            assert!(loaded.load_line(key, value));
+        }
+        assert_eq!(loaded, scores);
+        // loading a save twice keeps the best
+        assert!(loaded.load_line("daily.2025-12-09", "300"));
+        assert!(!loaded.load_line("money", "100"));
+        assert_eq!(loaded, scores);
+    }
+}
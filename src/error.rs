@@ -0,0 +1,59 @@
+//! Errors of the save and settings files
+
+use ggez::GameError;
+use std::{fmt, io, path::PathBuf};
+
+/// Errors from reading or writing the save and settings files
+/// * Read: The file couldn't be read.
+/// * Write: The file or its folder couldn't be written.
+#[derive(Debug)]
+pub(crate) enum SaveError {
+    Read(PathBuf, io::Error),
+    Write(PathBuf, io::Error),
+}
+
+/// Shows the file and the reason, for the notifications
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Read(path, err) | SaveError::Write(path, err) => {
+                write!(f, "{}: {}", path.display(), err)
+            }
+        }
+    }
+}
+
+/// Passes on the IO error as the cause
+impl std::error::Error for SaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SaveError::Read(_, err) | SaveError::Write(_, err) => Some(err),
+        }
+    }
+}
+
+/// Lets the save errors be passed on as ggez errors
+impl From<SaveError> for GameError {
+    fn from(err: SaveError) -> Self {
+        GameError::FilesystemError(err.to_string())
+    }
+}
+
+/// Tests for the save errors
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // SaveError tests
+    #[test]
+    fn test_save_error_display() {
+        let err = SaveError::Write(
+            PathBuf::from("save.txt"),
+            io::Error::new(io::ErrorKind::PermissionDenied, "denied"),
+        );
+        assert!(std::error::Error::source(&err).is_some());
+        assert_eq!(err.to_string(), "save.txt: denied");
+        let err: GameError = err.into();
+        assert!(matches!(err, GameError::FilesystemError(text) if text == "save.txt: denied"));
+    }
+}
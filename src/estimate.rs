@@ -0,0 +1,99 @@
+//! Estimates that help the player decide what to buy
+
+use crate::{AUTOCLICK_PERIOD, currency::Currency, upgrade::UpgradeEffect};
+
+/// Rates of the player the estimates are based on
+/// * grain_value: expected money of a dropped grain
+/// * next_grain_value: expected money of a dropped grain with the next ParticleTier level
+/// * income: money earned per second, averaged over the income window
+/// * clicks: clicks per second by hand, averaged over the session
+/// * combo: value multiplier of the sand dropped by hand
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub(crate) struct Rates {
+    pub(crate) grain_value: f64,
+    pub(crate) next_grain_value: f64,
+    pub(crate) income: f64,
+    pub(crate) clicks: f64,
+    pub(crate) combo: f64,
+}
+
+/// returns the expected money of a dropped grain at a ParticleTier level
+/// the sand is picked evenly from the unlocked particles, values are by tier,
+/// and tiers past the table drop the first particle
+pub(crate) fn grain_value(values: &[Currency], tier_level: u32) -> f64 {
+    let Some(first) = values.first() else {
+        return 0.0;
+    };
+    let unlocked = tier_level.max(1) as usize;
+    let total: f64 = (0..unlocked)
+        .map(|tier| values.get(tier).unwrap_or(first).as_f64())
+        .sum();
+    total / unlocked as f64
+}
+
+/// returns the extra money per second the next level of an effect earns
+/// None for the effects that don't earn money by themselves
+pub(crate) fn income_gain(effect: UpgradeEffect, rates: &Rates) -> Option<f64> {
+    match effect {
+        // the same grains are worth more
+        UpgradeEffect::ParticleTier if rates.grain_value > 0.0 => {
+            Some(rates.income * (rates.next_grain_value / rates.grain_value - 1.0))
+        }
+        UpgradeEffect::ParticleTier => Some(0.0),
+        // each level clicks once more every period
+        UpgradeEffect::AutoclickInterval => Some(rates.grain_value / AUTOCLICK_PERIOD as f64),
+        // each click drops one more grain
+        UpgradeEffect::DropCount => Some(rates.clicks * rates.grain_value * rates.combo),
+        UpgradeEffect::ContainerMultiplier | UpgradeEffect::SculptRadius => None,
+    }
+}
+
+/// returns the seconds until a cost is earned back at an income gain
+/// None without any gain
+pub(crate) fn payback(cost: Currency, gain: f64) -> Option<u64> {
+    (gain > 0.0).then(|| (cost.as_f64() / gain).ceil() as u64)
+}
+
+/// Tests for the estimates
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Estimate tests
+    #[test]
+    fn test_estimate_grain_value() {
+        let values = [Currency(1), Currency(3), Currency(8)];
+        assert_eq!(grain_value(&values, 0), 1.0);
+        assert_eq!(grain_value(&values, 1), 1.0);
+        assert_eq!(grain_value(&values, 2), 2.0);
+        assert_eq!(grain_value(&values, 3), 4.0);
+        // tiers past the table drop the first particle
+        assert_eq!(grain_value(&values, 4), 13.0 / 4.0);
+        assert_eq!(grain_value(&[], 2), 0.0);
+    }
+    #[test]
+    fn test_estimate_income_gain() {
+        let rates = Rates {
+            grain_value: 2.0,
+            next_grain_value: 3.0,
+            income: 10.0,
+            clicks: 0.5,
+            combo: 1.5,
+        };
+        assert_eq!(income_gain(UpgradeEffect::ParticleTier, &rates), Some(5.0));
+        assert_eq!(
+            income_gain(UpgradeEffect::AutoclickInterval, &rates),
+            Some(2.0 / AUTOCLICK_PERIOD as f64)
+        );
+        assert_eq!(income_gain(UpgradeEffect::DropCount, &rates), Some(1.5));
+        assert_eq!(income_gain(UpgradeEffect::SculptRadius, &rates), None);
+        let broke = Rates::default();
+        assert_eq!(income_gain(UpgradeEffect::ParticleTier, &broke), Some(0.0));
+    }
+    #[test]
+    fn test_estimate_payback() {
+        assert_eq!(payback(Currency(100), 0.4), Some(250));
+        assert_eq!(payback(Currency(100), 3.0), Some(34));
+        assert_eq!(payback(Currency(100), 0.0), None);
+    }
+}
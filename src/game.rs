@@ -0,0 +1,4439 @@
+//! Game state of Sand Drop Clicker and its event handling
+
+use crate::{
+    AUTOSAVE_TIME, BACKGROUND_FPS, BIG_SALE, BULK_BUY, DAY_LENGTH, EXPORT_DIR, FLASH_TIME, FPS,
+    FULL_FLASH_TIME, GAMEPAD_DEADZONE, GAMEPAD_SPEED, GRAIN_SIZE, INTENSE_FILL,
+    LAND_SOUND_INTERVAL, MAX_CATCH_UP, MAX_EFFECTS, MAX_TOUCHES, MONEY_POS, MUSIC_FADE,
+    PARTICLES_FILE, POUR_FADE, POUR_INTERVAL, SAVE_FILE, SCREENSHOT_DIR, SEMITONE, SETTINGS_FILE,
+    SHAKE_STRENGTH, SHAKE_TIME, TUTORIAL_GRAINS, UPGRADES_FILE, ZOOM_STEP,
+    audio::{Ducking, Music, Sound, Sounds},
+    blitz::{Blitz, BlitzScore, BlitzSummary, HighScores, format_date, today},
+    camera::Camera,
+    challenge::{Challenge, ChallengeRun, ChallengeSummary, Modifiers},
+    console::Console,
+    currency::Currency,
+    daily::DailyScores,
+    error::SaveError,
+    grain::{CollectingGrain, Effect, FloatingText},
+    input::{Action, BINDABLE_KEYS, MOUSE_BUTTONS, MouseAction, QUICK_BUY_KEYS},
+    lang::{Lang, set_lang, tr, tr_args},
+    particle::{PARTICLE_TABLE, ParticleTable, SandParticle},
+    profile::{Profile, SandboxTools},
+    replay::{Playback, Replay},
+    settings::{BackgroundMode, Settings, parse_enum},
+    sim::{SimEvent, SimState},
+    speedrun::{Speedrun, format_delta, format_split},
+    stats::Heatmap,
+    theme::Theme,
+    ui::{
+        HudText, Notifications, PlayerInfoKey, UpgradeLabel, grain_instances, grain_params,
+        grown_capacity, trail_params,
+    },
+    upgrade::{
+        UPGRADE_TABLE, Upgrade, UpgradeCategory, UpgradeEffect, UpgradeTable, set_upgrade_table,
+        upgrade_table,
+    },
+};
+use ggegui::Gui;
+use ggez::{
+    Context, GameError, GameResult,
+    audio::SoundSource,
+    event::{self, EventHandler, winit_event::TouchPhase},
+    graphics::{self, Color, DrawParam, Image, ImageFormat, InstanceArray, Rect, Text},
+    input::{
+        gamepad::{
+            GamepadId,
+            gilrs::{Axis, Button as PadButton},
+        },
+        keyboard::{KeyCode, KeyInput, KeyMods},
+        mouse,
+    },
+};
+use log::{debug, warn};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use strum::IntoEnumIterator;
+
+// Main game state
+// holds the game logic and GUI
+/// game state structure
+/// * sim: simulation of the money, upgrades, and grain physics
+/// * collecting: sold grains flying to the money counter
+/// * effects: short-lived sparkles around rare grains
+/// * effect_rng: random numbers of the sparkles and the screen shake, kept apart from the simulation's
+/// * floating_texts: rising texts showing the money gained
+/// * show_debug: flag to show/hide the debug overlay
+/// * paused: flag for whether the simulation is frozen
+/// * quit_dialog: flag to show the quit confirmation
+/// * quit_confirmed: flag for whether the player agreed to quit
+/// * physics_time: time spent on the grain physics in the last tick
+/// * gui_time: time spent building the GUI windows in the last update
+/// * mouse_pos: last known position of the mouse in the world
+/// * pointer: last known position of the mouse on the screen
+/// * camera: view onto the sand, zoomed and panned by the player
+/// * shake_timer: time left in the screen shake
+/// * flash_timer: time left in the screen flash
+/// * full_flash_timer: time left in the red flash of the capacity counter after a click into a full container
+/// * full_hint_shown: flag for whether the full container hint was shown since the container filled up
+/// * fullscreen: flag for whether the window is fullscreen
+/// * theme: current background theme
+/// * owned_themes: set of background themes the player bought
+/// * autosave_timer: time since the last save
+/// * save_path: location of the save file, if saving is enabled
+/// * settings_path: location of the settings file, if saving is enabled
+/// * screenshot_dir: folder for the screenshots, if saving is enabled
+/// * screenshot_requested: flag to capture the next frame
+/// * screenshots: screenshots being encoded on background threads
+/// * export_dir: folder for the exported statistics, if saving is enabled
+/// * notifications: short messages shown in the top-right corner
+/// * upgrade_labels: cached texts of the upgrade buy buttons and the values they show
+/// * game_text: cached text of the game info, rebuilt when its values change
+/// * player_text: cached text of the player info, rebuilt when its values change
+/// * settings: player settings
+/// * show_settings: flag to show/hide the settings window
+/// * reset_confirm: text typed to confirm resetting the progress
+/// * reset_profile: profile type the new game is played as when resetting
+/// * last_frame: time the last frame finished, for the FPS cap
+/// * show_guide: flag to show/hide the sand guide window
+/// * show_stats: flag to show/hide the statistics window
+/// * show_achievements: flag to show/hide the achievements window
+/// * tutorial: current step of the first-run tutorial, if it is running
+/// * drop_throttle: most grains a click drops, None for all of them
+/// * show_options: flag to show/hide the options window
+/// * gamepad_cursor: position of the gamepad drop cursor, once a gamepad was used
+/// * stick: tilt of the gamepad's left stick
+/// * selected_upgrade: upgrade picked with the gamepad, as an index into the buyable ones
+/// * touches: fingers on the play area
+/// * buy_count: levels an upgrade button buys, from the held modifier keys
+/// * show_history: flag to show/hide the history window
+/// * rebinding: action waiting for a new key in the settings window
+/// * gui: GUI instance for the game
+/// * batch: instance array for rendering grains
+/// * trail_batch: instance array for rendering the grain trails
+/// * effect_batch: instance array for rendering the sparkles
+/// * textured: flag for whether the batch uses the particle atlas
+/// * sounds: pre-loaded sound effects, None without an audio device
+/// * sound_queue: sounds to play in the next update, with their volume and pitch
+/// * land_sound_timer: time until landing grains can make a sound again
+/// * land_pending: rarest grain and amount of grains that landed since the last landing sound
+/// * music: looping music tracks, None without an audio device
+/// * music_mix: crossfade from the calm (0) to the intense (1) music
+/// * ducking: lowers the music for a moment after big sales
+/// * pour_volume: volume of the pouring sound, following the pour rate
+/// * focused: flag for whether the window has the focus
+/// * minimized: flag for whether the window is minimized
+/// * actions: game actions from the input handlers, applied in the next update
+/// * ticks: physics steps played since the session started, for the replays
+/// * recording: replay being recorded and the file it goes to, if recording
+/// * playback: replay being played back, if watching one
+/// * console: developer console, only there when started with `--dev`
+/// * heatmap: where the player dropped sand by hand this session
+/// * show_heatmap: flag to show/hide the click heatmap over the play area
+/// * speedrun: speedrun timer with the splits and personal bests of this save
+/// * challenge: challenge being played, with the main game waiting for it, if any
+/// * completed_challenges: challenges the player completed, each making the sand sell for more
+/// * show_challenges: flag to show/hide the challenges window
+/// * challenge_summary: result of the challenge that was just completed, shown until closed
+/// * blitz: blitz being played, with the main game waiting for it, if any
+/// * blitz_scores: best blitz scores of the player
+/// * blitz_summary: result of the blitz that just ended, shown until closed
+/// * daily_scores: best daily run score of each day the player played
+/// * profile: profile type the game is played as, its progress never mixes with the others
+/// * sandbox: tools of sandbox mode, used when the game is a sandbox
+/// * ng_plus: New Game+ count of the main game, each one scaling its values and costs
+pub struct SandDropClicker {
+    pub(crate) sim: SimState,
+    pub(crate) collecting: Vec<CollectingGrain>,
+    pub(crate) effects: Vec<Effect>,
+    pub(crate) effect_rng: StdRng,
+    pub(crate) floating_texts: Vec<FloatingText>,
+    pub(crate) show_debug: bool,
+    pub(crate) paused: bool,
+    pub(crate) quit_dialog: bool,
+    pub(crate) quit_confirmed: bool,
+    pub(crate) physics_time: Duration,
+    pub(crate) gui_time: Duration,
+    pub(crate) mouse_pos: (f32, f32),
+    pub(crate) pointer: (f32, f32),
+    pub(crate) camera: Camera,
+    pub(crate) shake_timer: f32,
+    pub(crate) flash_timer: f32,
+    pub(crate) full_flash_timer: f32,
+    pub(crate) full_hint_shown: bool,
+    pub(crate) fullscreen: bool,
+    pub(crate) theme: Theme,
+    pub(crate) owned_themes: HashSet<Theme>,
+    pub(crate) autosave_timer: f32,
+    pub(crate) save_path: Option<PathBuf>,
+    pub(crate) settings_path: Option<PathBuf>,
+    pub(crate) screenshot_dir: Option<PathBuf>,
+    pub(crate) screenshot_requested: bool,
+    pub(crate) screenshots: Vec<JoinHandle<Result<PathBuf, String>>>,
+    pub(crate) export_dir: Option<PathBuf>,
+    pub(crate) notifications: Notifications,
+    pub(crate) upgrade_labels: HashMap<Upgrade, (UpgradeLabel, String)>,
+    pub(crate) game_text: HudText<(u32, u32, Currency, Currency, bool, Lang, f32)>,
+    pub(crate) player_text: HudText<PlayerInfoKey>,
+    pub(crate) settings: Settings,
+    pub(crate) show_settings: bool,
+    pub(crate) reset_confirm: String,
+    pub(crate) reset_profile: Profile,
+    pub(crate) last_frame: Instant,
+    pub(crate) show_guide: bool,
+    pub(crate) show_stats: bool,
+    pub(crate) show_achievements: bool,
+    pub(crate) show_history: bool,
+    pub(crate) tutorial: Option<TutorialStep>,
+    pub(crate) drop_throttle: Option<u32>,
+    pub(crate) show_options: bool,
+    pub(crate) gamepad_cursor: Option<(f32, f32)>,
+    pub(crate) stick: (f32, f32),
+    pub(crate) selected_upgrade: usize,
+    pub(crate) touches: Vec<Touch>,
+    pub(crate) buy_count: u32,
+    pub(crate) rebinding: Option<Action>,
+    pub(crate) gui: Option<Gui>,
+    // needed for the graphics of the game: grains
+    pub(crate) batch: Option<InstanceArray>,
+    pub(crate) trail_batch: Option<InstanceArray>,
+    pub(crate) effect_batch: Option<InstanceArray>,
+    pub(crate) textured: bool,
+    pub(crate) sounds: Option<Sounds>,
+    pub(crate) sound_queue: Vec<(Sound, f32, f32)>,
+    pub(crate) land_sound_timer: f32,
+    pub(crate) land_pending: Option<(SandParticle, u32)>,
+    pub(crate) music: Option<Music>,
+    pub(crate) music_mix: f32,
+    pub(crate) ducking: Ducking,
+    pub(crate) pour_volume: f32,
+    pub(crate) focused: bool,
+    pub(crate) minimized: bool,
+    pub(crate) actions: Vec<GameAction>,
+    pub(crate) ticks: u64,
+    pub(crate) recording: Option<(PathBuf, Replay)>,
+    pub(crate) playback: Option<Playback>,
+    pub(crate) console: Option<Console>,
+    pub(crate) heatmap: Heatmap,
+    pub(crate) show_heatmap: bool,
+    pub(crate) speedrun: Speedrun,
+    pub(crate) challenge: Option<ChallengeRun>,
+    pub(crate) completed_challenges: HashSet<Challenge>,
+    pub(crate) show_challenges: bool,
+    pub(crate) challenge_summary: Option<ChallengeSummary>,
+    pub(crate) blitz: Option<Blitz>,
+    pub(crate) blitz_scores: HighScores,
+    pub(crate) blitz_summary: Option<BlitzSummary>,
+    pub(crate) daily_scores: DailyScores,
+    pub(crate) profile: Profile,
+    pub(crate) sandbox: SandboxTools,
+    pub(crate) ng_plus: u32,
+}
+
+/// Parts of the game that need a ggez context, all left out for tests
+/// * gui: GUI instance for the game
+/// * batch: instance array for rendering grains
+/// * trail_batch: instance array for rendering the grain trails
+/// * effect_batch: instance array for rendering the sparkles
+/// * textured: flag for whether the batch uses the particle atlas
+/// * sounds: pre-loaded sound effects, None without an audio device
+/// * music: looping music tracks, None without an audio device
+/// * save_path: location of the save file, if saving is enabled
+/// * settings_path: location of the settings file, if saving is enabled
+/// * screenshot_dir: folder for the screenshots, if saving is enabled
+/// * export_dir: folder for the exported statistics, if saving is enabled
+#[derive(Default)]
+struct Resources {
+    gui: Option<Gui>,
+    batch: Option<InstanceArray>,
+    trail_batch: Option<InstanceArray>,
+    effect_batch: Option<InstanceArray>,
+    textured: bool,
+    sounds: Option<Sounds>,
+    music: Option<Music>,
+    save_path: Option<PathBuf>,
+    settings_path: Option<PathBuf>,
+    screenshot_dir: Option<PathBuf>,
+    export_dir: Option<PathBuf>,
+}
+
+/// Implementation of the game logic and GUI handling
+/// for the SandDropClicker struct
+/// Contains methods for game initialization, GUI updates,
+/// sand particle management, upgrades, and event handling.
+impl SandDropClicker {
+    /// creates a new game state
+    /// initializes default values and loads the save file
+    /// fails if the modded particle or upgrade table is broken
+    pub fn new(ctx: &mut Context) -> GameResult<Self> {
+        let particle_table = load_particle_table(&ctx.fs.user_data_dir().join(PARTICLES_FILE))?;
+        // the upgrades have to be known before the game starts with them
+        let upgrade_table = load_upgrade_table(&ctx.fs.user_data_dir().join(UPGRADES_FILE))?;
+        if !set_upgrade_table(upgrade_table) {
+            warn!("the upgrade table was already in use, the modded upgrades are left out");
+        }
+        // create a shared texture for the grains
+        // fall back to a plain square if the atlas can't be loaded
+        let (texture, textured) = match Image::from_bytes(ctx, PARTICLE_ATLAS) {
+            Ok(atlas) => (atlas, true),
+            Err(err) => {
+                warn!(
+                    "couldn't load the particle sprites, using plain squares: {}",
+                    err
+                );
+                (Image::from_color(ctx, 1, 1, Some(Color::WHITE)), false)
+            }
+        };
+        let trail_array = InstanceArray::new(ctx, texture.clone());
+        let sparkle = Image::from_color(ctx, 1, 1, Some(Color::WHITE));
+        let effect_array = InstanceArray::new(ctx, sparkle);
+        let batch_array = InstanceArray::new(ctx, texture);
+        // create the game with default settings
+        let resources = Resources {
+            gui: Some(Gui::new(ctx)),
+            batch: Some(batch_array),
+            trail_batch: Some(trail_array),
+            effect_batch: Some(effect_array),
+            textured,
+            sounds: Sounds::new(ctx)
+                .map_err(|err| warn!("couldn't load the sounds: {}", err))
+                .ok(),
+            music: Music::new(ctx)
+                .map_err(|err| warn!("couldn't load the music: {}", err))
+                .ok(),
+            save_path: Some(ctx.fs.user_data_dir().join(SAVE_FILE)),
+            settings_path: Some(ctx.fs.user_config_dir().join(SETTINGS_FILE)),
+            screenshot_dir: Some(ctx.fs.user_data_dir().join(SCREENSHOT_DIR)),
+            export_dir: Some(ctx.fs.user_data_dir().join(EXPORT_DIR)),
+        };
+        let sim = SimState {
+            particle_table,
+            ..SimState::default()
+        };
+        let mut game = Self::with_resources(sim, resources);
+        // continue from the last save, if there is one
+        // a file that can't be read is reported and the game starts fresh
+        if let Err(err) = game.load_settings() {
+            warn!("couldn't read the settings: {}", err);
+            game.notify(tr_args("settings-load-failed", &[("error", &err)]));
+        }
+        if let Err(err) = game.load() {
+            warn!("couldn't read the save file: {}", err);
+            game.notify(tr_args("save-load-failed", &[("error", &err)]));
+        }
+        // new players are walked through the basics once
+        if !game.settings.tutorial_done {
+            game.tutorial = Some(TutorialStep::DropSand);
+        }
+        Ok(game)
+    }
+
+    /// creates a game state for testing
+    /// without a window, sound, or files
+    /// seeded, so the tests that step for a while never roll a different low gravity event
+    pub fn _test_state() -> Self {
+        let mut game = Self::with_resources(SimState::default(), Resources::default());
+        game.set_seed(1);
+        game
+    }
+
+    /// creates a game with default settings around the simulation
+    /// the resources that need a ggez context are handed in, so tests can leave them out
+    fn with_resources(sim: SimState, resources: Resources) -> Self {
+        Self {
+            sim,
+            collecting: Vec::new(),
+            effects: Vec::new(),
+            // the effects are cosmetic, drawing from the simulation's numbers would change its runs
+            effect_rng: StdRng::from_os_rng(),
+            floating_texts: Vec::new(),
+            show_debug: false,
+            paused: false,
+            quit_dialog: false,
+            quit_confirmed: false,
+            physics_time: Duration::ZERO,
+            gui_time: Duration::ZERO,
+            mouse_pos: (0.0, 0.0),
+            pointer: (0.0, 0.0),
+            camera: Camera::default(),
+            shake_timer: 0.0,
+            flash_timer: 0.0,
+            full_flash_timer: 0.0,
+            full_hint_shown: false,
+            fullscreen: false,
+            theme: Theme::Classic,
+            owned_themes: HashSet::from([Theme::Classic]),
+            autosave_timer: 0.0,
+            save_path: resources.save_path,
+            settings_path: resources.settings_path,
+            screenshot_dir: resources.screenshot_dir,
+            screenshot_requested: false,
+            screenshots: Vec::new(),
+            export_dir: resources.export_dir,
+            notifications: Notifications::default(),
+            upgrade_labels: HashMap::new(),
+            game_text: HudText::default(),
+            player_text: HudText::default(),
+            settings: Settings::default(),
+            show_settings: false,
+            reset_confirm: String::new(),
+            reset_profile: Profile::Normal,
+            last_frame: Instant::now(),
+            show_guide: false,
+            show_stats: false,
+            show_achievements: false,
+            show_history: false,
+            tutorial: None,
+            drop_throttle: None,
+            show_options: true,
+            gamepad_cursor: None,
+            stick: (0.0, 0.0),
+            selected_upgrade: 0,
+            touches: Vec::new(),
+            buy_count: 1,
+            rebinding: None,
+            gui: resources.gui,
+            batch: resources.batch,
+            trail_batch: resources.trail_batch,
+            effect_batch: resources.effect_batch,
+            textured: resources.textured,
+            sounds: resources.sounds,
+            sound_queue: Vec::new(),
+            land_sound_timer: 0.0,
+            land_pending: None,
+            music: resources.music,
+            music_mix: 0.0,
+            ducking: Ducking::default(),
+            pour_volume: 0.0,
+            focused: true,
+            minimized: false,
+            actions: Vec::new(),
+            ticks: 0,
+            recording: None,
+            playback: None,
+            console: None,
+            heatmap: Heatmap::default(),
+            show_heatmap: false,
+            speedrun: Speedrun::default(),
+            challenge: None,
+            completed_challenges: HashSet::new(),
+            show_challenges: false,
+            challenge_summary: None,
+            blitz: None,
+            blitz_scores: HighScores::default(),
+            blitz_summary: None,
+            daily_scores: DailyScores::default(),
+            profile: Profile::Normal,
+            sandbox: SandboxTools::default(),
+            ng_plus: 0,
+        }
+    }
+
+    /// seeds the random numbers of the simulation and of the effects
+    /// the same seed and the same inputs play out the same way
+    pub fn set_seed(&mut self, seed: u64) {
+        self.sim.rng = StdRng::seed_from_u64(seed);
+        self.effect_rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// starts the session over from a save, a seed, and a play area size
+    /// recordings and their replays start the same way, so they play out the same
+    pub(crate) fn restart(&mut self, save: &str, seed: u64, screen_size: (f32, f32)) {
+        self.reset_progress();
+        // the save tells which challenges were completed, and which one is played
+        // a blitz isn't saved, so it ends without a score
+        self.challenge = None;
+        self.completed_challenges.clear();
+        self.blitz = None;
+        self.blitz_scores = HighScores::default();
+        self.daily_scores = DailyScores::default();
+        self.profile = Profile::Normal;
+        self.sandbox = SandboxTools::default();
+        self.ng_plus = 0;
+        self.load_save(save);
+        self.set_seed(seed);
+        self.sim.resize(screen_size.0, screen_size.1);
+        self.drop_throttle = None;
+        self.touches.clear();
+        self.actions.clear();
+        self.ticks = 0;
+    }
+
+    /// starts recording the session into a replay file
+    /// the session starts over from the current progress, with the seed
+    pub fn start_recording(&mut self, path: PathBuf, seed: u64) {
+        let save = self.to_save();
+        let screen_size = self.sim.screen_size;
+        self.restart(&save, seed, screen_size);
+        debug!("recording a replay to {}", path.display());
+        let replay = Replay {
+            seed,
+            grain_size: self.grain_size(),
+            max_grains: self.settings.max_grains as usize,
+            screen_size,
+            save,
+            actions: Vec::new(),
+            end: 0,
+            checksum: 0,
+        };
+        self.recording = Some((path, replay));
+    }
+
+    /// writes the recorded replay to its file, with the state it ended in
+    pub(crate) fn finish_recording(&mut self) -> Result<(), SaveError> {
+        let Some((path, mut replay)) = self.recording.take() else {
+            return Ok(());
+        };
+        replay.end = self.ticks;
+        replay.checksum = self.sim.checksum();
+        write_file(&path, &replay.to_text())?;
+        debug!(
+            "recorded {} actions over {} steps to {}",
+            replay.actions.len(),
+            replay.end,
+            path.display()
+        );
+        Ok(())
+    }
+
+    /// starts playing back a replay file
+    /// the player's save is left alone while watching
+    /// a replay that can't be read stops the game with the reason
+    pub fn start_replay(&mut self, path: &PathBuf) -> GameResult {
+        let text = read_file(path)?.ok_or_else(|| {
+            GameError::ResourceLoadError(format!("{}: the replay doesn't exist", path.display()))
+        })?;
+        let replay = Replay::parse(&text)
+            .map_err(|err| GameError::ResourceLoadError(format!("{}: {}", path.display(), err)))?;
+        self.save_path = None;
+        self.tutorial = None;
+        self.restart(&replay.save, replay.seed, replay.screen_size);
+        debug!(
+            "playing back {} actions over {} steps from {}",
+            replay.actions.len(),
+            replay.end,
+            path.display()
+        );
+        self.playback = Some(Playback::new(replay));
+        Ok(())
+    }
+
+    /// applies the replay actions due before the next physics step
+    /// returns false once the replay is over, checking that it ended the same way
+    pub(crate) fn play_replay(&mut self) -> bool {
+        let Some(playback) = &mut self.playback else {
+            return true;
+        };
+        if playback.is_over(self.ticks) {
+            if playback.matched.is_none() {
+                let matched = playback.replay.checksum == self.sim.checksum();
+                playback.matched = Some(matched);
+                if matched {
+                    debug!("the replay ended the same way it was recorded");
+                } else {
+                    warn!("the replay ended differently than it was recorded");
+                }
+            }
+            return false;
+        }
+        for action in playback.due(self.ticks) {
+            self.perform(action);
+        }
+        true
+    }
+
+    /// returns the physics steps to play per fixed update
+    /// a replay can be paused or sped up
+    pub(crate) fn replay_steps(&self) -> u32 {
+        self.playback
+            .as_ref()
+            .map_or(1, |playback| playback.speed.steps())
+    }
+
+    /// enables the developer console, toggled with the grave key
+    /// normal play has no console, so it can't be used to cheat by accident
+    pub fn enable_console(&mut self) {
+        self.console = Some(Console::default());
+    }
+
+    /// turns the speedrun timer on and starts a new run
+    pub fn enable_speedrun(&mut self) {
+        self.settings.speedrun = true;
+        self.speedrun.start(&self.sim);
+    }
+
+    /// returns the unlocked upgrades in the order they are listed
+    pub(crate) fn listed_upgrades(&self) -> Vec<Upgrade> {
+        UpgradeCategory::iter()
+            .flat_map(|category| {
+                upgrade_table().iter().filter(move |upgrade| {
+                    upgrade.category() == category && self.sim.unlock.contains(upgrade)
+                })
+            })
+            .collect()
+    }
+
+    /// queues buying the listed upgrade in the slot of a number key
+    /// tells the player if it can't be afforded
+    pub(crate) fn quick_buy(&mut self, slot: usize) {
+        let Some(upgrade) = self.listed_upgrades().get(slot).copied() else {
+            return;
+        };
+        let Some(cost) = self.sim.upgrade_cost(upgrade) else {
+            return;
+        };
+        if self.sim.money < cost {
+            let name = upgrade.name();
+            let cost = self.money_text(cost);
+            self.notify(tr_args(
+                "upgrade-cant-afford",
+                &[("upgrade", &name), ("cost", &cost)],
+            ));
+        } else {
+            self.queue(GameAction::Buy { upgrade, count: 1 });
+        }
+    }
+
+    /// returns the seconds until the player can afford a cost
+    /// at the current income, or None without any income
+    pub(crate) fn time_to_afford(&self, cost: Currency) -> Option<u64> {
+        let missing = cost - self.sim.money;
+        let seconds = missing.div_ceil(self.sim.income.per_second())?;
+        Some(u64::try_from(seconds).unwrap_or(u64::MAX))
+    }
+
+    /// opens or closes an upgrade category and remembers it
+    pub(crate) fn toggle_category(&mut self, category: UpgradeCategory) {
+        let closed = &mut self.settings.closed_categories;
+        if !closed.remove(&category) {
+            closed.insert(category);
+        }
+        self.try_save_settings();
+    }
+
+    /// resets the game progress back to a new game
+    /// the player settings and the completed challenges are kept,
+    /// and a challenge starts over while the main game keeps waiting for it
+    pub(crate) fn reset_progress(&mut self) {
+        debug!("reset the progress");
+        self.sim.reset();
+        self.speedrun.start(&self.sim);
+        self.collecting.clear();
+        self.theme = Theme::Classic;
+        self.owned_themes = HashSet::from([Theme::Classic]);
+    }
+
+    /// starts over from a new game, played as the profile type
+    /// the profile type only changes with a new game, so the progress of the types never mixes,
+    /// and a challenge or a blitz starts over as the type it was started as
+    pub(crate) fn new_game(&mut self, profile: Profile) {
+        self.reset_progress();
+        if self.challenge.is_none() && self.blitz.is_none() {
+            // the records of another profile type don't carry over
+            if profile != self.profile {
+                self.completed_challenges.clear();
+                self.blitz_scores = HighScores::default();
+                self.daily_scores = DailyScores::default();
+                self.speedrun.bests.clear();
+            }
+            self.profile = profile;
+            self.sandbox = SandboxTools::default();
+            self.ng_plus = 0;
+            self.apply_modifiers();
+        }
+    }
+
+    /// checks if the main game can start a New Game+, once every upgrade is maxed
+    /// a sandbox game has everything for free, so it can't
+    pub(crate) fn can_new_game_plus(&self) -> bool {
+        self.challenge.is_none()
+            && self.blitz.is_none()
+            && self.profile.scored()
+            && self.sim.all_maxed()
+    }
+
+    /// starts the upgrades and the money over in a New Game+
+    /// the sand sells for more and the upgrades cost more with each one,
+    /// and the achievements, the statistics, and the themes are kept
+    pub(crate) fn new_game_plus(&mut self) {
+        if !self.can_new_game_plus() {
+            return;
+        }
+        self.ng_plus += 1;
+        self.sim.new_game_plus();
+        self.collecting.clear();
+        self.apply_modifiers();
+        self.speedrun.start(&self.sim);
+        debug!("started New Game+ {}", self.ng_plus);
+        self.notify(tr_args("ng-plus-started", &[("level", &self.ng_plus)]));
+    }
+
+    /// sets the rules of the simulation, from the challenge being played
+    /// or from the rewards of the completed challenges and the New Game+ count
+    /// the profile type adds its own rules, the sand of a hardcore game decays in its challenges too
+    pub(crate) fn apply_modifiers(&mut self) {
+        let modifiers = match &self.challenge {
+            Some(run) => run.challenge.modifiers(),
+            None => Modifiers {
+                ng_plus: self.ng_plus,
+                ..Modifiers::rewards(&self.completed_challenges)
+            },
+        };
+        self.sim.modifiers = self.profile.modifiers(modifiers, &self.sandbox);
+    }
+
+    /// changes the tools of sandbox mode, only a sandbox game has them
+    pub(crate) fn set_sandbox(&mut self, tools: SandboxTools) {
+        if self.profile == Profile::Sandbox {
+            self.sandbox = tools.clamped();
+            self.apply_modifiers();
+        }
+    }
+
+    /// starts a challenge run from a new game
+    /// the main game is kept in the save file format until the challenge ends
+    /// only one challenge runs at a time, and none during a blitz
+    pub(crate) fn start_challenge(&mut self, challenge: Challenge) {
+        if self.challenge.is_some() || self.blitz.is_some() || !self.profile.scored() {
+            return;
+        }
+        let main = self.to_save();
+        // the earned achievements and the speedrun of the main game carry on
+        let achievements = self.sim.achievements.clone();
+        let speedrun = std::mem::take(&mut self.speedrun);
+        self.reset_progress();
+        self.sim.achievements = achievements;
+        self.speedrun = speedrun;
+        self.challenge = Some(ChallengeRun { challenge, main });
+        self.apply_modifiers();
+        debug!("started the {:?} challenge", challenge);
+        self.notify(tr_args(
+            "challenge-started",
+            &[("challenge", &challenge.name())],
+        ));
+    }
+
+    /// ends the challenge run and goes back to the main game
+    /// a completed challenge is kept with its reward, and its summary is shown
+    pub(crate) fn end_challenge(&mut self, completed: bool) {
+        let Some(run) = self.challenge.take() else {
+            return;
+        };
+        let counters = self.sim.lifetime();
+        let achievements = std::mem::take(&mut self.sim.achievements);
+        let speedrun = std::mem::take(&mut self.speedrun);
+        self.reset_progress();
+        // the high scores come back with the main game
+        self.blitz_scores = HighScores::default();
+        self.load_save(&run.main);
+        // the achievements earned in the challenge are kept,
+        // and the speedrun goes on where it waited
+        for (achievement, time) in achievements {
+            self.sim.achievements.entry(achievement).or_insert(time);
+        }
+        self.speedrun = speedrun;
+        if completed {
+            debug!("completed the {:?} challenge", run.challenge);
+            self.completed_challenges.insert(run.challenge);
+            self.challenge_summary = Some(ChallengeSummary {
+                challenge: run.challenge,
+                counters,
+            });
+        } else {
+            debug!("gave up the {:?} challenge", run.challenge);
+            self.notify(tr("challenge-given-up"));
+        }
+        self.apply_modifiers();
+    }
+
+    /// ends the challenge run once its goal is reached
+    pub(crate) fn check_challenge(&mut self) {
+        if self
+            .challenge
+            .as_ref()
+            .is_some_and(|run| run.challenge.reached(&self.sim))
+        {
+            self.end_challenge(true);
+        }
+    }
+
+    /// starts a blitz on a simulation of its own, or the daily run of a day
+    /// the main game waits untouched until the blitz ends
+    /// only one blitz runs at a time, and none during a challenge
+    pub(crate) fn start_blitz(&mut self, daily: Option<u64>) {
+        if self.blitz.is_some() || self.challenge.is_some() || !self.profile.scored() {
+            return;
+        }
+        let mut sim = self.sim.new_run();
+        // the achievements that were already earned aren't announced again
+        sim.achievements = self.sim.achievements.clone();
+        let main = std::mem::replace(&mut self.sim, sim);
+        let blitz = Blitz::new(main, daily);
+        self.sim.modifiers = blitz.modifiers();
+        self.collecting.clear();
+        match blitz.daily_rules() {
+            // everyone playing the daily run of a day gets the same sand
+            Some(rules) => {
+                self.sim.rng = StdRng::seed_from_u64(rules.seed);
+                debug!("started the daily run with the seed {}", rules.seed);
+                self.notify(tr_args("daily-started", &[("rules", &rules.desc())]));
+            }
+            None => {
+                debug!("started a blitz");
+                self.notify(tr("blitz-started"));
+            }
+        }
+        self.blitz = Some(blitz);
+    }
+
+    /// ends the blitz and goes back to the main game
+    /// a blitz that ran out of time records its score, and its summary is shown
+    /// a daily run keeps the best score of its day, a plain blitz goes in the high-score table
+    pub(crate) fn end_blitz(&mut self, finished: bool) {
+        let Some(blitz) = self.blitz.take() else {
+            return;
+        };
+        let daily = blitz.daily;
+        let run = std::mem::replace(&mut self.sim, blitz.main);
+        self.sim.resize(run.screen_size.0, run.screen_size.1);
+        self.collecting.clear();
+        if finished {
+            let money = run.lifetime().money_earned;
+            let rank = match daily {
+                Some(day) => self
+                    .daily_scores
+                    .record(&format_date(day), money)
+                    .then_some(0),
+                None => self.blitz_scores.record(BlitzScore {
+                    money,
+                    date: format_date(today()),
+                }),
+            };
+            debug!("finished a blitz with {}$, ranked {:?}", money, rank);
+            self.blitz_summary = Some(BlitzSummary { money, rank, daily });
+            self.try_save();
+        } else {
+            debug!("gave up the blitz");
+            self.notify(tr("blitz-given-up"));
+        }
+    }
+
+    /// counts down the blitz by some seconds, and ends it once the time is up
+    pub(crate) fn update_blitz(&mut self, seconds: f32) {
+        if self
+            .blitz
+            .as_mut()
+            .is_some_and(|blitz| blitz.update(seconds))
+        {
+            self.end_blitz(true);
+        }
+    }
+
+    /// returns the simulation of the main game, which waits during a blitz
+    pub(crate) fn main_sim(&self) -> &SimState {
+        self.blitz.as_ref().map_or(&self.sim, |blitz| &blitz.main)
+    }
+
+    /// recolors the grains for the current color palette
+    pub(crate) fn apply_palette(&mut self) {
+        let colorblind = self.settings.colorblind;
+        let table = &self.sim.particle_table;
+        for grain in self.sim.container.iter_mut() {
+            grain.color = table.palette_color(grain.particle, colorblind);
+        }
+    }
+
+    /// drops sand by hand at the specified (x, y) position
+    /// the drop throttle limits how many grains fall
+    /// fast clicks build a combo that makes the grains worth more
+    pub(crate) fn click(&mut self, x: f32, y: f32) {
+        self.sync_settings();
+        if self.sim.is_full() {
+            self.reject_full();
+            return;
+        }
+        let added = self.sim.click(x, y, self.click_amount());
+        if added > 0 {
+            self.play(Sound::Drop, 0.5);
+        }
+        self.check_drop_tutorial();
+    }
+
+    /// tells the player why a click into the full container dropped nothing
+    /// the capacity counter flashes red with a dull thud, and the first rejected click
+    /// of each fill-up shows how to make room
+    pub(crate) fn reject_full(&mut self) {
+        self.full_flash_timer = FULL_FLASH_TIME;
+        // the landing patter played slow sounds like a thud
+        self.play_pitched(Sound::Land, 0.6, 0.5);
+        if !self.full_hint_shown {
+            self.full_hint_shown = true;
+            let key = self.settings.keybinds.label(Action::Convert);
+            self.notify(tr_args("container-full-hint", &[("key", &key)]));
+        }
+    }
+
+    /// queues a sound effect to play in the next update
+    /// nothing plays while muted, and each sound plays once per update
+    pub(crate) fn play(&mut self, sound: Sound, volume: f32) {
+        self.play_pitched(sound, volume, 1.0);
+    }
+
+    /// queues a sound effect played faster or slower by the pitch ratio
+    pub(crate) fn play_pitched(&mut self, sound: Sound, volume: f32, pitch: f32) {
+        if self.settings.muted
+            || self
+                .sound_queue
+                .iter()
+                .any(|(queued, _, _)| *queued == sound)
+        {
+            return;
+        }
+        self.sound_queue.push((sound, volume, pitch));
+    }
+
+    /// plays the queued sound effects
+    /// each sound restarts its own source, so muting can stop it
+    pub(crate) fn play_sounds(&mut self, ctx: &mut Context) {
+        for (sound, volume, pitch) in self.sound_queue.drain(..) {
+            if let Some(sounds) = &mut self.sounds {
+                let source = sounds.source(sound);
+                source.set_volume(volume);
+                source.set_pitch(pitch);
+                let _ = source.play(ctx);
+            }
+        }
+        if self.settings.muted
+            && let Some(sounds) = &mut self.sounds
+        {
+            sounds.stop_all(ctx);
+        }
+    }
+
+    /// mutes or unmutes all sounds and music
+    /// and tells the player the new audio state
+    pub(crate) fn toggle_mute(&mut self) {
+        self.settings.muted = !self.settings.muted;
+        let state = self.audio_state();
+        self.notify(state);
+    }
+
+    /// returns the audio state, muted or the music volume
+    pub(crate) fn audio_state(&self) -> String {
+        if self.settings.muted {
+            tr("audio-muted").to_string()
+        } else {
+            let volume = if self.settings.music_muted {
+                0.0
+            } else {
+                (self.settings.music_volume * 100.0).round()
+            };
+            tr_args("audio-on", &[("volume", &volume)])
+        }
+    }
+
+    /// returns true if the intense music should play
+    /// when the container is almost full or during low gravity
+    pub(crate) fn music_intense(&self) -> bool {
+        let fill = self.sim.get_amount() as f32 / self.sim.get_size().max(1) as f32;
+        fill > INTENSE_FILL || self.sim.low_gravity_timer > 0.0
+    }
+
+    /// moves the crossfade towards the music that should play
+    pub(crate) fn fade_music(&mut self, seconds: f32) {
+        let target = if self.music_intense() { 1.0 } else { 0.0 };
+        let step = seconds / MUSIC_FADE;
+        self.music_mix = if self.music_mix < target {
+            (self.music_mix + step).min(target)
+        } else {
+            (self.music_mix - step).max(target)
+        };
+    }
+
+    /// returns how much sand is being poured, from 0 to 1
+    pub(crate) fn pour_rate(&self) -> f32 {
+        let pouring = self.touches.iter().filter(|touch| touch.dragged).count();
+        pouring as f32 / MAX_TOUCHES as f32
+    }
+
+    /// makes the pouring sound follow the pour rate
+    /// it starts right away and fades out after the pouring stops
+    /// pausing or leaving the window silences it at once
+    pub(crate) fn fade_pour(&mut self, dt: f32) {
+        let target = self.pour_rate();
+        self.pour_volume = if self.paused || self.in_background() {
+            0.0
+        } else if target >= self.pour_volume {
+            target
+        } else {
+            (self.pour_volume - dt / POUR_FADE).max(target)
+        };
+    }
+
+    /// plays the pouring loop at its volume, and stops it when silent
+    pub(crate) fn update_pour_sound(&mut self, ctx: &mut Context) {
+        let volume = if self.settings.muted {
+            0.0
+        } else {
+            self.pour_volume
+        };
+        if let Some(sounds) = &mut self.sounds {
+            let pour = &mut sounds.pour;
+            pour.set_volume(volume);
+            if volume > 0.0 && pour.stopped() {
+                let _ = pour.play(ctx);
+            } else if volume <= 0.0 && !pour.stopped() {
+                let _ = pour.stop(ctx);
+            }
+        }
+    }
+
+    /// sets the volume of the music tracks from the settings and the crossfade
+    pub(crate) fn update_music(&mut self) {
+        let volume = if self.settings.muted || self.settings.music_muted {
+            0.0
+        } else {
+            self.settings.music_volume * self.ducking.gain
+        };
+        if let Some(music) = &mut self.music {
+            music.calm.set_volume(volume * (1.0 - self.music_mix));
+            music.intense.set_volume(volume * self.music_mix);
+        }
+    }
+
+    /// drops sand by hand at a random spot along the top of the container
+    pub(crate) fn drop_random(&mut self) {
+        let x = self.sim.rng.random_range(0.0..self.sim.screen_size.0);
+        self.click(x, 0.0);
+    }
+
+    /// copies the settings the simulation uses into it
+    /// a replay keeps the grain size and the grain cap it was recorded with
+    pub(crate) fn sync_settings(&mut self) {
+        self.sim.grain_size = match &self.playback {
+            Some(playback) => playback.replay.grain_size,
+            None => self.grain_size(),
+        };
+        let recorded = self.playback.as_ref().map(|playback| &playback.replay);
+        let recorded = recorded.or(self.recording.as_ref().map(|(_, replay)| replay));
+        self.sim.max_grains = match recorded {
+            Some(replay) => replay.max_grains,
+            None => self.settings.max_grains as usize,
+        };
+        self.sim.colorblind = self.settings.colorblind;
+    }
+
+    /// moves the tutorial on once enough sand was dropped
+    pub(crate) fn check_drop_tutorial(&mut self) {
+        if self.sim.get_amount() >= TUTORIAL_GRAINS {
+            self.advance_tutorial(TutorialStep::DropSand);
+        }
+    }
+
+    /// puts a finger down at the world position (x, y)
+    /// a tap drops sand like a click
+    pub(crate) fn touch_start(&mut self, x: f32, y: f32) {
+        if self.touches.len() >= MAX_TOUCHES {
+            return;
+        }
+        self.touches.push(Touch::new(x, y));
+        self.queue(GameAction::DropAt { x, y });
+    }
+
+    /// moves the finger closest to the world position (x, y)
+    /// a moved finger starts pouring
+    pub(crate) fn touch_move(&mut self, x: f32, y: f32) {
+        if let Some(index) = self.nearest_touch(x, y) {
+            let touch = &mut self.touches[index];
+            touch.x = x;
+            touch.y = y;
+            touch.dragged = true;
+        }
+    }
+
+    /// lifts the finger closest to the world position (x, y)
+    pub(crate) fn touch_end(&mut self, x: f32, y: f32) {
+        if let Some(index) = self.nearest_touch(x, y) {
+            self.touches.remove(index);
+        }
+    }
+
+    /// returns the index of the finger closest to the world position (x, y)
+    /// touch events don't say which finger they are from
+    pub(crate) fn nearest_touch(&self, x: f32, y: f32) -> Option<usize> {
+        let distance = |touch: &Touch| (touch.x - x).powi(2) + (touch.y - y).powi(2);
+        (0..self.touches.len())
+            .min_by(|a, b| distance(&self.touches[*a]).total_cmp(&distance(&self.touches[*b])))
+    }
+
+    /// drops sand under every dragged finger
+    pub(crate) fn pour(&mut self, seconds: f32) {
+        let mut drops = Vec::new();
+        for touch in &mut self.touches {
+            if !touch.dragged {
+                continue;
+            }
+            touch.pour_timer -= seconds;
+            while touch.pour_timer <= 0.0 {
+                drops.push((touch.x, touch.y));
+                touch.pour_timer += POUR_INTERVAL;
+            }
+        }
+        for (x, y) in drops {
+            self.apply(GameAction::DropAt { x, y });
+        }
+    }
+
+    /// moves the gamepad cursor by the stick tilt
+    /// the cursor stays inside the container
+    pub(crate) fn move_gamepad_cursor(&mut self, seconds: f32) {
+        let Some((x, y)) = self.gamepad_cursor else {
+            return;
+        };
+        let (mut dx, mut dy) = self.stick;
+        if dx.abs() < GAMEPAD_DEADZONE {
+            dx = 0.0;
+        }
+        if dy.abs() < GAMEPAD_DEADZONE {
+            dy = 0.0;
+        }
+        let (width, height) = self.sim.screen_size;
+        self.gamepad_cursor = Some((
+            (x + dx * GAMEPAD_SPEED * seconds).clamp(0.0, width),
+            (y + dy * GAMEPAD_SPEED * seconds).clamp(0.0, height),
+        ));
+    }
+
+    /// handles a gamepad button press
+    /// the first press puts the cursor in the middle of the container
+    pub(crate) fn gamepad_button(&mut self, button: PadButton) {
+        let (width, height) = self.sim.screen_size;
+        let (x, y) = *self
+            .gamepad_cursor
+            .get_or_insert((width / 2.0, height / 2.0));
+        match button {
+            PadButton::South if !self.paused => self.queue(GameAction::DropAt { x, y }),
+            PadButton::West => self.queue(GameAction::Convert),
+            PadButton::North => self.show_options = !self.show_options,
+            PadButton::DPadUp => self.select_upgrade(-1),
+            PadButton::DPadDown => self.select_upgrade(1),
+            PadButton::East => {
+                if let Some(upgrade) = self.gamepad_upgrade() {
+                    self.queue(GameAction::Buy { upgrade, count: 1 });
+                }
+            }
+            PadButton::Start => self.paused = !self.paused,
+            _ => {}
+        }
+    }
+
+    /// returns the upgrades that can still be bought
+    pub(crate) fn buyable_upgrades(&self) -> Vec<Upgrade> {
+        upgrade_table()
+            .iter()
+            .filter(|upgrade| self.sim.unlock.contains(upgrade) && !self.sim.is_maxed(*upgrade))
+            .collect()
+    }
+
+    /// moves the gamepad selection through the buyable upgrades
+    /// wraps around at either end
+    pub(crate) fn select_upgrade(&mut self, steps: i32) {
+        let count = self.buyable_upgrades().len() as i32;
+        if count > 0 {
+            let selected = self.selected_upgrade.min(count as usize - 1) as i32;
+            self.selected_upgrade = (selected + steps).rem_euclid(count) as usize;
+        }
+    }
+
+    /// returns the upgrade picked with the gamepad
+    /// nothing is picked while the options window is hidden
+    pub(crate) fn gamepad_upgrade(&self) -> Option<Upgrade> {
+        if !self.show_options {
+            return None;
+        }
+        let upgrades = self.buyable_upgrades();
+        let last = upgrades.len().checked_sub(1)?;
+        upgrades.get(self.selected_upgrade.min(last)).copied()
+    }
+
+    /// vacuum functionality
+    /// sells the settled grains near (x, y) right away
+    /// returns the money earned
+    pub(crate) fn vacuum(&mut self, x: f32, y: f32) -> Currency {
+        let (earned, taken) = self.sim.vacuum(x, y);
+        // the grains are sucked into the cursor
+        for grain in taken {
+            self.collecting.push(CollectingGrain::new(grain, (x, y)));
+        }
+        earned
+    }
+
+    /// sparkle effect functionality
+    /// spawns sparkles around rare falling grains and fades them out
+    pub(crate) fn sparkle(&mut self, seconds: f32) {
+        // fade out the old sparkles
+        for effect in &mut self.effects {
+            effect.update(seconds);
+        }
+        self.effects.retain(|effect| !effect.is_done());
+        // add new sparkles around the rare grains
+        for grain in self.sim.container.iter_mut() {
+            if grain.sparkle_timer <= 0.0 {
+                continue;
+            }
+            grain.sparkle_timer -= seconds;
+            if self.effects.len() >= MAX_EFFECTS {
+                continue;
+            }
+            let center = grain.rect.center();
+            let offset_x = self.effect_rng.random_range(-GRAIN_SIZE..GRAIN_SIZE);
+            let offset_y = self.effect_rng.random_range(-GRAIN_SIZE..GRAIN_SIZE);
+            self.effects
+                .push(Effect::new(center.x + offset_x, center.y + offset_y));
+        }
+    }
+
+    /// spawns a floating text at (x, y)
+    /// texts that would overlap are moved up
+    pub(crate) fn float_text(&mut self, text: String, x: f32, y: f32) {
+        if !self.settings.floating_text {
+            return;
+        }
+        let mut y = y;
+        while self
+            .floating_texts
+            .iter()
+            .any(|other| (other.x - x).abs() < 50.0 && (other.y - y).abs() < 20.0)
+        {
+            y -= 20.0;
+        }
+        self.floating_texts.push(FloatingText::new(text, x, y));
+    }
+
+    /// converts all sand particles into money
+    pub(crate) fn make_money(&mut self) {
+        // sell all sand particles for money
+        let money = self.sim.money;
+        let records = self.sim.stats.records;
+        let (earned, sold) = self.sim.sell();
+        // beating the best conversion is worth a toast
+        if self.sim.stats.records > records {
+            let money = self.money_text(earned);
+            self.notify(tr_args("stats-new-record", &[("money", &money)]));
+        }
+        // big sales shake and flash the screen
+        if earned > Currency::ZERO && earned.as_f64() >= money.as_f64() * BIG_SALE {
+            if self.settings.screen_shake {
+                self.shake_timer = SHAKE_TIME;
+            }
+            if self.settings.flash {
+                self.flash_timer = FLASH_TIME;
+            }
+            // the music makes room for the cash register
+            self.ducking.duck();
+        }
+        // show the money gained over the container
+        if earned > Currency::ZERO {
+            self.advance_tutorial(TutorialStep::Convert);
+            // bigger sales ring louder
+            let volume = 0.3 + earned.as_f64().log10() as f32 / 10.0;
+            self.play(Sound::Sale, volume.min(1.0));
+            self.float_text(
+                format!("+{}$", self.money_text(earned)),
+                self.sim.screen_size.0 / 2.0,
+                self.sim.screen_size.1 / 2.0,
+            );
+        }
+        // the sold grains fly to the money counter
+        // they are out of the container, so they can't be sold again
+        let target = self.camera.to_world(MONEY_POS.0, MONEY_POS.1);
+        for grain in sold {
+            self.collecting.push(CollectingGrain::new(grain, target));
+        }
+    }
+
+    /// converts window coordinates into play area coordinates
+    pub(crate) fn to_screen(&self, ctx: &Context, x: f32, y: f32) -> (f32, f32) {
+        let (width, height) = ctx.gfx.drawable_size();
+        (
+            x * self.sim.screen_size.0 / width,
+            y * self.sim.screen_size.1 / height,
+        )
+    }
+
+    /// returns the size of new grains
+    /// grains grow with the UI scale if the setting is on
+    pub(crate) fn grain_size(&self) -> f32 {
+        if self.settings.scale_grains {
+            GRAIN_SIZE * self.settings.ui_scale
+        } else {
+            GRAIN_SIZE
+        }
+    }
+
+    /// returns true if the game can quit right away
+    /// otherwise asks the player first, as there is unsold sand
+    pub(crate) fn confirm_quit(&mut self) -> bool {
+        if self.quit_confirmed || self.sim.get_amount() == 0 {
+            return true;
+        }
+        self.quit_dialog = true;
+        false
+    }
+
+    /// returns the time of day in seconds into the day/night cycle
+    pub(crate) fn time_of_day(&self) -> f32 {
+        match self.settings.fixed_time {
+            Some(time) => time.seconds(),
+            None => self.sim.total_time.as_secs_f32() % DAY_LENGTH,
+        }
+    }
+
+    /// returns the camera with the screen shake applied
+    /// the shake only moves the drawing, never the clicks
+    pub(crate) fn shaken_camera(&mut self) -> Camera {
+        if self.shake_timer <= 0.0 {
+            return self.camera;
+        }
+        let strength = SHAKE_STRENGTH * self.shake_timer / SHAKE_TIME;
+        // the shake runs once per drawn frame, so it must not use the simulation's numbers
+        Camera {
+            x: self.camera.x + self.effect_rng.random_range(-strength..strength),
+            y: self.camera.y + self.effect_rng.random_range(-strength..strength),
+            ..self.camera
+        }
+    }
+
+    /// returns true if the window is unfocused or minimized
+    pub(crate) fn in_background(&self) -> bool {
+        !self.focused || self.minimized
+    }
+
+    /// returns how the simulation runs right now, Run while the window is in the foreground
+    /// a minimized window has nothing to show, so it idles instead of running the grain physics,
+    /// and a replay has to play every step, so it waits for the window instead of idling
+    pub(crate) fn run_mode(&self) -> BackgroundMode {
+        if !self.in_background() {
+            return BackgroundMode::Run;
+        }
+        let mode = match self.settings.background {
+            BackgroundMode::Run if self.minimized => BackgroundMode::Idle,
+            mode => mode,
+        };
+        if mode == BackgroundMode::Idle && (self.recording.is_some() || self.playback.is_some()) {
+            BackgroundMode::Pause
+        } else {
+            mode
+        }
+    }
+
+    /// sets the window state from the window events
+    /// the time that came due while the game was paused in the background is dropped
+    /// when it starts again, so the physics doesn't catch up on it in a burst of steps
+    pub(crate) fn set_window(&mut self, ctx: &mut Context, focused: bool, minimized: bool) {
+        let before = self.run_mode();
+        self.focused = focused;
+        self.minimized = minimized;
+        let after = self.run_mode();
+        if before == after {
+            return;
+        }
+        if before == BackgroundMode::Pause {
+            while ctx.time.check_update_time(FPS) {}
+        }
+        debug!("the simulation switched from {:?} to {:?}", before, after);
+    }
+
+    /// advances the game by some seconds without the grain physics, while it idles in the background
+    /// the seconds come in one piece, as the idle autoclicker has no grains to step
+    pub(crate) fn idle(&mut self, seconds: f32) {
+        if self.paused || seconds <= 0.0 {
+            return;
+        }
+        self.sim.idle(seconds);
+        self.handle_events();
+        self.update_blitz(seconds);
+    }
+
+    /// runs the fixed physics steps that came due since the last update
+    /// after a stall only MAX_CATCH_UP steps run and the rest of the time is dropped,
+    /// so the frame doesn't freeze and the autoclicker doesn't pour out a burst of sand
+    pub(crate) fn catch_up(&mut self, due: u32, sculpt: Option<bool>, vacuum: bool) {
+        if due > MAX_CATCH_UP {
+            warn!(
+                "dropped {} physics steps after a stall of {:.1}s",
+                due - MAX_CATCH_UP,
+                due as f32 / FPS as f32
+            );
+        }
+        for _ in 0..due.min(MAX_CATCH_UP) {
+            for _ in 0..self.replay_steps() {
+                self.step(1.0 / FPS as f32, sculpt);
+            }
+            if vacuum {
+                let (x, y) = self.mouse_pos;
+                self.apply(GameAction::Vacuum { x, y });
+            }
+        }
+    }
+
+    /// advances the game by one fixed physics step
+    /// sculpt is Some(pull) while the sculpting tool is held
+    /// nothing happens while the game is paused, or in the background without running there
+    pub(crate) fn step(&mut self, seconds: f32, sculpt: Option<bool>) {
+        if self.paused || self.run_mode() != BackgroundMode::Run {
+            return;
+        }
+        // a replay feeds in its actions, and stops at its end
+        if !self.play_replay() {
+            return;
+        }
+        // move the gamepad cursor with the stick
+        self.move_gamepad_cursor(seconds);
+
+        // dragged fingers keep pouring
+        self.pour(seconds);
+
+        // run the simulation: grain physics, autoclicker, and events
+        let physics_start = Instant::now();
+        self.sync_settings();
+        self.sim.tick(seconds);
+        self.physics_time = physics_start.elapsed();
+        self.ticks += 1;
+        self.handle_events();
+        self.update_blitz(seconds);
+
+        // sculpting tool
+        if let Some(pull) = sculpt {
+            let (x, y) = self.mouse_pos;
+            self.apply(GameAction::Sculpt { x, y, pull });
+        }
+
+        // the music gets intense when the container is almost full
+        self.fade_music(seconds);
+
+        // landing grains patter, but not for every single grain
+        // the rarest grain that landed picks the pitch
+        self.land_sound_timer -= seconds;
+        if self.land_sound_timer <= 0.0
+            && let Some((rarest, count)) = self.land_pending.take()
+        {
+            let volume = (0.2 + count as f32 * 0.05).min(1.0);
+            let pitch = SEMITONE.powi(rarest.level() as i32);
+            self.play_pitched(Sound::Land, volume, pitch);
+            self.land_sound_timer = LAND_SOUND_INTERVAL;
+        }
+
+        // update the sold grains, and remove the ones that arrived
+        for collected in &mut self.collecting {
+            collected.update(seconds);
+        }
+        self.collecting.retain(|collected| !collected.is_done());
+
+        // sparkles around the rare grains
+        self.sparkle(seconds);
+
+        // rise and fade the floating texts
+        for text in &mut self.floating_texts {
+            text.update(seconds);
+        }
+        self.floating_texts.retain(|text| !text.is_done());
+
+        // calm down the big sale effects
+        self.shake_timer = (self.shake_timer - seconds).max(0.0);
+        self.flash_timer = (self.flash_timer - seconds).max(0.0);
+        self.full_flash_timer = (self.full_flash_timer - seconds).max(0.0);
+        // the full container hint can show again once there is room
+        if self.full_hint_shown && !self.sim.is_full() {
+            self.full_hint_shown = false;
+        }
+
+        // save the game every now and then
+        self.autosave_timer += seconds;
+        if self.autosave_timer >= AUTOSAVE_TIME {
+            self.autosave_timer = 0.0;
+            self.try_save();
+        }
+    }
+
+    /// reacts to what happened in the last simulation tick
+    /// landed grains make a sound, and earned achievements and unlocked upgrades are announced
+    pub(crate) fn handle_events(&mut self) {
+        for event in std::mem::take(&mut self.sim.events) {
+            match event {
+                SimEvent::Landed(particle) => {
+                    self.land_pending = Some(match self.land_pending {
+                        Some((rarest, count)) => (rarest.max_tier(particle), count + 1),
+                        None => (particle, 1),
+                    });
+                }
+                // the achievements of a blitz aren't kept
+                SimEvent::Achievement(_) if self.blitz.is_some() => {}
+                SimEvent::Achievement(achievement) => {
+                    let name = achievement.name();
+                    self.notify(tr_args("achievements-earned", &[("achievement", &name)]));
+                }
+                SimEvent::Unlocked(upgrade) => {
+                    let name = upgrade.name();
+                    self.notify(tr_args("upgrades-unlocked", &[("upgrade", &name)]));
+                }
+            }
+        }
+        // the autoclicker drops sand too
+        self.check_drop_tutorial();
+        self.check_challenge();
+    }
+
+    /// returns true if a mouse button mapped to the action is held down
+    pub(crate) fn mouse_held(&self, ctx: &Context, action: MouseAction) -> bool {
+        MOUSE_BUTTONS.iter().any(|button| {
+            self.settings.mouse.action(*button) == action && ctx.mouse.button_pressed(*button)
+        })
+    }
+
+    /// returns the amount of grains a click drops
+    pub(crate) fn click_amount(&self) -> u32 {
+        let amount = self.sim.drop_amount();
+        self.drop_throttle
+            .map_or(amount, |throttle| throttle.min(amount))
+    }
+
+    /// changes how many grains a click drops by a number of steps
+    /// going past the full amount drops all of them again
+    pub(crate) fn throttle_drops(&mut self, steps: i32) {
+        let amount = self.sim.drop_amount();
+        let throttle = (self.click_amount() as i32 + steps).clamp(1, amount as i32) as u32;
+        self.drop_throttle = (throttle < amount).then_some(throttle);
+    }
+
+    /// buys the specified upgrade if affordable and not maxed out
+    pub(crate) fn buy(&mut self, upgrade: Upgrade) {
+        if self.sim.buy(upgrade) {
+            self.play(Sound::Buy, 1.0);
+            self.advance_tutorial(TutorialStep::Upgrade);
+        }
+    }
+
+    /// buys up to count levels of the specified upgrade
+    /// stops at the max level or when the money runs out
+    /// returns the amount of levels that were bought
+    pub(crate) fn buy_levels(&mut self, upgrade: Upgrade, count: u32) -> u32 {
+        let mut bought = 0;
+        while bought < count {
+            let level = self.sim.upgrades.get(&upgrade).copied();
+            self.buy(upgrade);
+            if self.sim.upgrades.get(&upgrade).copied() == level {
+                break;
+            }
+            bought += 1;
+        }
+        // tell the player how many levels a multi-level purchase got
+        if count > 1 && bought > 0 {
+            let name = upgrade.name();
+            if count != u32::MAX && bought < count {
+                self.notify(tr_args(
+                    "upgrade-bought-partial",
+                    &[("count", &bought), ("wanted", &count), ("upgrade", &name)],
+                ));
+            } else {
+                self.notify(tr_args(
+                    "upgrade-bought",
+                    &[("count", &bought), ("upgrade", &name)],
+                ));
+            }
+        }
+        bought
+    }
+
+    /// buys the specified theme if affordable and switches to it
+    pub(crate) fn buy_theme(&mut self, theme: Theme) {
+        let cost = theme.cost();
+        if let Some(left) = self.sim.money.checked_sub(cost)
+            && !self.owned_themes.contains(&theme)
+        {
+            self.sim.money = left;
+            self.sim.stats.money_spent += cost;
+            self.owned_themes.insert(theme);
+            self.theme = theme;
+            debug!("bought the {:?} theme for {}$", theme, cost);
+        }
+    }
+
+    /// queues a game action to apply in the next update
+    pub(crate) fn queue(&mut self, action: GameAction) {
+        self.actions.push(action);
+    }
+
+    /// applies the queued game actions in the order they came in
+    pub(crate) fn apply_actions(&mut self) {
+        for action in std::mem::take(&mut self.actions) {
+            self.apply(action);
+        }
+    }
+
+    /// applies a game action to the game state
+    /// every change the player makes to the game goes through here
+    /// the action is recorded while recording, and left out while watching a replay
+    pub(crate) fn apply(&mut self, action: GameAction) {
+        if self.playback.is_some() {
+            return;
+        }
+        if let Some((_, replay)) = &mut self.recording {
+            replay.actions.push((self.ticks, action));
+        }
+        self.perform(action);
+    }
+
+    /// performs a game action on the game state, live or from a replay
+    pub(crate) fn perform(&mut self, action: GameAction) {
+        match action {
+            GameAction::DropAt { x, y } => {
+                self.heatmap.record(x, y, self.sim.screen_size);
+                self.click(x, y);
+            }
+            GameAction::DropRandom => self.drop_random(),
+            GameAction::Convert => self.make_money(),
+            GameAction::Vacuum { x, y } => {
+                self.vacuum(x, y);
+            }
+            GameAction::Sculpt { x, y, pull } => self.sim.sculpt(x, y, pull, 1.0 / FPS as f32),
+            GameAction::Throttle { steps } => self.throttle_drops(steps),
+            GameAction::Resize { width, height } => self.sim.resize(width, height),
+            GameAction::Buy { upgrade, count } => {
+                self.buy_levels(upgrade, count);
+            }
+            GameAction::BuyTheme(theme) => self.buy_theme(theme),
+            GameAction::Reset(profile) => self.new_game(profile),
+            GameAction::Give { money } => self.sim.money += money,
+            GameAction::SetLevel { upgrade, level } => {
+                let level = upgrade.max_level().map_or(level, |max| level.min(max));
+                self.sim.upgrades.insert(upgrade, level);
+            }
+            GameAction::Spawn { particle, amount } => {
+                self.sync_settings();
+                let x = self.sim.screen_size.0 / 2.0;
+                self.sim.add_grains_of(x, 0.0, amount, Some(particle));
+            }
+            GameAction::Clear => self.sim.clear(),
+            GameAction::Seed(seed) => self.set_seed(seed),
+            GameAction::StartChallenge(challenge) => self.start_challenge(challenge),
+            GameAction::EndChallenge => self.end_challenge(false),
+            GameAction::StartBlitz => self.start_blitz(None),
+            GameAction::StartDaily(day) => self.start_blitz(Some(day)),
+            GameAction::EndBlitz => self.end_blitz(false),
+            GameAction::SetSandbox(tools) => self.set_sandbox(tools),
+            GameAction::NewGamePlus => self.new_game_plus(),
+        }
+    }
+
+    /// returns the game progress in the save file format
+    /// each line is a `key=value` pair
+    /// a blitz isn't saved, the main game waiting for it is
+    pub(crate) fn to_save(&self) -> String {
+        let sim = self.main_sim();
+        let mut lines = vec![
+            format!("money={}", sim.money),
+            format!("total_clicks={}", sim.total_clicks),
+            format!("total_time={}", sim.total_time.as_secs_f64()),
+            format!("theme={:?}", self.theme),
+            format!("profile={:?}", self.profile),
+            format!("ng_plus={}", self.ng_plus),
+        ];
+        for (upgrade, level) in &sim.upgrades {
+            lines.push(format!("upgrade.{:?}={}", upgrade, level));
+        }
+        for upgrade in &sim.unlock {
+            lines.push(format!("unlock={:?}", upgrade));
+        }
+        for theme in &self.owned_themes {
+            lines.push(format!("owned_theme={:?}", theme));
+        }
+        lines.extend(sim.stats.to_save());
+        for (achievement, time) in &sim.achievements {
+            lines.push(format!("achievement.{:?}={}", achievement, time));
+        }
+        lines.extend(sim.history.to_save());
+        lines.extend(sim.timeline.to_save());
+        lines.extend(self.speedrun.to_save());
+        lines.extend(self.blitz_scores.to_save());
+        lines.extend(self.daily_scores.to_save());
+        if self.profile == Profile::Sandbox {
+            lines.extend(self.sandbox.to_save());
+        }
+        for challenge in &self.completed_challenges {
+            lines.push(format!("challenge_done={:?}", challenge));
+        }
+        // the main game waits in the save of the challenge, under its own prefix
+        if let Some(run) = &self.challenge {
+            lines.push(format!("challenge={:?}", run.challenge));
+            lines.extend(run.main.lines().map(|line| format!("main.{}", line)));
+        }
+        lines.join("\n")
+    }
+
+    /// restores the game progress from the save file format
+    /// unknown or broken lines are skipped
+    pub(crate) fn load_save(&mut self, text: &str) {
+        let mut challenge = None;
+        let mut main = Vec::new();
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key {
+                _ if key.starts_with("main.") => main.push(&line["main.".len()..]),
+                "challenge" => challenge = parse_enum(value),
+                "challenge_done" => {
+                    if let Some(challenge) = parse_enum(value) {
+                        self.completed_challenges.insert(challenge);
+                    }
+                }
+                "profile" => self.profile = parse_enum(value).unwrap_or(self.profile),
+                // saves from before the profile types only knew hardcore mode
+                "hardcore" if value == "true" => self.profile = Profile::Hardcore,
+                "ng_plus" => self.ng_plus = value.parse().unwrap_or(self.ng_plus),
+                "money" => self.sim.money = value.parse().unwrap_or(self.sim.money),
+                "total_clicks" => {
+                    self.sim.total_clicks = value.parse().unwrap_or(self.sim.total_clicks)
+                }
+                "total_time" => {
+                    if let Ok(secs) = value.parse::<f64>() {
+                        self.sim.total_time = Duration::from_secs_f64(secs.max(0.0));
+                    }
+                }
+                "theme" => {
+                    if let Some(theme) = parse_enum(value) {
+                        self.theme = theme;
+                    }
+                }
+                "unlock" => {
+                    if let Some(upgrade) = Upgrade::from_id(value) {
+                        self.sim.unlock.insert(upgrade);
+                    }
+                }
+                "owned_theme" => {
+                    if let Some(theme) = parse_enum(value) {
+                        self.owned_themes.insert(theme);
+                    }
+                }
+                _ if self.sim.stats.load_line(key, value) => {}
+                _ if self.speedrun.load_line(key, value) => {}
+                _ if self.blitz_scores.load_line(key, value) => {}
+                _ if self.daily_scores.load_line(key, value) => {}
+                _ if self.sandbox.load_line(key, value) => {}
+                "history" => self.sim.history.load_line(value),
+                "sample" => self.sim.timeline.load_line(value),
+                _ if key.starts_with("achievement.") => {
+                    let achievement = key.strip_prefix("achievement.").and_then(parse_enum);
+                    if let (Some(achievement), Ok(time)) = (achievement, value.parse()) {
+                        self.sim.achievements.insert(achievement, time);
+                    }
+                }
+                _ => {
+                    let upgrade = key.strip_prefix("upgrade.").and_then(Upgrade::from_id);
+                    if let (Some(upgrade), Ok(mut level)) = (upgrade, value.parse()) {
+                        // a ParticleTier level can't unlock more particles than the table has
+                        let particles = self.sim.particle_table.len();
+                        if upgrade.effect() == UpgradeEffect::ParticleTier && level > particles {
+                            warn!(
+                                "the save has {:?} at level {}, but there are only {} particles",
+                                upgrade, level, particles
+                            );
+                            level = particles;
+                        }
+                        self.sim.upgrades.insert(upgrade, level);
+                    }
+                }
+            }
+        }
+        // never use a theme the player doesn't own
+        if !self.owned_themes.contains(&self.theme) {
+            self.theme = Theme::Classic;
+        }
+        // saves from before the lifetime earnings count the money
+        // the player has, and spent, as earned
+        if !text.lines().any(|line| line.starts_with("money_earned=")) {
+            self.sim.stats.money_earned = self.sim.money + self.sim.stats.money_spent;
+        }
+        // saves from before the conversion earnings count all the earned money as sold
+        if !text.lines().any(|line| line.starts_with("sale_earned=")) {
+            self.sim.stats.sale_earned = self.sim.stats.money_earned;
+        }
+        if let Some(challenge) = challenge {
+            self.challenge = Some(ChallengeRun {
+                challenge,
+                main: main.join("\n"),
+            });
+        }
+        self.apply_modifiers();
+        // the session and the speedrun start with the loaded progress
+        self.sim.reset_session();
+        self.speedrun.start(&self.sim);
+    }
+
+    /// writes the game progress to the save file
+    pub(crate) fn save(&self) -> Result<(), SaveError> {
+        if let Some(path) = &self.save_path {
+            write_file(path, &self.to_save())?;
+            debug!("saved the game to {}", path.display());
+        }
+        Ok(())
+    }
+
+    /// reads the game progress from the save file, if there is one
+    pub(crate) fn load(&mut self) -> Result<(), SaveError> {
+        if let Some(path) = self.save_path.clone()
+            && let Some(text) = read_file(&path)?
+        {
+            self.load_save(&text);
+            debug!("loaded the game from {}", path.display());
+        }
+        Ok(())
+    }
+
+    /// saves the game progress, telling the player if it failed
+    pub(crate) fn try_save(&mut self) {
+        if let Err(err) = self.save() {
+            warn!("couldn't save the game: {}", err);
+            self.notify(tr_args("pause-save-failed", &[("error", &err)]));
+        }
+    }
+
+    /// saves the current frame to a PNG in the screenshots folder
+    /// the image is encoded on a background thread
+    pub(crate) fn screenshot(&mut self, ctx: &mut Context) {
+        let Some(dir) = self.screenshot_dir.clone() else {
+            return;
+        };
+        if let Err(err) = fs::create_dir_all(&dir) {
+            warn!("couldn't create {}: {}", dir.display(), err);
+            self.notify(tr_args(
+                "screenshot-dir-failed",
+                &[("dir", &dir.display()), ("error", &err)],
+            ));
+            return;
+        }
+        // copy the frame out of the GPU
+        let frame = ctx.gfx.frame();
+        let (width, height) = (frame.width(), frame.height());
+        let bgra = matches!(
+            frame.format(),
+            ImageFormat::Bgra8Unorm | ImageFormat::Bgra8UnormSrgb
+        );
+        let mut pixels = match frame.to_pixels(&ctx.gfx) {
+            Ok(pixels) => pixels,
+            Err(err) => {
+                warn!("couldn't capture the screen: {}", err);
+                self.notify(tr_args("screenshot-capture-failed", &[("error", &err)]));
+                return;
+            }
+        };
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_millis());
+        let path = dir.join(format!("screenshot-{}.png", stamp));
+        self.screenshots.push(thread::spawn(move || {
+            if bgra {
+                for pixel in pixels.chunks_exact_mut(4) {
+                    pixel.swap(0, 2);
+                }
+            }
+            image::save_buffer(&path, &pixels, width, height, image::ColorType::Rgba8)
+                .map(|_| path)
+                .map_err(|err| err.to_string())
+        }));
+    }
+
+    /// writes the timeline of the progress to a CSV file in the exports folder
+    /// returns the file it was written to
+    pub(crate) fn export_stats(&self) -> Result<Option<PathBuf>, SaveError> {
+        let Some(dir) = &self.export_dir else {
+            return Ok(None);
+        };
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_millis());
+        let path = dir.join(format!("stats-{}.csv", stamp));
+        let upgrades: Vec<Upgrade> = upgrade_table().iter().collect();
+        write_file(&path, &self.sim.timeline.to_csv(&upgrades))?;
+        debug!("exported the statistics to {}", path.display());
+        Ok(Some(path))
+    }
+
+    /// exports the statistics and tells the player where they went
+    pub(crate) fn try_export_stats(&mut self) {
+        match self.export_stats() {
+            Ok(Some(path)) => {
+                self.notify(tr_args("stats-exported", &[("path", &path.display())]));
+            }
+            Ok(None) => {}
+            Err(err) => {
+                warn!("couldn't export the statistics: {}", err);
+                self.notify(tr_args("stats-export-failed", &[("error", &err)]));
+            }
+        }
+    }
+
+    /// runs the speedrun timer and tells the player about the reached splits
+    /// the game is saved right away, so the personal bests aren't lost
+    /// the splits are for the main game, so the timer waits during a blitz or a challenge
+    pub(crate) fn update_speedrun(&mut self, dt: Duration) {
+        if self.blitz.is_some() || self.challenge.is_some() || !self.profile.scored() {
+            return;
+        }
+        let reached = self.speedrun.update(dt, &self.sim);
+        for split in &reached {
+            let time = format_split(self.speedrun.elapsed);
+            let message = match self.speedrun.delta(*split) {
+                Some(delta) => tr_args(
+                    "speedrun-split-delta",
+                    &[
+                        ("split", &split.name()),
+                        ("time", &time),
+                        ("delta", &format_delta(delta)),
+                    ],
+                ),
+                None => tr_args(
+                    "speedrun-split",
+                    &[("split", &split.name()), ("time", &time)],
+                ),
+            };
+            self.notify(message);
+        }
+        if !reached.is_empty() {
+            self.try_save();
+        }
+    }
+
+    /// reports the screenshots that finished saving
+    pub(crate) fn check_screenshots(&mut self) {
+        while let Some(i) = self.screenshots.iter().position(|s| s.is_finished()) {
+            let message = match self.screenshots.remove(i).join() {
+                Ok(Ok(path)) => {
+                    debug!("saved a screenshot to {}", path.display());
+                    tr_args("screenshot-saved", &[("path", &path.display())])
+                }
+                Ok(Err(err)) => {
+                    warn!("couldn't save the screenshot: {}", err);
+                    tr_args("screenshot-save-failed", &[("error", &err)])
+                }
+                Err(_) => {
+                    warn!("the screenshot thread panicked");
+                    tr("screenshot-failed").to_string()
+                }
+            };
+            self.notify(message);
+        }
+    }
+
+    /// moves the tutorial on if the player just did what the step asked
+    /// the last step ends the tutorial for good
+    pub(crate) fn advance_tutorial(&mut self, done: TutorialStep) {
+        if self.tutorial != Some(done) {
+            return;
+        }
+        self.tutorial = done.next();
+        if self.tutorial.is_none() {
+            self.skip_tutorial();
+        }
+    }
+
+    /// ends the tutorial and remembers not to show it again
+    pub(crate) fn skip_tutorial(&mut self) {
+        self.tutorial = None;
+        self.settings.tutorial_done = true;
+        self.try_save_settings();
+    }
+
+    /// shows a notification in the top-right corner for a few seconds
+    pub(crate) fn notify(&mut self, text: impl Into<String>) {
+        self.notifications.push(text.into());
+    }
+
+    /// performs a game action triggered by the keyboard
+    pub(crate) fn run_action(&mut self, ctx: &mut Context, action: Action) {
+        match action {
+            // holding the key drips sand at the key repeat rate
+            Action::Drop => {
+                if !self.paused {
+                    self.queue(GameAction::DropRandom);
+                }
+            }
+            Action::Convert => self.queue(GameAction::Convert),
+            Action::ToggleInfo => {
+                self.settings.show_info = !self.settings.show_info;
+                self.try_save_settings();
+            }
+            Action::ToggleStats => self.show_stats = !self.show_stats,
+            Action::ToggleAchievements => self.show_achievements = !self.show_achievements,
+            Action::ToggleSettings => self.show_settings = !self.show_settings,
+            Action::ToggleOptions => self.show_options = !self.show_options,
+            Action::ToggleDebug => self.show_debug = !self.show_debug,
+            Action::ToggleMute => {
+                self.toggle_mute();
+                self.try_save_settings();
+                // silence everything right away, not on the next update
+                self.play_sounds(ctx);
+                self.update_music();
+                self.update_pour_sound(ctx);
+            }
+            Action::Quit => ctx.request_quit(),
+            Action::Pause => self.paused = !self.paused,
+            Action::Screenshot => self.screenshot_requested = true,
+            Action::ResetCamera => self.camera = Camera::default(),
+            Action::Fullscreen => {
+                self.fullscreen = !self.fullscreen;
+                let mode = if self.fullscreen {
+                    ggez::conf::FullscreenType::Desktop
+                } else {
+                    ggez::conf::FullscreenType::Windowed
+                };
+                // a failed switch leaves the window as it was
+                if let Err(err) = ctx.gfx.set_fullscreen(mode) {
+                    warn!("couldn't switch to fullscreen: {}", err);
+                    self.fullscreen = !self.fullscreen;
+                    self.notify(tr_args("fullscreen-failed", &[("error", &err)]));
+                }
+            }
+        }
+    }
+
+    /// writes the player settings to the settings file
+    pub(crate) fn save_settings(&self) -> Result<(), SaveError> {
+        if let Some(path) = &self.settings_path {
+            write_file(path, &self.settings.to_text())?;
+            debug!("saved the settings to {}", path.display());
+        }
+        Ok(())
+    }
+
+    /// reads the player settings from the settings file, if there is one
+    /// the language is set up even if the file can't be read
+    pub(crate) fn load_settings(&mut self) -> Result<(), SaveError> {
+        let loaded = match &self.settings_path {
+            Some(path) => read_file(path),
+            None => Ok(None),
+        };
+        if let Ok(Some(text)) = &loaded {
+            self.settings = Settings::from_text(text);
+            debug!("loaded the settings");
+        }
+        set_lang(self.settings.lang);
+        loaded.map(|_| ())
+    }
+
+    /// saves the player settings, telling the player if it failed
+    pub(crate) fn try_save_settings(&mut self) {
+        if let Err(err) = self.save_settings() {
+            warn!("couldn't save the settings: {}", err);
+            self.notify(tr_args("settings-save-failed", &[("error", &err)]));
+        }
+    }
+}
+
+/// writes the text to a file, creating its folder if needed
+pub(crate) fn write_file(path: &PathBuf, text: &str) -> Result<(), SaveError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| SaveError::Write(path.clone(), err))?;
+    }
+    fs::write(path, text).map_err(|err| SaveError::Write(path.clone(), err))
+}
+
+/// reads the particle table, from the file in the save folder if there is one
+/// a broken table stops the game with the reason
+pub(crate) fn load_particle_table(path: &PathBuf) -> GameResult<ParticleTable> {
+    let (source, text) = read_table(path, "particle", PARTICLE_TABLE)?;
+    debug!("loading the sand particles from {}", source);
+    ParticleTable::parse(&text)
+        .map_err(|err| GameError::ResourceLoadError(format!("{}: {}", source, err)))
+}
+
+/// reads the upgrade table, from the file in the save folder if there is one
+/// a broken table stops the game with the reason
+pub(crate) fn load_upgrade_table(path: &PathBuf) -> GameResult<UpgradeTable> {
+    let (source, text) = read_table(path, "upgrade", UPGRADE_TABLE)?;
+    debug!("loading the upgrades from {}", source);
+    UpgradeTable::parse(&text)
+        .map_err(|err| GameError::ResourceLoadError(format!("{}: {}", source, err)))
+}
+
+/// reads the text of a data table, the built-in one if the file doesn't exist
+/// returns where the text came from and the text
+pub(crate) fn read_table(
+    path: &PathBuf,
+    name: &str,
+    built_in: &str,
+) -> Result<(String, String), SaveError> {
+    Ok(match read_file(path)? {
+        Some(text) => (path.display().to_string(), text),
+        None => (format!("the built-in {} table", name), built_in.to_string()),
+    })
+}
+
+/// reads the text of a file, None if there is no such file yet
+pub(crate) fn read_file(path: &PathBuf) -> Result<Option<String>, SaveError> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(SaveError::Read(path.clone(), err)),
+    }
+}
+
+/// Event handling for the SandDropClicker game
+/// Implements the ggez EventHandler trait
+/// to handle game updates, drawing, mouse clicks, and key events.
+impl EventHandler for SandDropClicker {
+    /// updates the game state
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        // fade the notifications, even while paused
+        self.notifications.update(ctx.time.delta().as_secs_f32());
+        // bring the music back after a big sale
+        self.ducking.update(ctx.time.delta().as_secs_f32());
+        self.fade_pour(ctx.time.delta().as_secs_f32());
+        // apply what the player did since the last update
+        self.apply_actions();
+        // check if the mouse is over the GUI
+        let over_gui = self.over_gui();
+        // the drop indicator replaces the cursor over the play area
+        mouse::set_cursor_hidden(ctx, !over_gui);
+        // sculpting tool, Shift pushes and Ctrl pulls
+        let sculpt = if over_gui {
+            None
+        } else if ctx.keyboard.is_mod_active(KeyMods::SHIFT) {
+            Some(false)
+        } else if ctx.keyboard.is_mod_active(KeyMods::CTRL) {
+            Some(true)
+        } else {
+            None
+        };
+        // holding the vacuum button keeps vacuuming
+        let vacuum = !over_gui && !self.paused && self.mouse_held(ctx, MouseAction::Vacuum);
+        // set up a fixed timestep for the physics of the grains
+        let mut due = 0;
+        while ctx.time.check_update_time(FPS) {
+            due += 1;
+        }
+        // an idle game in the background skips the steps and earns for their time at once
+        if self.run_mode() == BackgroundMode::Idle {
+            self.idle(due as f32 / FPS as f32);
+        } else {
+            self.catch_up(due, sculpt, vacuum);
+        }
+
+        // the speedrun timer runs in real time, even while paused
+        if self.settings.speedrun {
+            self.update_speedrun(ctx.time.delta());
+        }
+
+        // report the saved screenshots
+        self.check_screenshots();
+
+        // play the sounds of this update
+        self.play_sounds(ctx);
+        self.update_music();
+        self.update_pour_sound(ctx);
+
+        // update the GUI
+        if let Some(gui) = &mut self.gui {
+            let scale = self.settings.ui_scale;
+            gui.input.set_scale_factor(scale, self.sim.screen_size);
+            gui.ctx().set_pixels_per_point(scale);
+        }
+        // Shift buys ten upgrade levels and Ctrl as many as affordable
+        self.buy_count = if ctx.keyboard.is_mod_active(KeyMods::CTRL) {
+            u32::MAX
+        } else if ctx.keyboard.is_mod_active(KeyMods::SHIFT) {
+            BULK_BUY
+        } else {
+            1
+        };
+        let gui_start = Instant::now();
+        self.options_gui();
+        self.settings_gui();
+        self.guide_gui();
+        self.stats_gui();
+        self.achievements_gui();
+        self.challenges_gui();
+        self.challenge_summary_gui();
+        self.blitz_gui();
+        self.blitz_summary_gui();
+        self.sandbox_gui();
+        self.history_gui();
+        self.tutorial_gui();
+        self.replay_gui();
+        self.console_gui();
+        self.speedrun_gui();
+        self.notifications_gui();
+        if self.pause_gui() || self.quit_gui() {
+            ctx.request_quit();
+        }
+        self.gui_time = gui_start.elapsed();
+        if let Some(gui) = &mut self.gui {
+            gui.update(ctx)
+        }
+        Ok(())
+    }
+
+    /// draws the game state
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        // clear the screen
+        let mut canvas = graphics::Canvas::from_frame(ctx, self.clear_color());
+        let (width, height) = self.sim.screen_size;
+        canvas.set_screen_coordinates(Rect::new(0.0, 0.0, width, height));
+
+        // draw the theme background
+        self.background(&mut canvas);
+
+        // the heatmap lies under the sand
+        if self.show_heatmap {
+            self.heatmap_overlay(&mut canvas);
+        }
+
+        // the compacted sand lies under the grains
+        self.compacted_layer(&mut canvas);
+
+        // the sand is drawn through the camera, shaken after big sales
+        let world = self.shaken_camera().draw_param();
+
+        // the instances are set in place every frame, and the capacity of the
+        // arrays only grows, so a changing amount of grains doesn't reallocate them
+        // draw the grain trails behind the grains
+        if let Some(trail_batch) = &mut self.trail_batch
+            && self.settings.trails
+        {
+            // settled grains don't leave a trail
+            trail_batch.set(
+                self.sim
+                    .container
+                    .iter()
+                    .filter(|grain| !grain.is_done())
+                    .flat_map(|grain| trail_params(grain, self.textured)),
+            );
+            if let Some(capacity) =
+                grown_capacity(trail_batch.capacity(), trail_batch.instances().len())
+            {
+                trail_batch.resize(ctx, capacity);
+            }
+            canvas.draw(trail_batch, world);
+        }
+
+        if let Some(batch) = &mut self.batch {
+            // how far the physics is into the next step
+            let alpha = ctx.time.remaining_update_time().as_secs_f32() * FPS as f32;
+            let alpha = alpha.clamp(0.0, 1.0);
+            // draw the falling grains, with the sold grains on top
+            let collected = self
+                .collecting
+                .iter()
+                .map(|collected| grain_params(&collected.grain, self.textured, 1.0));
+            // the settled sand of a hardcore game fades as it decays
+            let modifiers = &self.sim.modifiers;
+            let decay = |age| modifiers.decay(age);
+            batch.set(
+                grain_instances(
+                    self.sim.container.iter(),
+                    self.textured,
+                    alpha,
+                    modifiers
+                        .decay_time
+                        .is_some()
+                        .then_some(&decay as &dyn Fn(f32) -> f32),
+                )
+                .chain(collected),
+            );
+            if let Some(capacity) = grown_capacity(batch.capacity(), batch.instances().len()) {
+                batch.resize(ctx, capacity);
+            }
+            canvas.draw(batch, world);
+        }
+
+        // draw the sparkles
+        if let Some(effect_batch) = &mut self.effect_batch {
+            effect_batch.clear();
+            for effect in &self.effects {
+                effect_batch.push(effect.draw_params());
+            }
+            canvas.draw(effect_batch, world);
+        }
+
+        // flash the screen after big sales
+        if self.flash_timer > 0.0 {
+            let alpha = 0.6 * self.flash_timer / FLASH_TIME;
+            let (width, height) = self.sim.screen_size;
+            canvas.draw(
+                &graphics::Quad,
+                DrawParam::default()
+                    .dest_rect(Rect::new(0.0, 0.0, width, height))
+                    .color(Color::new(1.0, 1.0, 1.0, alpha)),
+            );
+        }
+
+        // draw the floating texts
+        for text in &self.floating_texts {
+            canvas.draw(&Text::new(text.text.as_str()), text.draw_params());
+        }
+
+        // dim the sand while paused
+        if self.paused {
+            let (width, height) = self.sim.screen_size;
+            canvas.draw(
+                &graphics::Quad,
+                DrawParam::default()
+                    .dest_rect(Rect::new(0.0, 0.0, width, height))
+                    .color(Color::new(0.0, 0.0, 0.0, 0.4)),
+            );
+        }
+
+        // point out the play area to new players
+        if self.tutorial == Some(TutorialStep::DropSand) {
+            self.tutorial_highlight(&mut canvas);
+        }
+
+        // draw the player stat
+        self.game_info(&mut canvas);
+
+        // draw the container gauge
+        if self.settings.gauge {
+            self.fill_gauge(&mut canvas);
+        }
+
+        // draw the low gravity event banner
+        if self.sim.low_gravity_timer > 0.0 {
+            self.low_gravity_banner(&mut canvas);
+        }
+
+        // draw the gui
+        if let Some(gui) = &self.gui {
+            canvas.draw(gui, DrawParam::default())
+        }
+
+        // draw game info
+        // screenshots always show the stats
+        if self.settings.show_info || self.screenshot_requested {
+            self.player_info(&mut canvas);
+        }
+
+        // draw the debug overlay
+        if self.show_debug {
+            self.debug_info(&mut canvas, ctx.time.fps());
+        }
+
+        // draw the drop indicator in place of the cursor
+        if !self.over_gui() {
+            self.drop_indicator(&mut canvas);
+        }
+
+        // draw the gamepad cursor
+        if let Some((x, y)) = self.gamepad_cursor {
+            self.gamepad_indicator(&mut canvas, x, y);
+        }
+
+        // finish drawing
+        canvas.finish(ctx)?;
+
+        // capture the finished frame
+        if self.screenshot_requested {
+            self.screenshot_requested = false;
+            self.screenshot(ctx);
+        }
+
+        // wait out the rest of the frame to keep to the FPS cap
+        // a window in the background only draws a few frames to save power
+        let cap = if !self.in_background() {
+            self.settings.fps_cap
+        } else {
+            BACKGROUND_FPS
+        };
+        if cap > 0 {
+            let frame = Duration::from_secs_f64(1.0 / cap as f64);
+            let elapsed = self.last_frame.elapsed();
+            if elapsed < frame {
+                thread::sleep(frame - elapsed);
+            }
+        }
+        self.last_frame = Instant::now();
+        Ok(())
+    }
+
+    /// handle mouse clicks
+    /// if the pointer is over the GUI, ignore the click
+    /// otherwise, do what the button is mapped to in the settings
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        button: event::MouseButton,
+        x: f32,
+        y: f32,
+    ) -> Result<(), ggez::GameError> {
+        // no sand is dropped while paused
+        // touch screens also send a click for a finger, which already dropped sand
+        if self.paused || !self.touches.is_empty() {
+            return Ok(());
+        }
+        let (x, y) = self.to_screen(ctx, x, y);
+        let (x, y) = self.camera.to_world(x, y);
+        // clicks on the GUI belong to it and give no feedback,
+        // while a drop into the full container is rejected with feedback by click
+        if self.over_gui() {
+            return Ok(());
+        }
+        match self.settings.mouse.action(button) {
+            MouseAction::Drop => self.queue(GameAction::DropAt { x, y }),
+            MouseAction::Vacuum => self.queue(GameAction::Vacuum { x, y }),
+            // panning follows the mouse movement instead
+            MouseAction::Pan | MouseAction::Nothing => {}
+        }
+
+        Ok(())
+    }
+
+    /// handle mouse movement
+    /// keeps track of the mouse position for the sculpting tool
+    /// dragging with the pan button pans the camera
+    fn mouse_motion_event(
+        &mut self,
+        ctx: &mut Context,
+        x: f32,
+        y: f32,
+        dx: f32,
+        dy: f32,
+    ) -> Result<(), ggez::GameError> {
+        if self.mouse_held(ctx, MouseAction::Pan) {
+            let (dx, dy) = self.to_screen(ctx, dx, dy);
+            self.camera.pan(dx, dy);
+        }
+        let (x, y) = self.to_screen(ctx, x, y);
+        self.pointer = (x, y);
+        self.mouse_pos = self.camera.to_world(x, y);
+        Ok(())
+    }
+
+    /// handle mouse wheel scrolling
+    /// Ctrl+scroll zooms the camera around the mouse
+    /// scrolling over the play area changes how many grains a click drops
+    fn mouse_wheel_event(
+        &mut self,
+        ctx: &mut Context,
+        x: f32,
+        y: f32,
+    ) -> Result<(), ggez::GameError> {
+        if ctx.keyboard.is_mod_active(KeyMods::CTRL) {
+            let pos = ctx.mouse.position();
+            let (mouse_x, mouse_y) = self.to_screen(ctx, pos.x, pos.y);
+            self.camera.zoom_at(mouse_x, mouse_y, ZOOM_STEP.powf(y));
+        } else if !self.over_gui() {
+            self.queue(GameAction::Throttle {
+                steps: y.signum() as i32,
+            });
+        } else if let Some(gui) = &mut self.gui {
+            gui.input.mouse_wheel_event(x, y);
+        }
+        Ok(())
+    }
+
+    /// handle touch screens
+    /// touches over the GUI are left to the click the system sends for them
+    /// otherwise, a tap drops sand and a drag pours it
+    fn touch_event(
+        &mut self,
+        ctx: &mut Context,
+        phase: TouchPhase,
+        x: f64,
+        y: f64,
+    ) -> Result<(), ggez::GameError> {
+        let (x, y) = self.to_screen(ctx, x as f32, y as f32);
+        let over_gui = self.gui_at(x, y);
+        let (x, y) = self.camera.to_world(x, y);
+        match phase {
+            TouchPhase::Started if !over_gui && !self.paused => self.touch_start(x, y),
+            TouchPhase::Moved if !over_gui => self.touch_move(x, y),
+            TouchPhase::Ended | TouchPhase::Cancelled => self.touch_end(x, y),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// handle the window focus
+    /// fingers can't be lifted outside the window, so they are let go
+    fn focus_event(&mut self, ctx: &mut Context, gained: bool) -> Result<(), ggez::GameError> {
+        self.set_window(ctx, gained, self.minimized);
+        if !gained {
+            self.touches.clear();
+        }
+        Ok(())
+    }
+
+    /// handle gamepad buttons
+    fn gamepad_button_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        btn: PadButton,
+        _id: GamepadId,
+    ) -> Result<(), ggez::GameError> {
+        self.gamepad_button(btn);
+        Ok(())
+    }
+
+    /// handle gamepad sticks
+    /// the left stick moves the drop cursor
+    fn gamepad_axis_event(
+        &mut self,
+        _ctx: &mut Context,
+        axis: Axis,
+        value: f32,
+        _id: GamepadId,
+    ) -> Result<(), ggez::GameError> {
+        match axis {
+            Axis::LeftStickX => self.stick.0 = value,
+            // the stick points up for positive values, the screen down
+            Axis::LeftStickY => self.stick.1 = -value,
+            _ => return Ok(()),
+        }
+        if self.gamepad_cursor.is_none() {
+            let (width, height) = self.sim.screen_size;
+            self.gamepad_cursor = Some((width / 2.0, height / 2.0));
+        }
+        Ok(())
+    }
+
+    /// handle quitting, from Ctrl+Q or the window close button
+    /// asks first if there is unsold sand, returning true cancels the quit
+    /// a failed save is passed on, so the reason gets logged
+    fn quit_event(&mut self, _ctx: &mut Context) -> Result<bool, ggez::GameError> {
+        if !self.confirm_quit() {
+            return Ok(true);
+        }
+        self.finish_recording()?;
+        self.save()?;
+        Ok(false)
+    }
+
+    /// handle window resizing
+    /// moves the ground to the bottom of the new window
+    /// a replay keeps the sizes it was recorded with
+    fn resize_event(
+        &mut self,
+        ctx: &mut Context,
+        width: f32,
+        height: f32,
+    ) -> Result<(), ggez::GameError> {
+        // minimizing shrinks the window to nothing, which must not squash the play area
+        let minimized = width <= 0.0 || height <= 0.0;
+        self.set_window(ctx, self.focused, minimized);
+        if minimized {
+            return Ok(());
+        }
+        self.apply(GameAction::Resize { width, height });
+        Ok(())
+    }
+
+    /// handle key down events
+    /// the keys can be rebound, by default:
+    /// C to convert the sand into money
+    /// Ctrl+I to toggle info display
+    /// Ctrl+O to toggle the settings window
+    /// Ctrl+D to toggle the debug overlay
+    /// Ctrl+S to toggle the statistics window
+    /// Ctrl+A to toggle the achievements window
+    /// Ctrl+Q to quit the game
+    /// Escape to pause or resume the game
+    /// F11 to toggle fullscreen
+    /// Home to reset the camera
+    /// F12 to take a screenshot
+    fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, repeat: bool) -> GameResult {
+        // the settings window waits for a new key for an action
+        if let Some(action) = self.rebinding {
+            if let Some(key) = input.keycode
+                && BINDABLE_KEYS.contains(&key)
+            {
+                self.settings.keybinds.bind(action, key, input.mods);
+                self.rebinding = None;
+                self.try_save_settings();
+            }
+            return Ok(());
+        }
+        // the grave key drops the developer console down, if it is enabled
+        if input.keycode == Some(KeyCode::Grave)
+            && let Some(console) = &mut self.console
+        {
+            console.open = !console.open;
+            return Ok(());
+        }
+        // ignore the keys while typing into a text field
+        if let Some(gui) = &mut self.gui
+            && gui.ctx().wants_keyboard_input()
+        {
+            return Ok(());
+        }
+        let Some(key) = input.keycode else {
+            return Ok(());
+        };
+        if let Some(action) = self.settings.keybinds.action(key, input.mods) {
+            self.run_action(ctx, action);
+        } else if let Some(slot) = QUICK_BUY_KEYS.iter().position(|k| *k == key)
+            && !repeat
+            && !self.paused
+        {
+            // number keys buy the upgrades, one level per press
+            self.quick_buy(slot);
+        }
+        Ok(())
+    }
+}
+
+/// Structure representing a finger on the touch screen
+/// * x: horizontal world position of the finger
+/// * y: vertical world position of the finger
+/// * dragged: true once the finger moved, which makes it pour
+/// * pour_timer: time until the finger drops sand again
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Touch {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) dragged: bool,
+    pub(crate) pour_timer: f32,
+}
+
+/// Implementation of methods for the Touch struct
+/// * new: puts a finger down
+impl Touch {
+    /// puts a finger down, the first pour waits a full interval
+    pub(crate) fn new(x: f32, y: f32) -> Self {
+        Self {
+            x,
+            y,
+            dragged: false,
+            pour_timer: POUR_INTERVAL,
+        }
+    }
+}
+
+/// Steps of the first-run tutorial
+/// * DropSand: Click the play area to drop sand.
+/// * Convert: Sell the sand with the Convert button.
+/// * Upgrade: Buy the first upgrade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TutorialStep {
+    DropSand,
+    Convert,
+    Upgrade,
+}
+
+/// Implementation of methods for the TutorialStep enum
+/// * next: returns the step after this one, if any
+/// * text_key: returns the key of the step's instructions
+impl TutorialStep {
+    /// returns the step after this one, if any
+    pub(crate) fn next(&self) -> Option<TutorialStep> {
+        match self {
+            TutorialStep::DropSand => Some(TutorialStep::Convert),
+            TutorialStep::Convert => Some(TutorialStep::Upgrade),
+            TutorialStep::Upgrade => None,
+        }
+    }
+
+    /// returns the key of the step's instructions
+    pub(crate) fn text_key(&self) -> &str {
+        match self {
+            TutorialStep::DropSand => "tutorial-drop-sand",
+            TutorialStep::Convert => "tutorial-convert",
+            TutorialStep::Upgrade => "tutorial-upgrade",
+        }
+    }
+}
+
+/// Changes to the game the player can make
+/// * DropAt: Drop sand by hand at the world position.
+/// * DropRandom: Drop sand by hand at a random spot along the top.
+/// * Convert: Sell all the sand in the container.
+/// * Vacuum: Sell the settled sand near the world position.
+/// * Sculpt: Push the sand near the world position away, or pull it in, for one step.
+/// * Throttle: Change how many grains a click drops by a number of steps.
+/// * Resize: Change the size of the play area.
+/// * Buy: Buy up to count levels of the upgrade.
+/// * BuyTheme: Buy the background theme and switch to it.
+/// * Reset: Reset the progress back to a new game, played as the profile type.
+/// * Give: Add money, from the developer console.
+/// * SetLevel: Set the level of the upgrade, from the developer console.
+/// * Spawn: Drop an amount of grains of the sand particle, from the developer console.
+/// * Clear: Remove the sand from the container unsold, from the developer console.
+/// * Seed: Seed the random numbers, from the developer console.
+/// * StartChallenge: Start a challenge run, the main game waits for it.
+/// * EndChallenge: Give up the challenge run and go back to the main game.
+/// * StartBlitz: Start a blitz, the main game waits for it.
+/// * StartDaily: Start the daily run of a day, counted from 1970-01-01.
+/// * EndBlitz: Give up the blitz or the daily run and go back to the main game.
+/// * SetSandbox: Change the tools of sandbox mode.
+/// * NewGamePlus: Start the upgrades and the money over for higher values and costs, once every upgrade is maxed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum GameAction {
+    DropAt { x: f32, y: f32 },
+    DropRandom,
+    Convert,
+    Vacuum { x: f32, y: f32 },
+    Sculpt { x: f32, y: f32, pull: bool },
+    Throttle { steps: i32 },
+    Resize { width: f32, height: f32 },
+    Buy { upgrade: Upgrade, count: u32 },
+    BuyTheme(Theme),
+    Reset(Profile),
+    Give { money: Currency },
+    SetLevel { upgrade: Upgrade, level: u32 },
+    Spawn { particle: SandParticle, amount: u32 },
+    Clear,
+    Seed(u64),
+    StartChallenge(Challenge),
+    EndChallenge,
+    StartBlitz,
+    StartDaily(u64),
+    EndBlitz,
+    SetSandbox(SandboxTools),
+    NewGamePlus,
+}
+
+// Embedded textures for the sand particles, one sprite per particle type
+pub(crate) const PARTICLE_ATLAS: &[u8] = include_bytes!("../assets/particles.png");
+
+/// Tests for the game state
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DAILY_TIME, DECAY_TIME, DUCK_LEVEL, EFFECT_LIFE, FLOAT_TEXT_LIFE, GRAVITY,
+        LOW_GRAVITY_DURATION, LOW_GRAVITY_LIFT, SCREEN_SIZE, SPARKLE_TIME,
+        daily::DailyRules,
+        grain::Grain,
+        speedrun::Split,
+        stats::{Achievement, Counters, HistoryEntry},
+    };
+    use quickcheck::quickcheck;
+
+    // SandDropClicker tests
+    #[test]
+    fn test_game_add_grain() {
+        let mut game = SandDropClicker::_test_state();
+        let initial_amount = game.sim.get_amount();
+        game.sim.add_grain(100.0, 100.0);
+        assert_eq!(game.sim.get_amount(), initial_amount + 1);
+    }
+    #[test]
+    fn test_game_is_full() {
+        let mut game = SandDropClicker::_test_state();
+        // fill the container
+        let size = game.sim.get_size();
+        for _ in 0..size {
+            game.sim.add_grain(100.0, 100.0);
+        }
+        assert!(game.sim.is_full());
+    }
+    #[test]
+    fn test_game_get_size() {
+        let mut game = SandDropClicker::_test_state();
+        assert_eq!(game.sim.get_size(), 25);
+        // buy bigger container upgrade
+        game.sim.upgrades.insert(Upgrade::BIGGER_CONTAINER, 2);
+        assert_eq!(game.sim.get_size(), 75);
+    }
+    #[test]
+    fn test_game_get_amount() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.add_grain(100.0, 100.0);
+        game.sim.add_grain(200.0, 100.0);
+        assert_eq!(game.sim.get_amount(), 2);
+    }
+    #[test]
+    fn test_game_make_money() {
+        let mut game = SandDropClicker::_test_state();
+        // add some grains
+        game.sim.add_grain(100.0, 100.0);
+        game.sim.add_grain(200.0, 100.0);
+        // convert to money
+        game.make_money();
+        assert!(game.sim.money > Currency::ZERO);
+        assert_eq!(game.sim.get_amount(), 0);
+    }
+    #[test]
+    fn test_game_make_money_never_loses() {
+        // converting only ever adds what it earned to the money
+        fn property(money: u64, clicks: u8) -> bool {
+            let mut game = SandDropClicker::_test_state();
+            game.sim.money = Currency(money as u128);
+            for i in 0..clicks {
+                game.sim.click(i as f32 * 3.0, 0.0, 1);
+            }
+            let earned = game.sim.stats.money_earned;
+            game.make_money();
+            let earned = game.sim.stats.money_earned - earned;
+            game.sim.money == Currency(money as u128) + earned
+                && (clicks == 0 || earned > Currency::ZERO)
+        }
+        quickcheck(property as fn(u64, u8) -> bool);
+    }
+    #[test]
+    fn test_game_make_money_twice() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.add_grain(100.0, 100.0);
+        game.make_money();
+        let money = game.sim.money;
+        assert_eq!(game.collecting.len(), 1);
+        // selling again mid-flight doesn't pay for the same grain
+        game.sim.add_grain(100.0, 100.0);
+        game.make_money();
+        assert_eq!(game.sim.money, money + Currency(1));
+        assert_eq!(game.collecting.len(), 2);
+    }
+    #[test]
+    fn test_game_big_sale() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.add_grain(100.0, 100.0);
+        game.make_money();
+        assert_eq!(game.shake_timer, SHAKE_TIME);
+        assert_eq!(game.flash_timer, FLASH_TIME);
+        // small sales compared to the money don't shake
+        let mut game = SandDropClicker::_test_state();
+        game.sim.money = Currency(1000);
+        game.sim.add_grain(100.0, 100.0);
+        game.make_money();
+        assert_eq!(game.shake_timer, 0.0);
+        // the effects can be turned off
+        let mut game = SandDropClicker::_test_state();
+        game.settings.screen_shake = false;
+        game.settings.flash = false;
+        game.sim.add_grain(100.0, 100.0);
+        game.make_money();
+        assert_eq!(game.shake_timer, 0.0);
+        assert_eq!(game.flash_timer, 0.0);
+    }
+    #[test]
+    fn test_game_full_click() {
+        let mut game = SandDropClicker::_test_state();
+        let size = game.sim.get_size();
+        game.sim.add_grains(100.0, 100.0, size);
+        // clicks into the full container flash the counter and thud, the hint shows once
+        game.click(100.0, 100.0);
+        game.click(100.0, 100.0);
+        assert_eq!(game.full_flash_timer, FULL_FLASH_TIME);
+        assert_eq!(game.sound_queue[0].0, Sound::Land);
+        let key = game.settings.keybinds.label(Action::Convert);
+        let hint = tr_args("container-full-hint", &[("key", &key)]);
+        assert_eq!(game.notifications.visible(), vec![(hint.as_str(), 1.0)]);
+        // selling makes room, so the next fill-up shows the hint again
+        game.make_money();
+        game.step(1.0, None);
+        assert_eq!(game.full_flash_timer, 0.0);
+        assert!(!game.full_hint_shown);
+        game.sim.add_grains(100.0, 100.0, size);
+        game.click(100.0, 100.0);
+        assert!(game.full_hint_shown);
+    }
+    #[test]
+    fn test_game_buy_upgrade() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.money = Currency(10000);
+        game.buy(Upgrade::AUTO_CLICKER);
+        let level = *game.sim.upgrades.get(&Upgrade::AUTO_CLICKER).unwrap();
+        assert_eq!(level, 1);
+    }
+    #[test]
+    fn test_game_upgrade_label_cache() {
+        let mut game = SandDropClicker::_test_state();
+        let label = game.upgrade_label(Upgrade::AUTO_CLICKER);
+        assert_eq!(game.upgrade_label(Upgrade::AUTO_CLICKER), label);
+        // crossing the cost or buying a level rebuilds the label
+        game.sim.money = Currency(10000);
+        game.upgrade_label(Upgrade::AUTO_CLICKER);
+        assert!(game.upgrade_labels[&Upgrade::AUTO_CLICKER].0.affordable);
+        game.buy(Upgrade::AUTO_CLICKER);
+        assert_ne!(game.upgrade_label(Upgrade::AUTO_CLICKER), label);
+        assert_eq!(game.upgrade_labels[&Upgrade::AUTO_CLICKER].0.level, 1);
+    }
+    #[test]
+    fn test_game_is_maxed() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.upgrades.insert(Upgrade::AUTO_CLICKER, 100);
+        assert!(game.sim.is_maxed(Upgrade::AUTO_CLICKER));
+    }
+    #[test]
+    fn test_game_rand_sand() {
+        let mut game = SandDropClicker::_test_state();
+        for _ in 0..100 {
+            game.sim.upgrades.insert(Upgrade::PARTICLE_TIER, 5);
+            let sand = game.sim.rand_sand();
+            match sand {
+                SandParticle::Sand
+                | SandParticle::Quartz
+                | SandParticle::Shell
+                | SandParticle::Coral
+                | SandParticle::Pinksand => {}
+                _ => panic!("Random sand particle out of range!"),
+            }
+        }
+        // a missing or zeroed tier still drops sand
+        game.sim.upgrades.insert(Upgrade::PARTICLE_TIER, 0);
+        assert_eq!(game.sim.rand_sand(), SandParticle::Sand);
+        game.sim.upgrades.remove(&Upgrade::PARTICLE_TIER);
+        assert_eq!(game.sim.rand_sand(), SandParticle::Sand);
+    }
+    #[test]
+    fn test_game_apply_palette() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.add_grain(100.0, 100.0);
+        let table = ParticleTable::default();
+        let colorblind = table.palette_color(SandParticle::Sand, true);
+        assert_eq!(
+            game.sim.container[0].color,
+            table.palette_color(SandParticle::Sand, false)
+        );
+        game.settings.colorblind = true;
+        game.apply_palette();
+        assert_eq!(game.sim.container[0].color, colorblind);
+        // new grains use the palette too
+        game.sync_settings();
+        game.sim.add_grain(100.0, 100.0);
+        assert_eq!(game.sim.container[1].color, colorblind);
+    }
+    #[test]
+    fn test_game_float_text() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.add_grain(100.0, 100.0);
+        game.make_money();
+        assert_eq!(game.floating_texts.len(), 1);
+        assert_eq!(game.floating_texts[0].text, "+1$");
+        // overlapping texts are moved up
+        game.float_text("+2$".to_string(), 400.0, 300.0);
+        assert!(game.floating_texts[1].y < game.floating_texts[0].y);
+        // the texts fade out
+        for text in &mut game.floating_texts {
+            text.update(FLOAT_TEXT_LIFE);
+        }
+        assert!(game.floating_texts.iter().all(|text| text.is_done()));
+        // and can be turned off
+        game.settings.floating_text = false;
+        game.float_text("+3$".to_string(), 0.0, 0.0);
+        assert_eq!(game.floating_texts.len(), 2);
+    }
+    #[test]
+    fn test_game_money_text() {
+        let mut game = SandDropClicker::_test_state();
+        assert_eq!(game.money_text(Currency(1_500_000)), "1.50M");
+        game.settings.full_numbers = true;
+        assert_eq!(game.money_text(Currency(1_500_000)), "1500000");
+    }
+    #[test]
+    fn test_game_buy_theme() {
+        let mut game = SandDropClicker::_test_state();
+        game.buy_theme(Theme::Beach);
+        assert_eq!(game.theme, Theme::Classic);
+        game.sim.money = Theme::Beach.cost();
+        game.buy_theme(Theme::Beach);
+        assert_eq!(game.theme, Theme::Beach);
+        assert_eq!(game.sim.money, Currency::ZERO);
+        assert!(game.owned_themes.contains(&Theme::Beach));
+    }
+    #[test]
+    fn test_game_save_load() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.money = Currency(1234);
+        game.sim.total_clicks = 56;
+        game.sim.upgrades.insert(Upgrade::AUTO_CLICKER, 3);
+        game.sim.unlock.insert(Upgrade::AUTO_CLICKER);
+        game.owned_themes.insert(Theme::NightSky);
+        game.theme = Theme::NightSky;
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.sim.money, Currency(1234));
+        assert_eq!(loaded.sim.total_clicks, 56);
+        assert_eq!(loaded.sim.upgrades, game.sim.upgrades);
+        assert_eq!(loaded.sim.unlock, game.sim.unlock);
+        assert_eq!(loaded.theme, Theme::NightSky);
+        assert_eq!(loaded.owned_themes, game.owned_themes);
+    }
+    #[test]
+    fn test_game_unlocks() {
+        let mut game = SandDropClicker::_test_state();
+        // the tick unlocks the upgrades while the options window is never built
+        game.show_options = false;
+        game.sim.stats.money_earned = Upgrade::AUTO_CLICKER.def().unlock_earned;
+        game.step(1.0 / 30.0, None);
+        assert!(game.sim.unlock.contains(&Upgrade::AUTO_CLICKER));
+        assert!(game.sim.unlock.contains(&Upgrade::BIGGER_CONTAINER));
+        // an unaffordable upgrade stays listed, with its button disabled
+        assert!(
+            game.sim
+                .upgrade_cost(Upgrade::AUTO_CLICKER)
+                .is_some_and(|cost| game.sim.money < cost)
+        );
+        assert!(game.listed_upgrades().contains(&Upgrade::AUTO_CLICKER));
+        // the unlocks are saved
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.sim.unlock, game.sim.unlock);
+    }
+    #[test]
+    fn test_game_money_past_old_limit() {
+        let mut game = SandDropClicker::_test_state();
+        // a sale at the old i64 limit used to wrap around into debt
+        game.sim.money = Currency(i64::MAX as u128);
+        game.sim.add_grain(100.0, 100.0);
+        game.make_money();
+        assert_eq!(game.sim.money, Currency(i64::MAX as u128 + 1));
+        assert_eq!(game.money_text(game.sim.money), "9.22Qi");
+        // the money survives the save file
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.sim.money, game.sim.money);
+        // and stops at the most there can be
+        game.sim.money = Currency::MAX;
+        game.sim.add_grain(100.0, 100.0);
+        game.make_money();
+        assert_eq!(game.sim.money, Currency::MAX);
+        assert_eq!(game.money_text(game.sim.money), "3.40e38");
+    }
+    #[test]
+    fn test_game_load_broken_save() {
+        let mut game = SandDropClicker::_test_state();
+        game.load_save("money=abc\nnonsense\nupgrade.Unknown=4\ntheme=Beach");
+        assert_eq!(game.sim.money, Currency::ZERO);
+        assert_eq!(game.sim.upgrades.len(), 1);
+        // the theme isn't owned, so it isn't used
+        assert_eq!(game.theme, Theme::Classic);
+    }
+    #[test]
+    fn test_game_save_files() {
+        let dir = std::env::temp_dir().join(format!("sand-drop-save-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut game = SandDropClicker::_test_state();
+        game.save_path = Some(dir.join("save.txt"));
+        game.settings_path = Some(dir.join("settings.txt"));
+        // no files yet is a new game, not an error
+        assert!(game.load().is_ok());
+        assert!(game.load_settings().is_ok());
+        game.sim.money = Currency(1234);
+        game.settings.show_info = !game.settings.show_info;
+        assert!(game.save().is_ok());
+        assert!(game.save_settings().is_ok());
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.save_path = game.save_path.clone();
+        loaded.settings_path = game.settings_path.clone();
+        assert!(loaded.load().is_ok());
+        assert!(loaded.load_settings().is_ok());
+        assert_eq!(loaded.sim.money, Currency(1234));
+        assert_eq!(loaded.settings.show_info, game.settings.show_info);
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn test_game_export_stats() {
+        let dir = std::env::temp_dir().join(format!("sand-drop-export-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut game = SandDropClicker::_test_state();
+        // nothing is written without an exports folder
+        assert!(matches!(game.export_stats(), Ok(None)));
+        game.sim.money = Currency(700);
+        game.sim.tick(60.0);
+        // the samples are kept in the save
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.sim.timeline.samples, game.sim.timeline.samples);
+        game.export_dir = Some(dir.clone());
+        let path = game.export_stats().unwrap().unwrap();
+        assert!(path.starts_with(&dir));
+        let csv = fs::read_to_string(&path).unwrap();
+        assert!(csv.starts_with("minute,money,grains_dropped,conversions,"));
+        assert!(csv.contains("\n1,700,0,0,"));
+        // a file in the way of the folder is shown to the player instead of crashing
+        let blocker = dir.join("blocker");
+        fs::write(&blocker, "").unwrap();
+        game.export_dir = Some(blocker);
+        assert!(matches!(game.export_stats(), Err(SaveError::Write(..))));
+        game.try_export_stats();
+        assert_eq!(game.notifications.visible().len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn test_game_save_errors() {
+        let dir = std::env::temp_dir().join(format!("sand-drop-errors-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        // a file in the way of the save folder can't be written through,
+        // unlike read-only permissions, which don't stop root
+        let blocker = dir.join("blocker");
+        fs::write(&blocker, "").unwrap();
+        let mut game = SandDropClicker::_test_state();
+        game.save_path = Some(blocker.join("save.txt"));
+        game.settings_path = Some(blocker.join("settings.txt"));
+        assert!(matches!(game.save(), Err(SaveError::Write(..))));
+        assert!(matches!(game.save_settings(), Err(SaveError::Write(..))));
+        // the failures are shown to the player instead of crashing
+        game.try_save();
+        game.try_save_settings();
+        assert_eq!(game.notifications.visible().len(), 2);
+        // a folder where the file should be can't be read
+        game.save_path = Some(dir.clone());
+        game.settings_path = Some(dir.clone());
+        game.sim.money = Currency(99);
+        assert!(matches!(game.load(), Err(SaveError::Read(..))));
+        assert!(matches!(game.load_settings(), Err(SaveError::Read(..))));
+        assert_eq!(game.sim.money, Currency(99));
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn test_game_particle_table_file() {
+        let dir = std::env::temp_dir().join(format!("sand-drop-particles-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(PARTICLES_FILE);
+        // without a modded table the built-in one is used
+        assert_eq!(
+            load_particle_table(&path).unwrap(),
+            ParticleTable::default()
+        );
+        // a modded table takes its place
+        fs::write(&path, PARTICLE_TABLE.replace("value = 1\n", "value = 3\n")).unwrap();
+        let table = load_particle_table(&path).unwrap();
+        assert_eq!(table.value(SandParticle::Sand), Currency(3));
+        // and a broken one says where it broke
+        fs::write(&path, "[Mud]").unwrap();
+        match load_particle_table(&path) {
+            Err(GameError::ResourceLoadError(text)) => assert_eq!(
+                text,
+                format!("{}: line 1: unknown sand particle [Mud]", path.display())
+            ),
+            other => panic!("The broken table should fail, got {:?}", other),
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn test_game_upgrade_table_file() {
+        let dir = std::env::temp_dir().join(format!("sand-drop-upgrades-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(UPGRADES_FILE);
+        // without a modded table the built-in one is used
+        assert_eq!(load_upgrade_table(&path).unwrap(), UpgradeTable::default());
+        // a modded table takes its place
+        fs::write(
+            &path,
+            UPGRADE_TABLE.replace("base_cost = 50\n", "base_cost = 5\n"),
+        )
+        .unwrap();
+        let table = load_upgrade_table(&path).unwrap();
+        assert_eq!(table.defs[0].base_cost, 5.0);
+        // and a broken one says where it broke
+        fs::write(&path, "[Funnel]").unwrap();
+        match load_upgrade_table(&path) {
+            Err(GameError::ResourceLoadError(text)) => assert_eq!(
+                text,
+                format!("{}: [Funnel] is missing `effect`", path.display())
+            ),
+            other => panic!("The broken table should fail, got {:?}", other),
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn test_game_sparkle() {
+        let mut game = SandDropClicker::_test_state();
+        // only basic sand, nothing sparkles
+        game.sim.add_grain(100.0, 100.0);
+        game.sparkle(1.0 / 30.0);
+        assert!(game.effects.is_empty());
+        // a rare grain sparkles
+        let mut grain = Grain::new(100.0, 100.0, GRAIN_SIZE, Color::WHITE);
+        grain.sparkle_timer = SPARKLE_TIME;
+        game.sim.container.add(grain);
+        game.sparkle(1.0 / 30.0);
+        assert_eq!(game.effects.len(), 1);
+        // sparkles don't take up container space
+        assert_eq!(game.sim.get_amount(), 2);
+        // the sparkles are capped
+        for _ in 0..(MAX_EFFECTS * 2) {
+            game.sim.container[1].sparkle_timer = SPARKLE_TIME;
+            game.sparkle(0.0);
+        }
+        assert_eq!(game.effects.len(), MAX_EFFECTS);
+        // and cleaned up once they fade
+        game.sim.container[1].sparkle_timer = 0.0;
+        game.sparkle(EFFECT_LIFE);
+        assert!(game.effects.is_empty());
+    }
+    #[test]
+    fn test_game_low_gravity() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.add_grain(100.0, SCREEN_SIZE.1);
+        game.sim.start_low_gravity();
+        assert!(game.sim.gravity < 0.0);
+        // settled grains are woken up
+        assert!(!game.sim.container[0].is_done());
+        // after the lift off, gravity is weak but pulls down again
+        game.sim.low_gravity(LOW_GRAVITY_LIFT);
+        assert!(game.sim.gravity > 0.0 && game.sim.gravity < GRAVITY);
+        // gravity returns when the event is over
+        game.sim.low_gravity(LOW_GRAVITY_DURATION);
+        assert_eq!(game.sim.gravity, GRAVITY);
+        assert_eq!(game.sim.low_gravity_timer, 0.0);
+    }
+    #[test]
+    fn test_game_sculpt() {
+        let mut game = SandDropClicker::_test_state();
+        let ground = SCREEN_SIZE.1 - GRAIN_SIZE / 2.0;
+        game.sim.add_grain(100.0, ground);
+        game.sim.add_grain(SCREEN_SIZE.0 - GRAIN_SIZE / 2.0, ground);
+        game.sim.index_grains();
+        // push the grain on the left to the left
+        game.sim.sculpt(110.0, ground, false, 1.0 / 30.0);
+        assert!(game.sim.container[0].x_v < 0.0);
+        assert!(!game.sim.container[0].is_done());
+        // the far away grain is left alone
+        assert_eq!(game.sim.container[1].x_v, 0.0);
+        // pull the grain on the left back towards the cursor
+        let before = game.sim.container[0].x_v;
+        game.sim.sculpt(120.0, ground, true, 1.0 / 30.0);
+        assert!(game.sim.container[0].x_v > before);
+        // push the grain on the right into the wall
+        game.sim
+            .sculpt(SCREEN_SIZE.0 - 30.0, ground, false, 1.0 / 30.0);
+        assert!(game.sim.container[1].x_v > 0.0);
+        for _ in 0..30 {
+            game.sim.container[1].update(1.0 / 30.0, GRAVITY, SCREEN_SIZE);
+        }
+        assert!(game.sim.container[1].rect.right() <= SCREEN_SIZE.0);
+    }
+    #[test]
+    fn test_game_particle_tier_past_table() {
+        let mut game = SandDropClicker::_test_state();
+        let max = game.sim.particle_table.len();
+        // the last particle is unlocked, nothing is left to buy
+        game.sim.upgrades.insert(Upgrade::PARTICLE_TIER, max);
+        game.sim.money = Currency::MAX;
+        assert_eq!(game.sim.upgrade_cost(Upgrade::PARTICLE_TIER), None);
+        assert!(game.sim.is_maxed(Upgrade::PARTICLE_TIER));
+        assert!(!game.sim.buy(Upgrade::PARTICLE_TIER));
+        assert_eq!(
+            game.sim.levels_cost(Upgrade::PARTICLE_TIER, BULK_BUY),
+            (0, Currency::ZERO)
+        );
+        // a level past the table isn't free either
+        game.sim.upgrades.insert(Upgrade::PARTICLE_TIER, max + 1);
+        assert_eq!(game.sim.upgrade_cost(Upgrade::PARTICLE_TIER), None);
+        assert!(!game.sim.buy(Upgrade::PARTICLE_TIER));
+        assert_eq!(game.sim.money, Currency::MAX);
+        assert!(game.upgrade_label(Upgrade::PARTICLE_TIER).contains("MAX"));
+        // and a save with one is clamped to the table
+        game.load_save(&format!("upgrade.{:?}={}", Upgrade::PARTICLE_TIER, max + 1));
+        assert_eq!(game.sim.upgrades[&Upgrade::PARTICLE_TIER], max);
+    }
+    #[test]
+    fn test_game_challenge() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.money = Currency(5000);
+        game.sim.upgrades.insert(Upgrade::AUTO_CLICKER, 3);
+        // the challenge starts from a new game with its rules
+        game.apply(GameAction::StartChallenge(Challenge::NoAutoclicker));
+        assert_eq!(game.sim.money, Currency::ZERO);
+        assert_eq!(game.sim.upgrades.get(&Upgrade::AUTO_CLICKER), None);
+        assert!(game.sim.modifiers.hides(Upgrade::AUTO_CLICKER));
+        // another one can't start on top of it
+        game.apply(GameAction::StartChallenge(Challenge::TinyContainer));
+        assert_eq!(
+            game.challenge.as_ref().map(|run| run.challenge),
+            Some(Challenge::NoAutoclicker)
+        );
+        // the challenge and the main game waiting for it are saved
+        game.sim.money = Currency(70);
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.challenge, game.challenge);
+        assert_eq!(loaded.sim.money, Currency(70));
+        assert!(loaded.sim.modifiers.hides(Upgrade::AUTO_CLICKER));
+        // reaching the goal goes back to the main game with the reward
+        game.sim.stats.money_earned = Challenge::NoAutoclicker.goal();
+        game.step(1.0 / 30.0, None);
+        assert!(game.challenge.is_none());
+        assert_eq!(game.sim.money, Currency(5000));
+        assert_eq!(game.sim.upgrades.get(&Upgrade::AUTO_CLICKER), Some(&3));
+        assert!(
+            game.completed_challenges
+                .contains(&Challenge::NoAutoclicker)
+        );
+        assert_eq!(
+            game.challenge_summary.map(|summary| summary.challenge),
+            Some(Challenge::NoAutoclicker)
+        );
+        assert_eq!(game.sim.modifiers.value(Currency(100)), Currency(110));
+        // the completed challenges are kept in the save
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.completed_challenges, game.completed_challenges);
+        assert_eq!(loaded.sim.modifiers, game.sim.modifiers);
+    }
+    #[test]
+    fn test_game_challenge_give_up() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.money = Currency(5000);
+        game.apply(GameAction::StartChallenge(Challenge::ExpensiveTaste));
+        game.sim.money = Currency(10);
+        game.apply(GameAction::EndChallenge);
+        // giving up only brings the main game back
+        assert!(game.challenge.is_none());
+        assert_eq!(game.sim.money, Currency(5000));
+        assert!(game.completed_challenges.is_empty());
+        assert!(game.challenge_summary.is_none());
+        assert_eq!(game.sim.modifiers, Modifiers::default());
+    }
+    #[test]
+    fn test_game_challenge_keeps_achievements_and_speedrun() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.achievements.insert(Achievement::PocketMoney, 5);
+        game.speedrun.elapsed = Duration::from_secs(30);
+        // the challenge knows the earned achievements, and the timer waits
+        game.apply(GameAction::StartChallenge(Challenge::ExpensiveTaste));
+        assert!(
+            game.sim
+                .achievements
+                .contains_key(&Achievement::PocketMoney)
+        );
+        game.update_speedrun(Duration::from_secs(10));
+        assert_eq!(game.speedrun.elapsed, Duration::from_secs(30));
+        // the ones earned in the challenge come back with the main game
+        game.sim.achievements.insert(Achievement::GoldRush, 7);
+        game.apply(GameAction::EndChallenge);
+        assert!(
+            game.sim
+                .achievements
+                .contains_key(&Achievement::PocketMoney)
+        );
+        assert!(game.sim.achievements.contains_key(&Achievement::GoldRush));
+        assert_eq!(game.speedrun.elapsed, Duration::from_secs(30));
+    }
+    #[test]
+    fn test_game_blitz() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.money = Currency(5000);
+        game.sim.upgrades.insert(Upgrade::AUTO_CLICKER, 3);
+        // the blitz plays a new game with cheaper upgrades
+        game.apply(GameAction::StartBlitz);
+        assert_eq!(game.sim.money, Currency::ZERO);
+        assert_eq!(game.sim.upgrades.get(&Upgrade::AUTO_CLICKER), None);
+        assert!(game.sim.modifiers.cost(Currency(100)) < Currency(100));
+        // no challenge runs on top of it
+        game.apply(GameAction::StartChallenge(Challenge::TinyContainer));
+        assert!(game.challenge.is_none());
+        // the save keeps the main game, not the blitz
+        game.sim.money = Currency(70);
+        game.sim.stats.money_earned = Currency(900);
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.sim.money, Currency(5000));
+        // the time running out goes back to the main game and records the score
+        game.blitz.as_mut().unwrap().time_left = 0.01;
+        game.step(1.0 / 30.0, None);
+        assert!(game.blitz.is_none());
+        assert_eq!(game.sim.money, Currency(5000));
+        assert_eq!(game.sim.upgrades.get(&Upgrade::AUTO_CLICKER), Some(&3));
+        assert_eq!(
+            game.blitz_summary,
+            Some(BlitzSummary {
+                money: Currency(900),
+                rank: Some(0),
+                daily: None
+            })
+        );
+        assert_eq!(game.blitz_scores.scores[0].money, Currency(900));
+        // the high scores are kept in the save
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.blitz_scores, game.blitz_scores);
+    }
+    #[test]
+    fn test_game_daily() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.money = Currency(5000);
+        game.apply(GameAction::StartDaily(20_431));
+        // the daily run plays under the rules of its day, with its own seed
+        let rules = DailyRules::for_date("2025-12-09");
+        assert_eq!(game.sim.modifiers, rules.modifiers());
+        let mut same = SandDropClicker::_test_state();
+        same.apply(GameAction::StartDaily(20_431));
+        assert_eq!(game.sim.rng.random::<u64>(), same.sim.rng.random::<u64>());
+        // the best score of the day is kept, a worse one isn't
+        for money in [900, 300] {
+            game.apply(GameAction::StartDaily(20_431));
+            game.sim.stats.money_earned = Currency(money);
+            game.update_blitz(DAILY_TIME);
+            assert!(game.blitz.is_none());
+        }
+        assert_eq!(game.sim.money, Currency(5000));
+        assert_eq!(game.daily_scores.get(20_431), Some(Currency(900)));
+        assert_eq!(game.blitz_summary.and_then(|summary| summary.rank), None);
+        assert!(game.blitz_scores.scores.is_empty());
+        // the daily scores are kept in the save
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.daily_scores, game.daily_scores);
+    }
+    #[test]
+    fn test_game_blitz_give_up() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.money = Currency(5000);
+        game.apply(GameAction::StartBlitz);
+        game.sim.stats.money_earned = Currency(900);
+        game.apply(GameAction::EndBlitz);
+        // giving up only brings the main game back
+        assert!(game.blitz.is_none());
+        assert_eq!(game.sim.money, Currency(5000));
+        assert!(game.blitz_scores.scores.is_empty());
+        assert!(game.blitz_summary.is_none());
+        assert_eq!(game.sim.modifiers, Modifiers::default());
+    }
+    #[test]
+    fn test_game_upgrade_cost() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.upgrades.insert(Upgrade::MORE_PARTICLES, 2);
+        let cost = game.sim.upgrade_cost(Upgrade::MORE_PARTICLES).unwrap();
+        assert!(cost > Currency::ZERO);
+    }
+    #[test]
+    fn test_game_grain_size() {
+        let mut game = SandDropClicker::_test_state();
+        game.settings.ui_scale = 2.0;
+        assert_eq!(game.grain_size(), GRAIN_SIZE);
+        game.settings.scale_grains = true;
+        game.sync_settings();
+        game.sim.add_grain(100.0, 100.0);
+        assert_eq!(game.sim.container[0].rect.w, GRAIN_SIZE * 2.0);
+    }
+    #[test]
+    fn test_game_notify() {
+        let mut game = SandDropClicker::_test_state();
+        game.notify("Saved");
+        assert_eq!(game.notifications.visible(), vec![("Saved", 1.0)]);
+    }
+    #[test]
+    fn test_game_drop_indicator() {
+        let mut game = SandDropClicker::_test_state();
+        assert_eq!(game.sim.drop_amount(), 1);
+        let table = ParticleTable::default();
+        assert_eq!(
+            game.tier_colors(),
+            vec![table.palette_color(SandParticle::Sand, false)]
+        );
+        game.sim.upgrades.insert(Upgrade::MORE_PARTICLES, 2);
+        game.sim.upgrades.insert(Upgrade::PARTICLE_TIER, 3);
+        assert_eq!(game.sim.drop_amount(), 3);
+        assert_eq!(game.tier_colors().len(), 3);
+    }
+    #[test]
+    fn test_game_is_unlocked() {
+        let mut game = SandDropClicker::_test_state();
+        assert!(game.sim.is_unlocked(SandParticle::Sand));
+        assert!(!game.sim.is_unlocked(SandParticle::Quartz));
+        game.sim.upgrades.insert(Upgrade::PARTICLE_TIER, 2);
+        assert!(game.sim.is_unlocked(SandParticle::Quartz));
+        assert!(!game.sim.is_unlocked(SandParticle::Shell));
+    }
+    #[test]
+    fn test_game_resize() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.add_grain(700.0, SCREEN_SIZE.1);
+        game.sim.container[0].update(1.0 / 30.0, GRAVITY, game.sim.screen_size);
+        assert!(game.sim.container[0].is_done());
+        // shrinking moves the grain onto the new ground
+        game.sim.resize(400.0, 300.0);
+        assert_eq!(game.sim.get_amount(), 1);
+        assert_eq!(game.sim.container[0].rect.bottom(), 300.0);
+        assert!(game.sim.container[0].rect.right() <= 400.0);
+        assert!(game.sim.container[0].is_done());
+        // growing lets the grain fall to the new ground
+        game.sim.resize(400.0, 500.0);
+        assert!(!game.sim.container[0].is_done());
+    }
+    #[test]
+    fn test_game_stats() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.add_grain(100.0, 100.0);
+        game.make_money();
+        game.sim.money = Currency(10000);
+        game.buy(Upgrade::AUTO_CLICKER);
+        assert_eq!(game.sim.stats.money_earned, Currency(1));
+        assert_eq!(game.sim.stats.money_spent, Currency(10000) - game.sim.money);
+        assert_eq!(game.sim.stats.tier_drops.get(&SandParticle::Sand), Some(&1));
+        assert_eq!(game.sim.stats.tier_sold.get(&SandParticle::Sand), Some(&1));
+        // vacuumed grains are sold too
+        let floor = game.sim.screen_size.1;
+        game.sim.add_grain(100.0, floor - 10.0);
+        game.sim.container[0].settle(floor);
+        game.sim.index_grains();
+        game.vacuum(100.0, floor - 5.0);
+        assert_eq!(game.sim.stats.tier_drops.get(&SandParticle::Sand), Some(&2));
+        assert_eq!(game.sim.stats.tier_sold.get(&SandParticle::Sand), Some(&2));
+        // the statistics are saved
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.sim.stats.money_spent, game.sim.stats.money_spent);
+        assert_eq!(loaded.sim.stats.tier_drops, game.sim.stats.tier_drops);
+        assert_eq!(loaded.sim.stats.tier_sold, game.sim.stats.tier_sold);
+    }
+    #[test]
+    fn test_game_best_conversion() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.add_grain(100.0, 100.0);
+        game.make_money();
+        assert!(game.notifications.visible().is_empty());
+        // a bigger conversion beats the record
+        game.sim.add_grains(100.0, 100.0, 4);
+        game.make_money();
+        assert_eq!(game.sim.stats.records, 1);
+        assert_eq!(game.notifications.visible().len(), 1);
+        assert_eq!(game.sim.stats.last_sales.len(), 2);
+        let fill = 5.0 / game.sim.get_size() as f64 * 100.0 / 2.0;
+        assert_eq!(game.sim.stats.average_fill(), Some(fill));
+        // the record and the conversions are saved
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.sim.stats.records, 1);
+        assert_eq!(loaded.sim.stats.best_sale, game.sim.stats.best_sale);
+        assert_eq!(loaded.sim.stats.last_sales, game.sim.stats.last_sales);
+        assert_eq!(loaded.sim.stats.average_fill(), Some(fill));
+    }
+    #[test]
+    fn test_game_gold_rush() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.stats.tier_sold.insert(SandParticle::Gold, 98);
+        game.apply(GameAction::Spawn {
+            particle: SandParticle::Gold,
+            amount: 1,
+        });
+        game.make_money();
+        game.sim.tick(0.0);
+        assert!(!game.sim.achievements.contains_key(&Achievement::GoldRush));
+        game.apply(GameAction::Spawn {
+            particle: SandParticle::Gold,
+            amount: 1,
+        });
+        game.make_money();
+        game.sim.tick(0.0);
+        assert!(game.sim.achievements.contains_key(&Achievement::GoldRush));
+    }
+    #[test]
+    fn test_game_lifetime_earnings() {
+        let mut game = SandDropClicker::_test_state();
+        // old saves count the current money as earned
+        game.load_save("money=1500\ntotal_clicks=40");
+        assert_eq!(game.sim.stats.money_earned, Currency(1500));
+        game.sim.tick(0.0);
+        assert!(
+            game.sim
+                .achievements
+                .contains_key(&Achievement::PocketMoney)
+        );
+        // spending the money doesn't take the lifetime earnings away
+        game.sim.money = Currency::ZERO;
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.sim.stats.money_earned, Currency(1500));
+        let (current, goal) = loaded.sim.achievement_progress(Achievement::PocketMoney);
+        assert!(current >= goal);
+    }
+    #[test]
+    fn test_game_speedrun() {
+        let mut game = SandDropClicker::_test_state();
+        game.enable_speedrun();
+        game.sim.upgrades.insert(Upgrade::AUTO_CLICKER, 1);
+        game.update_speedrun(Duration::from_secs(30));
+        assert_eq!(
+            game.speedrun.splits.get(&Split::FirstAutoClicker),
+            Some(&Some(Duration::from_secs(30)))
+        );
+        // the personal bests are kept in the save, the run starts over
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(
+            loaded.speedrun.bests.get(&Split::FirstAutoClicker),
+            Some(&Duration::from_secs(30))
+        );
+        assert_eq!(loaded.speedrun.elapsed, Duration::ZERO);
+        // a new game is a new run, with the same personal bests
+        game.reset_progress();
+        assert!(game.speedrun.splits.is_empty());
+        assert_eq!(
+            game.speedrun.bests.get(&Split::FirstAutoClicker),
+            Some(&Duration::from_secs(30))
+        );
+    }
+    #[test]
+    fn test_game_session_stats() {
+        let mut game = SandDropClicker::_test_state();
+        game.load_save("money_earned=1500\ntotal_clicks=40");
+        // the session starts with the loaded progress
+        assert_eq!(game.sim.session(), Counters::default());
+        game.sim.click(100.0, 100.0, 1);
+        game.sim.tick(1.0);
+        assert_eq!(game.sim.session().clicks, 1);
+        assert_eq!(game.sim.lifetime().clicks, 41);
+        assert_eq!(game.sim.session().money_earned, Currency::ZERO);
+        // resetting the session keeps the lifetime stats
+        game.sim.reset_session();
+        assert_eq!(game.sim.session().clicks, 0);
+        assert_eq!(game.sim.lifetime().clicks, 41);
+        assert_eq!(game.sim.lifetime().money_earned, Currency(1500));
+    }
+    #[test]
+    fn test_game_achievements() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.total_clicks = 640;
+        assert_eq!(
+            game.sim.achievement_progress(Achievement::BusyFingers),
+            (640, 1000)
+        );
+        game.sim.check_achievements();
+        assert!(game.sim.achievements.is_empty());
+        // dropping a grain earns the first achievement
+        game.sim.total_time = Duration::from_secs(42);
+        let added = game.sim.add_grain(100.0, 100.0);
+        game.sim.stats.manual_grains += added as u64;
+        game.sim.check_achievements();
+        assert_eq!(
+            game.sim.achievements.get(&Achievement::FirstGrain),
+            Some(&42)
+        );
+        // the earned achievements are saved
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.sim.achievements, game.sim.achievements);
+    }
+    #[test]
+    fn test_game_reset_progress() {
+        let mut game = SandDropClicker::_test_state();
+        game.settings.trails = false;
+        game.sim.money = Currency(50000);
+        game.buy(Upgrade::AUTO_CLICKER);
+        game.buy_theme(Theme::Beach);
+        game.sim.add_grain(100.0, 100.0);
+        game.reset_progress();
+        assert_eq!(game.sim.money, Currency::ZERO);
+        assert_eq!(game.sim.get_amount(), 0);
+        assert!(game.sim.container.is_empty());
+        assert!(game.sim.unlock.is_empty());
+        assert_eq!(game.sim.upgrades.get(&Upgrade::AUTO_CLICKER), None);
+        assert_eq!(game.theme, Theme::Classic);
+        // the settings stay
+        assert!(!game.settings.trails);
+    }
+    #[test]
+    fn test_game_hardcore() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.money = Currency(5000);
+        game.apply(GameAction::Reset(Profile::Hardcore));
+        assert_eq!(game.profile, Profile::Hardcore);
+        assert_eq!(game.sim.money, Currency::ZERO);
+        assert_eq!(game.sim.modifiers.decay_time, Some(DECAY_TIME));
+        // the save is marked hardcore, and loads that way
+        let save = game.to_save();
+        assert!(save.lines().any(|line| line == "profile=Hardcore"));
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.restart(&save, 0, SCREEN_SIZE);
+        assert_eq!(loaded.profile, Profile::Hardcore);
+        assert_eq!(loaded.sim.modifiers.decay_time, Some(DECAY_TIME));
+        // a challenge is played in hardcore too, and can't change the mode
+        game.apply(GameAction::StartChallenge(Challenge::ExpensiveTaste));
+        game.apply(GameAction::Reset(Profile::Normal));
+        assert_eq!(game.profile, Profile::Hardcore);
+        assert_eq!(game.sim.modifiers.decay_time, Some(DECAY_TIME));
+        game.apply(GameAction::EndChallenge);
+        assert_eq!(game.profile, Profile::Hardcore);
+        // only a new game goes back to normal, and a normal save never loads as hardcore
+        game.apply(GameAction::Reset(Profile::Normal));
+        assert_eq!(game.profile, Profile::Normal);
+        assert_eq!(game.sim.modifiers.decay_time, None);
+        loaded.restart(&game.to_save(), 0, SCREEN_SIZE);
+        assert_eq!(loaded.profile, Profile::Normal);
+        // saves from before the profile types still load as hardcore
+        loaded.restart("hardcore=true", 0, SCREEN_SIZE);
+        assert_eq!(loaded.profile, Profile::Hardcore);
+    }
+    #[test]
+    fn test_game_sandbox() {
+        let mut game = SandDropClicker::_test_state();
+        game.completed_challenges.insert(Challenge::TinyContainer);
+        game.daily_scores.record("2025-12-09", Currency(500));
+        game.apply(GameAction::Reset(Profile::Sandbox));
+        assert!(game.sim.modifiers.sandbox);
+        // the records of the normal game stay with it
+        assert!(game.completed_challenges.is_empty());
+        assert!(game.daily_scores.best.is_empty());
+        // the money never runs out and every upgrade is listed
+        game.step(1.0 / FPS as f32, None);
+        assert_eq!(game.sim.money, Currency::MAX);
+        assert!(game.sim.unlock.contains(&Upgrade::AUTO_CLICKER));
+        assert_eq!(game.buy_levels(Upgrade::BIGGER_CONTAINER, 5), 5);
+        game.step(1.0 / FPS as f32, None);
+        assert_eq!(game.sim.money, Currency::MAX);
+        // the palette picks the sand of the drops
+        let tools = SandboxTools {
+            palette: Some(SandParticle::Diamond),
+            gravity: 0.5,
+            grain_size: 20.0,
+        };
+        game.apply(GameAction::SetSandbox(tools));
+        assert_eq!(game.sandbox, tools);
+        game.sim.click(100.0, 100.0, 3);
+        assert!(
+            game.sim
+                .container
+                .iter()
+                .all(|grain| { grain.particle == SandParticle::Diamond && grain.rect.w == 20.0 })
+        );
+        // no achievements, scores, or daily runs
+        assert!(game.sim.check_achievements().is_empty());
+        game.apply(GameAction::StartBlitz);
+        game.apply(GameAction::StartDaily(20_431));
+        game.apply(GameAction::StartChallenge(Challenge::TinyContainer));
+        assert!(game.blitz.is_none() && game.challenge.is_none());
+        // the save is marked sandbox, with its tools
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.restart(&game.to_save(), 0, SCREEN_SIZE);
+        assert_eq!(loaded.profile, Profile::Sandbox);
+        assert_eq!(loaded.sandbox, tools);
+        assert_eq!(loaded.sim.modifiers.palette, Some(SandParticle::Diamond));
+        // the tools are only for a sandbox game
+        game.apply(GameAction::Reset(Profile::Normal));
+        game.apply(GameAction::SetSandbox(tools));
+        assert_eq!(game.sandbox, SandboxTools::default());
+        assert_eq!(game.sim.modifiers, Modifiers::default());
+    }
+    #[test]
+    fn test_game_new_game_plus() {
+        let mut game = SandDropClicker::_test_state();
+        // only offered once every upgrade is maxed
+        game.apply(GameAction::NewGamePlus);
+        assert_eq!(game.ng_plus, 0);
+        for upgrade in upgrade_table().iter() {
+            if let Some(max) = upgrade.max_level() {
+                game.sim.upgrades.insert(upgrade, max);
+            }
+        }
+        let cost = game.sim.level_cost(Upgrade::BIGGER_CONTAINER, 0).unwrap();
+        game.sim.money = Currency(1000);
+        game.sim.total_clicks = 50;
+        game.sim.achievements.insert(Achievement::GoldRush, 10);
+        assert!(game.can_new_game_plus());
+        game.apply(GameAction::NewGamePlus);
+        assert_eq!(game.ng_plus, 1);
+        assert_eq!(game.sim.modifiers.ng_plus, 1);
+        // the upgrades and the money start over, the lifetime progress carries on
+        assert_eq!(game.sim.money, Currency::ZERO);
+        assert!(!game.sim.all_maxed());
+        assert_eq!(game.sim.total_clicks, 50);
+        assert!(game.sim.achievements.contains_key(&Achievement::GoldRush));
+        assert!(game.sim.level_cost(Upgrade::BIGGER_CONTAINER, 0).unwrap() > cost);
+        assert!(!game.can_new_game_plus());
+        // the count is saved, and a challenge is played without it
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.restart(&game.to_save(), 0, SCREEN_SIZE);
+        assert_eq!(loaded.ng_plus, 1);
+        assert_eq!(loaded.sim.modifiers.ng_plus, 1);
+        loaded.apply(GameAction::StartChallenge(Challenge::TinyContainer));
+        assert_eq!(loaded.sim.modifiers.ng_plus, 0);
+        loaded.apply(GameAction::EndChallenge);
+        assert_eq!(loaded.sim.modifiers.ng_plus, 1);
+        // a new game starts the count over
+        game.apply(GameAction::Reset(Profile::Normal));
+        assert_eq!(game.ng_plus, 0);
+        assert_eq!(game.sim.modifiers, Modifiers::default());
+    }
+    #[test]
+    fn test_game_pause() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.add_grain(100.0, 100.0);
+        game.step(1.0, None);
+        let time = game.sim.total_time;
+        let y = game.sim.container[0].rect.y;
+        // nothing moves or counts while paused
+        game.paused = true;
+        for _ in 0..10 {
+            game.step(1.0, None);
+        }
+        assert_eq!(game.sim.total_time, time);
+        assert_eq!(game.sim.container[0].rect.y, y);
+        game.paused = false;
+        game.step(1.0, None);
+        assert_eq!(game.sim.total_time, time + Duration::from_secs(1));
+    }
+    #[test]
+    fn test_game_background_pause() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.upgrades.insert(Upgrade::AUTO_CLICKER, 1);
+        // an unfocused window freezes the game, the autoclicker too
+        game.focused = false;
+        for _ in 0..100 {
+            game.step(1.0, None);
+        }
+        assert_eq!(game.sim.total_time, Duration::ZERO);
+        assert_eq!(game.sim.stats.auto_grains, 0);
+        // unless it runs in the background
+        game.settings.background = BackgroundMode::Run;
+        game.step(1.0, None);
+        assert_eq!(game.sim.total_time, Duration::from_secs(1));
+        // a minimized window only idles, even when running in the background
+        game.minimized = true;
+        assert_eq!(game.run_mode(), BackgroundMode::Idle);
+        game.step(1.0, None);
+        assert_eq!(game.sim.total_time, Duration::from_secs(1));
+        game.settings.background = BackgroundMode::Pause;
+        game.focused = true;
+        game.minimized = false;
+        game.step(1.0, None);
+        assert_eq!(game.sim.total_time, Duration::from_secs(2));
+    }
+    #[test]
+    fn test_game_background_idle() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.upgrades.insert(Upgrade::AUTO_CLICKER, 10);
+        game.settings.background = BackgroundMode::Idle;
+        game.minimized = true;
+        // the idle game earns money, but drops no sand
+        game.idle(60.0);
+        assert_eq!(game.sim.total_time, Duration::from_secs(60));
+        assert!(game.sim.money > Currency::ZERO);
+        assert!(game.sim.container.is_empty());
+        assert_eq!(game.sim.stats.auto_grains, 0);
+        // coming back doesn't pour out the sand of the idle time
+        game.minimized = false;
+        assert_eq!(game.run_mode(), BackgroundMode::Run);
+        game.step(1.0 / FPS as f32, None);
+        assert!(game.sim.container.amount() <= game.sim.drop_amount());
+        // a replay waits for the window instead of idling
+        game.minimized = true;
+        game.start_recording(PathBuf::from("idle.replay"), 1);
+        assert_eq!(game.run_mode(), BackgroundMode::Pause);
+    }
+    #[test]
+    fn test_game_stall() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.upgrades.insert(Upgrade::AUTO_CLICKER, 10);
+        game.sim.upgrades.insert(Upgrade::BIGGER_CONTAINER, 50);
+        // a 30 second stall only runs a few steps, the rest of the time is dropped
+        let started = Instant::now();
+        game.catch_up(30 * FPS, None, false);
+        assert!(started.elapsed() < Duration::from_secs(1));
+        let seconds = MAX_CATCH_UP as f32 / FPS as f32;
+        assert!((game.sim.total_time.as_secs_f32() - seconds).abs() < 1e-3);
+        assert!(game.sim.stats.auto_grains <= 2);
+        // the steps of a normal frame all run
+        game.catch_up(2, None, false);
+        assert!((game.sim.total_time.as_secs_f32() - seconds - 2.0 / FPS as f32).abs() < 1e-3);
+    }
+    #[test]
+    fn test_game_confirm_quit() {
+        let mut game = SandDropClicker::_test_state();
+        // nothing to lose, quit right away
+        assert!(game.confirm_quit());
+        assert!(!game.quit_dialog);
+        // unsold sand asks first
+        game.sim.add_grain(100.0, 100.0);
+        assert_eq!(game.sim.unsold_value(), Currency(1));
+        assert!(!game.confirm_quit());
+        assert!(game.quit_dialog);
+        game.quit_confirmed = true;
+        assert!(game.confirm_quit());
+    }
+    #[test]
+    fn test_game_contents() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim
+            .add_grains_of(100.0, 100.0, 2, Some(SandParticle::Gold));
+        game.sim
+            .add_grains_of(100.0, 100.0, 3, Some(SandParticle::Sand));
+        let contents = game.sim.contents();
+        // sorted by tier, without the empty types
+        assert_eq!(
+            contents,
+            vec![
+                (SandParticle::Sand, 3, Currency(1)),
+                (SandParticle::Gold, 2, Currency(1024))
+            ]
+        );
+        // the total is what converting pays out
+        let total = game.sim.unsold_value();
+        assert_eq!(total, Currency(3 + 2 * 1024));
+        let money = game.sim.money;
+        game.make_money();
+        assert_eq!(game.sim.money, money + total);
+    }
+    #[test]
+    fn test_game_toggle_category() {
+        let mut game = SandDropClicker::_test_state();
+        game.toggle_category(UpgradeCategory::Economy);
+        assert!(
+            game.settings
+                .closed_categories
+                .contains(&UpgradeCategory::Economy)
+        );
+        game.toggle_category(UpgradeCategory::Economy);
+        assert!(game.settings.closed_categories.is_empty());
+    }
+    #[test]
+    fn test_game_history() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.money = Currency(1_000_000);
+        game.buy(Upgrade::AUTO_CLICKER);
+        game.sim
+            .add_grains_of(100.0, 100.0, 5, Some(SandParticle::Sand));
+        game.make_money();
+        assert!(matches!(
+            game.sim.history.entries[0],
+            HistoryEntry::Purchase {
+                upgrade: Upgrade::AUTO_CLICKER,
+                level: 1,
+                ..
+            }
+        ));
+        assert_eq!(
+            game.sim.history.entries[1],
+            HistoryEntry::Sale {
+                time: 0,
+                earned: Currency(5)
+            }
+        );
+        // the history survives saving and loading
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.sim.history.entries, game.sim.history.entries);
+    }
+    #[test]
+    fn test_game_income() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim
+            .add_grains_of(100.0, 100.0, 1, Some(SandParticle::Gold));
+        game.make_money();
+        game.step(1.0, None);
+        assert_eq!(game.sim.income.per_second(), Currency(1024));
+        game.reset_progress();
+        assert_eq!(game.sim.income.per_second(), Currency::ZERO);
+    }
+    #[test]
+    fn test_game_time_to_afford() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.money = Currency(100);
+        // no income, no estimate
+        assert_eq!(game.time_to_afford(Currency(1000)), None);
+        game.sim.income.record(Currency(300));
+        assert_eq!(game.time_to_afford(Currency(1000)), Some(3));
+        assert_eq!(game.time_to_afford(Currency(50)), Some(0));
+    }
+    #[test]
+    fn test_game_tutorial() {
+        let mut game = SandDropClicker::_test_state();
+        game.tutorial = Some(TutorialStep::DropSand);
+        // selling doesn't skip ahead
+        game.make_money();
+        assert_eq!(game.tutorial, Some(TutorialStep::DropSand));
+        for _ in 0..TUTORIAL_GRAINS {
+            game.click(100.0, 100.0);
+        }
+        assert_eq!(game.tutorial, Some(TutorialStep::Convert));
+        game.make_money();
+        assert_eq!(game.tutorial, Some(TutorialStep::Upgrade));
+        game.sim.money = Currency(1_000_000);
+        game.buy(Upgrade::BIGGER_CONTAINER);
+        assert_eq!(game.tutorial, None);
+        assert!(game.settings.tutorial_done);
+    }
+    #[test]
+    fn test_game_skip_tutorial() {
+        let mut game = SandDropClicker::_test_state();
+        game.tutorial = Some(TutorialStep::Convert);
+        game.skip_tutorial();
+        assert_eq!(game.tutorial, None);
+        assert!(game.settings.tutorial_done);
+    }
+    #[test]
+    fn test_game_vacuum() {
+        let mut game = SandDropClicker::_test_state();
+        let ground = SCREEN_SIZE.1 - GRAIN_SIZE / 2.0;
+        game.sim.add_grain(100.0, ground);
+        game.sim.add_grain(105.0, ground);
+        game.sim.add_grain(300.0, ground);
+        // the grains settle on the ground
+        game.sim.step_grains(1.0 / 30.0);
+        game.sim.index_grains();
+        let value = game.sim.sale_value(game.sim.container[0].particle)
+            + game.sim.sale_value(game.sim.container[1].particle);
+        let money = game.sim.money;
+        // only the grains near the cursor are sold, for exactly their value
+        assert_eq!(game.vacuum(102.0, ground), value);
+        assert_eq!(game.sim.money, money + value);
+        assert_eq!(game.sim.container.len(), 1);
+        assert_eq!(game.sim.get_amount(), 1);
+        assert_eq!(game.collecting.len(), 2);
+        // the grains that were taken aren't sold again before the grid is rebuilt
+        assert_eq!(game.vacuum(102.0, ground), Currency::ZERO);
+        // falling grains are left alone
+        game.sim.add_grain(100.0, 100.0);
+        game.sim.index_grains();
+        assert_eq!(game.vacuum(100.0, 100.0), Currency::ZERO);
+        assert_eq!(game.sim.get_amount(), 2);
+    }
+    #[test]
+    fn test_game_drop_throttle() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.upgrades.insert(Upgrade::MORE_PARTICLES, 4);
+        assert_eq!(game.click_amount(), 5);
+        // scrolling down drops fewer grains, but at least one
+        game.throttle_drops(-3);
+        assert_eq!(game.click_amount(), 2);
+        game.throttle_drops(-3);
+        assert_eq!(game.click_amount(), 1);
+        game.click(100.0, 100.0);
+        assert_eq!(game.sim.get_amount(), 1);
+        // the autoclicker still drops all of them
+        assert_eq!(game.sim.add_grain(100.0, 100.0), 5);
+        // scrolling back up drops all of them again, even after an upgrade
+        game.throttle_drops(10);
+        assert_eq!(game.drop_throttle, None);
+        game.sim.upgrades.insert(Upgrade::MORE_PARTICLES, 5);
+        assert_eq!(game.click_amount(), 6);
+    }
+    #[test]
+    fn test_game_gamepad_cursor() {
+        let mut game = SandDropClicker::_test_state();
+        // nothing happens until a gamepad is used
+        game.step(1.0, None);
+        assert_eq!(game.gamepad_cursor, None);
+        game.gamepad_button(PadButton::South);
+        game.apply_actions();
+        let (width, height) = game.sim.screen_size;
+        assert_eq!(game.gamepad_cursor, Some((width / 2.0, height / 2.0)));
+        assert_eq!(game.sim.get_amount(), 1);
+        // the cursor follows the stick but stays in the container
+        game.stick = (1.0, GAMEPAD_DEADZONE / 2.0);
+        game.step(0.5, None);
+        assert_eq!(
+            game.gamepad_cursor,
+            Some((width / 2.0 + 200.0, height / 2.0))
+        );
+        game.step(10.0, None);
+        assert_eq!(game.gamepad_cursor, Some((width, height / 2.0)));
+    }
+    #[test]
+    fn test_game_gamepad_upgrades() {
+        let mut game = SandDropClicker::_test_state();
+        assert_eq!(game.gamepad_upgrade(), None);
+        game.sim.unlock.insert(Upgrade::BIGGER_CONTAINER);
+        game.sim.unlock.insert(Upgrade::AUTO_CLICKER);
+        assert_eq!(game.gamepad_upgrade(), Some(Upgrade::BIGGER_CONTAINER));
+        game.gamepad_button(PadButton::DPadDown);
+        assert_eq!(game.gamepad_upgrade(), Some(Upgrade::AUTO_CLICKER));
+        game.gamepad_button(PadButton::DPadDown);
+        assert_eq!(game.gamepad_upgrade(), Some(Upgrade::BIGGER_CONTAINER));
+        game.gamepad_button(PadButton::DPadUp);
+        game.sim.money = Currency(1_000_000);
+        game.gamepad_button(PadButton::East);
+        game.apply_actions();
+        assert_eq!(game.sim.upgrades.get(&Upgrade::AUTO_CLICKER), Some(&1));
+        // the hidden options window can't be bought from
+        game.gamepad_button(PadButton::North);
+        assert_eq!(game.gamepad_upgrade(), None);
+    }
+    #[test]
+    fn test_game_touch_tap() {
+        let mut game = SandDropClicker::_test_state();
+        game.touch_start(100.0, 100.0);
+        game.apply_actions();
+        assert_eq!(game.sim.get_amount(), 1);
+        assert_eq!(game.sim.total_clicks, 1);
+        // a finger that doesn't move doesn't pour
+        game.step(1.0, None);
+        assert_eq!(game.sim.get_amount(), 1);
+        game.touch_end(100.0, 100.0);
+        assert!(game.touches.is_empty());
+    }
+    #[test]
+    fn test_game_touch_pour() {
+        let mut game = SandDropClicker::_test_state();
+        game.touch_start(100.0, 100.0);
+        game.touch_start(300.0, 100.0);
+        // only two fingers pour at once
+        game.touch_start(500.0, 100.0);
+        game.apply_actions();
+        assert_eq!(game.touches.len(), MAX_TOUCHES);
+        assert_eq!(game.sim.get_amount(), 2);
+        // the closest finger follows the drag
+        game.touch_move(290.0, 120.0);
+        assert_eq!((game.touches[1].x, game.touches[1].y), (290.0, 120.0));
+        assert!(!game.touches[0].dragged);
+        game.step(POUR_INTERVAL * 3.5, None);
+        assert_eq!(game.sim.get_amount(), 5);
+        game.touch_move(110.0, 100.0);
+        game.step(POUR_INTERVAL, None);
+        assert_eq!(game.sim.get_amount(), 7);
+        game.touch_end(290.0, 120.0);
+        assert_eq!(game.touches.len(), 1);
+        assert_eq!(game.touches[0].x, 110.0);
+    }
+    #[test]
+    fn test_game_buy_levels() {
+        let mut game = SandDropClicker::_test_state();
+        // exactly enough for the first four levels
+        game.sim.money = (0..4)
+            .map(|n| Currency::from_f64(Upgrade::AUTO_CLICKER.cost(n)))
+            .sum();
+        assert_eq!(game.buy_levels(Upgrade::AUTO_CLICKER, BULK_BUY), 4);
+        assert_eq!(game.sim.money, Currency::ZERO);
+        assert_eq!(game.sim.upgrades.get(&Upgrade::AUTO_CLICKER), Some(&4));
+        assert_eq!(
+            game.notifications.visible().last().map(|(text, _)| *text),
+            Some("Bought 4 of 10 levels of Auto Clicker")
+        );
+        // buying the most stops at the max level
+        game.sim.money = Currency::MAX / 2;
+        let max = Upgrade::SCULPTOR.max_level().unwrap();
+        assert_eq!(game.buy_levels(Upgrade::SCULPTOR, u32::MAX), max);
+        assert!(game.sim.is_maxed(Upgrade::SCULPTOR));
+    }
+    #[test]
+    fn test_game_drop_random() {
+        let mut game = SandDropClicker::_test_state();
+        game.drop_random();
+        assert_eq!(game.sim.total_clicks, 1);
+        assert_eq!(game.sim.get_amount(), 1);
+        let grain = &game.sim.container[0];
+        assert!((0.0..=game.sim.screen_size.0).contains(&grain.rect.center().x));
+        // a full container takes no more sand
+        while !game.sim.is_full() {
+            game.sim.add_grain(100.0, 100.0);
+        }
+        game.drop_random();
+        assert_eq!(game.sim.total_clicks, 1);
+    }
+    #[test]
+    fn test_game_quick_buy() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.unlock.insert(Upgrade::SCULPTOR);
+        game.sim.unlock.insert(Upgrade::BIGGER_CONTAINER);
+        game.sim.unlock.insert(Upgrade::AUTO_CLICKER);
+        // the upgrades are numbered by category
+        assert_eq!(
+            game.listed_upgrades(),
+            vec![
+                Upgrade::AUTO_CLICKER,
+                Upgrade::SCULPTOR,
+                Upgrade::BIGGER_CONTAINER
+            ]
+        );
+        game.sim.money = game.sim.upgrade_cost(Upgrade::SCULPTOR).unwrap();
+        game.quick_buy(1);
+        game.apply_actions();
+        assert_eq!(game.sim.upgrades.get(&Upgrade::SCULPTOR), Some(&1));
+        assert_eq!(game.sim.money, Currency::ZERO);
+        // a failed purchase is reported
+        game.quick_buy(0);
+        game.apply_actions();
+        assert_eq!(game.sim.upgrades.get(&Upgrade::AUTO_CLICKER), None);
+        assert_eq!(game.notifications.visible().len(), 1);
+        game.quick_buy(8);
+        assert_eq!(game.notifications.visible().len(), 1);
+    }
+    #[test]
+    fn test_game_actions() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.unlock.insert(Upgrade::AUTO_CLICKER);
+        game.queue(GameAction::DropAt { x: 100.0, y: 100.0 });
+        game.queue(GameAction::DropRandom);
+        // nothing changes until the actions are applied
+        assert_eq!(game.sim.get_amount(), 0);
+        game.apply_actions();
+        assert!(game.actions.is_empty());
+        assert_eq!(game.sim.get_amount(), 2);
+        assert_eq!(game.sim.total_clicks, 2);
+        // the actions are applied in order
+        game.sim.money = Currency(1_000_000);
+        game.queue(GameAction::Buy {
+            upgrade: Upgrade::AUTO_CLICKER,
+            count: 3,
+        });
+        game.queue(GameAction::BuyTheme(Theme::NightSky));
+        game.queue(GameAction::Convert);
+        game.apply_actions();
+        assert_eq!(game.sim.upgrades.get(&Upgrade::AUTO_CLICKER), Some(&3));
+        assert_eq!(game.theme, Theme::NightSky);
+        assert_eq!(game.sim.get_amount(), 0);
+        game.apply(GameAction::Reset(Profile::Normal));
+        assert_eq!(game.sim.upgrades.get(&Upgrade::AUTO_CLICKER), None);
+        assert_eq!(game.theme, Theme::Classic);
+    }
+    #[test]
+    fn test_game_replay() {
+        let dir = std::env::temp_dir().join(format!("sand-drop-replay-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("replay.txt");
+        let seconds = 1.0 / FPS as f32;
+        // record a session with every kind of action
+        let mut game = SandDropClicker::_test_state();
+        game.sim.money = Currency(5000);
+        game.start_recording(path.clone(), 7);
+        assert_eq!(game.sim.money, Currency(5000));
+        game.queue(GameAction::Buy {
+            upgrade: Upgrade::AUTO_CLICKER,
+            count: 2,
+        });
+        for i in 0..60 {
+            if i % 7 == 0 {
+                game.queue(GameAction::DropAt {
+                    x: 100.0 + i as f32 * 5.0,
+                    y: 0.0,
+                });
+                game.queue(GameAction::DropRandom);
+            }
+            if i == 20 {
+                game.queue(GameAction::Throttle { steps: -1 });
+                game.apply(GameAction::Resize {
+                    width: 700.0,
+                    height: 500.0,
+                });
+            }
+            game.apply_actions();
+            let sculpt = (30..40).contains(&i).then_some(i % 2 == 0);
+            game.step(seconds, sculpt);
+        }
+        game.apply(GameAction::Vacuum { x: 300.0, y: 500.0 });
+        game.apply(GameAction::Convert);
+        for _ in 0..30 {
+            game.step(seconds, None);
+        }
+        game.finish_recording().unwrap();
+        assert!(game.recording.is_none());
+        assert!(game.sim.total_clicks > 0);
+
+        // the replay plays out the same way
+        let mut replayed = SandDropClicker::_test_state();
+        replayed.start_replay(&path).unwrap();
+        assert_eq!(replayed.save_path, None);
+        // live input is left out while watching
+        replayed.queue(GameAction::Convert);
+        replayed.apply_actions();
+        for _ in 0..=game.ticks {
+            replayed.step(seconds, None);
+        }
+        assert_eq!(replayed.ticks, game.ticks);
+        assert_eq!(replayed.sim.checksum(), game.sim.checksum());
+        let playback = replayed.playback.as_ref().unwrap();
+        assert_eq!(playback.matched, Some(true));
+        // nothing moves once the replay is over
+        replayed.step(seconds, None);
+        assert_eq!(replayed.ticks, game.ticks);
+
+        // a replay that plays out differently is caught
+        let text = fs::read_to_string(&path).unwrap();
+        let (kept, _) = text.split_once("checksum=").unwrap();
+        fs::write(&path, format!("{}checksum=0", kept)).unwrap();
+        let mut diverged = SandDropClicker::_test_state();
+        diverged.start_replay(&path).unwrap();
+        for _ in 0..=game.ticks {
+            diverged.step(seconds, None);
+        }
+        assert_eq!(diverged.playback.unwrap().matched, Some(false));
+        // a broken replay can't be played
+        fs::write(&path, "seed=x").unwrap();
+        assert!(SandDropClicker::_test_state().start_replay(&path).is_err());
+        assert!(
+            SandDropClicker::_test_state()
+                .start_replay(&dir.join("missing.txt"))
+                .is_err()
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn test_game_heatmap() {
+        let mut game = SandDropClicker::_test_state();
+        game.apply(GameAction::DropAt { x: 10.0, y: 10.0 });
+        // random drops aren't the player's habits
+        game.apply(GameAction::DropRandom);
+        assert_eq!(game.heatmap.counts.iter().sum::<u32>(), 1);
+        assert_eq!(game.heatmap.counts[0], 1);
+        // the heatmap is only kept for the session
+        let mut loaded = SandDropClicker::_test_state();
+        loaded.load_save(&game.to_save());
+        assert_eq!(loaded.heatmap.counts.iter().sum::<u32>(), 0);
+    }
+    #[test]
+    fn test_game_console_actions() {
+        let mut game = SandDropClicker::_test_state();
+        // normal play has no console
+        assert!(game.console.is_none());
+        game.apply(GameAction::Give {
+            money: Currency(100_000),
+        });
+        assert_eq!(game.sim.money, Currency(100_000));
+        // levels stop at the max level of the upgrade
+        game.apply(GameAction::SetLevel {
+            upgrade: Upgrade::SCULPTOR,
+            level: 1000,
+        });
+        assert_eq!(
+            game.sim.upgrades.get(&Upgrade::SCULPTOR).copied(),
+            Upgrade::SCULPTOR.max_level()
+        );
+        game.apply(GameAction::Spawn {
+            particle: SandParticle::Diamond,
+            amount: 5,
+        });
+        assert_eq!(game.sim.container.count(SandParticle::Diamond), 5);
+        game.apply(GameAction::Clear);
+        assert_eq!(game.sim.get_amount(), 0);
+        assert_eq!(game.sim.money, Currency(100_000));
+        // a seed makes the random drops repeatable
+        game.apply(GameAction::Seed(3));
+        game.drop_random();
+        let x = game.sim.container[0].rect.x;
+        game.apply(GameAction::Seed(3));
+        game.drop_random();
+        assert_eq!(game.sim.container[1].rect.x, x);
+    }
+    #[test]
+    fn test_game_combo() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.combo.count = 24;
+        game.sim.combo.last_click = Some(0.0);
+        game.click(100.0, 100.0);
+        // the grain is worth half as much again
+        let value = game.sim.sale_value(game.sim.container[0].particle);
+        assert_eq!(game.sim.container[0].bonus, value.as_f64() * 0.5);
+        // the autoclicker neither builds nor gets the combo
+        game.sim.add_grain(100.0, 100.0);
+        assert_eq!(game.sim.combo.count, 25);
+        assert_eq!(game.sim.container[1].bonus, 0.0);
+        let total: Currency = game
+            .sim
+            .container
+            .iter()
+            .map(|g| game.sim.sale_value(g.particle))
+            .sum();
+        assert_eq!(
+            game.sim.unsold_value(),
+            total + Currency::from_f64(value.as_f64() * 0.5)
+        );
+    }
+    #[test]
+    fn test_game_sounds() {
+        let mut game = SandDropClicker::_test_state();
+        game.click(100.0, 100.0);
+        game.click(200.0, 100.0);
+        // each sound plays once per update
+        assert_eq!(game.sound_queue, vec![(Sound::Drop, 0.5, 1.0)]);
+        game.sound_queue.clear();
+        for _ in 0..120 {
+            game.step(1.0 / FPS as f32, None);
+        }
+        assert_eq!(game.sound_queue.len(), 1);
+        assert_eq!(game.sound_queue[0].0, Sound::Land);
+        game.sound_queue.clear();
+        // nothing plays while muted
+        game.settings.muted = true;
+        game.make_money();
+        assert!(game.sound_queue.is_empty());
+    }
+    #[test]
+    fn test_game_music_crossfade() {
+        let mut game = SandDropClicker::_test_state();
+        game.step(1.0, None);
+        assert_eq!(game.music_mix, 0.0);
+        // an almost full container fades in the intense music
+        while !game.music_intense() {
+            game.sim.add_grain(100.0, 100.0);
+        }
+        game.step(MUSIC_FADE / 2.0, None);
+        assert_eq!(game.music_mix, 0.5);
+        game.step(MUSIC_FADE, None);
+        assert_eq!(game.music_mix, 1.0);
+        // and fades back after selling
+        game.make_money();
+        game.step(MUSIC_FADE / 4.0, None);
+        assert_eq!(game.music_mix, 0.75);
+    }
+    #[test]
+    fn test_game_land_sound_pitch() {
+        let mut game = SandDropClicker::_test_state();
+        for particle in [
+            SandParticle::Sand,
+            SandParticle::Coral,
+            SandParticle::Quartz,
+        ] {
+            let mut grain = Grain::new(
+                100.0,
+                game.sim.screen_size.1 - 20.0,
+                GRAIN_SIZE,
+                Color::WHITE,
+            );
+            grain.particle = particle;
+            game.sim.container.add(grain);
+        }
+        // the landing sound waits for its turn, then plays for the rarest grain
+        game.land_sound_timer = 1.0;
+        while game.sound_queue.is_empty() {
+            game.step(1.0 / FPS as f32, None);
+        }
+        let (sound, _, pitch) = game.sound_queue[0];
+        assert_eq!(sound, Sound::Land);
+        assert_eq!(pitch, SEMITONE.powi(SandParticle::Coral.level() as i32));
+        assert_eq!(game.land_pending, None);
+    }
+    #[test]
+    fn test_game_ducking() {
+        let mut game = SandDropClicker::_test_state();
+        game.sim.add_grain(100.0, 100.0);
+        game.make_money();
+        assert_eq!(game.ducking.gain, DUCK_LEVEL);
+        // small sales keep the music as it is
+        game.ducking = Ducking::default();
+        game.sim.money = Currency(1_000_000);
+        game.sim.add_grain(100.0, 100.0);
+        game.make_money();
+        assert_eq!(game.ducking.gain, 1.0);
+    }
+    #[test]
+    fn test_game_pour_sound() {
+        let mut game = SandDropClicker::_test_state();
+        game.touch_start(100.0, 100.0);
+        game.fade_pour(0.1);
+        assert_eq!(game.pour_volume, 0.0);
+        // the sound starts with the pouring and follows its rate
+        game.touch_move(110.0, 100.0);
+        game.fade_pour(0.1);
+        assert_eq!(game.pour_volume, 0.5);
+        // and fades out after the finger is lifted
+        game.touch_end(110.0, 100.0);
+        game.fade_pour(POUR_FADE / 4.0);
+        assert_eq!(game.pour_volume, 0.25);
+        game.fade_pour(POUR_FADE);
+        assert_eq!(game.pour_volume, 0.0);
+        // pausing silences it at once
+        game.touch_start(100.0, 100.0);
+        game.touch_move(110.0, 100.0);
+        game.fade_pour(0.1);
+        game.paused = true;
+        game.fade_pour(0.01);
+        assert_eq!(game.pour_volume, 0.0);
+    }
+    #[test]
+    fn test_game_toggle_mute() {
+        let mut game = SandDropClicker::_test_state();
+        game.toggle_mute();
+        assert!(game.settings.muted);
+        assert_eq!(game.audio_state(), "Sound: muted");
+        game.toggle_mute();
+        assert!(!game.settings.muted);
+        assert_eq!(game.audio_state(), "Sound: on, music at 50%");
+        let toasts: Vec<&str> = game
+            .notifications
+            .visible()
+            .iter()
+            .map(|(text, _)| *text)
+            .collect();
+        assert_eq!(toasts, vec!["Sound: muted", "Sound: on, music at 50%"]);
+    }
+}
@@ -0,0 +1,788 @@
+//! Grains of sand falling into the container and their effects
+
+use crate::{
+    COLLECT_TIME, EFFECT_LIFE, FLOAT_TEXT_LIFE, GRAIN_SIZE, MAX_FALL_SPEED, SLEEP_VELOCITY,
+    TRAIL_LENGTH, particle::SandParticle,
+};
+use ggez::graphics::{Color, DrawParam, Rect};
+use rayon::prelude::*;
+use std::{
+    collections::HashMap,
+    ops::{Index, IndexMut},
+};
+
+/// Whether a grain of sand is moving
+/// * Falling: The grain moves with the physics, also while bouncing or lifted by low gravity.
+/// * Settled: The grain rests on the ground and its physics sleep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrainState {
+    Falling,
+    Settled,
+}
+
+/// Structure representing a grain of sand
+/// * rect: rectangle representing the grain's position and size
+/// * color: color of the grain
+/// * rotation: current rotation of the grain
+/// * r_v: rotational velocity of the grain
+/// * x_v: horizontal velocity of the grain
+/// * y_v: vertical velocity of the grain
+/// * y_a: vertical acceleration of the grain
+/// * restitution: fraction of speed kept when bouncing off the ground
+/// * particle: type of sand particle the grain is made of
+/// * trail: ring buffer of past positions of the grain
+/// * trail_next: index in the trail for the next position
+/// * trail_count: number of positions stored in the trail
+/// * sparkle_timer: time left for the grain to sparkle
+/// * prev: position of the grain before the last physics step
+/// * bonus: extra money the grain sells for, from a click combo
+/// * state: whether the grain is falling or settled on the ground
+/// * age: seconds the grain has rested on the ground, for the sand decay of hardcore mode
+#[derive(Debug, Clone)]
+pub struct Grain {
+    pub(crate) rect: Rect,
+    pub(crate) color: Color,
+    pub(crate) rotation: f32,
+    pub(crate) r_v: f32,
+    pub(crate) x_v: f32,
+    pub(crate) y_v: f32,
+    pub(crate) y_a: f32,
+    pub(crate) restitution: f32,
+    pub(crate) particle: SandParticle,
+    pub(crate) trail: [(f32, f32); TRAIL_LENGTH],
+    pub(crate) trail_next: usize,
+    pub(crate) trail_count: usize,
+    pub(crate) sparkle_timer: f32,
+    pub(crate) prev: (f32, f32),
+    pub(crate) bonus: f64,
+    pub(crate) state: GrainState,
+    pub(crate) age: f32,
+}
+
+/// Implementation of methods for the Grain struct
+/// * new: creates a new grain of sand
+/// * is_done: returns true if the grain is done (settled on the ground)
+/// * settle: puts the grain to rest on the ground
+/// * wake: makes a settled grain move again
+/// * push: adds to the velocity of the grain, waking it up
+/// * update: updates the position of the grain based on physics
+/// * position: returns the position of the grain between physics steps
+impl Grain {
+    /// creates a new grain of sand
+    pub fn new(x: f32, y: f32, size: f32, rgb: Color) -> Self {
+        let grain_rect = Rect::new(x - size / 2.0, y - size / 2.0, size, size);
+
+        Self {
+            rect: grain_rect,
+            color: rgb,
+            rotation: 0.0,
+            r_v: 3.0,
+            x_v: 0.0,
+            y_v: 0.0,
+            y_a: 0.0,
+            restitution: 0.0,
+            particle: SandParticle::Sand,
+            trail: [(0.0, 0.0); TRAIL_LENGTH],
+            trail_next: 0,
+            trail_count: 0,
+            sparkle_timer: 0.0,
+            prev: (grain_rect.x, grain_rect.y),
+            bonus: 0.0,
+            state: GrainState::Falling,
+            age: 0.0,
+        }
+    }
+
+    /// returns true if the grain is done (settled on the ground)
+    /// new grains fall until they hit the ground, even when spawned below it
+    pub fn is_done(&self) -> bool {
+        self.state == GrainState::Settled
+    }
+
+    /// puts the grain to rest on the ground at the height floor
+    pub(crate) fn settle(&mut self, floor: f32) {
+        self.rect.y = floor - self.rect.h;
+        self.y_v = 0.0;
+        self.x_v = 0.0;
+        // a settled grain has no trail
+        self.trail_count = 0;
+        // the decay starts over every time the grain lands
+        self.age = 0.0;
+        self.state = GrainState::Settled;
+    }
+
+    /// makes a settled grain move again
+    /// gives it a small upward kick and a horizontal drift
+    pub(crate) fn wake(&mut self, drift: f32) {
+        self.state = GrainState::Falling;
+        self.x_v = drift;
+        if self.y_v.abs() <= SLEEP_VELOCITY {
+            self.y_v = -(SLEEP_VELOCITY + 1.0);
+        }
+    }
+
+    /// adds to the velocity of the grain, waking it up
+    pub(crate) fn push(&mut self, x_v: f32, y_v: f32) {
+        self.state = GrainState::Falling;
+        self.x_v += x_v;
+        self.y_v += y_v;
+        // settled grains need a hop to start moving
+        if self.y_v.abs() <= SLEEP_VELOCITY {
+            self.y_v = -(SLEEP_VELOCITY + 1.0);
+        }
+    }
+
+    /// updates the position of the grain based on physics
+    /// bounds is the width of the screen and the height of the ground
+    pub fn update(&mut self, dt: f32, gravity: f32, bounds: (f32, f32)) {
+        // remember where the grain was for drawing between steps
+        self.prev = (self.rect.x, self.rect.y);
+        // put the physics to sleep if on the ground
+        if self.is_done() {
+            return;
+        }
+        // remember where the grain was for the trail
+        let center = self.rect.center();
+        self.trail[self.trail_next] = (center.x, center.y);
+        self.trail_next = (self.trail_next + 1) % TRAIL_LENGTH;
+        self.trail_count = (self.trail_count + 1).min(TRAIL_LENGTH);
+        // apply gravity
+        self.y_v += gravity * dt;
+        // apply acceleration
+        self.y_v += self.y_a * dt;
+        // keep the speed sane, even after gravity changes
+        self.y_v = self.y_v.clamp(-MAX_FALL_SPEED, MAX_FALL_SPEED);
+        // update position based on velocity
+        self.rect.translate([self.x_v * dt, self.y_v * dt]);
+        self.rotation += self.r_v * dt;
+        // keep the grain inside the screen sides
+        self.rect.x = self.rect.x.clamp(0.0, bounds.0 - self.rect.w);
+        // check for ceiling collision when floating up
+        if self.y_v < 0.0 && self.rect.top() <= 0.0 {
+            self.rect.y = 0.0;
+            self.y_v = 0.0;
+        }
+        // check for ground collision when falling
+        if self.y_v >= 0.0 && self.rect.bottom() >= bounds.1 {
+            self.rect.y = bounds.1 - self.rect.h;
+            // bounce back up with some of the speed, or settle if too slow
+            let bounce = self.y_v * self.restitution;
+            if bounce > SLEEP_VELOCITY {
+                self.y_v = -bounce;
+            } else {
+                self.settle(bounds.1);
+            }
+        }
+    }
+
+    /// returns the position of the grain between physics steps
+    /// alpha is how far the next step is, from 0 (last step) to 1 (now)
+    pub(crate) fn position(&self, alpha: f32) -> (f32, f32) {
+        (
+            self.prev.0 + (self.rect.x - self.prev.0) * alpha,
+            self.prev.1 + (self.rect.y - self.prev.1) * alpha,
+        )
+    }
+}
+
+/// creates an amount of falling grains spread over the play area
+/// builds the grains in bulk outside the game loop, e.g. for benchmarks
+/// the grains fill rows from the top and wrap around before the ground
+pub fn scatter_grains(amount: usize, size: f32, bounds: (f32, f32)) -> Vec<Grain> {
+    let columns = ((bounds.0 / size) as usize).max(1);
+    let rows = ((bounds.1 / size) as usize).saturating_sub(1).max(1);
+    (0..amount)
+        .map(|i| {
+            let x = (i % columns) as f32 * size + size / 2.0;
+            let y = (i / columns % rows) as f32 * size + size / 2.0;
+            let mut grain = Grain::new(x, y, size, Color::WHITE);
+            // spread out the speeds, so the grains don't land all at once
+            grain.y_v = (i % 7) as f32 * 20.0;
+            grain
+        })
+        .collect()
+}
+
+/// Storage of the grains in the container that recycles the slots of sold grains
+/// so dropping and selling sand doesn't allocate once the pool is big enough
+/// * slots: grains of the pool, including the ones that were sold
+/// * alive: flag for whether each slot holds a grain in the container
+/// * free: slots of sold grains, reused by the next drops
+/// * order: drop number of the grain in each slot, lower ones are older
+/// * dropped: amount of grains ever pushed, the next drop number
+/// * len: amount of grains in the container
+#[derive(Debug, Default)]
+pub struct GrainPool {
+    pub(crate) slots: Vec<Grain>,
+    pub(crate) alive: Vec<bool>,
+    pub(crate) free: Vec<usize>,
+    pub(crate) order: Vec<u64>,
+    pub(crate) dropped: u64,
+    pub(crate) len: usize,
+}
+
+/// Implementation of methods for the GrainPool struct
+/// * len: returns the amount of grains in the container
+/// * is_empty: checks if there are no grains in the container
+/// * push: adds a grain, reusing the slot of a sold grain
+/// * remove: takes a grain out of its slot
+/// * get: returns the grain in a slot, if it holds one
+/// * get_mut: returns the grain in a slot mutably, if it holds one
+/// * clear: removes all grains, keeping their slots for reuse
+/// * take_all: removes all grains, returning copies of them
+/// * iter: returns the grains in the container
+/// * iter_mut: returns the grains in the container, mutably
+/// * par_iter_mut: returns the grains in the container, mutably and split over the threads
+/// * indexed: returns the grains in the container with their slots
+/// * oldest: returns the slots of the oldest grains that match a filter
+impl GrainPool {
+    /// returns the amount of grains in the container
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// checks if there are no grains in the container
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// adds a grain, reusing the slot of a sold grain if there is one
+    /// returns the slot of the grain
+    pub fn push(&mut self, grain: Grain) -> usize {
+        self.len += 1;
+        self.dropped += 1;
+        match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot] = grain;
+                self.alive[slot] = true;
+                self.order[slot] = self.dropped;
+                slot
+            }
+            None => {
+                self.slots.push(grain);
+                self.alive.push(true);
+                self.order.push(self.dropped);
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    /// takes the grain out of the slot, which is free for the next drop
+    /// returns None if the slot holds no grain
+    pub fn remove(&mut self, slot: usize) -> Option<Grain> {
+        if !self.alive.get(slot).copied().unwrap_or(false) {
+            return None;
+        }
+        self.alive[slot] = false;
+        self.free.push(slot);
+        self.len -= 1;
+        Some(self.slots[slot].clone())
+    }
+
+    /// returns the grain in the slot, None if the slot holds no grain
+    pub fn get(&self, slot: usize) -> Option<&Grain> {
+        self.alive
+            .get(slot)
+            .copied()
+            .unwrap_or(false)
+            .then(|| &self.slots[slot])
+    }
+
+    /// returns the grain in the slot mutably, None if the slot holds no grain
+    pub fn get_mut(&mut self, slot: usize) -> Option<&mut Grain> {
+        if !self.alive.get(slot).copied().unwrap_or(false) {
+            return None;
+        }
+        Some(&mut self.slots[slot])
+    }
+
+    /// removes all grains, keeping their slots for reuse
+    pub fn clear(&mut self) {
+        self.alive.fill(false);
+        self.free.clear();
+        // the first slots are reused first, keeping the grains packed
+        self.free.extend((0..self.slots.len()).rev());
+        self.len = 0;
+    }
+
+    /// removes all grains, returning copies of them
+    pub fn take_all(&mut self) -> Vec<Grain> {
+        let grains = self.iter().cloned().collect();
+        self.clear();
+        grains
+    }
+
+    /// returns the grains in the container, skipping the free slots
+    pub fn iter(&self) -> impl Iterator<Item = &Grain> {
+        self.slots
+            .iter()
+            .zip(&self.alive)
+            .filter_map(|(grain, alive)| alive.then_some(grain))
+    }
+
+    /// returns the grains in the container mutably, skipping the free slots
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Grain> {
+        self.slots
+            .iter_mut()
+            .zip(&self.alive)
+            .filter_map(|(grain, alive)| alive.then_some(grain))
+    }
+
+    /// returns the grains in the container mutably and split over the threads
+    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = &mut Grain> {
+        self.slots
+            .par_iter_mut()
+            .zip(self.alive.par_iter())
+            .filter_map(|(grain, alive)| alive.then_some(grain))
+    }
+
+    /// returns the grains in the container with their slots
+    pub fn indexed(&self) -> impl Iterator<Item = (usize, &Grain)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .zip(&self.alive)
+            .filter_map(|(slot, alive)| alive.then_some(slot))
+    }
+
+    /// returns the slots of the oldest grains that match the filter, oldest first
+    /// at most amount slots are returned
+    pub fn oldest(&self, amount: usize, filter: impl Fn(&Grain) -> bool) -> Vec<usize> {
+        let mut slots: Vec<usize> = self
+            .indexed()
+            .filter(|(_, grain)| filter(grain))
+            .map(|(slot, _)| slot)
+            .collect();
+        slots.sort_unstable_by_key(|&slot| self.order[slot]);
+        slots.truncate(amount);
+        slots
+    }
+}
+
+impl Index<usize> for GrainPool {
+    type Output = Grain;
+
+    fn index(&self, slot: usize) -> &Grain {
+        debug_assert!(self.alive[slot], "slot {} holds no grain", slot);
+        &self.slots[slot]
+    }
+}
+
+impl IndexMut<usize> for GrainPool {
+    fn index_mut(&mut self, slot: usize) -> &mut Grain {
+        debug_assert!(self.alive[slot], "slot {} holds no grain", slot);
+        &mut self.slots[slot]
+    }
+}
+
+impl FromIterator<Grain> for GrainPool {
+    fn from_iter<I: IntoIterator<Item = Grain>>(grains: I) -> Self {
+        let mut pool = Self::default();
+        for grain in grains {
+            pool.push(grain);
+        }
+        pool
+    }
+}
+
+/// Structure representing a sold grain flying to the money counter
+/// * grain: the sold grain
+/// * start: position the grain started flying from
+/// * target: position of the money counter in the world
+/// * elapsed: time since the grain was sold
+#[derive(Debug)]
+pub(crate) struct CollectingGrain {
+    pub(crate) grain: Grain,
+    pub(crate) start: (f32, f32),
+    pub(crate) target: (f32, f32),
+    pub(crate) elapsed: f32,
+}
+
+/// Implementation of methods for the CollectingGrain struct
+/// * new: starts the flight of a sold grain
+/// * is_done: returns true if the grain reached the money counter
+/// * update: moves, shrinks, and fades the grain
+impl CollectingGrain {
+    /// starts the flight of a sold grain
+    pub(crate) fn new(grain: Grain, target: (f32, f32)) -> Self {
+        let center = grain.rect.center();
+        Self {
+            grain,
+            start: (center.x, center.y),
+            target,
+            elapsed: 0.0,
+        }
+    }
+
+    /// returns true if the grain reached the money counter
+    pub(crate) fn is_done(&self) -> bool {
+        self.elapsed >= COLLECT_TIME
+    }
+
+    /// moves, shrinks, and fades the grain
+    pub(crate) fn update(&mut self, dt: f32) {
+        self.elapsed += dt;
+        let t = (self.elapsed / COLLECT_TIME).min(1.0);
+        // speed up towards the end of the flight
+        let eased = t * t;
+        let x = self.start.0 + (self.target.0 - self.start.0) * eased;
+        let y = self.start.1 + (self.target.1 - self.start.1) * eased;
+        let size = GRAIN_SIZE * (1.0 - 0.8 * t);
+        self.grain.rect = Rect::new(x - size / 2.0, y - size / 2.0, size, size);
+        self.grain.color.a = 1.0 - t;
+    }
+}
+
+/// Structure representing a short-lived sparkle
+/// * x: horizontal position of the sparkle
+/// * y: vertical position of the sparkle
+/// * life: time left before the sparkle disappears
+#[derive(Debug)]
+pub(crate) struct Effect {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) life: f32,
+}
+
+/// Implementation of methods for the Effect struct
+/// * new: creates a new sparkle
+/// * is_done: returns true if the sparkle faded out
+/// * update: ages the sparkle
+/// * draw_params: returns the draw parameters for the sparkle
+impl Effect {
+    /// creates a new sparkle
+    pub(crate) fn new(x: f32, y: f32) -> Self {
+        Self {
+            x,
+            y,
+            life: EFFECT_LIFE,
+        }
+    }
+
+    /// returns true if the sparkle faded out
+    pub(crate) fn is_done(&self) -> bool {
+        self.life <= 0.0
+    }
+
+    /// ages the sparkle
+    pub(crate) fn update(&mut self, dt: f32) {
+        self.life -= dt;
+    }
+
+    /// returns the draw parameters for the sparkle
+    pub(crate) fn draw_params(&self) -> DrawParam {
+        let alpha = (self.life / EFFECT_LIFE).clamp(0.0, 1.0);
+        DrawParam::default()
+            .dest([self.x, self.y])
+            .scale([3.0, 3.0])
+            .offset([0.5, 0.5])
+            .color(Color::new(1.0, 1.0, 1.0, alpha))
+    }
+}
+
+/// Structure representing a rising and fading text
+/// * text: the text to show
+/// * x: horizontal position of the text
+/// * y: vertical position of the text
+/// * life: time left before the text disappears
+#[derive(Debug)]
+pub(crate) struct FloatingText {
+    pub(crate) text: String,
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) life: f32,
+}
+
+/// Implementation of methods for the FloatingText struct
+/// * new: creates a new floating text
+/// * is_done: returns true if the text faded out
+/// * update: moves the text up and ages it
+/// * draw_params: returns the draw parameters for the text
+impl FloatingText {
+    /// creates a new floating text
+    pub(crate) fn new(text: String, x: f32, y: f32) -> Self {
+        Self {
+            text,
+            x,
+            y,
+            life: FLOAT_TEXT_LIFE,
+        }
+    }
+
+    /// returns true if the text faded out
+    pub(crate) fn is_done(&self) -> bool {
+        self.life <= 0.0
+    }
+
+    /// moves the text up and ages it
+    pub(crate) fn update(&mut self, dt: f32) {
+        self.y -= 40.0 * dt;
+        self.life -= dt;
+    }
+
+    /// returns the draw parameters for the text
+    pub(crate) fn draw_params(&self) -> DrawParam {
+        let alpha = (self.life / FLOAT_TEXT_LIFE).clamp(0.0, 1.0);
+        DrawParam::from([self.x, self.y]).color(Color::new(1.0, 0.84, 0.0, alpha))
+    }
+}
+
+/// Structure for looking up grains by position
+/// * cell_size: width and height of each cell
+/// * cells: map of cell coordinates to grain indices
+#[derive(Debug)]
+pub struct SpatialGrid {
+    pub(crate) cell_size: f32,
+    pub(crate) cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+/// Implementation of methods for the SpatialGrid struct
+/// * new: creates a new empty grid
+/// * cell: returns the cell coordinates of a position
+/// * rebuild: fills the grid with the current grains
+/// * query: returns the grains in the cells around a position
+impl SpatialGrid {
+    /// creates a new empty grid
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// returns the cell coordinates of a position
+    pub(crate) fn cell(&self, x: f32, y: f32) -> (i32, i32) {
+        (
+            (x / self.cell_size).floor() as i32,
+            (y / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// fills the grid with the current grains
+    /// takes the grains with their indices, e.g. the slots of a pool
+    pub fn rebuild<'a>(&mut self, grains: impl IntoIterator<Item = (usize, &'a Grain)>) {
+        // keep the cell allocations around for the next rebuild
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        for (i, grain) in grains {
+            let center = grain.rect.center();
+            let cell = self.cell(center.x, center.y);
+            self.cells.entry(cell).or_default().push(i);
+        }
+    }
+
+    /// returns the grains in the cells around a position
+    /// the caller still needs to check the exact distance
+    pub fn query(&self, x: f32, y: f32, radius: f32) -> Vec<usize> {
+        let (min_x, min_y) = self.cell(x - radius, y - radius);
+        let (max_x, max_y) = self.cell(x + radius, y + radius);
+        let mut found = Vec::new();
+        for cx in min_x..=max_x {
+            for cy in min_y..=max_y {
+                if let Some(cell) = self.cells.get(&(cx, cy)) {
+                    found.extend(cell.iter().copied());
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Tests for the grains
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GRAVITY, MONEY_POS, SCREEN_SIZE, particle::ParticleTable};
+
+    // CollectingGrain tests
+    #[test]
+    fn test_collecting_grain_update() {
+        let grain = Grain::new(400.0, 500.0, GRAIN_SIZE, Color::WHITE);
+        let mut collected = CollectingGrain::new(grain, MONEY_POS);
+        collected.update(COLLECT_TIME / 2.0);
+        assert!(!collected.is_done());
+        assert!(collected.grain.rect.w < GRAIN_SIZE);
+        assert!(collected.grain.color.a < 1.0);
+        collected.update(COLLECT_TIME / 2.0);
+        assert!(collected.is_done());
+        let center = collected.grain.rect.center();
+        assert!((center.x - MONEY_POS.0).abs() < 1e-3);
+        assert!((center.y - MONEY_POS.1).abs() < 1e-3);
+    }
+
+    // GrainPool tests
+    #[test]
+    fn test_grain_pool_reuse() {
+        let mut pool = GrainPool::default();
+        let first = pool.push(Grain::new(10.0, 10.0, GRAIN_SIZE, Color::WHITE));
+        let second = pool.push(Grain::new(20.0, 20.0, GRAIN_SIZE, Color::WHITE));
+        assert_eq!((first, second), (0, 1));
+        assert_eq!(pool.len(), 2);
+        // a removed grain frees its slot, once
+        assert!(pool.remove(first).is_some());
+        assert!(pool.remove(first).is_none());
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.iter().count(), 1);
+        // the next grain takes the free slot instead of growing the pool
+        assert_eq!(
+            pool.push(Grain::new(30.0, 30.0, GRAIN_SIZE, Color::WHITE)),
+            first
+        );
+        assert_eq!(pool.slots.len(), 2);
+        assert_eq!(pool[first].rect.x, 30.0 - GRAIN_SIZE / 2.0);
+    }
+    #[test]
+    fn test_grain_pool_clear() {
+        let mut pool: GrainPool = (0..5)
+            .map(|i| Grain::new(i as f32, 0.0, GRAIN_SIZE, Color::WHITE))
+            .collect();
+        assert_eq!(pool.take_all().len(), 5);
+        assert!(pool.is_empty());
+        assert_eq!(pool.iter().count(), 0);
+        // the cleared slots are reused from the front
+        for i in 0..5 {
+            assert_eq!(pool.push(Grain::new(0.0, 0.0, GRAIN_SIZE, Color::WHITE)), i);
+        }
+        assert_eq!(pool.push(Grain::new(0.0, 0.0, GRAIN_SIZE, Color::WHITE)), 5);
+        assert_eq!(pool.len(), 6);
+    }
+
+    #[test]
+    fn test_grain_pool_oldest() {
+        let mut pool = GrainPool::default();
+        for i in 0..4 {
+            pool.push(Grain::new(i as f32 * 10.0, 0.0, GRAIN_SIZE, Color::WHITE));
+        }
+        // a reused slot holds a newer grain than the ones after it
+        pool.remove(0);
+        pool.push(Grain::new(100.0, 0.0, GRAIN_SIZE, Color::WHITE));
+        assert_eq!(pool.oldest(2, |_| true), vec![1, 2]);
+        assert_eq!(pool.oldest(10, |_| true), vec![1, 2, 3, 0]);
+        assert_eq!(pool.oldest(10, |grain| grain.rect.x > 10.0), vec![2, 3, 0]);
+    }
+
+    // SpatialGrid tests
+    #[test]
+    fn test_spatial_grid_query() {
+        let grains = [
+            Grain::new(10.0, 10.0, GRAIN_SIZE, Color::WHITE),
+            Grain::new(15.0, 12.0, GRAIN_SIZE, Color::WHITE),
+            Grain::new(500.0, 500.0, GRAIN_SIZE, Color::WHITE),
+        ];
+        let mut grid = SpatialGrid::new(GRAIN_SIZE * 4.0);
+        grid.rebuild(grains.iter().enumerate());
+        let mut found = grid.query(12.0, 12.0, 20.0);
+        found.sort();
+        assert_eq!(found, vec![0, 1]);
+        assert_eq!(grid.query(500.0, 500.0, 5.0), vec![2]);
+    }
+
+    // Grain tests
+    #[test]
+    fn test_grain_new() {
+        let grain = Grain::new(100.0, 200.0, GRAIN_SIZE, Color::WHITE);
+        assert_eq!(grain.rect.x, 100.0 - GRAIN_SIZE / 2.0);
+        assert_eq!(grain.rect.y, 200.0 - GRAIN_SIZE / 2.0);
+        assert_eq!(grain.rect.w, GRAIN_SIZE);
+        assert_eq!(grain.rect.h, GRAIN_SIZE);
+        assert_eq!(grain.color, Color::WHITE);
+    }
+    #[test]
+    fn test_grain_is_done() {
+        // a grain spawned below the ground is moved onto it and settles
+        let mut grain = Grain::new(0.0, SCREEN_SIZE.1 + 10.0, GRAIN_SIZE, Color::WHITE);
+        assert!(!grain.is_done());
+        grain.update(1.0 / 30.0, GRAVITY, SCREEN_SIZE);
+        assert!(grain.is_done());
+        assert_eq!(grain.state, GrainState::Settled);
+        assert_eq!(grain.rect.bottom(), SCREEN_SIZE.1);
+        // a slow grain in the air still falls
+        let mut grain = Grain::new(0.0, 100.0, GRAIN_SIZE, Color::WHITE);
+        grain.update(1.0 / 30.0, 0.0, SCREEN_SIZE);
+        assert!(!grain.is_done());
+    }
+    #[test]
+    fn test_grain_update() {
+        let mut grain = Grain::new(0.0, 0.0, GRAIN_SIZE, Color::WHITE);
+        grain.update(1.0, GRAVITY, SCREEN_SIZE);
+        assert!(grain.rect.y > 0.0);
+    }
+    #[test]
+    fn test_grain_position() {
+        let mut grain = Grain::new(100.0, 100.0, GRAIN_SIZE, Color::WHITE);
+        grain.y_v = 300.0;
+        grain.update(1.0 / 30.0, 0.0, SCREEN_SIZE);
+        let (prev, now) = (grain.prev, (grain.rect.x, grain.rect.y));
+        assert_eq!(grain.position(0.0), prev);
+        assert_eq!(grain.position(0.5), (prev.0, (prev.1 + now.1) / 2.0));
+        assert_eq!(grain.position(1.0), now);
+        // settled grains stay where they are, after the step that moved them onto the ground
+        let mut grain = Grain::new(0.0, SCREEN_SIZE.1 + 10.0, GRAIN_SIZE, Color::WHITE);
+        grain.update(1.0 / 30.0, GRAVITY, SCREEN_SIZE);
+        assert!(grain.is_done());
+        grain.update(1.0 / 30.0, GRAVITY, SCREEN_SIZE);
+        assert_eq!(grain.position(0.5), (grain.rect.x, grain.rect.y));
+    }
+    #[test]
+    fn test_grain_scatter() {
+        let grains = scatter_grains(10_000, GRAIN_SIZE, SCREEN_SIZE);
+        assert_eq!(grains.len(), 10_000);
+        // every grain starts inside the play area and is still falling
+        for grain in &grains {
+            assert!(grain.rect.left() >= 0.0 && grain.rect.right() <= SCREEN_SIZE.0);
+            assert!(grain.rect.top() >= 0.0);
+            assert!(!grain.is_done());
+        }
+    }
+    #[test]
+    fn test_grain_bounce() {
+        let mut grain = Grain::new(0.0, SCREEN_SIZE.1 - GRAIN_SIZE, GRAIN_SIZE, Color::WHITE);
+        grain.restitution = ParticleTable::default().restitution(SandParticle::Diamond);
+        grain.y_v = 200.0;
+        grain.update(1.0 / 30.0, GRAVITY, SCREEN_SIZE);
+        // the grain hit the ground and bounced back up, still falling while on the ground
+        assert!(grain.y_v < 0.0);
+        assert_eq!(grain.rect.bottom(), SCREEN_SIZE.1);
+        assert!(!grain.is_done());
+        // the bounces decay until the grain settles
+        let mut bounces = 1;
+        while !grain.is_done() {
+            let falling = grain.y_v > 0.0;
+            grain.update(1.0 / 30.0, GRAVITY, SCREEN_SIZE);
+            if falling && grain.y_v < 0.0 {
+                bounces += 1;
+            }
+            assert!(bounces < 100, "the grain never settled");
+        }
+        assert!(bounces > 1);
+        assert_eq!(grain.rect.bottom(), SCREEN_SIZE.1);
+        assert_eq!(grain.y_v, 0.0);
+    }
+    #[test]
+    fn test_grain_wake() {
+        let mut grain = Grain::new(
+            100.0,
+            SCREEN_SIZE.1 - GRAIN_SIZE / 2.0,
+            GRAIN_SIZE,
+            Color::WHITE,
+        );
+        grain.settle(SCREEN_SIZE.1);
+        assert!(grain.is_done());
+        grain.wake(10.0);
+        assert!(!grain.is_done());
+        // inverted gravity lifts the grain off the ground
+        grain.update(1.0 / 30.0, -GRAVITY * 0.1, SCREEN_SIZE);
+        assert!(grain.rect.bottom() < SCREEN_SIZE.1);
+        assert!(grain.rect.x > 100.0 - GRAIN_SIZE / 2.0);
+    }
+    #[test]
+    fn test_grain_no_bounce() {
+        let mut grain = Grain::new(0.0, SCREEN_SIZE.1 - GRAIN_SIZE, GRAIN_SIZE, Color::WHITE);
+        grain.restitution = ParticleTable::default().restitution(SandParticle::Sand);
+        grain.y_v = 200.0;
+        grain.update(1.0 / 30.0, GRAVITY, SCREEN_SIZE);
+        assert_eq!(grain.y_v, 0.0);
+        assert!(grain.is_done());
+    }
+}
@@ -0,0 +1,489 @@
+//! Keyboard and mouse bindings
+
+use crate::{lang::tr, settings::parse_enum};
+use ggez::{
+    event,
+    input::keyboard::{KeyCode, KeyMods},
+};
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+// Mouse buttons that can be mapped to actions
+pub(crate) const MOUSE_BUTTONS: [event::MouseButton; 3] = [
+    event::MouseButton::Left,
+    event::MouseButton::Right,
+    event::MouseButton::Middle,
+];
+
+// Keys that buy the listed upgrades, in order
+pub(crate) const QUICK_BUY_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+// Keys that can be bound to game actions
+pub(crate) const BINDABLE_KEYS: [KeyCode; 57] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Key0,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::Escape,
+    KeyCode::Space,
+    KeyCode::Return,
+    KeyCode::Tab,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::Insert,
+    KeyCode::Delete,
+    KeyCode::PageUp,
+];
+
+/// Game actions that can be triggered by the keyboard
+/// * Drop: drops sand at a random spot along the top.
+/// * Convert: sells the collected sand.
+/// * ToggleInfo: shows/hides the player information.
+/// * ToggleStats: shows/hides the statistics window.
+/// * ToggleAchievements: shows/hides the achievements window.
+/// * ToggleSettings: shows/hides the settings window.
+/// * ToggleDebug: shows/hides the debug overlay.
+/// * Quit: quits the game.
+/// * Pause: pauses/resumes the game.
+/// * Screenshot: saves a screenshot.
+/// * ResetCamera: resets the zoom and pan of the view.
+/// * Fullscreen: toggles fullscreen.
+/// * ToggleMute: mutes/unmutes the sounds.
+#[derive(Hash, Eq, PartialEq, Debug, EnumIter, Clone, Copy)]
+pub(crate) enum Action {
+    Drop,
+    Convert,
+    ToggleInfo,
+    ToggleStats,
+    ToggleAchievements,
+    ToggleSettings,
+    ToggleOptions,
+    ToggleDebug,
+    Quit,
+    Pause,
+    Screenshot,
+    ResetCamera,
+    Fullscreen,
+    ToggleMute,
+}
+
+/// Implementation of methods for the Action enum
+/// * name: returns the display name of the action
+impl Action {
+    /// returns the display name of the action
+    pub(crate) fn name(&self) -> &'static str {
+        tr(match self {
+            Action::Drop => "action-drop",
+            Action::Convert => "action-convert",
+            Action::ToggleInfo => "action-toggle-info",
+            Action::ToggleStats => "action-toggle-stats",
+            Action::ToggleAchievements => "action-toggle-achievements",
+            Action::ToggleSettings => "action-toggle-settings",
+            Action::ToggleOptions => "action-toggle-options",
+            Action::ToggleDebug => "action-toggle-debug",
+            Action::Quit => "action-quit",
+            Action::Pause => "action-pause",
+            Action::Screenshot => "action-screenshot",
+            Action::ResetCamera => "action-reset-camera",
+            Action::Fullscreen => "action-fullscreen",
+            Action::ToggleMute => "action-toggle-mute",
+        })
+    }
+}
+
+/// Keyboard bindings of the game actions
+/// * keys: key and modifiers that have to be held for each action
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Keybinds {
+    pub(crate) keys: HashMap<Action, (KeyCode, KeyMods)>,
+}
+
+/// Default key bindings of the game
+impl Default for Keybinds {
+    fn default() -> Self {
+        Self {
+            keys: HashMap::from([
+                (Action::Drop, (KeyCode::Space, KeyMods::NONE)),
+                (Action::Convert, (KeyCode::C, KeyMods::NONE)),
+                (Action::ToggleInfo, (KeyCode::I, KeyMods::CTRL)),
+                (Action::ToggleStats, (KeyCode::S, KeyMods::CTRL)),
+                (Action::ToggleAchievements, (KeyCode::A, KeyMods::CTRL)),
+                (Action::ToggleSettings, (KeyCode::O, KeyMods::CTRL)),
+                (Action::ToggleOptions, (KeyCode::Tab, KeyMods::NONE)),
+                (Action::ToggleDebug, (KeyCode::D, KeyMods::CTRL)),
+                (Action::Quit, (KeyCode::Q, KeyMods::CTRL)),
+                (Action::Pause, (KeyCode::Escape, KeyMods::NONE)),
+                (Action::Screenshot, (KeyCode::F12, KeyMods::NONE)),
+                (Action::ResetCamera, (KeyCode::Home, KeyMods::NONE)),
+                (Action::Fullscreen, (KeyCode::F11, KeyMods::NONE)),
+                (Action::ToggleMute, (KeyCode::M, KeyMods::NONE)),
+            ]),
+        }
+    }
+}
+
+/// Implementation of methods for the Keybinds struct
+/// * action: returns the action bound to a key press
+/// * bind: binds a key press to an action
+/// * conflicts: checks if another action uses the same key press
+/// * label: returns the key combination bound to an action
+/// * to_text: returns the bindings in the settings file format
+/// * load_line: reads a binding from the settings file format
+impl Keybinds {
+    /// returns the action bound to a key press
+    /// the held modifiers have to match the binding exactly
+    pub(crate) fn action(&self, key: KeyCode, mods: KeyMods) -> Option<Action> {
+        Action::iter().find(|action| self.keys.get(action) == Some(&(key, mods)))
+    }
+
+    /// binds a key press to an action
+    /// only the Ctrl, Alt and Shift modifiers are kept
+    pub(crate) fn bind(&mut self, action: Action, key: KeyCode, mods: KeyMods) {
+        let mut kept = KeyMods::NONE;
+        for modifier in [KeyMods::CTRL, KeyMods::ALT, KeyMods::SHIFT] {
+            if mods.contains(modifier) {
+                kept.insert(modifier);
+            }
+        }
+        self.keys.insert(action, (key, kept));
+    }
+
+    /// checks if another action uses the same key press
+    pub(crate) fn conflicts(&self, action: Action) -> bool {
+        let Some(binding) = self.keys.get(&action) else {
+            return false;
+        };
+        self.keys
+            .iter()
+            .any(|(other, key)| *other != action && key == binding)
+    }
+
+    /// returns the key combination bound to an action, e.g. Ctrl+I
+    pub(crate) fn label(&self, action: Action) -> String {
+        let Some((key, mods)) = self.keys.get(&action) else {
+            return tr("key-none").to_string();
+        };
+        let mut label = String::new();
+        if mods.contains(KeyMods::CTRL) {
+            label.push_str("Ctrl+");
+        }
+        if mods.contains(KeyMods::ALT) {
+            label.push_str("Alt+");
+        }
+        if mods.contains(KeyMods::SHIFT) {
+            label.push_str("Shift+");
+        }
+        label.push_str(&format!("{:?}", key));
+        label
+    }
+
+    /// returns the bindings in the settings file format
+    /// each line is a `key.<Action>=<label>` pair
+    pub(crate) fn to_text(&self) -> Vec<String> {
+        Action::iter()
+            .filter(|action| self.keys.contains_key(action))
+            .map(|action| format!("key.{:?}={}", action, self.label(action)))
+            .collect()
+    }
+
+    /// reads a binding from the settings file format
+    /// returns false if the line isn't a binding
+    pub(crate) fn load_line(&mut self, key: &str, value: &str) -> bool {
+        let Some(action) = key.strip_prefix("key.") else {
+            return false;
+        };
+        let Some(action) = parse_enum(action) else {
+            return true;
+        };
+        // the label is the modifiers followed by the key
+        let mut mods = KeyMods::NONE;
+        let mut parts: Vec<&str> = value.split('+').collect();
+        let Some(name) = parts.pop() else {
+            return true;
+        };
+        for part in parts {
+            match part {
+                "Ctrl" => mods.insert(KeyMods::CTRL),
+                "Alt" => mods.insert(KeyMods::ALT),
+                "Shift" => mods.insert(KeyMods::SHIFT),
+                _ => return true,
+            }
+        }
+        if let Some(key) = BINDABLE_KEYS
+            .iter()
+            .find(|key| format!("{:?}", key) == name)
+        {
+            self.keys.insert(action, (*key, mods));
+        }
+        true
+    }
+}
+
+/// Actions that can be mapped to the mouse buttons
+/// * Drop: drops sand like a click.
+/// * Vacuum: vacuums up and sells the settled sand.
+/// * Pan: moves the camera while dragging.
+/// * Nothing: does nothing.
+#[derive(Hash, Eq, PartialEq, Debug, EnumIter, Clone, Copy)]
+pub(crate) enum MouseAction {
+    Drop,
+    Vacuum,
+    Pan,
+    Nothing,
+}
+
+/// Implementation of methods for the MouseAction enum
+/// * name: returns the display name of the mouse action
+impl MouseAction {
+    /// returns the display name of the mouse action
+    pub(crate) fn name(&self) -> &'static str {
+        tr(match self {
+            MouseAction::Drop => "mouse-action-drop",
+            MouseAction::Vacuum => "mouse-action-vacuum",
+            MouseAction::Pan => "mouse-action-pan",
+            MouseAction::Nothing => "mouse-action-nothing",
+        })
+    }
+}
+
+/// returns the display name of a mouse button
+pub(crate) fn mouse_button_name(button: event::MouseButton) -> &'static str {
+    tr(match button {
+        event::MouseButton::Left => "mouse-left",
+        event::MouseButton::Right => "mouse-right",
+        event::MouseButton::Middle => "mouse-middle",
+        _ => "mouse-other",
+    })
+}
+
+/// Mouse button mapping of the mouse actions
+/// * actions: action of each mapped mouse button
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MouseButtons {
+    pub(crate) actions: HashMap<event::MouseButton, MouseAction>,
+}
+
+/// Default mouse buttons, left drops, right vacuums and middle pans
+impl Default for MouseButtons {
+    fn default() -> Self {
+        Self {
+            actions: HashMap::from([
+                (event::MouseButton::Left, MouseAction::Drop),
+                (event::MouseButton::Right, MouseAction::Vacuum),
+                (event::MouseButton::Middle, MouseAction::Pan),
+            ]),
+        }
+    }
+}
+
+/// Implementation of methods for the MouseButtons struct
+/// * action: returns the action of a mouse button
+/// * bind: maps a mouse button to an action
+/// * has_drop: checks if a button drops sand
+/// * swap: swaps the actions of the left and right buttons
+/// * to_text: returns the mapping in the settings file format
+/// * load_line: reads a mapping from the settings file format
+impl MouseButtons {
+    /// returns the action of a mouse button
+    pub(crate) fn action(&self, button: event::MouseButton) -> MouseAction {
+        *self.actions.get(&button).unwrap_or(&MouseAction::Nothing)
+    }
+
+    /// maps a mouse button to an action
+    /// returns false if no button would drop sand anymore
+    pub(crate) fn bind(&mut self, button: event::MouseButton, action: MouseAction) -> bool {
+        let old = self.actions.insert(button, action);
+        if !self.has_drop() {
+            match old {
+                Some(old) => self.actions.insert(button, old),
+                None => self.actions.remove(&button),
+            };
+            return false;
+        }
+        true
+    }
+
+    /// checks if a button drops sand
+    pub(crate) fn has_drop(&self) -> bool {
+        self.actions
+            .values()
+            .any(|action| *action == MouseAction::Drop)
+    }
+
+    /// swaps the actions of the left and right buttons
+    pub(crate) fn swap(&mut self) {
+        let left = self.action(event::MouseButton::Left);
+        let right = self.action(event::MouseButton::Right);
+        self.actions.insert(event::MouseButton::Left, right);
+        self.actions.insert(event::MouseButton::Right, left);
+    }
+
+    /// returns the mapping in the settings file format
+    /// each line is a `mouse.<Button>=<MouseAction>` pair
+    pub(crate) fn to_text(&self) -> Vec<String> {
+        MOUSE_BUTTONS
+            .iter()
+            .map(|button| format!("mouse.{:?}={:?}", button, self.action(*button)))
+            .collect()
+    }
+
+    /// reads a mapping from the settings file format
+    /// returns false if the line isn't a mapping
+    pub(crate) fn load_line(&mut self, key: &str, value: &str) -> bool {
+        let Some(name) = key.strip_prefix("mouse.") else {
+            return false;
+        };
+        if let Some(button) = MOUSE_BUTTONS
+            .iter()
+            .find(|button| format!("{:?}", button) == name)
+            && let Some(action) = parse_enum(value)
+        {
+            self.actions.insert(*button, action);
+        }
+        true
+    }
+}
+
+/// Tests for the bindings
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+
+    // Keybinds tests
+    #[test]
+    fn test_keybinds_action() {
+        let keybinds = Keybinds::default();
+        assert_eq!(
+            keybinds.action(KeyCode::C, KeyMods::NONE),
+            Some(Action::Convert)
+        );
+        assert_eq!(keybinds.action(KeyCode::C, KeyMods::CTRL), None);
+        assert_eq!(keybinds.action(KeyCode::I, KeyMods::NONE), None);
+        assert_eq!(
+            keybinds.action(KeyCode::I, KeyMods::CTRL),
+            Some(Action::ToggleInfo)
+        );
+        // every action has a binding and none of them clash
+        for action in Action::iter() {
+            assert!(keybinds.keys.contains_key(&action));
+            assert!(!keybinds.conflicts(action));
+        }
+    }
+    #[test]
+    fn test_keybinds_rebind() {
+        let mut keybinds = Keybinds::default();
+        keybinds.bind(Action::Convert, KeyCode::Q, KeyMods::CTRL | KeyMods::LOGO);
+        assert_eq!(keybinds.label(Action::Convert), "Ctrl+Q");
+        assert!(keybinds.conflicts(Action::Convert));
+        assert!(keybinds.conflicts(Action::Quit));
+        assert!(!keybinds.conflicts(Action::Pause));
+    }
+    #[test]
+    fn test_keybinds_text() {
+        let mut keybinds = Keybinds::default();
+        assert!(keybinds.load_line("key.Pause", "Alt+Shift+P"));
+        assert_eq!(keybinds.label(Action::Pause), "Alt+Shift+P");
+        // broken bindings keep the old key
+        assert!(keybinds.load_line("key.Quit", "Super+Q"));
+        assert!(keybinds.load_line("key.Quit", "Ctrl+Nothing"));
+        assert_eq!(keybinds.label(Action::Quit), "Ctrl+Q");
+        assert!(!keybinds.load_line("trails", "true"));
+    }
+
+    // MouseButtons tests
+    #[test]
+    fn test_mouse_buttons_bind() {
+        let mut mouse = MouseButtons::default();
+        assert_eq!(
+            mouse.action(event::MouseButton::Other(4)),
+            MouseAction::Nothing
+        );
+        // the only drop button can't be taken away
+        assert!(!mouse.bind(event::MouseButton::Left, MouseAction::Vacuum));
+        assert_eq!(mouse.action(event::MouseButton::Left), MouseAction::Drop);
+        assert!(mouse.bind(event::MouseButton::Middle, MouseAction::Drop));
+        assert!(mouse.bind(event::MouseButton::Left, MouseAction::Nothing));
+        mouse.swap();
+        assert_eq!(mouse.action(event::MouseButton::Left), MouseAction::Vacuum);
+        assert_eq!(
+            mouse.action(event::MouseButton::Right),
+            MouseAction::Nothing
+        );
+    }
+    #[test]
+    fn test_mouse_buttons_text() {
+        let text = "mouse.Left=Vacuum\nmouse.Right=Drop\nmouse.Middle=Fly";
+        let settings = Settings::from_text(text);
+        assert_eq!(
+            settings.mouse.action(event::MouseButton::Left),
+            MouseAction::Vacuum
+        );
+        assert_eq!(
+            settings.mouse.action(event::MouseButton::Right),
+            MouseAction::Drop
+        );
+        assert_eq!(
+            settings.mouse.action(event::MouseButton::Middle),
+            MouseAction::Pan
+        );
+        // a mapping without a drop button goes back to the default
+        let settings = Settings::from_text("mouse.Left=Pan");
+        assert_eq!(settings.mouse, MouseButtons::default());
+    }
+}
@@ -0,0 +1,150 @@
+//! Translations of the game texts
+
+use std::{cell::Cell, collections::HashMap, fmt::Display, sync::OnceLock};
+use strum_macros::EnumIter;
+
+// Texts of the game in each language
+pub(crate) const LANG_EN: &str = include_str!("../assets/lang/en.ftl");
+
+pub(crate) const LANG_DE: &str = include_str!("../assets/lang/de.ftl");
+
+/// Languages the game is translated to
+/// * English: The default language.
+/// * German: Deutsch.
+#[derive(Hash, Eq, PartialEq, Debug, EnumIter, Clone, Copy, Default)]
+pub(crate) enum Lang {
+    #[default]
+    English,
+    German,
+}
+
+/// Implementation of methods for the Lang enum
+/// * name: returns the name of the language in itself
+/// * table: returns the texts of the language by key
+impl Lang {
+    /// returns the name of the language in itself
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            Lang::English => "English",
+            Lang::German => "Deutsch",
+        }
+    }
+
+    /// returns the texts of the language by key
+    /// the text file is only read the first time
+    pub(crate) fn table(&self) -> &'static HashMap<&'static str, String> {
+        static ENGLISH: OnceLock<HashMap<&str, String>> = OnceLock::new();
+        static GERMAN: OnceLock<HashMap<&str, String>> = OnceLock::new();
+        match self {
+            Lang::English => ENGLISH.get_or_init(|| parse_lang(LANG_EN)),
+            Lang::German => GERMAN.get_or_init(|| parse_lang(LANG_DE)),
+        }
+    }
+}
+
+thread_local! {
+    // language the texts are shown in, set from the settings
+    static LANG: Cell<Lang> = const { Cell::new(Lang::English) };
+}
+
+/// reads a language file into texts by key
+/// each line is a `key = text` pair, lines starting with # are comments
+pub(crate) fn parse_lang(text: &'static str) -> HashMap<&'static str, String> {
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, text)| (key.trim(), text.trim().replace("\\n", "\n")))
+        .collect()
+}
+
+/// switches the language of the texts
+pub(crate) fn set_lang(lang: Lang) {
+    LANG.with(|current| current.set(lang));
+}
+
+/// returns the text for a key in the current language
+/// falls back to English if the text isn't translated
+pub(crate) fn tr(key: &str) -> &'static str {
+    let lang = LANG.with(Cell::get);
+    lang.table()
+        .get(key)
+        .or_else(|| Lang::English.table().get(key))
+        .map_or("???", |text| text.as_str())
+}
+
+/// returns the text for a key, or the text itself if it isn't a key
+/// lets modded data files use plain text instead of translated texts
+pub(crate) fn tr_or_text(text: &'static str) -> &'static str {
+    match tr(text) {
+        "???" => text,
+        translated => translated,
+    }
+}
+
+/// returns the text for a key with its `{ $name }` parts filled in
+/// the language decides where the values go
+pub(crate) fn tr_args(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = tr(key).to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{ ${} }}", name), &value.to_string());
+    }
+    text
+}
+
+/// Tests for the translations
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        input::Action,
+        particle::SandParticle,
+        profile::Profile,
+        stats::Achievement,
+        theme::{Theme, TimeOfDay},
+        upgrade::{Upgrade, UpgradeCategory, upgrade_table},
+    };
+    use strum::IntoEnumIterator;
+
+    // Lang tests
+    #[test]
+    fn test_lang_tables() {
+        // every language has the same texts
+        let mut english: Vec<_> = Lang::English.table().keys().collect();
+        english.sort();
+        for lang in Lang::iter() {
+            let mut keys: Vec<_> = lang.table().keys().collect();
+            keys.sort();
+            assert_eq!(keys, english, "{:?}", lang);
+        }
+        // and every enum name has a text
+        let names: Vec<&str> = upgrade_table()
+            .iter()
+            .flat_map(|upgrade| [upgrade.name(), upgrade.btn_txt(), upgrade.desc()])
+            .chain(Achievement::iter().flat_map(|a| [a.name(), a.desc()]))
+            .chain(UpgradeCategory::iter().map(|category| category.name()))
+            .chain(SandParticle::iter().map(|sand| sand.name()))
+            .chain(Theme::iter().map(|theme| theme.name()))
+            .chain(TimeOfDay::iter().map(|time| time.name()))
+            .chain(Action::iter().map(|action| action.name()))
+            .chain(Profile::iter().flat_map(|profile| [profile.name(), profile.desc()]))
+            .collect();
+        assert!(!names.contains(&"???"));
+    }
+    #[test]
+    fn test_lang_tr() {
+        assert_eq!(tr("column-sand"), "Sand");
+        assert_eq!(tr("no-such-text"), "???");
+        assert_eq!(
+            tr_args("player-info", &[("time", &"0:00:01"), ("clicks", &5)]),
+            "Total Time: 0:00:01\nTotal Clicks: 5"
+        );
+        // the language is only switched for this test's thread
+        set_lang(Lang::German);
+        assert_eq!(Upgrade::AUTO_CLICKER.name(), "Autoklicker");
+        assert_eq!(
+            tr_args("quit-unsold", &[("amount", &3), ("money", &"12")]),
+            "Du hast 3 unverkaufte Körner im Wert von 12$"
+        );
+        set_lang(Lang::English);
+    }
+}
@@ -0,0 +1,239 @@
+//  Sand-Drop-Clicker
+//
+//  Description:
+//      A simple clicker game where you drop sand particles.
+//
+//  By:         Artem Suprun
+//  Date:       12/09/2025
+//  License:    Apache License 2.0
+//  Github:     https://github.com/Artemsuprun/Sand-Drop-Clicker
+
+//! # Sand Drop Clicker
+//! A simple clicker game where you drop sand particles by clicking
+//! on the screen. You can earn money by converting sand particles
+//! and use that money to buy upgrades that enhance your sand dropping
+//! capabilities. The game features different types of sand particles,
+//! each with its own value, and various upgrades to improve your
+//! sand dropping efficiency.
+
+//! ## Controls:
+//! - Click anywhere on the screen to drop sand particles.
+//! - Scroll to change how many grains a click drops.
+//! - Click fast to build a combo, which makes the dropped sand worth more.
+//! - Press or hold `Space` to drop sand at random spots along the top.
+//! - Press `C` to convert the collected sand into money.
+//! - Press `1` to `9` to buy the upgrade with that number.
+//! - Shift-click an upgrade to buy 10 levels, or Ctrl-click to buy as many as you can afford.
+//! - Hold `Shift` and move the mouse to push sand away.
+//! - Hold `Ctrl` and move the mouse to pull sand in.
+//! - Right-click or hold the right mouse button to vacuum up and sell settled sand.
+//! - Press `Ctrl + I` to toggle the display of player information.
+//! - Press `Ctrl + O` to open the settings.
+//! - Press `Ctrl + D` to toggle the debug overlay.
+//! - Press `Ctrl + S` to toggle the statistics window.
+//!   Its Export Stats button saves a CSV with a row for every minute played.
+//! - Press `Ctrl + A` to toggle the achievements window.
+//! - Press `Escape` to pause the game.
+//! - Press `F11` to toggle fullscreen.
+//! - Press `F12` to save a screenshot.
+//! - Press `M` to mute or unmute all sounds and music.
+//! - Hold `Ctrl` and scroll to zoom, drag with the middle mouse button to pan,
+//!   and press `Home` to reset the camera.
+//! - Press `Ctrl + Q` to quit the game.
+//! - The key presses above can be rebound in the settings, and the mouse
+//!   buttons can be remapped or swapped there.
+//! - On a touch screen, tapping drops sand and dragging a finger pours it,
+//!   with up to two fingers at once.
+//! - With a gamepad, the left stick moves the drop cursor, `A` drops sand,
+//!   `X` converts, `Y` shows/hides the Options window, the d-pad picks an
+//!   upgrade, `B` buys it, and `Start` pauses.
+//! - Start the game with `--seed <number>` to make the random sand
+//!   repeatable between runs.
+//! - Start the game with `--record <file>` to record the session into a
+//!   replay file when quitting, and with `--replay <file>` to watch it again.
+//!   The replay banner pauses the replay or plays it at 1x or 4x speed, and
+//!   tells whether it ended the same way it was recorded.
+//! - Start the game with `--dev` to enable the developer console, which
+//!   drops down with the `` ` `` key and takes commands like `give 100000`,
+//!   `set upgrade autoclicker 10`, `spawn diamond 50`, `clear`, `seed 1234`,
+//!   and `help`.
+//! - Start the game with `--speedrun`, or turn it on in the settings, to
+//!   time the run in real time with splits at the first Quartz, the first
+//!   AutoClicker, 1M$ earned, and all upgrades maxed. The personal bests are
+//!   kept in the save file, and each split shows how far ahead or behind it is.
+//! - Open Challenges from the pause menu to play a run with modified rules:
+//!   no AutoClicker, a tiny container, or triple costs. The main game waits
+//!   while a challenge runs, and completing one makes all sand sell for more.
+//! - Start a Blitz from the pause menu to earn as much as possible in 5
+//!   minutes with cheaper upgrades. The main game waits untouched, and the
+//!   best 10 scores are listed in the statistics window.
+//! - Start the Daily Run from the pause menu to play 10 minutes under the
+//!   rules of the day, the same for everyone playing that day. The best
+//!   score of each day shows in the calendar of the statistics window.
+//! - Pick Hardcore when starting over in the settings for a game where
+//!   settled sand fades and is lost after 60 seconds, unless it is converted.
+//!   The save file marks the game as hardcore, so the two never mix.
+//! - Pick Sandbox when starting over in the settings to paint with sand:
+//!   the money never runs out, every upgrade is listed, and the Sandbox
+//!   window picks the sand particle of the drops and sets the gravity and
+//!   the grain size. A sandbox game earns no achievements or scores, and
+//!   can't play challenges, blitzes, or daily runs.
+//! - Once every upgrade is maxed, the New Game+ button of the Options
+//!   window starts the upgrades and the money over, keeping the achievements
+//!   and the statistics. Each New Game+ makes the sand sell for 4x as much
+//!   and the upgrades cost 5x as much, and the player info shows the count.
+//! - Start the game with `--verbose`, or set `RUST_LOG=debug`, to log
+//!   purchases, conversions, the autoclicker, and saving to the terminal.
+
+//! ## Needed Crates:
+//! - ggez: Game framework for Rust.
+//! - ggegui: GUI library for ggez.
+//! - rand: Random number generation.
+//! - log: Logging facade for the debug logs.
+//! - env_logger: Prints the logs, filtered by `RUST_LOG`.
+//! - strum: Enum iteration utilities.
+//! - strum_macros: Macros for strum.
+
+//! ## Modules:
+//! - audio: Sound effects and music.
+//! - blitz: Timed blitz runs and their high-score table.
+//! - camera: Camera the sand is viewed through, with zoom and pan.
+//! - challenge: Challenge runs with modified rules.
+//! - console: Developer console, only enabled with `--dev`.
+//! - container: Sand in the container: the grains and the counts of each sand particle.
+//! - currency: Money that never overflows.
+//! - daily: Daily runs seeded from the date.
+//! - error: Errors of the save and settings files.
+//! - estimate: Grain values and upgrade payback times, to help pick what to buy.
+//! - game: The game state and its event handling.
+//! - grain: Grains of sand falling into the container.
+//! - input: Keyboard and mouse bindings.
+//! - lang: Translations of the game texts.
+//! - particle: Types of sand particles and their values.
+//! - profile: Profile types a game is played as, and the tools of sandbox mode.
+//! - replay: Recording and playback of play sessions.
+//! - settings: Player settings saved between runs.
+//! - sim: The headless simulation: money, upgrades, and grain physics.
+//! - speedrun: Speedrun timer with splits at the goals of the game.
+//! - stats: Statistics, achievements, income, and history of the player.
+//! - table: Data tables of the game, written in a small subset of TOML.
+//! - theme: Background themes and the day/night cycle.
+//! - ui: GUI windows and on-screen displays.
+//! - upgrade: Upgrades the player can buy.
+
+// Modules of the game
+mod audio;
+mod blitz;
+mod camera;
+mod challenge;
+mod console;
+mod container;
+pub mod currency;
+mod daily;
+mod error;
+mod estimate;
+pub mod game;
+pub mod grain;
+mod input;
+mod lang;
+pub mod particle;
+mod profile;
+mod replay;
+mod settings;
+pub mod sim;
+mod speedrun;
+pub mod stats;
+mod table;
+mod theme;
+pub mod ui;
+pub mod upgrade;
+
+// Global Variable
+pub const FPS: u32 = 30; // Frames per second
+pub(crate) const FPS_CAPS: [u32; 5] = [0, 30, 60, 120, 144]; // Frame rate caps to pick from, 0 for none
+pub(crate) const MAX_CATCH_UP: u32 = 5; // Most physics steps run in one update, the rest of a stall is dropped
+pub(crate) const BACKGROUND_FPS: u32 = 5; // Frames drawn per second while the window is unfocused
+pub(crate) const GRAIN_CAPS: [u32; 5] = [0, 2_000, 5_000, 20_000, 50_000]; // Active grain caps to pick from, 0 for none
+/// size of the window when the game starts
+pub const SCREEN_SIZE: (f32, f32) = (800.0, 600.0); // Screen dimensions
+pub const GRAIN_SIZE: f32 = 10.0; // Size of each grain of sand
+pub const GRAVITY: f32 = 300.0; // Gravity affecting the grains
+pub(crate) const SLEEP_VELOCITY: f32 = 20.0; // Speed below which a landed grain stops bouncing
+pub(crate) const MAX_FALL_SPEED: f32 = 600.0; // Terminal velocity of the grains
+pub(crate) const PARALLEL_GRAINS: usize = 5_000; // Grains from which the physics is split over the threads
+pub(crate) const LOW_GRAVITY_CHANCE: f32 = 1.0 / 600.0; // Chance per second of a low gravity event
+pub(crate) const LOW_GRAVITY_DURATION: f32 = 15.0; // Length of a low gravity event in seconds
+pub(crate) const LOW_GRAVITY_LIFT: f32 = 1.5; // Seconds of the event where gravity is inverted
+pub(crate) const SCULPT_RADIUS: f32 = 40.0; // Base radius of the sculpting tool
+pub(crate) const SCULPT_STRENGTH: f32 = 1500.0; // Base strength of the sculpting tool
+pub(crate) const VACUUM_RADIUS: f32 = 30.0; // Radius of the right-click vacuum
+pub(crate) const AUTOCLICK_PERIOD: f32 = 5.0; // Seconds between autoclicks, divided by the autoclicker level
+pub(crate) const GAMEPAD_SPEED: f32 = 400.0; // Pixels per second the gamepad cursor moves at full tilt
+pub(crate) const GAMEPAD_DEADZONE: f32 = 0.2; // Stick tilt that is ignored
+pub(crate) const MIN_INSTANCES: usize = 256; // Smallest capacity the grain instance arrays grow to
+pub(crate) const MAX_TOUCHES: usize = 2; // Fingers that can pour at once
+pub(crate) const POUR_INTERVAL: f32 = 0.1; // Seconds between drops while dragging a finger
+pub(crate) const BULK_BUY: u32 = 10; // Levels bought by Shift-clicking an upgrade
+pub(crate) const COMBO_WINDOW: f32 = 0.3; // Seconds between clicks that build the combo
+pub(crate) const COMBO_DECAY: f32 = 1.0; // Seconds without a click before the combo ends
+pub(crate) const COMBO_STEP: f64 = 0.02; // Extra value of the dropped sand per combo step
+pub(crate) const COMBO_MAX: f64 = 0.5; // Most extra value the combo can give
+pub(crate) const LAND_SOUND_INTERVAL: f32 = 0.1; // Seconds between the sounds of landing grains
+pub(crate) const SEMITONE: f32 = 1.059_463_1; // Pitch ratio of one semitone, the landing sound rises one per tier
+pub(crate) const MUSIC_FADE: f32 = 3.0; // Seconds the music takes to crossfade
+pub(crate) const INTENSE_FILL: f32 = 0.8; // Container fill above which the music gets intense
+pub(crate) const DUCK_LEVEL: f32 = 0.3; // Music volume left while a big sale rings
+pub(crate) const DUCK_RECOVERY: f32 = 1.0; // Seconds the music takes to come back after a big sale
+pub(crate) const POUR_FADE: f32 = 0.2; // Seconds the pouring sound takes to fade out
+pub(crate) const SPRITE_SIZE: f32 = 16.0; // Size of each sprite in the particle atlas
+pub(crate) const TRAIL_LENGTH: usize = 3; // Number of past positions kept for the motion trail
+pub(crate) const COLLECT_TIME: f32 = 0.5; // Seconds for a sold grain to fly to the money counter
+pub(crate) const MONEY_POS: (f32, f32) = (20.0, 30.0); // Position of the money counter on screen
+pub(crate) const SPARKLE_TIME: f32 = 1.0; // Seconds a rare grain sparkles after being dropped
+pub(crate) const EFFECT_LIFE: f32 = 0.4; // Seconds a sparkle lasts
+pub(crate) const MAX_EFFECTS: usize = 200; // Maximum number of sparkles on screen
+pub(crate) const FLOAT_TEXT_LIFE: f32 = 1.0; // Seconds a floating money text lasts
+pub(crate) const AUTOSAVE_TIME: f32 = 30.0; // Seconds between automatic saves
+pub(crate) const DAY_LENGTH: f32 = 600.0; // Seconds in a full day/night cycle
+pub(crate) const TEXT_SIZE: f32 = 16.0; // Size of the info text at a UI scale of 1
+pub(crate) const SHAKE_TIME: f32 = 0.3; // Seconds the screen shakes after a big sale
+pub(crate) const SHAKE_STRENGTH: f32 = 8.0; // Pixels the screen shakes at the start
+pub(crate) const FLASH_TIME: f32 = 0.2; // Seconds the screen flashes after a big sale
+pub(crate) const FULL_FLASH_TIME: f32 = 0.4; // Seconds the capacity counter flashes red after a click into a full container
+pub(crate) const BIG_SALE: f64 = 0.5; // Fraction of the current money a sale must earn to be big
+pub(crate) const SCIENTIFIC_MONEY: u128 = 10_000_000_000_000_000_000; // Money shown in scientific notation from here on
+pub(crate) const MIN_ZOOM: f32 = 0.25; // Smallest camera zoom
+pub(crate) const MAX_ZOOM: f32 = 4.0; // Largest camera zoom
+pub(crate) const ZOOM_STEP: f32 = 1.1; // Zoom change per scroll wheel step
+pub(crate) const SAVE_FILE: &str = "save.txt"; // Name of the save file in the user data directory
+pub(crate) const SETTINGS_FILE: &str = "settings.txt"; // Name of the settings file in the user config directory
+pub(crate) const PARTICLES_FILE: &str = "particles.toml"; // Name of the modded particle table in the user data directory
+pub(crate) const UPGRADES_FILE: &str = "upgrades.toml"; // Name of the modded upgrade table in the user data directory
+pub(crate) const SCREENSHOT_DIR: &str = "screenshots"; // Name of the screenshot folder in the user data directory
+pub(crate) const EXPORT_DIR: &str = "exports"; // Name of the stats export folder in the user data directory
+pub(crate) const NOTIFY_TIME: f32 = 3.0; // Seconds a notification is shown
+pub(crate) const MAX_NOTIFICATIONS: usize = 5; // Notifications shown at once
+pub(crate) const HISTORY_SIZE: usize = 200; // Purchases and sales kept in the history
+pub(crate) const LAST_SALES: usize = 20; // Conversions listed in the statistics window
+pub(crate) const INCOME_WINDOW: usize = 60; // Seconds the money per second is averaged over
+pub(crate) const CONSOLE_LINES: usize = 100; // Lines kept in the developer console
+pub(crate) const HEATMAP_GRID: (usize, usize) = (32, 24); // Columns and rows of the click heatmap
+pub(crate) const TIMELINE_SIZE: usize = 24 * 60; // Minutes of samples kept in the timeline
+pub(crate) const GRAPH_INTERVAL: f32 = 5.0; // Seconds between the points of the income graph
+pub(crate) const GRAPH_POINTS: usize = 120; // Points of the income graph, 10 minutes of play
+pub(crate) const TINY_CONTAINER: u32 = 25; // Container size in the Tiny Container challenge
+pub(crate) const CHALLENGE_COSTS: f64 = 3.0; // Cost multiplier in the Expensive Taste challenge
+pub(crate) const CHALLENGE_REWARD: f64 = 0.1; // Extra sale value of each completed challenge
+pub(crate) const BLITZ_TIME: f32 = 300.0; // Seconds of a blitz
+pub(crate) const BLITZ_COSTS: f64 = 0.25; // Cost multiplier in a blitz
+pub(crate) const BLITZ_SCORES: usize = 10; // Scores kept in the blitz high-score table
+pub(crate) const DAILY_TIME: f32 = 600.0; // Seconds of a daily run
+pub(crate) const DAILY_BOOSTS: [f64; 3] = [2.0, 3.0, 5.0]; // Values the boosted sand particle of a daily run can be worth
+pub(crate) const DAILY_SCALES: [f64; 4] = [0.5, 0.75, 1.25, 1.5]; // Sizes the container of a daily run can be scaled to
+pub(crate) const DAILY_WEEKS: u64 = 5; // Weeks of daily runs shown in the calendar
+pub(crate) const DECAY_TIME: f32 = 60.0; // Seconds settled sand rests on the ground before it decays in hardcore mode
+pub(crate) const SANDBOX_GRAVITY: (f32, f32) = (0.1, 3.0); // Range of the gravity factor in sandbox mode
+pub(crate) const SANDBOX_GRAIN_SIZE: (f32, f32) = (2.0, 30.0); // Range of the grain size in sandbox mode
+pub(crate) const NG_PLUS_VALUE: f64 = 4.0; // Factor on the money the sand sells for with each New Game+
+pub(crate) const NG_PLUS_COST: f64 = 5.0; // Factor on the upgrade costs with each New Game+
+pub(crate) const TUTORIAL_GRAINS: u32 = 5; // Grains in the container before the tutorial shows Convert
@@ -0,0 +1,367 @@
+//! Types of sand particles and their values
+
+use crate::{currency::Currency, lang::tr, settings::parse_enum, table::parse_sections};
+use ggez::graphics::{Color, Rect};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Different types of sand particles available in the game
+#[derive(Hash, Eq, PartialEq, Debug, EnumIter, Clone, Copy)]
+pub enum SandParticle {
+    Sand,
+    Quartz,
+    Shell,
+    Coral,
+    Pinksand,
+    Volcanic,
+    Glauconite,
+    Gemstones,
+    Iron,
+    Starsand,
+    Gold,
+    Diamond,
+}
+
+/// Implementation of methods for the SandParticle enum
+/// * name: returns the display name of the sand particle
+/// * level: returns the level number of the sand particle
+/// * max_tier: returns the higher tier of two sand particles
+/// * sprite_rect: returns the area of the particle atlas for the sand particle
+/// * from_u32: returns the sand particle from its level number
+/// * from_roll: returns the sand particle a random roll picks at a ParticleTier level
+/// * max_level: returns the maximum level of sand particles
+impl SandParticle {
+    /// returns the display name of the sand particle
+    pub(crate) fn name(&self) -> &'static str {
+        tr(match self {
+            SandParticle::Sand => "sand-sand",
+            SandParticle::Quartz => "sand-quartz",
+            SandParticle::Shell => "sand-shell",
+            SandParticle::Coral => "sand-coral",
+            SandParticle::Pinksand => "sand-pinksand",
+            SandParticle::Volcanic => "sand-volcanic",
+            SandParticle::Glauconite => "sand-glauconite",
+            SandParticle::Gemstones => "sand-gemstones",
+            SandParticle::Iron => "sand-iron",
+            SandParticle::Starsand => "sand-starsand",
+            SandParticle::Gold => "sand-gold",
+            SandParticle::Diamond => "sand-diamond",
+        })
+    }
+
+    /// returns the level number of the sand particle
+    pub(crate) fn level(&self) -> u32 {
+        *self as u32
+    }
+
+    /// returns the higher tier of two sand particles
+    pub(crate) fn max_tier(self, other: SandParticle) -> SandParticle {
+        if other.level() > self.level() {
+            other
+        } else {
+            self
+        }
+    }
+
+    /// returns the area of the particle atlas for the sand particle
+    /// the area is in texture coordinates (0.0 to 1.0)
+    pub(crate) fn sprite_rect(&self) -> Rect {
+        let width = 1.0 / SandParticle::max_level() as f32;
+        Rect::new(self.level() as f32 * width, 0.0, width, 1.0)
+    }
+
+    /// returns the sand particle from its level number
+    pub(crate) fn from_u32(num: u32) -> Option<Self> {
+        match num {
+            0 => Some(SandParticle::Sand),
+            1 => Some(SandParticle::Quartz),
+            2 => Some(SandParticle::Shell),
+            3 => Some(SandParticle::Coral),
+            4 => Some(SandParticle::Pinksand),
+            5 => Some(SandParticle::Volcanic),
+            6 => Some(SandParticle::Glauconite),
+            7 => Some(SandParticle::Gemstones),
+            8 => Some(SandParticle::Iron),
+            9 => Some(SandParticle::Starsand),
+            10 => Some(SandParticle::Gold),
+            11 => Some(SandParticle::Diamond),
+            _ => None,
+        }
+    }
+
+    /// returns the sand particle a random roll picks at a ParticleTier level
+    /// level 0 drops like level 1 and levels past the last particle are clamped
+    pub(crate) fn from_roll(roll: u32, level: u32) -> SandParticle {
+        let level = level.clamp(1, SandParticle::max_level());
+        SandParticle::from_u32(roll % level).unwrap_or(SandParticle::Sand)
+    }
+
+    /// returns the maximum level of sand particles
+    pub(crate) fn max_level() -> u32 {
+        SandParticle::iter().count() as u32
+    }
+}
+
+// Built-in sand particle table, replaced by a particles.toml in the save folder
+pub(crate) const PARTICLE_TABLE: &str = include_str!("../assets/particles.toml");
+
+// Keys a sand particle can have in the particle table
+pub(crate) const PARTICLE_FIELDS: [&str; 5] =
+    ["value", "color", "colorblind", "cost", "restitution"];
+
+/// Data of a sand particle, read from the particle table
+/// * value: money a grain sells for
+/// * color: color of the grains
+/// * colorblind: colorblind-safe color of the grains
+/// * cost: cost of unlocking the particle with the ParticleTier upgrade
+/// * restitution: fraction of its speed a grain keeps when it lands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ParticleDef {
+    pub(crate) value: Currency,
+    pub(crate) color: Color,
+    pub(crate) colorblind: Color,
+    pub(crate) cost: Currency,
+    pub(crate) restitution: f32,
+}
+
+/// Data of all the sand particles
+/// * defs: data of each sand particle, indexed by its level
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ParticleTable {
+    pub(crate) defs: Vec<ParticleDef>,
+}
+
+/// Built-in particle table of the game
+impl Default for ParticleTable {
+    fn default() -> Self {
+        // the built-in table is checked by the tests
+        ParticleTable::parse(PARTICLE_TABLE).expect("the built-in particle table is broken")
+    }
+}
+
+/// Implementation of methods for the ParticleTable struct
+/// * parse: reads a particle table, or the reason it is broken
+/// * get: returns the data of a sand particle
+/// * value: returns the money a grain of the sand particle sells for
+/// * palette_color: returns the color of the sand particle for a palette
+/// * restitution: returns how bouncy the sand particle is
+/// * cost: returns the cost of unlocking the sand particle of a level
+/// * len: returns the amount of sand particles in the table
+impl ParticleTable {
+    /// reads a particle table, or the reason it is broken
+    /// each sand particle has a `[Particle]` section
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let sections = parse_sections(text, &PARTICLE_FIELDS)?;
+        if let Some(section) = sections
+            .iter()
+            .find(|section| parse_enum::<SandParticle>(section.name).is_none())
+        {
+            return Err(format!(
+                "line {}: unknown sand particle [{}]",
+                section.line, section.name
+            ));
+        }
+        let mut defs = Vec::new();
+        for particle in SandParticle::iter() {
+            let name = format!("{:?}", particle);
+            let Some(section) = sections.iter().find(|section| section.name == name) else {
+                return Err(format!("[{}] is missing", name));
+            };
+            let required = |key: &str| format!("[{}] is missing `{}`", name, key);
+            let number = |text: &str| text.parse().ok();
+            defs.push(ParticleDef {
+                value: section
+                    .field("value", number)?
+                    .ok_or_else(|| required("value"))?,
+                color: section
+                    .field("color", parse_color)?
+                    .ok_or_else(|| required("color"))?,
+                colorblind: section
+                    .field("colorblind", parse_color)?
+                    .ok_or_else(|| required("colorblind"))?,
+                cost: section
+                    .field("cost", number)?
+                    .ok_or_else(|| required("cost"))?,
+                restitution: section
+                    .field("restitution", |text| text.parse().ok())?
+                    .unwrap_or(0.1),
+            });
+        }
+        Ok(Self { defs })
+    }
+
+    /// returns the data of a sand particle
+    pub(crate) fn get(&self, particle: SandParticle) -> &ParticleDef {
+        &self.defs[particle.level() as usize]
+    }
+
+    /// returns the money a grain of the sand particle sells for
+    pub(crate) fn value(&self, particle: SandParticle) -> Currency {
+        self.get(particle).value
+    }
+
+    /// returns the color of the sand particle for a palette
+    pub(crate) fn palette_color(&self, particle: SandParticle, colorblind: bool) -> Color {
+        let def = self.get(particle);
+        if colorblind {
+            def.colorblind
+        } else {
+            def.color
+        }
+    }
+
+    /// returns how much of its speed the sand particle keeps after landing
+    pub(crate) fn restitution(&self, particle: SandParticle) -> f32 {
+        self.get(particle).restitution
+    }
+
+    /// returns the cost of unlocking the sand particle of a level
+    /// None past the last level, where nothing is left to unlock
+    pub(crate) fn cost(&self, level: u32) -> Option<Currency> {
+        self.defs.get(level as usize).map(|def| def.cost)
+    }
+
+    /// returns the amount of sand particles in the table, the most ParticleTier levels there are
+    pub(crate) fn len(&self) -> u32 {
+        self.defs.len() as u32
+    }
+}
+
+/// reads a color written as `[red, green, blue]`
+pub(crate) fn parse_color(text: &str) -> Option<Color> {
+    let inner = text.strip_prefix('[')?.strip_suffix(']')?;
+    let parts: Vec<u8> = inner
+        .split(',')
+        .map(|part| part.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    match parts[..] {
+        [r, g, b] => Some(Color::from_rgb(r, g, b)),
+        _ => None,
+    }
+}
+
+/// Tests for the sand particles
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // SandParticle tests
+    #[test]
+    fn test_sand_particle_from_u32() {
+        assert_eq!(SandParticle::from_u32(0), Some(SandParticle::Sand));
+        assert_eq!(SandParticle::from_u32(5), Some(SandParticle::Volcanic));
+        assert_eq!(SandParticle::from_u32(12), None);
+    }
+    #[test]
+    fn test_sand_particle_from_roll() {
+        // level 0 only drops sand instead of dividing by zero
+        assert_eq!(SandParticle::from_roll(0, 0), SandParticle::Sand);
+        assert_eq!(SandParticle::from_roll(u32::MAX, 0), SandParticle::Sand);
+        assert_eq!(SandParticle::from_roll(7, 1), SandParticle::Sand);
+        // every particle can drop at the max level
+        let max = SandParticle::max_level();
+        assert_eq!(SandParticle::from_roll(0, max), SandParticle::Sand);
+        assert_eq!(SandParticle::from_roll(max - 1, max), SandParticle::Diamond);
+        // levels past the max are clamped
+        assert_eq!(SandParticle::from_roll(max, max + 5), SandParticle::Sand);
+        assert_eq!(
+            SandParticle::from_roll(max + 11, u32::MAX),
+            SandParticle::Diamond
+        );
+    }
+    #[test]
+    fn test_sand_particle_sprite_rect() {
+        let first = SandParticle::Sand.sprite_rect();
+        let last = SandParticle::Diamond.sprite_rect();
+        assert_eq!(first.x, 0.0);
+        assert_eq!(first.w, 1.0 / 12.0);
+        assert!((last.right() - 1.0).abs() < 1e-6);
+    }
+    #[test]
+    fn test_sand_particle_max_level() {
+        assert_eq!(SandParticle::max_level(), 12);
+    }
+
+    // ParticleTable tests
+    #[test]
+    fn test_particle_table_default() {
+        let table = ParticleTable::default();
+        assert_eq!(table.defs.len(), SandParticle::max_level() as usize);
+        assert_eq!(table.value(SandParticle::Gold), Currency(1024));
+        assert_eq!(
+            table.palette_color(SandParticle::Coral, false),
+            Color::from_rgb(248, 131, 121)
+        );
+        assert_eq!(table.restitution(SandParticle::Diamond), 0.6);
+        assert_eq!(table.cost(0), Some(Currency::ZERO));
+        assert_eq!(table.cost(1), Some(Currency(100)));
+        assert_eq!(table.cost(11), Some(Currency(10_000_000)));
+        // nothing is left to unlock past the last particle, it isn't free
+        assert_eq!(table.cost(12), None);
+        assert_eq!(table.len(), 12);
+    }
+    #[test]
+    fn test_particle_table_colorblind() {
+        // every particle has its own colorblind-safe color
+        let table = ParticleTable::default();
+        let colors: Vec<Color> = SandParticle::iter()
+            .map(|particle| table.palette_color(particle, true))
+            .collect();
+        for (i, a) in colors.iter().enumerate() {
+            for b in &colors[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+    #[test]
+    fn test_particle_table_override() {
+        // a modded table changes the values, the left out restitution is the default
+        let text = PARTICLE_TABLE
+            .replace("[Sand]\nvalue = 1\n", "[Sand]\nvalue = 7\n")
+            .replace("restitution = 0.05\n", "");
+        let table = ParticleTable::parse(&text).unwrap();
+        assert_eq!(table.value(SandParticle::Sand), Currency(7));
+        assert_eq!(table.restitution(SandParticle::Sand), 0.1);
+    }
+    #[test]
+    fn test_particle_table_errors() {
+        let broken =
+            |from: &str, to: &str| ParticleTable::parse(&PARTICLE_TABLE.replacen(from, to, 1));
+        assert_eq!(
+            broken("[Sand]", "[Mud]"),
+            Err("line 11: unknown sand particle [Mud]".to_string())
+        );
+        assert_eq!(
+            broken("[Quartz]", "[Sand]"),
+            Err("line 18: [Sand] is defined twice".to_string())
+        );
+        assert_eq!(
+            broken("value = 1\n", "valu = 1\n"),
+            Err("line 12: unknown key `valu` in [Sand]".to_string())
+        );
+        assert_eq!(
+            broken("value = 1\n", "value = lots\n"),
+            Err("line 12: `value` of [Sand] isn't valid: lots".to_string())
+        );
+        assert_eq!(
+            broken("color = [243, 213, 103]", "color = [243, 213]"),
+            Err("line 13: `color` of [Sand] isn't valid: [243, 213]".to_string())
+        );
+        assert_eq!(
+            broken("value = 1\n", ""),
+            Err("[Sand] is missing `value`".to_string())
+        );
+        assert_eq!(
+            ParticleTable::parse("value = 1"),
+            Err("line 1: `value` is outside of a section".to_string())
+        );
+        assert_eq!(
+            ParticleTable::parse(""),
+            Err("[Sand] is missing".to_string())
+        );
+        assert_eq!(
+            ParticleTable::parse("[Sand]\nnonsense"),
+            Err("line 2: expected `key = value`".to_string())
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Profile types a game is played as, and the tools of sandbox mode
+
+use crate::{
+    DECAY_TIME, GRAIN_SIZE, SANDBOX_GRAIN_SIZE, SANDBOX_GRAVITY, challenge::Modifiers, lang::tr,
+    particle::SandParticle, settings::parse_enum,
+};
+use strum_macros::EnumIter;
+
+/// Type of profile a game is played as, picked when starting a new game
+/// * Normal: The regular game.
+/// * Hardcore: Settled sand decays unless it is converted in time.
+/// * Sandbox: Free play with infinite money, without achievements or scores.
+#[derive(Hash, Eq, PartialEq, Debug, EnumIter, Clone, Copy, Default)]
+pub(crate) enum Profile {
+    #[default]
+    Normal,
+    Hardcore,
+    Sandbox,
+}
+
+/// Implementation of methods for the Profile enum
+/// * name: returns the display name of the profile type
+/// * desc: returns the rules of the profile type
+/// * badge: returns the badge marking a game of the profile type, None for a normal game
+/// * scored: checks if the game earns achievements and scores
+/// * modifiers: adds the rules of the profile type to the rules of a run
+impl Profile {
+    /// returns the display name of the profile type
+    pub(crate) fn name(&self) -> &'static str {
+        tr(match self {
+            Profile::Normal => "profile-normal",
+            Profile::Hardcore => "profile-hardcore",
+            Profile::Sandbox => "profile-sandbox",
+        })
+    }
+
+    /// returns the rules of the profile type
+    pub(crate) fn desc(&self) -> &'static str {
+        tr(match self {
+            Profile::Normal => "profile-normal-desc",
+            Profile::Hardcore => "profile-hardcore-desc",
+            Profile::Sandbox => "profile-sandbox-desc",
+        })
+    }
+
+    /// returns the badge marking a game of the profile type, None for a normal game
+    pub(crate) fn badge(&self) -> Option<&'static str> {
+        match self {
+            Profile::Normal => None,
+            Profile::Hardcore => Some(tr("hardcore-badge")),
+            Profile::Sandbox => Some(tr("sandbox-badge")),
+        }
+    }
+
+    /// checks if the game earns achievements and scores
+    /// a sandbox game has everything for free, so it never does
+    pub(crate) fn scored(&self) -> bool {
+        *self != Profile::Sandbox
+    }
+
+    /// adds the rules of the profile type to the rules of a run
+    /// the sand of a hardcore game decays, and a sandbox game plays with its tools
+    pub(crate) fn modifiers(&self, modifiers: Modifiers, tools: &SandboxTools) -> Modifiers {
+        match self {
+            Profile::Normal => modifiers,
+            Profile::Hardcore => Modifiers {
+                decay_time: Some(DECAY_TIME),
+                ..modifiers
+            },
+            Profile::Sandbox => Modifiers {
+                sandbox: true,
+                palette: tools.palette,
+                gravity_scale: tools.gravity,
+                grain_size: Some(tools.grain_size),
+                ..modifiers
+            },
+        }
+    }
+}
+
+/// Tools of sandbox mode, for painting with sand
+/// * palette: sand particle the next drops are made of, None for random sand
+/// * gravity: factor on the gravity pulling the grains down
+/// * grain_size: size of the new grains
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SandboxTools {
+    pub(crate) palette: Option<SandParticle>,
+    pub(crate) gravity: f32,
+    pub(crate) grain_size: f32,
+}
+
+/// Default tools, which drop random sand like the regular game
+impl Default for SandboxTools {
+    fn default() -> Self {
+        Self {
+            palette: None,
+            gravity: 1.0,
+            grain_size: GRAIN_SIZE,
+        }
+    }
+}
+
+/// Implementation of methods for the SandboxTools struct
+/// * clamped: returns the tools with the sliders kept in their ranges
+/// * to_save: returns the tools as save file lines
+/// * load_line: reads a save file line, returns false if it isn't a tool
+impl SandboxTools {
+    /// returns the tools with the gravity and the grain size kept in the ranges of their sliders
+    pub(crate) fn clamped(self) -> Self {
+        Self {
+            gravity: self.gravity.clamp(SANDBOX_GRAVITY.0, SANDBOX_GRAVITY.1),
+            grain_size: self
+                .grain_size
+                .clamp(SANDBOX_GRAIN_SIZE.0, SANDBOX_GRAIN_SIZE.1),
+            ..self
+        }
+    }
+
+    /// returns the tools as save file lines
+    pub(crate) fn to_save(self) -> Vec<String> {
+        let mut lines = vec![
+            format!("sandbox.gravity={}", self.gravity),
+            format!("sandbox.grain_size={}", self.grain_size),
+        ];
+        if let Some(particle) = self.palette {
+            lines.push(format!("sandbox.palette={:?}", particle));
+        }
+        lines
+    }
+
+    /// reads a save file line, returns false if it isn't a tool
+    pub(crate) fn load_line(&mut self, key: &str, value: &str) -> bool {
+        match key {
+            "sandbox.palette" => self.palette = parse_enum(value),
+            "sandbox.gravity" => self.gravity = value.parse().unwrap_or(self.gravity),
+            "sandbox.grain_size" => self.grain_size = value.parse().unwrap_or(self.grain_size),
+            _ => return false,
+        }
+        *self = self.clamped();
+        true
+    }
+}
+
+/// Tests for the profile types
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    // Profile tests
+    #[test]
+    fn test_profile_modifiers() {
+        let tools = SandboxTools::default();
+        let rules = Modifiers::default();
+        assert_eq!(Profile::Normal.modifiers(rules.clone(), &tools), rules);
+        let hardcore = Profile::Hardcore.modifiers(rules.clone(), &tools);
+        assert_eq!(hardcore.decay_time, Some(DECAY_TIME));
+        assert!(!hardcore.sandbox);
+        let tools = SandboxTools {
+            palette: Some(SandParticle::Iron),
+            gravity: 0.5,
+            grain_size: 4.0,
+        };
+        let sandbox = Profile::Sandbox.modifiers(rules, &tools);
+        assert!(sandbox.sandbox);
+        assert_eq!(sandbox.palette, Some(SandParticle::Iron));
+        assert_eq!(sandbox.gravity_scale, 0.5);
+        assert_eq!(sandbox.grain_size, Some(4.0));
+        assert_eq!(sandbox.decay_time, None);
+        // only a sandbox game goes without scores, and it is the one marked as such
+        assert!(Profile::iter().all(|profile| profile.scored() != (profile == Profile::Sandbox)));
+        assert_eq!(Profile::Normal.badge(), None);
+    }
+
+    // SandboxTools tests
+    #[test]
+    fn test_sandbox_tools_save_load() {
+        let tools = SandboxTools {
+            palette: Some(SandParticle::Diamond),
+            gravity: 2.0,
+            grain_size: 6.0,
+        };
+        let mut loaded = SandboxTools::default();
+        for line in tools.to_save() {
+            let (key, value) = line.split_once('=').unwrap();
+            assert!(loaded.load_line(key, value));
+        }
+        assert_eq!(loaded, tools);
+        // the sliders stay in their ranges
+        assert!(loaded.load_line("sandbox.gravity", "1000"));
+        assert_eq!(loaded.gravity, SANDBOX_GRAVITY.1);
+        assert!(loaded.load_line("sandbox.palette", "Nope"));
+        assert_eq!(loaded.palette, None);
+        assert!(!loaded.load_line("money", "100"));
+    }
+}
@@ -0,0 +1,421 @@
+//! Recording and playback of play sessions
+
+use crate::{
+    game::GameAction,
+    profile::{Profile, SandboxTools},
+    settings::parse_enum,
+    upgrade::Upgrade,
+};
+
+/// Recorded play session, enough to play it again the same way
+/// * seed: seed of the random numbers
+/// * grain_size: size of the dropped grains
+/// * max_grains: grain cap the sand was compacted at, 0 for no limit
+/// * screen_size: size of the play area at the start
+/// * save: game progress at the start, in the save file format
+/// * actions: game actions with the physics step they were applied before
+/// * end: physics step the recording stopped at
+/// * checksum: checksum of the simulation at the end
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Replay {
+    pub(crate) seed: u64,
+    pub(crate) grain_size: f32,
+    pub(crate) max_grains: usize,
+    pub(crate) screen_size: (f32, f32),
+    pub(crate) save: String,
+    pub(crate) actions: Vec<(u64, GameAction)>,
+    pub(crate) end: u64,
+    pub(crate) checksum: u64,
+}
+
+/// Implementation of methods for the Replay struct
+/// * to_text: returns the replay in the replay file format
+/// * parse: reads a replay file, or the reason it is broken
+impl Replay {
+    /// returns the replay in the replay file format
+    /// each line is a `key=value` pair, the save lines are kept as `save=key=value`
+    pub(crate) fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("seed={}", self.seed),
+            format!("grain_size={}", self.grain_size),
+            format!("max_grains={}", self.max_grains),
+            format!("screen={},{}", self.screen_size.0, self.screen_size.1),
+        ];
+        lines.extend(self.save.lines().map(|line| format!("save={}", line)));
+        for (tick, action) in &self.actions {
+            lines.push(format!("action={},{}", tick, action_text(action)));
+        }
+        lines.push(format!("end={}", self.end));
+        lines.push(format!("checksum={:016x}", self.checksum));
+        lines.join("\n")
+    }
+
+    /// reads a replay file, or the reason it is broken
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let (mut seed, mut grain_size, mut screen_size) = (None, None, None);
+        let (mut end, mut checksum) = (None, None);
+        // older replays were recorded without a grain cap
+        let mut max_grains = 0;
+        let mut save = Vec::new();
+        let mut actions = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let number = index + 1;
+            let broken = || format!("line {}: isn't valid: {}", number, line);
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(broken());
+            };
+            match key {
+                "seed" => seed = Some(value.parse().map_err(|_| broken())?),
+                "grain_size" => grain_size = Some(value.parse().map_err(|_| broken())?),
+                "max_grains" => max_grains = value.parse().map_err(|_| broken())?,
+                "screen" => {
+                    let (width, height) = value.split_once(',').ok_or_else(broken)?;
+                    screen_size = width.parse().ok().zip(height.parse().ok());
+                    screen_size.ok_or_else(broken)?;
+                }
+                "save" => save.push(value),
+                "action" => {
+                    let (tick, action) = value.split_once(',').ok_or_else(broken)?;
+                    let tick: u64 = tick.parse().map_err(|_| broken())?;
+                    let action = parse_action(action).ok_or_else(broken)?;
+                    if actions.last().is_some_and(|(last, _)| *last > tick) {
+                        return Err(format!("line {}: the actions are out of order", number));
+                    }
+                    actions.push((tick, action));
+                }
+                "end" => end = Some(value.parse().map_err(|_| broken())?),
+                "checksum" => {
+                    checksum = Some(u64::from_str_radix(value, 16).map_err(|_| broken())?)
+                }
+                _ => return Err(broken()),
+            }
+        }
+        let missing = |key: &str| format!("the replay is missing `{}`", key);
+        Ok(Self {
+            seed: seed.ok_or_else(|| missing("seed"))?,
+            grain_size: grain_size.ok_or_else(|| missing("grain_size"))?,
+            max_grains,
+            screen_size: screen_size.ok_or_else(|| missing("screen"))?,
+            save: save.join("\n"),
+            actions,
+            end: end.ok_or_else(|| missing("end"))?,
+            checksum: checksum.ok_or_else(|| missing("checksum"))?,
+        })
+    }
+}
+
+/// returns a game action in the replay file format
+/// the name of the action comes first, then its values
+pub(crate) fn action_text(action: &GameAction) -> String {
+    match action {
+        GameAction::DropAt { x, y } => format!("DropAt,{},{}", x, y),
+        GameAction::DropRandom => "DropRandom".to_string(),
+        GameAction::Convert => "Convert".to_string(),
+        GameAction::Vacuum { x, y } => format!("Vacuum,{},{}", x, y),
+        GameAction::Sculpt { x, y, pull } => format!("Sculpt,{},{},{}", x, y, pull),
+        GameAction::Throttle { steps } => format!("Throttle,{}", steps),
+        GameAction::Resize { width, height } => format!("Resize,{},{}", width, height),
+        GameAction::Buy { upgrade, count } => format!("Buy,{:?},{}", upgrade, count),
+        GameAction::BuyTheme(theme) => format!("BuyTheme,{:?}", theme),
+        GameAction::Reset(profile) => format!("Reset,{:?}", profile),
+        GameAction::Give { money } => format!("Give,{}", money),
+        GameAction::SetLevel { upgrade, level } => format!("SetLevel,{:?},{}", upgrade, level),
+        GameAction::Spawn { particle, amount } => format!("Spawn,{:?},{}", particle, amount),
+        GameAction::Clear => "Clear".to_string(),
+        GameAction::Seed(seed) => format!("Seed,{}", seed),
+        GameAction::StartChallenge(challenge) => format!("StartChallenge,{:?}", challenge),
+        GameAction::EndChallenge => "EndChallenge".to_string(),
+        GameAction::StartBlitz => "StartBlitz".to_string(),
+        GameAction::StartDaily(day) => format!("StartDaily,{}", day),
+        GameAction::EndBlitz => "EndBlitz".to_string(),
+        GameAction::SetSandbox(tools) => {
+            let palette = tools
+                .palette
+                .map_or("Random".to_string(), |particle| format!("{:?}", particle));
+            format!(
+                "SetSandbox,{},{},{}",
+                palette, tools.gravity, tools.grain_size
+            )
+        }
+        GameAction::NewGamePlus => "NewGamePlus".to_string(),
+    }
+}
+
+/// reads a game action in the replay file format
+/// returns None for unknown or broken actions
+pub(crate) fn parse_action(text: &str) -> Option<GameAction> {
+    let parts: Vec<&str> = text.split(',').collect();
+    Some(match parts[..] {
+        ["DropAt", x, y] => GameAction::DropAt {
+            x: x.parse().ok()?,
+            y: y.parse().ok()?,
+        },
+        ["DropRandom"] => GameAction::DropRandom,
+        ["Convert"] => GameAction::Convert,
+        ["Vacuum", x, y] => GameAction::Vacuum {
+            x: x.parse().ok()?,
+            y: y.parse().ok()?,
+        },
+        ["Sculpt", x, y, pull] => GameAction::Sculpt {
+            x: x.parse().ok()?,
+            y: y.parse().ok()?,
+            pull: pull.parse().ok()?,
+        },
+        ["Throttle", steps] => GameAction::Throttle {
+            steps: steps.parse().ok()?,
+        },
+        ["Resize", width, height] => GameAction::Resize {
+            width: width.parse().ok()?,
+            height: height.parse().ok()?,
+        },
+        ["Buy", upgrade, count] => GameAction::Buy {
+            upgrade: Upgrade::from_id(upgrade)?,
+            count: count.parse().ok()?,
+        },
+        ["BuyTheme", theme] => GameAction::BuyTheme(parse_enum(theme)?),
+        // replays from before the profile types reset to a normal game
+        ["Reset"] => GameAction::Reset(Profile::Normal),
+        ["Reset", profile] => GameAction::Reset(parse_enum(profile)?),
+        ["Give", money] => GameAction::Give {
+            money: money.parse().ok()?,
+        },
+        ["SetLevel", upgrade, level] => GameAction::SetLevel {
+            upgrade: Upgrade::from_id(upgrade)?,
+            level: level.parse().ok()?,
+        },
+        ["Spawn", particle, amount] => GameAction::Spawn {
+            particle: parse_enum(particle)?,
+            amount: amount.parse().ok()?,
+        },
+        ["Clear"] => GameAction::Clear,
+        ["Seed", seed] => GameAction::Seed(seed.parse().ok()?),
+        ["StartChallenge", challenge] => GameAction::StartChallenge(parse_enum(challenge)?),
+        ["EndChallenge"] => GameAction::EndChallenge,
+        ["StartBlitz"] => GameAction::StartBlitz,
+        ["StartDaily", day] => GameAction::StartDaily(day.parse().ok()?),
+        ["EndBlitz"] => GameAction::EndBlitz,
+        ["SetSandbox", palette, gravity, grain_size] => GameAction::SetSandbox(SandboxTools {
+            palette: match palette {
+                "Random" => None,
+                particle => Some(parse_enum(particle)?),
+            },
+            gravity: gravity.parse().ok()?,
+            grain_size: grain_size.parse().ok()?,
+        }),
+        ["NewGamePlus"] => GameAction::NewGamePlus,
+        _ => return None,
+    })
+}
+
+/// Playback speeds of a replay
+/// * Paused: The replay stands still.
+/// * Normal: The replay plays as fast as it was recorded.
+/// * Fast: The replay plays four times as fast.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReplaySpeed {
+    Paused,
+    Normal,
+    Fast,
+}
+
+/// Implementation of methods for the ReplaySpeed enum
+/// * steps: returns the physics steps the replay plays per step of the game
+/// * label: returns the label of the speed button
+impl ReplaySpeed {
+    /// returns the physics steps the replay plays per step of the game
+    pub(crate) fn steps(&self) -> u32 {
+        match self {
+            ReplaySpeed::Paused => 0,
+            ReplaySpeed::Normal => 1,
+            ReplaySpeed::Fast => 4,
+        }
+    }
+
+    /// returns the label of the speed button
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            ReplaySpeed::Paused => "⏸",
+            ReplaySpeed::Normal => "1x",
+            ReplaySpeed::Fast => "4x",
+        }
+    }
+}
+
+/// Replay being played back
+/// * replay: the recorded session
+/// * next: index of the next action to play
+/// * speed: playback speed picked by the player
+/// * matched: whether the end matched the recording, once the replay is over
+#[derive(Debug)]
+pub(crate) struct Playback {
+    pub(crate) replay: Replay,
+    pub(crate) next: usize,
+    pub(crate) speed: ReplaySpeed,
+    pub(crate) matched: Option<bool>,
+}
+
+/// Implementation of methods for the Playback struct
+/// * new: starts playing a replay from the beginning
+/// * due: returns the actions to apply before a physics step
+/// * is_over: returns true once the replay reached its end
+impl Playback {
+    /// starts playing a replay from the beginning
+    pub(crate) fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            next: 0,
+            speed: ReplaySpeed::Normal,
+            matched: None,
+        }
+    }
+
+    /// returns the actions to apply before a physics step
+    /// each action is only returned once
+    pub(crate) fn due(&mut self, tick: u64) -> Vec<GameAction> {
+        let actions: Vec<GameAction> = self.replay.actions[self.next..]
+            .iter()
+            .take_while(|(at, _)| *at <= tick)
+            .map(|(_, action)| *action)
+            .collect();
+        self.next += actions.len();
+        actions
+    }
+
+    /// returns true once the replay reached its end
+    pub(crate) fn is_over(&self, tick: u64) -> bool {
+        tick >= self.replay.end
+    }
+}
+
+/// Tests for the replays
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{challenge::Challenge, particle::SandParticle, theme::Theme};
+
+    // Replay tests
+    #[test]
+    fn test_replay_text() {
+        let replay = Replay {
+            seed: 42,
+            grain_size: 12.5,
+            max_grains: 5_000,
+            screen_size: (800.0, 600.0),
+            save: "money=10\ntheme=Beach".to_string(),
+            actions: vec![
+                (0, GameAction::DropAt { x: 400.25, y: 0.0 }),
+                (3, GameAction::Throttle { steps: -1 }),
+                (
+                    3,
+                    GameAction::Buy {
+                        upgrade: Upgrade::AUTO_CLICKER,
+                        count: 10,
+                    },
+                ),
+                (90, GameAction::BuyTheme(Theme::Beach)),
+                (
+                    91,
+                    GameAction::Sculpt {
+                        x: 1.5,
+                        y: 2.0,
+                        pull: true,
+                    },
+                ),
+            ],
+            end: 120,
+            checksum: 0xdead_beef,
+        };
+        let text = replay.to_text();
+        assert!(text.contains("save=money=10\n"));
+        assert!(text.contains("action=3,Buy,AutoClicker,10\n"));
+        assert!(text.ends_with("checksum=00000000deadbeef"));
+        assert_eq!(Replay::parse(&text), Ok(replay));
+    }
+    #[test]
+    fn test_replay_parse_errors() {
+        assert_eq!(
+            Replay::parse("seed=1\nnonsense"),
+            Err("line 2: isn't valid: nonsense".to_string())
+        );
+        assert_eq!(
+            Replay::parse("seed=1\naction=5,Dance"),
+            Err("line 2: isn't valid: action=5,Dance".to_string())
+        );
+        assert_eq!(
+            Replay::parse("action=5,Convert\naction=4,Convert"),
+            Err("line 2: the actions are out of order".to_string())
+        );
+        assert_eq!(
+            Replay::parse("seed=1"),
+            Err("the replay is missing `grain_size`".to_string())
+        );
+    }
+    #[test]
+    fn test_replay_parse_action() {
+        assert_eq!(parse_action("Convert"), Some(GameAction::Convert));
+        let spawn = GameAction::Spawn {
+            particle: SandParticle::Diamond,
+            amount: 50,
+        };
+        assert_eq!(action_text(&spawn), "Spawn,Diamond,50");
+        assert_eq!(parse_action("Spawn,Diamond,50"), Some(spawn));
+        assert_eq!(
+            parse_action("Vacuum,1,2"),
+            Some(GameAction::Vacuum { x: 1.0, y: 2.0 })
+        );
+        // upgrades the table doesn't have can't be played
+        assert_eq!(parse_action("Buy,Unknown,1"), None);
+        assert_eq!(parse_action("DropAt,1"), None);
+        let start = GameAction::StartChallenge(Challenge::TinyContainer);
+        assert_eq!(parse_action(&action_text(&start)), Some(start));
+        assert_eq!(parse_action("EndChallenge"), Some(GameAction::EndChallenge));
+        assert_eq!(parse_action("StartBlitz"), Some(GameAction::StartBlitz));
+        let daily = GameAction::StartDaily(20_431);
+        assert_eq!(parse_action(&action_text(&daily)), Some(daily));
+        let reset = GameAction::Reset(Profile::Hardcore);
+        assert_eq!(parse_action(&action_text(&reset)), Some(reset));
+        assert_eq!(
+            parse_action("Reset"),
+            Some(GameAction::Reset(Profile::Normal))
+        );
+        let sandbox = GameAction::SetSandbox(SandboxTools {
+            palette: Some(SandParticle::Iron),
+            gravity: 0.5,
+            grain_size: 12.0,
+        });
+        assert_eq!(action_text(&sandbox), "SetSandbox,Iron,0.5,12");
+        assert_eq!(parse_action("SetSandbox,Iron,0.5,12"), Some(sandbox));
+        let random = GameAction::SetSandbox(SandboxTools::default());
+        assert_eq!(parse_action(&action_text(&random)), Some(random));
+        assert_eq!(parse_action("NewGamePlus"), Some(GameAction::NewGamePlus));
+    }
+
+    // Playback tests
+    #[test]
+    fn test_playback_due() {
+        let mut playback = Playback::new(Replay {
+            seed: 1,
+            grain_size: 10.0,
+            max_grains: 0,
+            screen_size: (800.0, 600.0),
+            save: String::new(),
+            actions: vec![
+                (0, GameAction::Convert),
+                (2, GameAction::DropRandom),
+                (2, GameAction::Convert),
+            ],
+            end: 5,
+            checksum: 0,
+        });
+        assert_eq!(playback.due(0), [GameAction::Convert]);
+        assert!(playback.due(1).is_empty());
+        assert_eq!(
+            playback.due(2),
+            [GameAction::DropRandom, GameAction::Convert]
+        );
+        assert!(playback.due(2).is_empty());
+        assert!(!playback.is_over(4));
+        assert!(playback.is_over(5));
+        assert_eq!(ReplaySpeed::Fast.steps(), 4);
+    }
+}
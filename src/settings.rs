@@ -0,0 +1,309 @@
+//! Player settings saved between runs
+
+use crate::{
+    FPS,
+    input::{Keybinds, MouseButtons},
+    lang::{Lang, tr},
+    theme::TimeOfDay,
+    upgrade::UpgradeCategory,
+};
+use std::{collections::HashSet, fmt::Debug};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Enum representing what the game does while the window is in the background
+/// * Pause: The game stops until the window is back.
+/// * Idle: The autoclicker keeps earning money, without dropping any sand.
+/// * Run: The sand keeps falling, but a minimized window only idles.
+#[derive(PartialEq, Debug, EnumIter, Clone, Copy, Default)]
+pub(crate) enum BackgroundMode {
+    #[default]
+    Pause,
+    Idle,
+    Run,
+}
+
+/// Implementation of methods for the BackgroundMode enum
+/// * name: returns the display name of the mode
+/// * hint: returns the explanation of the mode
+impl BackgroundMode {
+    /// returns the display name of the mode
+    pub(crate) fn name(&self) -> &'static str {
+        tr(match self {
+            BackgroundMode::Pause => "background-pause",
+            BackgroundMode::Idle => "background-idle",
+            BackgroundMode::Run => "background-run",
+        })
+    }
+
+    /// returns the explanation of the mode
+    pub(crate) fn hint(&self) -> &'static str {
+        tr(match self {
+            BackgroundMode::Pause => "background-pause-hint",
+            BackgroundMode::Idle => "background-idle-hint",
+            BackgroundMode::Run => "background-run-hint",
+        })
+    }
+}
+
+/// Player settings
+/// * trails: flag to draw motion trails behind falling grains
+/// * colorblind: flag to use the colorblind-safe palette
+/// * floating_text: flag to show the money gained as floating text
+/// * full_numbers: flag to show exact money amounts instead of 1.23K
+/// * fixed_time: time of day to lock the day/night cycle to, if any
+/// * ui_scale: size multiplier for the GUI and info text
+/// * scale_grains: flag to also apply the UI scale to new grains
+/// * screen_shake: flag to shake the screen after big sales
+/// * flash: flag to flash the screen after big sales
+/// * gauge: flag to show the container gauge
+/// * show_info: flag to show the player info
+/// * fps_cap: most frames drawn per second, 0 for no limit
+/// * max_grains: most grains simulated before the oldest settled ones are compacted, 0 for no limit
+/// * closed_categories: upgrade categories collapsed in the options window
+/// * keybinds: keyboard bindings of the game actions
+/// * mouse: actions of the mouse buttons
+/// * lang: language of the texts
+/// * tutorial_done: flag for whether the first-run tutorial was finished or skipped
+/// * muted: flag to silence all sounds
+/// * music_volume: volume of the music, from 0 to 1
+/// * music_muted: flag to silence the music
+/// * speedrun: flag to time the game with the speedrun timer
+/// * background: what the game does while the window is unfocused or minimized
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Settings {
+    pub(crate) trails: bool,
+    pub(crate) colorblind: bool,
+    pub(crate) floating_text: bool,
+    pub(crate) full_numbers: bool,
+    pub(crate) fixed_time: Option<TimeOfDay>,
+    pub(crate) ui_scale: f32,
+    pub(crate) scale_grains: bool,
+    pub(crate) screen_shake: bool,
+    pub(crate) flash: bool,
+    pub(crate) gauge: bool,
+    pub(crate) show_info: bool,
+    pub(crate) fps_cap: u32,
+    pub(crate) max_grains: u32,
+    pub(crate) closed_categories: HashSet<UpgradeCategory>,
+    pub(crate) keybinds: Keybinds,
+    pub(crate) mouse: MouseButtons,
+    pub(crate) lang: Lang,
+    pub(crate) tutorial_done: bool,
+    pub(crate) muted: bool,
+    pub(crate) music_volume: f32,
+    pub(crate) music_muted: bool,
+    pub(crate) speedrun: bool,
+    pub(crate) background: BackgroundMode,
+}
+
+/// Default values for the player settings
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            trails: true,
+            colorblind: false,
+            floating_text: true,
+            full_numbers: false,
+            fixed_time: None,
+            ui_scale: 1.0,
+            scale_grains: false,
+            screen_shake: true,
+            flash: true,
+            gauge: true,
+            show_info: false,
+            fps_cap: 0,
+            max_grains: 20_000,
+            closed_categories: HashSet::new(),
+            keybinds: Keybinds::default(),
+            mouse: MouseButtons::default(),
+            lang: Lang::English,
+            tutorial_done: false,
+            muted: false,
+            music_volume: 0.5,
+            music_muted: false,
+            speedrun: false,
+            background: BackgroundMode::Pause,
+        }
+    }
+}
+
+/// Implementation of methods for the Settings struct
+/// * to_text: returns the settings in the settings file format
+/// * from_text: reads the settings from the settings file format
+impl Settings {
+    /// returns the settings in the settings file format
+    /// each line is a `key=value` pair
+    pub(crate) fn to_text(&self) -> String {
+        [
+            format!("trails={}", self.trails),
+            format!("colorblind={}", self.colorblind),
+            format!("floating_text={}", self.floating_text),
+            format!("full_numbers={}", self.full_numbers),
+            format!(
+                "fixed_time={}",
+                self.fixed_time
+                    .map_or("None".to_string(), |time| format!("{:?}", time))
+            ),
+            format!("ui_scale={}", self.ui_scale),
+            format!("scale_grains={}", self.scale_grains),
+            format!("screen_shake={}", self.screen_shake),
+            format!("flash={}", self.flash),
+            format!("gauge={}", self.gauge),
+            format!("show_info={}", self.show_info),
+            format!("fps_cap={}", self.fps_cap),
+            format!("max_grains={}", self.max_grains),
+            format!("lang={:?}", self.lang),
+            format!("tutorial_done={}", self.tutorial_done),
+            format!("muted={}", self.muted),
+            format!("music_volume={}", self.music_volume),
+            format!("music_muted={}", self.music_muted),
+            format!("speedrun={}", self.speedrun),
+            format!("background={:?}", self.background),
+            format!(
+                "closed_categories={}",
+                UpgradeCategory::iter()
+                    .filter(|category| self.closed_categories.contains(category))
+                    .map(|category| format!("{:?}", category))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        ]
+        .into_iter()
+        .chain(self.keybinds.to_text())
+        .chain(self.mouse.to_text())
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+
+    /// reads the settings from the settings file format
+    /// missing or broken values keep their defaults
+    pub(crate) fn from_text(text: &str) -> Self {
+        let mut settings = Settings::default();
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key {
+                "trails" => settings.trails = value.parse().unwrap_or(settings.trails),
+                "colorblind" => settings.colorblind = value.parse().unwrap_or(settings.colorblind),
+                "floating_text" => {
+                    settings.floating_text = value.parse().unwrap_or(settings.floating_text)
+                }
+                "full_numbers" => {
+                    settings.full_numbers = value.parse().unwrap_or(settings.full_numbers)
+                }
+                "fixed_time" => settings.fixed_time = parse_enum(value),
+                "ui_scale" => {
+                    let scale = value.parse().unwrap_or(settings.ui_scale);
+                    settings.ui_scale = f32::clamp(scale, 1.0, 2.0);
+                }
+                "scale_grains" => {
+                    settings.scale_grains = value.parse().unwrap_or(settings.scale_grains)
+                }
+                "screen_shake" => {
+                    settings.screen_shake = value.parse().unwrap_or(settings.screen_shake)
+                }
+                "flash" => settings.flash = value.parse().unwrap_or(settings.flash),
+                "gauge" => settings.gauge = value.parse().unwrap_or(settings.gauge),
+                "show_info" => settings.show_info = value.parse().unwrap_or(settings.show_info),
+                "fps_cap" => {
+                    // the cap can't go below the physics rate
+                    let cap = value.parse().unwrap_or(settings.fps_cap);
+                    settings.fps_cap = if cap == 0 { 0 } else { cap.max(FPS) };
+                }
+                "max_grains" => settings.max_grains = value.parse().unwrap_or(settings.max_grains),
+                "lang" => settings.lang = parse_enum(value).unwrap_or(settings.lang),
+                "tutorial_done" => {
+                    settings.tutorial_done = value.parse().unwrap_or(settings.tutorial_done)
+                }
+                "muted" => settings.muted = value.parse().unwrap_or(settings.muted),
+                "music_volume" => {
+                    let volume = value.parse().unwrap_or(settings.music_volume);
+                    settings.music_volume = f32::clamp(volume, 0.0, 1.0);
+                }
+                "music_muted" => {
+                    settings.music_muted = value.parse().unwrap_or(settings.music_muted)
+                }
+                "speedrun" => settings.speedrun = value.parse().unwrap_or(settings.speedrun),
+                "background" => {
+                    settings.background = parse_enum(value).unwrap_or(settings.background)
+                }
+                // older settings files only knew whether to keep running
+                "run_in_background" if value == "true" => settings.background = BackgroundMode::Run,
+                "closed_categories" => {
+                    settings.closed_categories = value.split(',').filter_map(parse_enum).collect()
+                }
+                _ => {
+                    if !settings.keybinds.load_line(key, value) {
+                        settings.mouse.load_line(key, value);
+                    }
+                }
+            }
+        }
+        // a button always has to drop sand
+        if !settings.mouse.has_drop() {
+            settings.mouse = MouseButtons::default();
+        }
+        settings
+    }
+}
+
+/// returns the enum variant with the given debug name
+/// used for reading enums back from the save file
+pub(crate) fn parse_enum<T: IntoEnumIterator + Debug>(name: &str) -> Option<T> {
+    T::iter().find(|variant| format!("{:?}", variant) == name)
+}
+
+/// Tests for the settings
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Action;
+    use ggez::input::keyboard::{KeyCode, KeyMods};
+
+    // Settings tests
+    #[test]
+    fn test_settings_text() {
+        let mut settings = Settings {
+            trails: false,
+            colorblind: true,
+            floating_text: false,
+            full_numbers: true,
+            fixed_time: Some(TimeOfDay::Dusk),
+            ui_scale: 1.5,
+            scale_grains: true,
+            screen_shake: false,
+            flash: false,
+            gauge: false,
+            show_info: true,
+            fps_cap: 60,
+            max_grains: 5_000,
+            closed_categories: HashSet::from([
+                UpgradeCategory::Economy,
+                UpgradeCategory::Container,
+            ]),
+            keybinds: Keybinds::default(),
+            mouse: MouseButtons::default(),
+            lang: Lang::German,
+            tutorial_done: true,
+            muted: true,
+            music_volume: 0.25,
+            music_muted: true,
+            speedrun: true,
+            background: BackgroundMode::Idle,
+        };
+        settings
+            .keybinds
+            .bind(Action::Convert, KeyCode::Space, KeyMods::SHIFT);
+        settings.mouse.swap();
+        assert_eq!(Settings::from_text(&settings.to_text()), settings);
+        assert_eq!(Settings::from_text("trails=maybe"), Settings::default());
+        assert_eq!(Settings::from_text("ui_scale=9").ui_scale, 2.0);
+        assert_eq!(Settings::from_text("fps_cap=5").fps_cap, FPS);
+        assert_eq!(
+            Settings::from_text("run_in_background=true").background,
+            BackgroundMode::Run
+        );
+    }
+}
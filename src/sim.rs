@@ -0,0 +1,1805 @@
+//! Headless simulation of the game: money, upgrades, and grain physics
+
+use crate::{
+    AUTOCLICK_PERIOD, COMBO_DECAY, COMBO_MAX, COMBO_STEP, COMBO_WINDOW, GRAIN_SIZE, GRAVITY,
+    LOW_GRAVITY_CHANCE, LOW_GRAVITY_DURATION, LOW_GRAVITY_LIFT, PARALLEL_GRAINS, SCREEN_SIZE,
+    SCULPT_RADIUS, SCULPT_STRENGTH, SPARKLE_TIME, VACUUM_RADIUS,
+    challenge::Modifiers,
+    container::Container,
+    currency::Currency,
+    estimate::{Rates, grain_value, income_gain, payback},
+    grain::{Grain, GrainState, SpatialGrid},
+    particle::{ParticleTable, SandParticle},
+    stats::{
+        Achievement, Counters, History, HistoryEntry, Income, IncomeGraph, Sample, Stats, Timeline,
+    },
+    upgrade::{Upgrade, UpgradeEffect, upgrade_table},
+};
+use log::{debug, trace, warn};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use rayon::prelude::*;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+use strum::IntoEnumIterator;
+
+/// Things that happened during the last simulation tick
+/// * Landed: A grain of the sand particle settled on the ground.
+/// * Achievement: The achievement was earned.
+/// * Unlocked: The upgrade is now listed in the shop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum SimEvent {
+    Landed(SandParticle),
+    Achievement(Achievement),
+    Unlocked(Upgrade),
+}
+
+/// Simulation state of the game, without a window, rendering, or sound
+/// * money: player's current money
+/// * particle_table: value, colors, and cost of each sand particle
+/// * container: sand in the container, the grains and the counts of each sand particle
+/// * upgrades: map of upgrades and their levels
+/// * unlock: set of unlocked upgrades
+/// * total_clicks: total number of clicks made by the player
+/// * total_time: total time spent in the game
+/// * autoclicker_timer: timer for the autoclicker upgrade
+/// * autoclicker_blocked: flag for whether a full container stopped the autoclicker
+/// * idle_earnings: money the idle autoclicker earned below a whole coin, kept for the next idle tick
+/// * gravity: current gravity affecting the grains
+/// * low_gravity_timer: time left in the low gravity event
+/// * low_gravity_chance: chance per second of a low gravity event
+/// * screen_size: current size of the play area, the ground is its bottom
+/// * grid: spatial grid for looking up grains by position
+/// * stats: lifetime statistics of the player
+/// * achievements: earned achievements and the play time they were earned at
+/// * history: recent purchases and sales of the player
+/// * income: money earned in each of the last seconds
+/// * timeline: samples of the progress, one per minute of play
+/// * income_graph: money per minute over the last minutes of play
+/// * session_start: lifetime counters when this session started
+/// * combo: streak of fast clicks
+/// * grain_size: size of new grains
+/// * colorblind: flag for whether new grains use the colorblind-safe palette
+/// * max_grains: most grains kept in the pool before the oldest are compacted, 0 for no limit
+/// * modifiers: rules the run is played with, from a challenge or the challenge rewards
+/// * events: what happened during the last tick
+/// * rng: random number generator, seeded for repeatable runs
+pub struct SimState {
+    pub money: Currency,
+    pub(crate) particle_table: ParticleTable,
+    pub(crate) container: Container,
+    pub upgrades: HashMap<Upgrade, u32>,
+    pub(crate) unlock: HashSet<Upgrade>,
+    pub total_clicks: u32,
+    pub(crate) total_time: Duration,
+    pub(crate) autoclicker_timer: f32,
+    pub(crate) autoclicker_blocked: bool,
+    pub(crate) idle_earnings: f64,
+    pub(crate) gravity: f32,
+    pub(crate) low_gravity_timer: f32,
+    pub(crate) low_gravity_chance: f32,
+    pub(crate) screen_size: (f32, f32),
+    pub(crate) grid: SpatialGrid,
+    pub stats: Stats,
+    pub(crate) achievements: HashMap<Achievement, u64>,
+    pub(crate) history: History,
+    pub(crate) income: Income,
+    pub(crate) timeline: Timeline,
+    pub(crate) income_graph: IncomeGraph,
+    pub(crate) session_start: Counters,
+    pub(crate) combo: Combo,
+    pub(crate) grain_size: f32,
+    pub(crate) colorblind: bool,
+    pub(crate) max_grains: usize,
+    pub(crate) modifiers: Modifiers,
+    pub(crate) events: Vec<SimEvent>,
+    pub(crate) rng: StdRng,
+}
+
+impl Default for SimState {
+    fn default() -> Self {
+        Self {
+            money: Currency::ZERO,
+            particle_table: ParticleTable::default(),
+            container: Container::default(),
+            // start with basic sand
+            upgrades: upgrade_table()
+                .iter()
+                .filter(|upgrade| upgrade.def().start_level > 0)
+                .map(|upgrade| (upgrade, upgrade.def().start_level))
+                .collect(),
+            unlock: HashSet::new(),
+            total_clicks: 0,
+            total_time: Duration::ZERO,
+            autoclicker_timer: 0.0,
+            autoclicker_blocked: false,
+            idle_earnings: 0.0,
+            gravity: GRAVITY,
+            low_gravity_timer: 0.0,
+            low_gravity_chance: LOW_GRAVITY_CHANCE,
+            screen_size: SCREEN_SIZE,
+            grid: SpatialGrid::new(GRAIN_SIZE * 4.0),
+            stats: Stats::default(),
+            achievements: HashMap::new(),
+            history: History::default(),
+            income: Income::default(),
+            timeline: Timeline::default(),
+            income_graph: IncomeGraph::default(),
+            session_start: Counters::default(),
+            combo: Combo::default(),
+            grain_size: GRAIN_SIZE,
+            colorblind: false,
+            max_grains: 0,
+            modifiers: Modifiers::default(),
+            events: Vec::new(),
+            rng: StdRng::from_os_rng(),
+        }
+    }
+}
+
+/// Implementation of methods for the SimState struct
+/// * with_seed: creates a simulation with repeatable random numbers
+/// * new_run: returns a new game played under the same settings
+/// * reset: resets the progress back to a new game
+/// * new_game_plus: starts the upgrades and the money over, keeping the lifetime progress
+/// * tick: advances the simulation by a number of seconds
+/// * idle: advances the simulation by a number of seconds without the grain physics
+/// * step_grains: moves the falling grains, in parallel when there are many
+/// * refill: tops the money up in free play
+/// * decay: removes the settled grains that decayed
+/// * compact: folds the oldest settled grains out of the pool past the grain cap
+/// * compacted_amount: returns the amount of compacted grains
+/// * click: drops sand by hand, building the combo
+/// * add_grain: adds the grains of a click at a position
+/// * add_grains: adds an amount of grains around a position
+/// * add_grains_of: adds an amount of grains of a sand particle around a position
+/// * spawn_grains: adds an amount of grains worth a bonus around a position
+/// * autoclicker: drops sand with the autoclicker upgrade
+/// * idle_autoclicker: credits the money of the autoclicker without dropping sand
+/// * low_gravity: starts and counts down the low gravity event
+/// * start_low_gravity: starts the low gravity event
+/// * index_grains: rebuilds the spatial grid from where the grains are
+/// * sculpt: pushes the grains near a position away, or pulls them in
+/// * resize: changes the size of the play area
+/// * sale_value: returns the money a sand particle sells for
+/// * contents: returns the sand types in the container
+/// * unsold_value: returns the money the sand in the container sells for
+/// * is_full: checks if the container is full
+/// * get_size: returns the size of the container
+/// * get_amount: returns the amount of grains in the container
+/// * lifetime: returns the counters of the player over the whole game
+/// * session: returns the counters of the player in this session
+/// * reset_session: starts counting the session from the current lifetime counters
+/// * rates: returns the rates of the player the estimates are based on
+/// * payback_time: returns the seconds the next level of an upgrade takes to pay for itself
+/// * achievement_progress: returns the progress of an achievement
+/// * check_unlocks: lists the upgrades whose unlock rules are met in the shop
+/// * check_achievements: earns the achievements that reached their goal
+/// * effect_level: returns the summed levels of the upgrades with an effect
+/// * is_unlocked: checks if a sand particle is unlocked
+/// * drop_amount: returns the amount of grains a click drops
+/// * upgrade_cost: returns the cost of an upgrade
+/// * level_cost: returns the cost of an upgrade at a level
+/// * levels_cost: returns the cost of the next levels of an upgrade
+/// * rand_sand: returns a random unlocked sand particle
+/// * is_maxed: checks if an upgrade is maxed out
+/// * all_maxed: checks if every upgrade with a max level is maxed out
+/// * vacuum: sells the settled grains near a position
+/// * sell: converts all sand particles into money
+/// * deposit: adds earned money, saturating at the largest amount
+/// * clear: removes all sand from the container without selling it
+/// * buy: buys an upgrade if affordable
+/// * sample: returns a sample of the progress for the timeline
+/// * checksum: returns a fingerprint of the state, for checking replays
+impl SimState {
+    /// creates a simulation whose random numbers come from the seed
+    /// the same seed and the same inputs always play out the same way
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            ..Self::default()
+        }
+    }
+
+    /// returns a new game played under the same settings
+    /// the size of the play area, the grain settings, the rules, and the random numbers are kept
+    pub(crate) fn new_run(&self) -> Self {
+        Self {
+            screen_size: self.screen_size,
+            particle_table: self.particle_table.clone(),
+            grain_size: self.grain_size,
+            colorblind: self.colorblind,
+            max_grains: self.max_grains,
+            modifiers: self.modifiers.clone(),
+            rng: self.rng.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// resets the progress back to a new game, under the same settings
+    pub(crate) fn reset(&mut self) {
+        *self = self.new_run();
+    }
+
+    /// starts the upgrades, the money, and the container over for a New Game+
+    /// the clicks, the play time, the statistics, and the achievements carry on,
+    /// and the rules are set again by the game with the next New Game+ count
+    pub(crate) fn new_game_plus(&mut self) {
+        let mut next = self.new_run();
+        next.total_clicks = self.total_clicks;
+        next.total_time = self.total_time;
+        next.session_start = self.session_start;
+        next.stats = std::mem::take(&mut self.stats);
+        next.achievements = std::mem::take(&mut self.achievements);
+        next.history = std::mem::take(&mut self.history);
+        next.timeline = std::mem::take(&mut self.timeline);
+        *self = next;
+    }
+
+    /// advances the simulation by dt seconds
+    /// moves the grains, runs the autoclicker and the low gravity event,
+    /// and earns the finished achievements and upgrade unlocks
+    pub fn tick(&mut self, dt: f32) {
+        self.events.clear();
+        self.refill();
+        let minute = self.total_time.as_secs() / 60;
+        // update the total_time stat
+        self.total_time += Duration::from_secs_f32(dt);
+        self.income.update(dt);
+        let per_minute = self.income.per_second() * 60;
+        self.income_graph
+            .update(dt, self.total_time.as_secs(), per_minute);
+
+        // the combo ends after a pause in the clicks
+        self.combo.update(self.total_time.as_secs_f32());
+
+        // update the position of the falling particles.
+        self.step_grains(dt);
+        self.decay();
+        self.compact();
+        // index where the grains ended up for the sculpting tool and the vacuum
+        self.index_grains();
+
+        // autoclicker upgrade
+        self.autoclicker(dt);
+
+        // earn the finished achievements, free play earns none
+        for achievement in self.check_achievements() {
+            self.events.push(SimEvent::Achievement(achievement));
+        }
+
+        // list the upgrades that reached their unlock rules in the shop
+        for upgrade in self.check_unlocks() {
+            self.events.push(SimEvent::Unlocked(upgrade));
+        }
+
+        // random low gravity event
+        self.low_gravity(dt);
+
+        // sample the progress every minute of play
+        if self.total_time.as_secs() / 60 > minute {
+            let sample = self.sample();
+            self.timeline.push(sample);
+        }
+
+        // TODO: collision between grains
+    }
+
+    /// advances the simulation by dt seconds without the grain physics, for a window in the background
+    /// the grains already in the container stay where they are,
+    /// and the autoclicker earns money instead of dropping sand
+    pub(crate) fn idle(&mut self, dt: f32) {
+        self.events.clear();
+        self.refill();
+        let minute = self.total_time.as_secs() / 60;
+        self.total_time += Duration::from_secs_f32(dt);
+        self.income.update(dt);
+        let per_minute = self.income.per_second() * 60;
+        self.income_graph
+            .update(dt, self.total_time.as_secs(), per_minute);
+        self.combo.update(self.total_time.as_secs_f32());
+
+        // the sand on the ground still decays in the background
+        for grain in self.container.iter_mut().filter(|grain| grain.is_done()) {
+            grain.age += dt;
+        }
+        self.decay();
+
+        self.idle_autoclicker(dt);
+
+        for achievement in self.check_achievements() {
+            self.events.push(SimEvent::Achievement(achievement));
+        }
+        for upgrade in self.check_unlocks() {
+            self.events.push(SimEvent::Unlocked(upgrade));
+        }
+        if self.total_time.as_secs() / 60 > minute {
+            let sample = self.sample();
+            self.timeline.push(sample);
+        }
+    }
+
+    /// moves the falling grains by dt seconds, ages the settled ones, and records the ones that landed
+    /// many grains are split over the threads, which gives the same result,
+    /// as every grain moves on its own and the landings keep their order
+    pub(crate) fn step_grains(&mut self, dt: f32) {
+        let gravity = self.gravity * self.modifiers.gravity_scale;
+        let bounds = self.screen_size;
+        let step = move |grain: &mut Grain| {
+            // skip updating if the grain is done, it only gets older
+            // and is drawn where it lies, not between the steps
+            if grain.is_done() {
+                grain.age += dt;
+                grain.prev = (grain.rect.x, grain.rect.y);
+                return None;
+            }
+            grain.update(dt, gravity, bounds);
+            grain.is_done().then_some(SimEvent::Landed(grain.particle))
+        };
+        if self.container.len() >= PARALLEL_GRAINS {
+            self.events
+                .par_extend(self.container.par_iter_mut().filter_map(step));
+        } else {
+            self.events
+                .extend(self.container.iter_mut().filter_map(step));
+        }
+    }
+
+    /// tops the money up to the most there can be in free play, so it never runs out
+    pub(crate) fn refill(&mut self) {
+        if self.modifiers.sandbox {
+            self.money = Currency::MAX;
+        }
+    }
+
+    /// removes the settled grains that rested on the ground for the decay time of the rules
+    /// the decayed sand is lost without being sold, and the container counts drop with it
+    pub(crate) fn decay(&mut self) {
+        let Some(limit) = self.modifiers.decay_time else {
+            return;
+        };
+        let decayed: Vec<usize> = self
+            .container
+            .indexed()
+            .filter(|(_, grain)| grain.is_done() && grain.age >= limit)
+            .map(|(slot, _)| slot)
+            .collect();
+        for slot in decayed {
+            if self.container.remove(slot).is_some() {
+                self.stats.decayed += 1;
+            }
+        }
+    }
+
+    /// folds the oldest settled grains out of the pool once it holds more than max_grains
+    /// so the physics and the drawing stay fast with a huge container
+    /// the compacted sand still fills the container and sells like the grains did
+    /// decaying sand is never compacted, as the compacted grains have no age to decay with
+    pub(crate) fn compact(&mut self) {
+        if self.modifiers.decay_time.is_none() {
+            self.container.compact(self.max_grains);
+        }
+    }
+
+    /// returns the amount of compacted grains
+    pub(crate) fn compacted_amount(&self) -> u32 {
+        self.container.compacted_amount()
+    }
+
+    /// drops an amount of sand by hand at the specified (x, y) position
+    /// fast clicks build a combo that makes the grains worth more
+    /// returns the amount of grains that were added
+    pub fn click(&mut self, x: f32, y: f32, amount: u32) -> u32 {
+        if self.is_full() {
+            return 0;
+        }
+        // increment total clicks
+        self.total_clicks += 1;
+        self.combo.click(self.total_time.as_secs_f32());
+        let bonus = self.combo.multiplier() - 1.0;
+        let added = self.spawn_grains(x, y, amount, None, bonus);
+        self.stats.manual_grains += added as u64;
+        added
+    }
+
+    /// adds a grain of sand at the specified (x, y) position
+    /// takes into account upgrades for multiple grains
+    /// returns the amount of grains that were added
+    pub(crate) fn add_grain(&mut self, x: f32, y: f32) -> u32 {
+        self.add_grains(x, y, self.drop_amount())
+    }
+
+    /// adds an amount of grains around the specified (x, y) position
+    /// returns the amount of grains that were added
+    pub(crate) fn add_grains(&mut self, x: f32, y: f32, amount: u32) -> u32 {
+        self.add_grains_of(x, y, amount, None)
+    }
+
+    /// adds an amount of grains of the sand particle around the specified (x, y) position
+    /// random unlocked sand is used without a particle
+    /// returns the amount of grains that were added
+    pub(crate) fn add_grains_of(
+        &mut self,
+        x: f32,
+        y: f32,
+        amount: u32,
+        particle: Option<SandParticle>,
+    ) -> u32 {
+        self.spawn_grains(x, y, amount, particle, 0.0)
+    }
+
+    /// adds an amount of grains around the specified (x, y) position
+    /// the grains sell for the bonus fraction more than their sand particle
+    /// returns the amount of grains that were added
+    pub(crate) fn spawn_grains(
+        &mut self,
+        x: f32,
+        y: f32,
+        amount: u32,
+        particle: Option<SandParticle>,
+        bonus: f64,
+    ) -> u32 {
+        // variable to track how many grains have been added
+        let mut i: u32 = 0;
+        let container_size = self.get_size();
+        let current_amount = self.get_amount();
+        // grains are centered on their position, so they have to stay half a grain from the edges
+        let size = self.modifiers.grain_size.unwrap_or(self.grain_size);
+        let half = size / 2.0;
+        let (left, right) = (half, (self.screen_size.0 - half).max(half));
+        while i < amount {
+            let mut new_x = x;
+            let mut new_y = y;
+            // add slight random offset for multiple grains
+            if i > 0 {
+                let max_offset = 50.0;
+                let offset_x = self.rng.random_range(-max_offset..max_offset);
+                let offset_y = self.rng.random_range(-max_offset..max_offset);
+                new_x = x + offset_x;
+                new_y = y + offset_y;
+            }
+            // keep the whole grain inside the play area, not above its top
+            new_x = new_x.clamp(left, right);
+            new_y = new_y.max(half);
+
+            // check if gain can fit in container
+            if current_amount + i >= container_size {
+                break;
+            }
+
+            // add a sand particle at (x, y)
+            // the palette of the rules picks the sand instead of the dice
+            let sand = match particle.or(self.modifiers.palette) {
+                Some(particle) => particle,
+                None => self.rand_sand(),
+            };
+            let color = self.particle_table.palette_color(sand, self.colorblind);
+            let mut grain = Grain::new(new_x, new_y, size, color);
+            grain.restitution = self.particle_table.restitution(sand);
+            grain.particle = sand;
+            grain.bonus = self.sale_value(sand).as_f64() * bonus;
+            // rare grains sparkle while they fall
+            if sand.level() >= SandParticle::Starsand.level() {
+                grain.sparkle_timer = SPARKLE_TIME;
+            }
+            // the container counts the grain with its sand particle
+            *self.stats.tier_drops.entry(sand).or_insert(0) += 1;
+            self.container.add(grain);
+
+            i += 1;
+        }
+        i
+    }
+
+    /// autoclicker upgrade functionality
+    /// the time past the last click carries over, so the rate is exactly level/5 clicks per second
+    /// the timer holds while the container is full: selling doesn't release a burst of clicks,
+    /// and the next click comes once the rest of its period has passed
+    pub(crate) fn autoclicker(&mut self, seconds: f32) {
+        // get the autoclicker level
+        let autoclicker_level = self.effect_level(UpgradeEffect::AutoclickInterval);
+        if autoclicker_level == 0 {
+            return;
+        }
+        // a full container stops the autoclicker until the sand is sold
+        let blocked = self.is_full();
+        if blocked != self.autoclicker_blocked {
+            self.autoclicker_blocked = blocked;
+            if blocked {
+                debug!("autoclicker stopped, the container is full");
+            } else {
+                debug!("autoclicker resumed");
+            }
+        }
+        if !blocked {
+            // increment the timer
+            self.autoclicker_timer += seconds;
+            let frequency = AUTOCLICK_PERIOD / autoclicker_level as f32; // seconds per click
+            // determine how many clicks to make
+            let clicks = (self.autoclicker_timer / frequency).floor() as u32;
+            let mut dropped = 0;
+            for _ in 0..clicks {
+                // the spawn keeps grains at the edges inside the play area
+                let x = self.rng.random::<f32>() * self.screen_size.0;
+                let y = 0.0;
+                let added = self.add_grain(x, y);
+                self.stats.auto_grains += added as u64;
+                dropped += added;
+            }
+            // keep the time toward the next click
+            self.autoclicker_timer -= clicks as f32 * frequency;
+            if clicks > 0 {
+                debug!(
+                    "autoclicker level {} dropped {} grains",
+                    autoclicker_level, dropped
+                );
+            }
+        }
+    }
+
+    /// credits the money the autoclicker makes in the seconds, without dropping any grains
+    /// each click earns the expected money of the grains it would drop,
+    /// and the money below a whole coin is kept for the next call
+    /// a full container still stops the autoclicker, as the sand would have nowhere to go
+    pub(crate) fn idle_autoclicker(&mut self, seconds: f32) {
+        let autoclicker_level = self.effect_level(UpgradeEffect::AutoclickInterval);
+        if autoclicker_level == 0 || self.is_full() {
+            return;
+        }
+        self.autoclicker_timer += seconds;
+        let frequency = AUTOCLICK_PERIOD / autoclicker_level as f32; // seconds per click
+        let clicks = (self.autoclicker_timer / frequency).floor() as u32;
+        self.autoclicker_timer -= clicks as f32 * frequency;
+        if clicks == 0 {
+            return;
+        }
+        let values: Vec<Currency> = SandParticle::iter()
+            .map(|particle| self.sale_value(particle))
+            .collect();
+        let tier = self.effect_level(UpgradeEffect::ParticleTier);
+        let grains = clicks as f64 * self.drop_amount() as f64;
+        self.idle_earnings += grains * grain_value(&values, tier) * self.modifiers.value_factor();
+        let earned = Currency::from_f64(self.idle_earnings.floor());
+        if earned > Currency::ZERO {
+            self.idle_earnings -= earned.as_f64();
+            // earned, but not sold, so the average sale leaves it out
+            self.stats.money_earned += earned;
+            self.income.record(earned);
+            self.deposit(earned);
+            trace!(
+                "idle autoclicker level {} earned {}$ for {} clicks",
+                autoclicker_level, earned, clicks
+            );
+        }
+    }
+
+    /// low gravity event functionality
+    /// randomly starts the event and counts it down
+    pub(crate) fn low_gravity(&mut self, seconds: f32) {
+        if self.low_gravity_timer <= 0.0 {
+            // roll for a new event
+            if self.rng.random::<f32>() < self.low_gravity_chance * seconds {
+                self.start_low_gravity();
+            }
+            return;
+        }
+        self.low_gravity_timer -= seconds;
+        let elapsed = LOW_GRAVITY_DURATION - self.low_gravity_timer;
+        if self.low_gravity_timer <= 0.0 {
+            // the event is over, everything rains back down
+            self.low_gravity_timer = 0.0;
+            self.gravity = GRAVITY;
+        } else if elapsed >= LOW_GRAVITY_LIFT {
+            // after the lift off, the grains slowly float down
+            self.gravity = GRAVITY * 0.1;
+        }
+    }
+
+    /// starts the low gravity event
+    /// gravity is briefly inverted so settled grains lift off
+    pub(crate) fn start_low_gravity(&mut self) {
+        self.low_gravity_timer = LOW_GRAVITY_DURATION;
+        self.gravity = -GRAVITY * 0.1;
+        // wake up the settled grains with a little drift
+        for grain in self.container.iter_mut() {
+            let drift = self.rng.random_range(-20.0..20.0);
+            grain.wake(drift);
+        }
+    }
+
+    /// rebuilds the spatial grid from where the grains ended up, once per tick
+    /// the tools that look for grains by position share it until the next tick,
+    /// so the slots of grains taken out since are skipped, and new drops are found a tick late
+    pub(crate) fn index_grains(&mut self) {
+        self.grid.rebuild(self.container.indexed());
+    }
+
+    /// sculpting tool functionality
+    /// pushes the grains near (x, y) away, or pulls them in
+    pub(crate) fn sculpt(&mut self, x: f32, y: f32, pull: bool, seconds: f32) {
+        // the tool gets bigger and stronger with the upgrade
+        let level = self.effect_level(UpgradeEffect::SculptRadius) as f32;
+        let radius = SCULPT_RADIUS + 10.0 * level;
+        let strength = SCULPT_STRENGTH + 250.0 * level;
+        let direction = if pull { -1.0 } else { 1.0 };
+        // only look at the grains close to the cursor
+        for i in self.grid.query(x, y, radius) {
+            let Some(grain) = self.container.get_mut(i) else {
+                continue;
+            };
+            let center = grain.rect.center();
+            let dx = center.x - x;
+            let dy = center.y - y;
+            let dist = (dx * dx + dy * dy).sqrt().max(1.0);
+            if dist > radius {
+                continue;
+            }
+            // the force is strongest near the cursor
+            let force = strength * (1.0 - dist / radius) * seconds * direction;
+            grain.push(dx / dist * force, dy / dist * force);
+        }
+    }
+
+    /// changes the size of the play area
+    /// grains below the new ground settle on it, none are removed,
+    /// and the settled grains above a lower ground fall to it
+    pub(crate) fn resize(&mut self, width: f32, height: f32) {
+        self.screen_size = (width, height);
+        for grain in self.container.iter_mut() {
+            grain.rect.x = grain.rect.x.min(width - grain.rect.w).max(0.0);
+            if grain.rect.bottom() > height {
+                grain.settle(height);
+            } else if grain.rect.bottom() < height {
+                grain.state = GrainState::Falling;
+            }
+        }
+    }
+
+    /// returns the money a single sand particle sells for, with the boost of the rules
+    /// before the value multiplier of the rules, which applies to whole sales
+    pub(crate) fn sale_value(&self, particle: SandParticle) -> Currency {
+        self.modifiers
+            .boost(particle, self.particle_table.value(particle))
+    }
+
+    /// returns the sand types in the container, sorted by tier
+    /// with their count and the money each grain sells for
+    pub fn contents(&self) -> Vec<(SandParticle, u32, Currency)> {
+        SandParticle::iter()
+            .filter_map(|particle| {
+                let count = self.container.count(particle);
+                (count > 0).then(|| (particle, count, self.sale_value(particle)))
+            })
+            .collect()
+    }
+
+    /// returns the money the sand in the container sells for
+    /// including the extra money of the grains dropped in a combo
+    /// the value multiplier of the rules applies to the whole sale, so cheap grains keep it
+    pub(crate) fn unsold_value(&self) -> Currency {
+        let bonus = self.container.bonus();
+        let value = self
+            .contents()
+            .iter()
+            .map(|(_, count, value)| *value * *count)
+            .sum::<Currency>()
+            + Currency::from_f64(bonus);
+        self.modifiers.value(value)
+    }
+
+    /// checks if the container is full
+    pub fn is_full(&self) -> bool {
+        // container size
+        let size = self.get_size();
+        let amount = self.get_amount();
+        amount >= size
+    }
+
+    /// returns the size of the container based on upgrades
+    pub fn get_size(&self) -> u32 {
+        // the rules can fix the size, whatever is bought
+        if let Some(size) = self.modifiers.container_size {
+            return size;
+        }
+        // base container size
+        let base_size = 25;
+        // amount of upgrades for bigger container.
+        let upgrade = 1 + self.effect_level(UpgradeEffect::ContainerMultiplier);
+        // calculate the total size, scaled by the rules
+        self.modifiers.size(base_size * upgrade)
+    }
+
+    /// returns the current amount of particles in the container
+    pub fn get_amount(&self) -> u32 {
+        // count the amount of particles in the container
+        // the compacted grains still take up their space
+        self.container.amount()
+    }
+
+    /// returns the counters of the player over the whole game
+    pub(crate) fn lifetime(&self) -> Counters {
+        Counters {
+            time: self.total_time,
+            clicks: self.total_clicks,
+            money_earned: self.stats.money_earned,
+            money_spent: self.stats.money_spent,
+            manual_grains: self.stats.manual_grains,
+            auto_grains: self.stats.auto_grains,
+            sales: self.stats.sales,
+        }
+    }
+
+    /// returns the counters of the player in this session
+    pub(crate) fn session(&self) -> Counters {
+        self.lifetime().since(&self.session_start)
+    }
+
+    /// starts counting the session from the current lifetime counters
+    pub(crate) fn reset_session(&mut self) {
+        self.session_start = self.lifetime();
+    }
+
+    /// returns the rates of the player the estimates are based on
+    pub(crate) fn rates(&self) -> Rates {
+        let values: Vec<Currency> = SandParticle::iter()
+            .map(|particle| self.sale_value(particle))
+            .collect();
+        let tier = self.effect_level(UpgradeEffect::ParticleTier);
+        let session = self.session();
+        let seconds = session.time.as_secs_f64();
+        let multiplier = self.modifiers.value_factor();
+        Rates {
+            grain_value: grain_value(&values, tier) * multiplier,
+            next_grain_value: grain_value(&values, tier + 1) * multiplier,
+            income: self.income.per_second().as_f64(),
+            clicks: if seconds > 0.0 {
+                session.clicks as f64 / seconds
+            } else {
+                0.0
+            },
+            combo: self.combo.multiplier(),
+        }
+    }
+
+    /// returns the seconds the next level of an upgrade takes to pay for itself
+    /// at the current rates, Some(None) if there is no income to go by,
+    /// and None for maxed upgrades and the ones that don't earn money by themselves
+    pub(crate) fn payback_time(&self, upgrade: Upgrade) -> Option<Option<u64>> {
+        let cost = self.upgrade_cost(upgrade)?;
+        let gain = income_gain(upgrade.effect(), &self.rates())?;
+        Some(payback(cost, gain))
+    }
+
+    /// returns the current and goal values of an achievement
+    /// the achievements count the lifetime values, never the session ones
+    pub(crate) fn achievement_progress(&self, achievement: Achievement) -> (u128, u128) {
+        let stats = &self.stats;
+        let lifetime = self.lifetime();
+        match achievement {
+            Achievement::FirstGrain => {
+                let grains = lifetime.manual_grains + lifetime.auto_grains;
+                (grains as u128, 1)
+            }
+            Achievement::BusyFingers => (lifetime.clicks as u128, 1000),
+            Achievement::PocketMoney => (lifetime.money_earned.0, 1000),
+            Achievement::Millionaire => (lifetime.money_earned.0, 1_000_000),
+            Achievement::FullHouse => (self.get_amount() as u128, self.get_size() as u128),
+            Achievement::Automation => {
+                let level = self.effect_level(UpgradeEffect::AutoclickInterval);
+                (level as u128, 1)
+            }
+            Achievement::DiamondHands => {
+                let diamonds = *stats.tier_drops.get(&SandParticle::Diamond).unwrap_or(&0);
+                (diamonds as u128, 1)
+            }
+            Achievement::GoldRush => {
+                let gold = *stats.tier_sold.get(&SandParticle::Gold).unwrap_or(&0);
+                (gold as u128, 100)
+            }
+            Achievement::RecordBreaker => (stats.records as u128, 10),
+            Achievement::Dedicated => (lifetime.time.as_secs() as u128, 3600),
+        }
+    }
+
+    /// unlocks the upgrades whose rules are met: enough money earned in the game,
+    /// and a level of the required upgrade if there is one
+    /// unlocked upgrades stay listed, also when the money is spent below their cost
+    /// returns the upgrades that were just unlocked
+    pub(crate) fn check_unlocks(&mut self) -> Vec<Upgrade> {
+        let mut unlocked = Vec::new();
+        for upgrade in upgrade_table().iter() {
+            if self.unlock.contains(&upgrade) || self.modifiers.hides(upgrade) {
+                continue;
+            }
+            let earned = self.stats.money_earned >= upgrade.def().unlock_earned;
+            let required = upgrade
+                .requires()
+                .is_none_or(|required| self.upgrades.get(&required).copied().unwrap_or(0) > 0);
+            // free play lists every upgrade right away
+            if self.modifiers.sandbox || (earned && required) {
+                debug!("unlocked the {:?} upgrade", upgrade);
+                self.unlock.insert(upgrade);
+                unlocked.push(upgrade);
+            }
+        }
+        unlocked
+    }
+
+    /// earns the achievements that reached their goal
+    /// returns the achievements that were just earned
+    pub(crate) fn check_achievements(&mut self) -> Vec<Achievement> {
+        let mut earned = Vec::new();
+        if self.modifiers.sandbox {
+            return earned;
+        }
+        for achievement in Achievement::iter() {
+            if self.achievements.contains_key(&achievement) {
+                continue;
+            }
+            let (current, goal) = self.achievement_progress(achievement);
+            if current >= goal {
+                self.achievements
+                    .insert(achievement, self.total_time.as_secs());
+                earned.push(achievement);
+            }
+        }
+        earned
+    }
+
+    /// returns the summed levels of the upgrades with an effect
+    pub(crate) fn effect_level(&self, effect: UpgradeEffect) -> u32 {
+        self.upgrades
+            .iter()
+            .filter(|(upgrade, _)| upgrade.effect() == effect)
+            .map(|(_, level)| level)
+            .sum()
+    }
+
+    /// returns true if the ParticleTier effect unlocked the sand particle
+    pub(crate) fn is_unlocked(&self, sand: SandParticle) -> bool {
+        sand.level() < self.effect_level(UpgradeEffect::ParticleTier)
+    }
+
+    /// returns the amount of grains a click drops
+    pub fn drop_amount(&self) -> u32 {
+        1 + self.effect_level(UpgradeEffect::DropCount)
+    }
+
+    /// returns the cost of the specified upgrade
+    /// None once it is maxed out
+    pub fn upgrade_cost(&self, upgrade: Upgrade) -> Option<Currency> {
+        self.level_cost(upgrade, *self.upgrades.get(&upgrade).unwrap_or(&0))
+    }
+
+    /// returns the cost of buying the level after n of the specified upgrade
+    /// None past the max level, or past the last particle for the ParticleTier upgrades
+    pub fn level_cost(&self, upgrade: Upgrade, n: u32) -> Option<Currency> {
+        if upgrade.max_level().is_some_and(|max| n >= max) {
+            return None;
+        }
+        let cost = match upgrade.effect() {
+            // the sand particles are priced in the particle table
+            UpgradeEffect::ParticleTier => self.particle_table.cost(n)?,
+            _ => Currency::from_f64(upgrade.cost(n)),
+        };
+        Some(self.modifiers.cost(cost))
+    }
+
+    /// returns how many of the next count levels of the specified upgrade
+    /// are left to buy, and what they cost together
+    pub fn levels_cost(&self, upgrade: Upgrade, count: u32) -> (u32, Currency) {
+        let level = *self.upgrades.get(&upgrade).unwrap_or(&0);
+        let costs: Vec<Currency> = (level..level.saturating_add(count))
+            .map_while(|n| self.level_cost(upgrade, n))
+            .collect();
+        (costs.len() as u32, costs.into_iter().sum())
+    }
+
+    /// returns a random sand particle based on the ParticleTier effect level
+    /// a table without the effect only drops basic sand
+    pub(crate) fn rand_sand(&mut self) -> SandParticle {
+        let level = self.effect_level(UpgradeEffect::ParticleTier);
+        SandParticle::from_roll(self.rng.random(), level)
+    }
+
+    /// checks if the specified upgrade is maxed out
+    /// an upgrade without a next level to buy is, even below its max level
+    pub fn is_maxed(&self, upgrade: Upgrade) -> bool {
+        self.upgrade_cost(upgrade).is_none()
+    }
+
+    /// checks if every upgrade that has a max level is maxed out
+    /// the upgrades that can always grow further don't count
+    pub(crate) fn all_maxed(&self) -> bool {
+        upgrade_table()
+            .iter()
+            .filter(|upgrade| upgrade.max_level().is_some())
+            .all(|upgrade| self.is_maxed(upgrade))
+    }
+
+    /// vacuum functionality
+    /// sells the settled grains near (x, y) right away
+    /// returns the money earned and the grains that were taken
+    pub(crate) fn vacuum(&mut self, x: f32, y: f32) -> (Currency, Vec<Grain>) {
+        // the slots of the taken grains are reused by the next drops
+        let mut taken = Vec::new();
+        for i in self.grid.query(x, y, VACUUM_RADIUS) {
+            let Some(grain) = self.container.get(i) else {
+                continue;
+            };
+            let center = grain.rect.center();
+            let dist = ((center.x - x).powi(2) + (center.y - y).powi(2)).sqrt();
+            if grain.is_done() && dist <= VACUUM_RADIUS {
+                // the grain leaves the container, so it can't be sold twice
+                taken.extend(self.container.remove(i));
+            }
+        }
+        let mut earned = Currency::ZERO;
+        let mut bonus = 0.0;
+        for grain in &taken {
+            earned += self.sale_value(grain.particle);
+            bonus += grain.bonus;
+            *self.stats.tier_sold.entry(grain.particle).or_insert(0) += 1;
+        }
+        // the combo bonus is rounded once, like a sale of the same grains
+        let earned = self.modifiers.value(earned + Currency::from_f64(bonus));
+        if earned > Currency::ZERO {
+            // the vacuum sells a few grains every tick, too many for the debug logs
+            trace!("vacuumed {} grains for {}$", taken.len(), earned);
+            self.deposit(earned);
+            self.stats.money_earned += earned;
+            self.income.record(earned);
+        }
+        (earned, taken)
+    }
+
+    /// converts all sand particles into money
+    /// returns the money earned and the sold grains, which left the container
+    pub fn sell(&mut self) -> (Currency, Vec<Grain>) {
+        // sell all sand particles for money
+        let earned = self.unsold_value();
+        if earned > Currency::ZERO {
+            let breakdown: Vec<String> = self
+                .contents()
+                .iter()
+                .map(|(particle, count, value)| format!("{} {:?} at {}$", count, particle, value))
+                .collect();
+            debug!("converted {} for {}$", breakdown.join(", "), earned);
+        }
+        for (particle, count) in self.container.counts() {
+            *self.stats.tier_sold.entry(*particle).or_insert(0) += *count as u64;
+        }
+        if earned == Currency::MAX {
+            warn!(
+                "the container is worth more than the most money there can be, the sale saturated"
+            );
+        }
+        self.deposit(earned);
+        if earned > Currency::ZERO {
+            let fill = self.get_amount() as f64 / self.get_size().max(1) as f64;
+            let time = self.total_time.as_secs_f32();
+            if self.stats.record_sale(time, earned, fill.min(1.0)) {
+                debug!("new best conversion of {}$", earned);
+            }
+            self.history.push(HistoryEntry::Sale {
+                time: self.total_time.as_secs(),
+                earned,
+            });
+            self.income.record(earned);
+        }
+        // the compacted sand has no grains to fly to the money counter
+        (earned, self.container.take_all())
+    }
+
+    /// adds earned money, which saturates at the largest amount instead of wrapping
+    /// the money past it is lost, which is logged, unless free play has the most there can be
+    pub(crate) fn deposit(&mut self, earned: Currency) {
+        self.money = match self.money.checked_add(earned) {
+            Some(money) => money,
+            None if self.modifiers.sandbox => Currency::MAX,
+            None => {
+                warn!(
+                    "the money saturated at {}$, {}$ of the sale were lost",
+                    Currency::MAX,
+                    earned - (Currency::MAX - self.money)
+                );
+                Currency::MAX
+            }
+        };
+    }
+
+    /// removes all sand from the container without selling it
+    pub(crate) fn clear(&mut self) {
+        self.container.clear();
+        self.autoclicker_blocked = false;
+    }
+
+    /// buys the specified upgrade if affordable and not maxed out
+    /// the upgrades the rules leave out can't be bought
+    /// returns true if the upgrade was bought
+    pub fn buy(&mut self, upgrade: Upgrade) -> bool {
+        if self.modifiers.hides(upgrade) {
+            return false;
+        }
+        let Some(cost) = self.upgrade_cost(upgrade) else {
+            return false;
+        };
+        let Some(left) = self.money.checked_sub(cost) else {
+            return false;
+        };
+        self.money = left;
+        self.stats.money_spent += cost;
+        self.upgrades
+            .entry(upgrade)
+            .and_modify(|count| *count += 1)
+            .or_insert(1);
+        let level = self.upgrades[&upgrade];
+        debug!("bought {:?} level {} for {}$", upgrade, level, cost);
+        self.history.push(HistoryEntry::Purchase {
+            time: self.total_time.as_secs(),
+            upgrade,
+            level,
+            cost,
+        });
+        true
+    }
+
+    /// returns a sample of the progress for the timeline
+    pub(crate) fn sample(&self) -> Sample {
+        Sample {
+            time: self.total_time.as_secs(),
+            money: self.money,
+            grains: self.stats.manual_grains + self.stats.auto_grains,
+            sales: self.stats.sales,
+            levels: upgrade_table()
+                .iter()
+                .filter_map(|upgrade| Some((upgrade, *self.upgrades.get(&upgrade)?)))
+                .collect(),
+        }
+    }
+
+    /// returns a fingerprint of the state, for checking replays
+    /// covers the money, the clicks, the upgrade levels, the sand counts, and the grains
+    pub fn checksum(&self) -> u64 {
+        // FNV-1a, which is the same on every platform and run
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut feed = |bytes: &[u8]| {
+            for byte in bytes {
+                hash = (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+        feed(&self.money.0.to_le_bytes());
+        feed(&self.total_clicks.to_le_bytes());
+        for upgrade in upgrade_table().iter() {
+            feed(
+                &self
+                    .upgrades
+                    .get(&upgrade)
+                    .copied()
+                    .unwrap_or(0)
+                    .to_le_bytes(),
+            );
+        }
+        for particle in SandParticle::iter() {
+            feed(&self.container.count(particle).to_le_bytes());
+        }
+        for grain in self.container.iter() {
+            feed(&grain.rect.x.to_bits().to_le_bytes());
+            feed(&grain.rect.y.to_bits().to_le_bytes());
+            feed(&[grain.particle.level() as u8]);
+        }
+        hash
+    }
+}
+
+/// Streak of fast manual clicks
+/// * count: combo steps built so far, 0 without a combo
+/// * last_click: time of the last click
+#[derive(Debug, Default)]
+pub(crate) struct Combo {
+    pub(crate) count: u32,
+    pub(crate) last_click: Option<f32>,
+}
+
+/// Implementation of methods for the Combo struct
+/// * click: builds the combo if the click came quickly
+/// * update: ends the combo after a pause in the clicks
+/// * multiplier: returns the value multiplier of the dropped sand
+impl Combo {
+    /// builds the combo if the click came quickly after the last one
+    /// slower clicks keep the combo alive without building it
+    pub(crate) fn click(&mut self, now: f32) {
+        self.update(now);
+        if let Some(last) = self.last_click
+            && now - last < COMBO_WINDOW
+        {
+            self.count += 1;
+        }
+        self.last_click = Some(now);
+    }
+
+    /// ends the combo after a pause in the clicks
+    pub(crate) fn update(&mut self, now: f32) {
+        if let Some(last) = self.last_click
+            && now - last > COMBO_DECAY
+        {
+            self.count = 0;
+        }
+    }
+
+    /// returns the value multiplier of the dropped sand
+    pub(crate) fn multiplier(&self) -> f64 {
+        1.0 + (self.count as f64 * COMBO_STEP).min(COMBO_MAX)
+    }
+}
+
+/// Tests for the simulation
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DECAY_TIME, challenge::Challenge, grain::scatter_grains};
+    use quickcheck::quickcheck;
+
+    // SimState tests
+    #[test]
+    fn test_sim_headless_run() {
+        // a head start to afford the pricier upgrades
+        let mut sim = SimState {
+            money: Currency(2_000),
+            ..SimState::default()
+        };
+        let dt = 1.0 / 30.0;
+        for tick in 0..10_000 {
+            // click along the top of the container twice a second
+            if tick % 15 == 0 {
+                let x = 100.0 + (tick / 15 % 6) as f32 * 100.0;
+                sim.click(x, 0.0, sim.drop_amount());
+            }
+            // sell every ten seconds and spend the money
+            if tick % 300 == 299 {
+                sim.sell();
+                for upgrade in [
+                    Upgrade::MORE_PARTICLES,
+                    Upgrade::AUTO_CLICKER,
+                    Upgrade::BIGGER_CONTAINER,
+                ] {
+                    sim.buy(upgrade);
+                }
+            }
+            sim.tick(dt);
+        }
+        // only basic sand drops, so the run is the same every time
+        assert_eq!(sim.money, Currency(135));
+        assert_eq!(sim.get_amount(), 16);
+        assert_eq!(sim.upgrades[&Upgrade::MORE_PARTICLES], 1);
+        assert_eq!(sim.upgrades[&Upgrade::AUTO_CLICKER], 1);
+        assert_eq!(sim.upgrades[&Upgrade::BIGGER_CONTAINER], 15);
+        assert_eq!(sim.total_clicks, 667);
+    }
+    #[test]
+    fn test_sim_money_saturates() {
+        let mut sim = SimState::default();
+        // a full container of sand worth more than the most money there can be
+        let diamond = SandParticle::Diamond.level() as usize;
+        sim.particle_table.defs[diamond].value = Currency::MAX / 4;
+        let size = sim.get_size();
+        sim.add_grains_of(100.0, 100.0, size, Some(SandParticle::Diamond));
+        let (earned, _) = sim.sell();
+        assert_eq!(earned, Currency::MAX);
+        assert_eq!(sim.money, Currency::MAX);
+        // a sale on top of almost all the money stays at the largest amount
+        sim.money = Currency::MAX - Currency(5);
+        sim.add_grains_of(100.0, 100.0, 1, Some(SandParticle::Gold));
+        sim.sell();
+        assert_eq!(sim.money, Currency::MAX);
+    }
+    #[test]
+    fn test_sim_autoclicker_blocked() {
+        let mut sim = SimState::default();
+        sim.upgrades.insert(Upgrade::AUTO_CLICKER, 1);
+        while !sim.is_full() {
+            sim.add_grain(100.0, 100.0);
+        }
+        // a full container stops the autoclicker
+        sim.autoclicker(10.0);
+        assert!(sim.autoclicker_blocked);
+        assert_eq!(sim.stats.auto_grains, 0);
+        // selling the sand starts it again
+        sim.sell();
+        sim.autoclicker(10.0);
+        assert!(!sim.autoclicker_blocked);
+        assert_eq!(sim.stats.auto_grains, 2);
+    }
+    #[test]
+    fn test_sim_spawn_edges() {
+        let mut sim = SimState::default();
+        sim.upgrades.insert(Upgrade::MORE_PARTICLES, 5);
+        sim.click(0.0, 0.0, sim.drop_amount());
+        sim.click(SCREEN_SIZE.0, 0.0, sim.drop_amount());
+        sim.upgrades.insert(Upgrade::AUTO_CLICKER, 10);
+        sim.autoclicker(AUTOCLICK_PERIOD);
+        assert!(sim.get_amount() > 2);
+        // every grain starts fully inside the play area
+        for grain in sim.container.iter() {
+            assert!(grain.rect.left() >= 0.0, "{:?}", grain.rect);
+            assert!(grain.rect.right() <= SCREEN_SIZE.0, "{:?}", grain.rect);
+            assert!(grain.rect.top() >= 0.0, "{:?}", grain.rect);
+        }
+    }
+    #[test]
+    fn test_sim_autoclicker_rate() {
+        for level in [1, 3, 7] {
+            let mut sim = SimState::default();
+            sim.upgrades.insert(Upgrade::AUTO_CLICKER, level);
+            // sixty seconds of ticks, emptied so the container never fills
+            for _ in 0..60 * 30 {
+                sim.autoclicker(1.0 / 30.0);
+                sim.container.clear();
+            }
+            let expected = 60.0 * level as f32 / AUTOCLICK_PERIOD;
+            let clicks = sim.stats.auto_grains as f32 / sim.drop_amount() as f32;
+            assert!(
+                (clicks - expected).abs() <= 1.0,
+                "{} != {}",
+                clicks,
+                expected
+            );
+            assert!(sim.autoclicker_timer < AUTOCLICK_PERIOD / level as f32);
+        }
+    }
+    #[test]
+    fn test_sim_idle() {
+        let mut sim = SimState::default();
+        sim.upgrades.insert(Upgrade::AUTO_CLICKER, 6);
+        // a minute in small idle ticks earns what the clicks would have dropped
+        for _ in 0..60 * 30 {
+            sim.idle(1.0 / 30.0);
+        }
+        let clicks = 60.0 * 6.0 / AUTOCLICK_PERIOD as f64;
+        let value = sim.drop_amount() as f64 * sim.rates().grain_value;
+        let money = sim.money.as_f64();
+        assert!(
+            (money - clicks * value).abs() <= value,
+            "{} != {}",
+            money,
+            clicks * value
+        );
+        assert_eq!(sim.stats.money_earned, sim.money);
+        // without a single grain, or a sale in the average
+        assert!(sim.container.is_empty());
+        assert_eq!(sim.stats.auto_grains, 0);
+        assert_eq!(sim.stats.sale_earned, Currency::ZERO);
+        sim.add_grains(400.0, SCREEN_SIZE.1, 2);
+        let (sold, _) = sim.sell();
+        assert_eq!(sim.stats.average_sale(), sold);
+        assert!(sim.idle_earnings < 1.0);
+    }
+    #[test]
+    fn test_sim_autoclicker_holds_when_full() {
+        let mut sim = SimState::default();
+        sim.upgrades.insert(Upgrade::AUTO_CLICKER, 1);
+        sim.autoclicker(AUTOCLICK_PERIOD / 2.0);
+        while !sim.is_full() {
+            sim.add_grain(100.0, 100.0);
+        }
+        // the time spent full doesn't count toward the next click
+        sim.autoclicker(60.0);
+        assert_eq!(sim.autoclicker_timer, AUTOCLICK_PERIOD / 2.0);
+        sim.sell();
+        sim.autoclicker(AUTOCLICK_PERIOD / 2.0 - 0.5);
+        assert_eq!(sim.stats.auto_grains, 0);
+        sim.autoclicker(0.5);
+        assert_eq!(sim.stats.auto_grains, 1);
+    }
+    #[test]
+    fn test_sim_modifiers() {
+        let mut sim = SimState {
+            money: Currency::MAX,
+            ..SimState::default()
+        };
+        let cost = sim.upgrade_cost(Upgrade::BIGGER_CONTAINER).unwrap();
+        sim.add_grains_of(100.0, 0.0, 3, Some(SandParticle::Sand));
+        let value = sim.unsold_value();
+        sim.modifiers = Modifiers {
+            hidden: HashSet::from([UpgradeEffect::AutoclickInterval]),
+            container_size: Some(10),
+            cost_multiplier: 3.0,
+            value_multiplier: 2.0,
+            ..Modifiers::default()
+        };
+        assert_eq!(sim.upgrade_cost(Upgrade::BIGGER_CONTAINER), Some(cost * 3));
+        assert_eq!(sim.unsold_value(), value * 2);
+        // the container keeps its size, whatever is bought
+        assert!(sim.buy(Upgrade::BIGGER_CONTAINER));
+        assert_eq!(sim.get_size(), 10);
+        // a hidden upgrade is never listed or bought
+        sim.stats.money_earned = Currency::MAX;
+        assert!(!sim.check_unlocks().contains(&Upgrade::AUTO_CLICKER));
+        assert!(!sim.buy(Upgrade::AUTO_CLICKER));
+        // a new game keeps the rules
+        sim.reset();
+        assert_eq!(sim.get_size(), 10);
+    }
+    #[test]
+    fn test_sim_ng_plus_no_overflow() {
+        let base = SimState::default();
+        let top = SandParticle::iter().next_back().unwrap();
+        let mut sim = SimState {
+            modifiers: Modifiers {
+                ng_plus: 3,
+                boosts: HashMap::from([(top, 2.0)]),
+                container_size: Some(1000),
+                ..Modifiers::rewards(&Challenge::iter().collect())
+            },
+            ..SimState::default()
+        };
+        // every level is priced higher than before, and none past the most money there can be
+        for upgrade in upgrade_table().iter() {
+            for level in 0..upgrade.max_level().unwrap_or(100) {
+                let (Some(cost), Some(before)) = (
+                    sim.level_cost(upgrade, level),
+                    base.level_cost(upgrade, level),
+                ) else {
+                    break;
+                };
+                assert!(cost < Currency::MAX);
+                assert!(cost >= before);
+            }
+        }
+        let cost = base.upgrade_cost(Upgrade::BIGGER_CONTAINER).unwrap();
+        assert_eq!(
+            sim.upgrade_cost(Upgrade::BIGGER_CONTAINER),
+            Some(cost * 125)
+        );
+        // a full container of the best sand sells for the scaled value without saturating
+        sim.add_grains_of(100.0, 0.0, 1000, Some(top));
+        assert!(sim.is_full());
+        let value = sim.sale_value(top);
+        assert_eq!(value, base.sale_value(top) * 2);
+        let expected = Currency::from_f64((value * 1000).as_f64() * sim.modifiers.value_factor());
+        assert_eq!(sim.unsold_value(), expected);
+        assert!(sim.rates().grain_value.is_finite());
+        let (earned, _) = sim.sell();
+        assert_eq!(earned, expected);
+        assert!(earned < Currency::MAX);
+        assert_eq!(sim.money, expected);
+    }
+    #[test]
+    fn test_sim_decay() {
+        let mut sim = SimState::default();
+        sim.add_grains_of(100.0, 0.0, 3, Some(SandParticle::Sand));
+        let floor = sim.screen_size.1;
+        for grain in sim.container.iter_mut() {
+            grain.settle(floor);
+        }
+        // without decay the sand lasts forever
+        sim.step_grains(DECAY_TIME * 2.0);
+        sim.decay();
+        assert_eq!(sim.get_amount(), 3);
+        // the settled sand is lost unsold once it is old enough, and is never compacted
+        sim.modifiers.decay_time = Some(DECAY_TIME);
+        sim.max_grains = 1;
+        sim.compact();
+        assert_eq!(sim.compacted_amount(), 0);
+        sim.container[0].age = 0.0;
+        sim.decay();
+        assert_eq!(sim.get_amount(), 1);
+        assert_eq!(sim.container.count(SandParticle::Sand), 1);
+        assert_eq!(sim.stats.decayed, 2);
+        assert_eq!(sim.money, Currency::ZERO);
+        // it decays in the background too
+        sim.idle(DECAY_TIME);
+        assert_eq!(sim.get_amount(), 0);
+        assert_eq!(sim.stats.decayed, 3);
+    }
+    #[test]
+    fn test_sim_settled_grain_stays_put() {
+        let mut sim = SimState::with_seed(1);
+        sim.add_grains(400.0, 0.0, 1);
+        while !sim.container[0].is_done() {
+            sim.tick(1.0 / 30.0);
+        }
+        // after the step that landed it, the grain is drawn at the same spot every frame
+        sim.tick(1.0 / 30.0);
+        assert_eq!(
+            sim.container[0].position(0.0),
+            sim.container[0].position(1.0)
+        );
+    }
+    #[test]
+    fn test_sim_unlocks() {
+        // money that wasn't earned doesn't unlock anything
+        let mut sim = SimState {
+            money: Currency(1_000_000),
+            ..SimState::default()
+        };
+        sim.tick(1.0 / 30.0);
+        assert!(sim.unlock.is_empty());
+        // earning enough lists the upgrade on the next tick, without any window
+        sim.stats.money_earned = Upgrade::BIGGER_CONTAINER.def().unlock_earned;
+        sim.tick(1.0 / 30.0);
+        assert!(sim.unlock.contains(&Upgrade::BIGGER_CONTAINER));
+        assert!(!sim.unlock.contains(&Upgrade::AUTO_CLICKER));
+        assert!(
+            sim.events
+                .contains(&SimEvent::Unlocked(Upgrade::BIGGER_CONTAINER))
+        );
+        // spending the money keeps it listed, and it is only announced once
+        sim.money = Currency::ZERO;
+        sim.tick(1.0 / 30.0);
+        assert!(sim.unlock.contains(&Upgrade::BIGGER_CONTAINER));
+        assert!(
+            !sim.events
+                .iter()
+                .any(|event| matches!(event, SimEvent::Unlocked(_)))
+        );
+    }
+    #[test]
+    fn test_sim_tick_events() {
+        // no low gravity event wakes the grain up again
+        let mut sim = SimState {
+            low_gravity_chance: 0.0,
+            ..Default::default()
+        };
+        sim.click(100.0, 100.0, 1);
+        // the grain lands once, and the first grain earns an achievement
+        let mut landed = 0;
+        for _ in 0..300 {
+            sim.tick(1.0 / 30.0);
+            for event in &sim.events {
+                match event {
+                    SimEvent::Landed(particle) => {
+                        assert_eq!(*particle, SandParticle::Sand);
+                        landed += 1;
+                    }
+                    SimEvent::Achievement(achievement) => {
+                        assert_eq!(*achievement, Achievement::FirstGrain);
+                    }
+                    SimEvent::Unlocked(upgrade) => panic!("{:?} unlocked without money", upgrade),
+                }
+            }
+        }
+        assert_eq!(landed, 1);
+        assert!(sim.achievements.contains_key(&Achievement::FirstGrain));
+    }
+    #[test]
+    fn test_sim_payback_time() {
+        let mut sim = SimState::default();
+        let rates = sim.rates();
+        assert_eq!(
+            rates.grain_value,
+            sim.sale_value(SandParticle::Sand).as_f64()
+        );
+        assert_eq!(rates.clicks, 0.0);
+        // the autoclicker earns without any income, the better sand doesn't
+        let gain = rates.grain_value / AUTOCLICK_PERIOD as f64;
+        let cost = sim.upgrade_cost(Upgrade::AUTO_CLICKER).unwrap();
+        assert_eq!(
+            sim.payback_time(Upgrade::AUTO_CLICKER),
+            Some(payback(cost, gain))
+        );
+        assert_eq!(sim.payback_time(Upgrade::PARTICLE_TIER), Some(None));
+        assert_eq!(sim.payback_time(Upgrade::BIGGER_CONTAINER), None);
+        // clicking by hand makes more grains per click pay off
+        sim.click(100.0, 100.0, 1);
+        sim.tick(2.0);
+        assert_eq!(sim.rates().clicks, 0.5);
+        assert!(matches!(
+            sim.payback_time(Upgrade::MORE_PARTICLES),
+            Some(Some(_))
+        ));
+    }
+    #[test]
+    fn test_sim_timeline() {
+        let mut sim = SimState::default();
+        sim.click(100.0, 100.0, 3);
+        sim.money = Currency(250);
+        // a sample is taken once per minute of play
+        for _ in 0..59 {
+            sim.tick(1.0);
+        }
+        assert!(sim.timeline.samples.is_empty());
+        sim.tick(1.0);
+        sim.tick(1.0);
+        assert_eq!(sim.timeline.samples.len(), 1);
+        let sample = &sim.timeline.samples[0];
+        assert_eq!(sample.time, 60);
+        assert_eq!(sample.money, Currency(250));
+        assert_eq!(sample.grains, 3);
+        assert_eq!(sample.levels, [(Upgrade::PARTICLE_TIER, 1)]);
+        sim.tick(60.0);
+        assert_eq!(sim.timeline.samples.len(), 2);
+    }
+
+    #[test]
+    fn test_sim_seeded_runs() {
+        // drop every kind of sand, with the autoclicker spreading it around
+        let run = |seed| {
+            let mut sim = SimState::with_seed(seed);
+            sim.upgrades.insert(Upgrade::PARTICLE_TIER, 5);
+            sim.upgrades.insert(Upgrade::MORE_PARTICLES, 3);
+            sim.upgrades.insert(Upgrade::AUTO_CLICKER, 2);
+            for tick in 0..300 {
+                if tick % 10 == 0 {
+                    sim.click(400.0, 0.0, sim.drop_amount());
+                }
+                sim.tick(1.0 / 30.0);
+            }
+            sim
+        };
+        let grains = |sim: &SimState| {
+            sim.container
+                .iter()
+                .map(|grain| {
+                    (
+                        grain.particle,
+                        grain.rect.x.to_bits(),
+                        grain.rect.y.to_bits(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        // the same seed gives the same sand in the same spots
+        let (first, second) = (run(42), run(42));
+        assert_eq!(first.container.counts(), second.container.counts());
+        assert_eq!(grains(&first), grains(&second));
+        // another seed gives other sand
+        assert_ne!(grains(&first), grains(&run(7)));
+    }
+    #[test]
+    fn test_sim_cost_never_drops() {
+        // every next level costs at least as much as the one before
+        fn property(level: u16) -> bool {
+            let sim = SimState::default();
+            let n = level as u32;
+            upgrade_table()
+                .iter()
+                .all(|upgrade| match upgrade.max_level() {
+                    Some(max) if n + 1 >= max => true,
+                    _ => sim.level_cost(upgrade, n) <= sim.level_cost(upgrade, n + 1),
+                })
+        }
+        quickcheck(property as fn(u16) -> bool);
+    }
+    #[test]
+    fn test_sim_bulk_cost_matches_single_buys() {
+        // buying levels one at a time costs what the bulk price said
+        fn property(start: u8, count: u8) -> bool {
+            upgrade_table().iter().all(|upgrade| {
+                let mut sim = SimState {
+                    money: Currency::MAX,
+                    ..SimState::default()
+                };
+                let start = upgrade
+                    .max_level()
+                    .map_or(start as u32, |max| max.min(start as u32));
+                sim.upgrades.insert(upgrade, start);
+                let (levels, cost) = sim.levels_cost(upgrade, count as u32);
+                let bought = (0..count).filter(|_| sim.buy(upgrade)).count() as u32;
+                bought == levels && Currency::MAX - sim.money == cost
+            })
+        }
+        quickcheck(property as fn(u8, u8) -> bool);
+    }
+    #[test]
+    fn test_sim_sale_pays_the_container_once() {
+        // a sale earns what the container holds, and nothing more when sold again
+        fn property(seed: u64, tier: u8, clicks: Vec<(u16, bool)>) -> bool {
+            let mut sim = SimState::with_seed(seed);
+            let tier = 1 + tier as u32 % SandParticle::max_level();
+            sim.upgrades.insert(Upgrade::PARTICLE_TIER, tier);
+            let mut dropped = 0;
+            for (x, wait) in clicks {
+                let x = x as f32 % SCREEN_SIZE.0;
+                dropped += sim.click(x, 0.0, sim.drop_amount());
+                if wait {
+                    sim.tick(0.5);
+                }
+            }
+            let (money, unsold) = (sim.money, sim.unsold_value());
+            let (earned, sold) = sim.sell();
+            let (again, _) = sim.sell();
+            earned == unsold
+                && sim.money == money + earned
+                && sold.len() as u32 == dropped
+                && again == Currency::ZERO
+        }
+        quickcheck(property as fn(u64, u8, Vec<(u16, bool)>) -> bool);
+    }
+    #[test]
+    fn test_sim_vacuum_pays_like_a_sale() {
+        let mut sim = SimState::with_seed(1);
+        for x in [396.0, 400.0, 404.0] {
+            sim.add_grains(x, SCREEN_SIZE.1, 1);
+        }
+        for grain in sim.container.iter_mut() {
+            grain.bonus = 0.4;
+        }
+        sim.step_grains(1.0 / 30.0);
+        sim.index_grains();
+        let unsold = sim.unsold_value();
+        // three bonuses of 0.4 are worth a coin together, not rounded away one by one
+        let (earned, taken) = sim.vacuum(400.0, SCREEN_SIZE.1);
+        assert_eq!(taken.len(), 3);
+        assert_eq!(earned, unsold);
+        assert_eq!(sim.get_amount(), 0);
+    }
+    #[test]
+    fn test_sim_vacuum_average_sale() {
+        let mut sim = SimState::with_seed(1);
+        sim.add_grains(400.0, SCREEN_SIZE.1, 3);
+        sim.step_grains(1.0 / 30.0);
+        sim.index_grains();
+        let (vacuumed, _) = sim.vacuum(400.0, SCREEN_SIZE.1);
+        assert!(vacuumed > Currency::ZERO);
+        // the vacuumed money is earned, but only the conversion is a sale
+        sim.add_grains(400.0, SCREEN_SIZE.1, 2);
+        let (sold, _) = sim.sell();
+        assert_eq!(sim.stats.sales, 1);
+        assert_eq!(sim.stats.money_earned, vacuumed + sold);
+        assert_eq!(sim.stats.average_sale(), sold);
+    }
+    #[test]
+    fn test_sim_parallel_physics() {
+        let amount = PARALLEL_GRAINS * 2;
+        let mut sim = SimState::with_seed(1);
+        sim.container = scatter_grains(amount, GRAIN_SIZE, SCREEN_SIZE)
+            .into_iter()
+            .collect();
+        let mut expected = scatter_grains(amount, GRAIN_SIZE, SCREEN_SIZE);
+        let mut landed = Vec::new();
+        for _ in 0..90 {
+            sim.step_grains(1.0 / 30.0);
+            // the same steps, one grain after another
+            for grain in &mut expected {
+                if !grain.is_done() {
+                    grain.update(1.0 / 30.0, GRAVITY, SCREEN_SIZE);
+                    if grain.is_done() {
+                        landed.push(SimEvent::Landed(grain.particle));
+                    }
+                }
+            }
+        }
+        // the threads move the grains exactly like a single one does
+        assert!(!landed.is_empty());
+        assert_eq!(sim.events, landed);
+        let positions: Vec<_> = sim
+            .container
+            .iter()
+            .map(|grain| (grain.rect.x.to_bits(), grain.rect.y.to_bits()))
+            .collect();
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|grain| (grain.rect.x.to_bits(), grain.rect.y.to_bits()))
+            .collect();
+        assert_eq!(positions, expected);
+    }
+    #[test]
+    fn test_sim_pooled_grains() {
+        let mut sim = SimState::with_seed(3);
+        sim.upgrades.insert(Upgrade::MORE_PARTICLES, 5);
+        sim.upgrades.insert(Upgrade::BIGGER_CONTAINER, 20);
+        let size = sim.get_size();
+        let (mut dropped, mut sold) = (0, 0);
+        for tick in 0..5_000 {
+            dropped += sim.click((tick % 80) as f32 * 10.0, 0.0, sim.drop_amount());
+            sim.tick(1.0 / 30.0);
+            // vacuum some of the settled grains, leaving free slots between the others
+            if tick % 7 == 0 {
+                sold += sim.vacuum((tick % 80) as f32 * 10.0, SCREEN_SIZE.1).1.len() as u32;
+            }
+            if tick % 100 == 99 {
+                sold += sim.sell().1.len() as u32;
+            }
+            // the counts and the capacity checks match the grains in the container
+            let counted: u32 = sim.container.counts().values().sum();
+            assert_eq!(sim.get_amount(), counted);
+            assert_eq!(sim.container.iter().count() as u32, counted);
+            assert_eq!(sim.is_full(), counted >= size);
+            assert_eq!(dropped - sold, counted);
+        }
+        assert!(sold > 0);
+        // the pool never grows past the container, the sold slots are reused
+        assert!(sim.container.pool().slots.len() as u32 <= size);
+    }
+    #[test]
+    fn test_sim_compacted_grains() {
+        let mut sim = SimState::with_seed(5);
+        sim.upgrades.insert(Upgrade::BIGGER_CONTAINER, 3);
+        sim.max_grains = 20;
+        let dropped = sim.add_grains(400.0, SCREEN_SIZE.1, 60);
+        for _ in 0..300 {
+            sim.step_grains(1.0 / 30.0);
+        }
+        assert!(sim.container.iter().all(Grain::is_done));
+        // the oldest settled grains are compacted first
+        sim.compact();
+        assert_eq!(sim.container.len(), 20);
+        assert_eq!(sim.compacted_amount(), dropped - 20);
+        let newest = sim.container.pool().dropped - 20;
+        assert!(
+            sim.container
+                .indexed()
+                .all(|(slot, _)| sim.container.pool().order[slot] > newest)
+        );
+        // a falling grain is never compacted, the settled ones make room for it
+        let falling = sim.add_grains(400.0, 0.0, 5);
+        let dropped = dropped + falling;
+        let unsold = sim.unsold_value();
+        sim.compact();
+        assert_eq!(sim.container.len(), 20);
+        assert_eq!(
+            sim.container
+                .iter()
+                .filter(|grain| !grain.is_done())
+                .count() as u32,
+            falling
+        );
+        // the compacted sand still takes up space and sells like the grains
+        assert_eq!(sim.get_amount(), dropped);
+        assert_eq!(sim.unsold_value(), unsold);
+        let (earned, sold) = sim.sell();
+        assert_eq!(earned, unsold);
+        assert_eq!(sold.len(), 20);
+        assert_eq!(sim.get_amount(), 0);
+        assert_eq!(sim.compacted_amount(), 0);
+        assert_eq!(sim.stats.tier_sold.values().sum::<u64>(), dropped as u64);
+    }
+    #[test]
+    fn test_sim_redropping_creates_no_money() {
+        // dropping as much sand again after a sale earns no more than the grains are worth
+        fn property(seed: u64, tier: u8, clicks: u8) -> bool {
+            let mut sim = SimState::with_seed(seed);
+            let tier = 1 + tier as u32 % SandParticle::max_level();
+            sim.upgrades.insert(Upgrade::PARTICLE_TIER, tier);
+            let best = SandParticle::from_u32(tier - 1).unwrap();
+            let worth = sim.particle_table.value(best).as_f64() * (1.0 + COMBO_MAX);
+            let mut dropped = 0;
+            let mut earned = Currency::ZERO;
+            for _ in 0..2 {
+                for i in 0..clicks {
+                    dropped += sim.click(i as f32 * 3.0, 0.0, sim.drop_amount());
+                }
+                earned += sim.sell().0;
+            }
+            // each of the two sales rounds the combo bonus once
+            earned.as_f64() <= dropped as f64 * worth + 1.0
+        }
+        quickcheck(property as fn(u64, u8, u8) -> bool);
+    }
+
+    // Combo tests
+    #[test]
+    fn test_combo_build() {
+        let mut combo = Combo::default();
+        combo.click(0.0);
+        assert_eq!(combo.count, 0);
+        combo.click(0.2);
+        combo.click(0.4);
+        assert_eq!(combo.count, 2);
+        assert_eq!(combo.multiplier(), 1.04);
+        // slower clicks keep the combo without building it
+        combo.click(0.9);
+        assert_eq!(combo.count, 2);
+        // the bonus is capped
+        for i in 0..100 {
+            combo.click(1.0 + i as f32 * 0.1);
+        }
+        assert_eq!(combo.multiplier(), 1.0 + COMBO_MAX);
+    }
+    #[test]
+    fn test_combo_decay() {
+        let mut combo = Combo::default();
+        combo.click(0.0);
+        combo.click(0.1);
+        combo.update(1.0);
+        assert_eq!(combo.count, 1);
+        combo.update(1.2);
+        assert_eq!(combo.count, 0);
+        // a click after the pause starts over
+        combo.click(5.0);
+        combo.click(5.1);
+        assert_eq!(combo.count, 1);
+    }
+}
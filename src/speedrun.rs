@@ -0,0 +1,225 @@
+//! Speedrun timer with splits at the goals of the game
+
+use crate::{
+    lang::tr, particle::SandParticle, settings::parse_enum, sim::SimState, upgrade::UpgradeEffect,
+};
+use std::{collections::HashMap, time::Duration};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Goals a speedrun is split at, in the order they are usually reached
+/// * FirstQuartz: Unlock Quartz.
+/// * FirstAutoClicker: Buy the first AutoClicker.
+/// * Millionaire: Earn 1000000$ over the lifetime.
+/// * AllMaxed: Max out every upgrade that has a max level.
+#[derive(Hash, Eq, PartialEq, Debug, EnumIter, Clone, Copy)]
+pub(crate) enum Split {
+    FirstQuartz,
+    FirstAutoClicker,
+    Millionaire,
+    AllMaxed,
+}
+
+/// Implementation of methods for the Split enum
+/// * name: returns the display name of the split
+/// * reached: checks if the simulation reached the goal of the split
+impl Split {
+    /// returns the display name of the split
+    pub(crate) fn name(&self) -> &'static str {
+        tr(match self {
+            Split::FirstQuartz => "split-first-quartz",
+            Split::FirstAutoClicker => "split-first-autoclicker",
+            Split::Millionaire => "split-millionaire",
+            Split::AllMaxed => "split-all-maxed",
+        })
+    }
+
+    /// checks if the simulation reached the goal of the split
+    pub(crate) fn reached(&self, sim: &SimState) -> bool {
+        match self {
+            Split::FirstQuartz => sim.is_unlocked(SandParticle::Quartz),
+            Split::FirstAutoClicker => sim.effect_level(UpgradeEffect::AutoclickInterval) > 0,
+            Split::Millionaire => sim.lifetime().money_earned.0 >= 1_000_000,
+            Split::AllMaxed => sim.all_maxed(),
+        }
+    }
+}
+
+/// Speedrun of the current game
+/// * elapsed: real time since the run started, the pause menu doesn't stop it
+/// * splits: time each goal was reached in this run, None if it was reached before the run
+/// * bests: personal best time of each split, from before this run
+#[derive(Debug, Default)]
+pub(crate) struct Speedrun {
+    pub(crate) elapsed: Duration,
+    pub(crate) splits: HashMap<Split, Option<Duration>>,
+    pub(crate) bests: HashMap<Split, Duration>,
+}
+
+/// Implementation of methods for the Speedrun struct
+/// * start: starts a new run from the current progress
+/// * update: runs the timer and records the reached splits
+/// * delta: returns how far ahead or behind the personal best a split was
+/// * personal_bests: returns the personal bests with the faster splits of this run
+/// * to_save: returns the personal bests as save file lines
+/// * load_line: reads a save file line, returns false if it isn't a personal best
+impl Speedrun {
+    /// starts a new run from the current progress
+    /// the goals that were already reached aren't timed
+    pub(crate) fn start(&mut self, sim: &SimState) {
+        // keep the personal bests this run set
+        self.bests = self.personal_bests();
+        self.elapsed = Duration::ZERO;
+        self.splits = Split::iter()
+            .filter(|split| split.reached(sim))
+            .map(|split| (split, None))
+            .collect();
+    }
+
+    /// runs the timer and records the reached splits
+    /// returns the splits that were just reached
+    pub(crate) fn update(&mut self, dt: Duration, sim: &SimState) -> Vec<Split> {
+        self.elapsed += dt;
+        let reached: Vec<Split> = Split::iter()
+            .filter(|split| !self.splits.contains_key(split) && split.reached(sim))
+            .collect();
+        for split in &reached {
+            self.splits.insert(*split, Some(self.elapsed));
+        }
+        reached
+    }
+
+    /// returns how far ahead or behind the personal best a split was
+    /// in seconds, negative when it was faster
+    pub(crate) fn delta(&self, split: Split) -> Option<f64> {
+        let time = (*self.splits.get(&split)?)?;
+        let best = self.bests.get(&split)?;
+        Some(time.as_secs_f64() - best.as_secs_f64())
+    }
+
+    /// returns the personal bests with the faster splits of this run
+    fn personal_bests(&self) -> HashMap<Split, Duration> {
+        let mut bests = self.bests.clone();
+        for (split, time) in &self.splits {
+            if let Some(time) = time
+                && bests.get(split).is_none_or(|best| time < best)
+            {
+                bests.insert(*split, *time);
+            }
+        }
+        bests
+    }
+
+    /// returns the personal bests as save file lines
+    pub(crate) fn to_save(&self) -> Vec<String> {
+        let bests = self.personal_bests();
+        Split::iter()
+            .filter_map(|split| {
+                let best = bests.get(&split)?;
+                Some(format!("best.{:?}={}", split, best.as_secs_f64()))
+            })
+            .collect()
+    }
+
+    /// reads a save file line, returns false if it isn't a personal best
+    pub(crate) fn load_line(&mut self, key: &str, value: &str) -> bool {
+        let Some(name) = key.strip_prefix("best.") else {
+            return false;
+        };
+        if let (Some(split), Ok(secs)) = (parse_enum(name), value.parse::<f64>())
+            && secs.is_finite()
+            && secs >= 0.0
+        {
+            self.bests.insert(split, Duration::from_secs_f64(secs));
+        }
+        true
+    }
+}
+
+/// returns the time of a split, like 1:05.30
+/// the hours are only shown when there are some
+pub(crate) fn format_split(time: Duration) -> String {
+    let centis = time.as_millis() / 10;
+    let (hours, minutes) = (centis / 360_000, centis / 6000 % 60);
+    let (secs, centis) = (centis / 100 % 60, centis % 100);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}.{:02}", hours, minutes, secs, centis)
+    } else {
+        format!("{}:{:02}.{:02}", minutes, secs, centis)
+    }
+}
+
+/// returns a difference to the personal best, like +0:01.50
+pub(crate) fn format_delta(delta: f64) -> String {
+    let sign = if delta < 0.0 { '-' } else { '+' };
+    format!(
+        "{}{}",
+        sign,
+        format_split(Duration::from_secs_f64(delta.abs()))
+    )
+}
+
+/// Tests for the speedrun timer
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        currency::Currency,
+        upgrade::{Upgrade, upgrade_table},
+    };
+
+    // Split tests
+    #[test]
+    fn test_split_reached() {
+        let mut sim = SimState::default();
+        assert!(Split::iter().all(|split| !split.reached(&sim)));
+        sim.upgrades.insert(Upgrade::AUTO_CLICKER, 1);
+        sim.stats.money_earned = Currency(1_000_000);
+        assert!(Split::FirstAutoClicker.reached(&sim));
+        assert!(Split::Millionaire.reached(&sim));
+        assert!(!Split::AllMaxed.reached(&sim));
+        for upgrade in upgrade_table().iter() {
+            if let Some(max) = upgrade.max_level() {
+                sim.upgrades.insert(upgrade, max);
+            }
+        }
+        assert!(Split::FirstQuartz.reached(&sim));
+        assert!(Split::AllMaxed.reached(&sim));
+    }
+
+    // Speedrun tests
+    #[test]
+    fn test_speedrun_splits() {
+        let mut sim = SimState::default();
+        sim.stats.money_earned = Currency(1_000_000);
+        let mut speedrun = Speedrun::default();
+        speedrun.load_line("best.FirstAutoClicker", "20");
+        speedrun.start(&sim);
+        // goals reached before the run aren't timed
+        assert_eq!(speedrun.splits.get(&Split::Millionaire), Some(&None));
+        assert!(speedrun.update(Duration::from_secs(15), &sim).is_empty());
+        sim.upgrades.insert(Upgrade::AUTO_CLICKER, 1);
+        let reached = speedrun.update(Duration::from_secs(1), &sim);
+        assert_eq!(reached, [Split::FirstAutoClicker]);
+        assert_eq!(speedrun.delta(Split::FirstAutoClicker), Some(-4.0));
+        assert_eq!(speedrun.delta(Split::Millionaire), None);
+        // the faster split becomes the personal best
+        assert_eq!(speedrun.to_save(), ["best.FirstAutoClicker=16"]);
+        let mut loaded = Speedrun::default();
+        assert!(loaded.load_line("best.FirstAutoClicker", "16"));
+        assert!(loaded.load_line("best.Nothing", "5"));
+        assert!(loaded.load_line("best.AllMaxed", "-5"));
+        assert!(!loaded.load_line("money", "5"));
+        assert_eq!(
+            loaded.bests,
+            HashMap::from([(Split::FirstAutoClicker, Duration::from_secs(16))])
+        );
+    }
+    #[test]
+    fn test_speedrun_format() {
+        assert_eq!(format_split(Duration::from_millis(65_304)), "1:05.30");
+        assert_eq!(format_split(Duration::from_secs(3723)), "1:02:03.00");
+        assert_eq!(format_delta(-1.5), "-0:01.50");
+        assert_eq!(format_delta(12.0), "+0:12.00");
+    }
+}
@@ -0,0 +1,826 @@
+//! Statistics, achievements, income and history of the player
+
+use crate::{
+    GRAPH_INTERVAL, GRAPH_POINTS, HEATMAP_GRID, HISTORY_SIZE, INCOME_WINDOW, LAST_SALES,
+    TIMELINE_SIZE, currency::Currency, lang::tr, particle::SandParticle, settings::parse_enum,
+    upgrade::Upgrade,
+};
+use ggez::graphics::Rect;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+use strum_macros::EnumIter;
+
+/// Achievements the player can earn
+/// * FirstGrain: Drop the first grain.
+/// * BusyFingers: Click 1000 times.
+/// * PocketMoney: Earn 1000$.
+/// * Millionaire: Earn 1000000$.
+/// * FullHouse: Fill the container.
+/// * Automation: Buy an autoclicker.
+/// * DiamondHands: Drop a diamond grain.
+/// * GoldRush: Sell 100 gold grains.
+/// * RecordBreaker: Beat the best conversion 10 times.
+/// * Dedicated: Play for an hour.
+#[derive(Hash, Eq, PartialEq, Debug, EnumIter, Clone, Copy)]
+pub(crate) enum Achievement {
+    FirstGrain,
+    BusyFingers,
+    PocketMoney,
+    Millionaire,
+    FullHouse,
+    Automation,
+    DiamondHands,
+    GoldRush,
+    RecordBreaker,
+    Dedicated,
+}
+
+/// Implementation of methods for the Achievement enum
+/// * name: returns the display name of the achievement
+/// * desc: returns what the player has to do for the achievement
+/// * icon: returns the emoji shown next to the achievement
+impl Achievement {
+    /// returns the display name of the achievement
+    pub(crate) fn name(&self) -> &'static str {
+        tr(match self {
+            Achievement::FirstGrain => "achievement-first-grain",
+            Achievement::BusyFingers => "achievement-busy-fingers",
+            Achievement::PocketMoney => "achievement-pocket-money",
+            Achievement::Millionaire => "achievement-millionaire",
+            Achievement::FullHouse => "achievement-full-house",
+            Achievement::Automation => "achievement-automation",
+            Achievement::DiamondHands => "achievement-diamond-hands",
+            Achievement::GoldRush => "achievement-gold-rush",
+            Achievement::RecordBreaker => "achievement-record-breaker",
+            Achievement::Dedicated => "achievement-dedicated",
+        })
+    }
+
+    /// returns what the player has to do for the achievement
+    pub(crate) fn desc(&self) -> &'static str {
+        tr(match self {
+            Achievement::FirstGrain => "achievement-first-grain-desc",
+            Achievement::BusyFingers => "achievement-busy-fingers-desc",
+            Achievement::PocketMoney => "achievement-pocket-money-desc",
+            Achievement::Millionaire => "achievement-millionaire-desc",
+            Achievement::FullHouse => "achievement-full-house-desc",
+            Achievement::Automation => "achievement-automation-desc",
+            Achievement::DiamondHands => "achievement-diamond-hands-desc",
+            Achievement::GoldRush => "achievement-gold-rush-desc",
+            Achievement::RecordBreaker => "achievement-record-breaker-desc",
+            Achievement::Dedicated => "achievement-dedicated-desc",
+        })
+    }
+
+    /// returns the emoji shown next to the achievement
+    pub(crate) fn icon(&self) -> &str {
+        match self {
+            Achievement::FirstGrain => "🏖",
+            Achievement::BusyFingers => "👆",
+            Achievement::PocketMoney => "💰",
+            Achievement::Millionaire => "🏦",
+            Achievement::FullHouse => "📦",
+            Achievement::Automation => "🤖",
+            Achievement::DiamondHands => "💎",
+            Achievement::GoldRush => "🏆",
+            Achievement::RecordBreaker => "📈",
+            Achievement::Dedicated => "⏰",
+        }
+    }
+}
+
+/// Lifetime statistics of the player
+/// * money_earned: money earned over the whole game, the basis of the money achievements
+/// * sale_earned: money earned from conversions only, the basis of the average sale
+/// * money_spent: money spent on upgrades and themes over the whole game
+/// * manual_grains: grains dropped by clicking
+/// * auto_grains: grains dropped by the autoclicker
+/// * tier_drops: grains dropped of each sand particle
+/// * tier_sold: grains sold of each sand particle
+/// * best_sale: most money earned in a single conversion
+/// * records: number of conversions that beat the best one before them
+/// * sales: number of conversions that earned money
+/// * fill_total: summed container fill of the conversions, in percent
+/// * fill_sales: number of conversions the container fill was counted for
+/// * recent_sales: time and money of the conversions in the last minute
+/// * last_sales: play time in seconds and money of the last conversions, oldest first
+/// * decayed: grains lost to the sand decay of hardcore mode
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub money_earned: Currency,
+    pub sale_earned: Currency,
+    pub money_spent: Currency,
+    pub manual_grains: u64,
+    pub auto_grains: u64,
+    pub tier_drops: HashMap<SandParticle, u64>,
+    pub tier_sold: HashMap<SandParticle, u64>,
+    pub best_sale: Currency,
+    pub records: u64,
+    pub sales: u64,
+    pub fill_total: f64,
+    pub fill_sales: u64,
+    pub recent_sales: VecDeque<(f32, Currency)>,
+    pub last_sales: VecDeque<(u64, Currency)>,
+    pub decayed: u64,
+}
+
+/// Implementation of methods for the Stats struct
+/// * record_sale: adds a conversion to the statistics
+/// * average_sale: returns the average money earned per conversion
+/// * average_fill: returns the average container fill at the conversions
+/// * money_per_minute: returns the money earned in the last minute
+/// * to_save: returns the statistics as save file lines
+/// * load_line: reads a save file line, returns false if it isn't a statistic
+impl Stats {
+    /// adds a conversion to the statistics
+    /// time is the total play time in seconds, fill is the container fill from 0 to 1
+    /// returns true if the conversion beat the best one, the first one doesn't count
+    pub(crate) fn record_sale(&mut self, time: f32, earned: Currency, fill: f64) -> bool {
+        let record = self.best_sale > Currency::ZERO && earned > self.best_sale;
+        if record {
+            self.records += 1;
+        }
+        self.money_earned += earned;
+        self.sale_earned += earned;
+        self.best_sale = self.best_sale.max(earned);
+        self.sales += 1;
+        self.fill_total += fill * 100.0;
+        self.fill_sales += 1;
+        if self.last_sales.len() >= LAST_SALES {
+            self.last_sales.pop_front();
+        }
+        self.last_sales.push_back((time as u64, earned));
+        self.recent_sales.push_back((time, earned));
+        // forget the sales older than a minute
+        while let Some(&(sold, _)) = self.recent_sales.front() {
+            if time - sold <= 60.0 {
+                break;
+            }
+            self.recent_sales.pop_front();
+        }
+        record
+    }
+
+    /// returns the average money earned per conversion
+    /// the vacuum and the idle autoclicker earn money without a conversion, so they don't count
+    pub(crate) fn average_sale(&self) -> Currency {
+        self.sale_earned / self.sales
+    }
+
+    /// returns the average container fill at the conversions, in percent
+    /// None before the first conversion
+    pub(crate) fn average_fill(&self) -> Option<f64> {
+        (self.fill_sales > 0).then(|| self.fill_total / self.fill_sales as f64)
+    }
+
+    /// returns the money earned in the last minute
+    /// time is the total play time in seconds
+    pub(crate) fn money_per_minute(&self, time: f32) -> Currency {
+        self.recent_sales
+            .iter()
+            .filter(|(sold, _)| time - sold <= 60.0)
+            .map(|(_, earned)| earned)
+            .sum()
+    }
+
+    /// returns the statistics as save file lines
+    pub(crate) fn to_save(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("money_earned={}", self.money_earned),
+            format!("sale_earned={}", self.sale_earned),
+            format!("money_spent={}", self.money_spent),
+            format!("manual_grains={}", self.manual_grains),
+            format!("auto_grains={}", self.auto_grains),
+            format!("best_sale={}", self.best_sale),
+            format!("records={}", self.records),
+            format!("sales={}", self.sales),
+            format!("fill_total={}", self.fill_total),
+            format!("fill_sales={}", self.fill_sales),
+            format!("decayed={}", self.decayed),
+        ];
+        for (sand, count) in &self.tier_drops {
+            lines.push(format!("dropped.{:?}={}", sand, count));
+        }
+        for (sand, count) in &self.tier_sold {
+            lines.push(format!("sold.{:?}={}", sand, count));
+        }
+        for (time, earned) in &self.last_sales {
+            lines.push(format!("last_sale={},{}", time, earned));
+        }
+        lines
+    }
+
+    /// reads a save file line, returns false if it isn't a statistic
+    pub(crate) fn load_line(&mut self, key: &str, value: &str) -> bool {
+        match key {
+            "money_earned" => self.money_earned = value.parse().unwrap_or(self.money_earned),
+            "sale_earned" => self.sale_earned = value.parse().unwrap_or(self.sale_earned),
+            "money_spent" => self.money_spent = value.parse().unwrap_or(self.money_spent),
+            "manual_grains" => self.manual_grains = value.parse().unwrap_or(self.manual_grains),
+            "auto_grains" => self.auto_grains = value.parse().unwrap_or(self.auto_grains),
+            "best_sale" => self.best_sale = value.parse().unwrap_or(self.best_sale),
+            "records" => self.records = value.parse().unwrap_or(self.records),
+            "sales" => self.sales = value.parse().unwrap_or(self.sales),
+            "fill_total" => {
+                let total = value.parse().unwrap_or(self.fill_total);
+                if total >= 0.0 {
+                    self.fill_total = total;
+                }
+            }
+            "fill_sales" => self.fill_sales = value.parse().unwrap_or(self.fill_sales),
+            "decayed" => self.decayed = value.parse().unwrap_or(self.decayed),
+            "last_sale" => {
+                let sale = value.split_once(',');
+                if let Some((Ok(time), Ok(earned))) =
+                    sale.map(|(time, earned)| (time.parse(), earned.parse()))
+                    && self.last_sales.len() < LAST_SALES
+                {
+                    self.last_sales.push_back((time, earned));
+                }
+            }
+            _ => {
+                let (counts, name) = if let Some(name) = key.strip_prefix("dropped.") {
+                    (&mut self.tier_drops, name)
+                } else if let Some(name) = key.strip_prefix("sold.") {
+                    (&mut self.tier_sold, name)
+                } else {
+                    return false;
+                };
+                if let (Some(sand), Ok(count)) = (parse_enum(name), value.parse()) {
+                    counts.insert(sand, count);
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Money earned per second over the last minute
+/// * buckets: money earned in each second, oldest first, the last one is still running
+/// * elapsed: time passed in the running second
+#[derive(Debug, Default)]
+pub(crate) struct Income {
+    pub(crate) buckets: VecDeque<Currency>,
+    pub(crate) elapsed: f32,
+}
+
+/// Implementation of methods for the Income struct
+/// * update: moves on to a new second when one has passed
+/// * record: adds money earned to the running second
+/// * per_second: returns the average money earned per second
+impl Income {
+    /// moves on to a new second when one has passed
+    /// the seconds older than the window are dropped
+    pub(crate) fn update(&mut self, dt: f32) {
+        self.elapsed += dt;
+        while self.elapsed >= 1.0 {
+            self.elapsed -= 1.0;
+            self.buckets.push_back(Currency::ZERO);
+            if self.buckets.len() > INCOME_WINDOW {
+                self.buckets.pop_front();
+            }
+        }
+    }
+
+    /// adds money earned to the running second
+    pub(crate) fn record(&mut self, money: Currency) {
+        match self.buckets.back_mut() {
+            Some(bucket) => *bucket += money,
+            None => self.buckets.push_back(money),
+        }
+    }
+
+    /// returns the average money earned per second
+    /// a fresh game is averaged over the time it has run
+    pub(crate) fn per_second(&self) -> Currency {
+        let seconds = self.buckets.len().saturating_sub(1) as f32 + self.elapsed;
+        let seconds = seconds.clamp(1.0, INCOME_WINDOW as f32);
+        let total: Currency = self.buckets.iter().sum();
+        Currency::from_f64((total.as_f64() / seconds as f64).floor())
+    }
+}
+
+/// Counters of the player, shown for the whole game and for this session
+/// * time: time spent in the game
+/// * clicks: number of clicks made by the player
+/// * money_earned: money earned from conversions
+/// * money_spent: money spent on upgrades and themes
+/// * manual_grains: grains dropped by clicking
+/// * auto_grains: grains dropped by the autoclicker
+/// * sales: number of conversions that earned money
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct Counters {
+    pub(crate) time: Duration,
+    pub(crate) clicks: u32,
+    pub(crate) money_earned: Currency,
+    pub(crate) money_spent: Currency,
+    pub(crate) manual_grains: u64,
+    pub(crate) auto_grains: u64,
+    pub(crate) sales: u64,
+}
+
+/// Implementation of methods for the Counters struct
+/// * since: returns what was counted after the start
+impl Counters {
+    /// returns what was counted after the start
+    /// counters that went down since then count nothing
+    pub(crate) fn since(&self, start: &Counters) -> Counters {
+        Counters {
+            time: self.time.saturating_sub(start.time),
+            clicks: self.clicks.saturating_sub(start.clicks),
+            money_earned: self.money_earned - start.money_earned,
+            money_spent: self.money_spent - start.money_spent,
+            manual_grains: self.manual_grains.saturating_sub(start.manual_grains),
+            auto_grains: self.auto_grains.saturating_sub(start.auto_grains),
+            sales: self.sales.saturating_sub(start.sales),
+        }
+    }
+}
+
+/// Money per minute over the last minutes of play, for the graph in the statistics window
+/// * points: the sampled play time in seconds with the money per minute, oldest first
+/// * elapsed: seconds since the last point was sampled
+#[derive(Debug, Default)]
+pub(crate) struct IncomeGraph {
+    pub(crate) points: VecDeque<(u64, Currency)>,
+    pub(crate) elapsed: f32,
+}
+
+/// Implementation of methods for the IncomeGraph struct
+/// * update: samples a point when the interval has passed
+/// * peak: returns the highest money per minute of the graph
+impl IncomeGraph {
+    /// samples a point when the interval has passed
+    /// the points older than the graph are dropped
+    pub(crate) fn update(&mut self, dt: f32, time: u64, per_minute: Currency) {
+        self.elapsed += dt;
+        if self.elapsed < GRAPH_INTERVAL {
+            return;
+        }
+        self.elapsed %= GRAPH_INTERVAL;
+        if self.points.len() >= GRAPH_POINTS {
+            self.points.pop_front();
+        }
+        self.points.push_back((time, per_minute));
+    }
+
+    /// returns the highest money per minute of the graph
+    pub(crate) fn peak(&self) -> Currency {
+        self.points
+            .iter()
+            .map(|(_, money)| *money)
+            .max()
+            .unwrap_or(Currency::ZERO)
+    }
+}
+
+/// Entry of the purchase and sale history
+/// * Purchase: An upgrade was bought, with the play time, reached level and cost.
+/// * Sale: Sand was converted, with the play time and the money earned.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum HistoryEntry {
+    Purchase {
+        time: u64,
+        upgrade: Upgrade,
+        level: u32,
+        cost: Currency,
+    },
+    Sale {
+        time: u64,
+        earned: Currency,
+    },
+}
+
+/// Recent purchases and sales of the player
+/// * entries: the last entries, oldest first
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    pub(crate) entries: VecDeque<HistoryEntry>,
+}
+
+/// Implementation of methods for the History struct
+/// * push: adds an entry, dropping the oldest one when full
+/// * to_save: returns the history in the save file format
+/// * load_line: reads an entry from the save file format
+impl History {
+    /// adds an entry, dropping the oldest one when full
+    pub(crate) fn push(&mut self, entry: HistoryEntry) {
+        if self.entries.len() >= HISTORY_SIZE {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// returns the history in the save file format
+    /// each entry is a `history=` line with comma separated fields
+    pub(crate) fn to_save(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|entry| match entry {
+                HistoryEntry::Purchase {
+                    time,
+                    upgrade,
+                    level,
+                    cost,
+                } => format!("history=Purchase,{},{:?},{},{}", time, upgrade, level, cost),
+                HistoryEntry::Sale { time, earned } => format!("history=Sale,{},{}", time, earned),
+            })
+            .collect()
+    }
+
+    /// reads an entry from the save file format
+    /// broken entries are skipped
+    pub(crate) fn load_line(&mut self, value: &str) {
+        let fields: Vec<&str> = value.split(',').collect();
+        let entry = match fields[..] {
+            ["Purchase", time, upgrade, level, cost] => {
+                match (
+                    time.parse(),
+                    Upgrade::from_id(upgrade),
+                    level.parse(),
+                    cost.parse(),
+                ) {
+                    (Ok(time), Some(upgrade), Ok(level), Ok(cost)) => {
+                        Some(HistoryEntry::Purchase {
+                            time,
+                            upgrade,
+                            level,
+                            cost,
+                        })
+                    }
+                    _ => None,
+                }
+            }
+            ["Sale", time, earned] => match (time.parse(), earned.parse()) {
+                (Ok(time), Ok(earned)) => Some(HistoryEntry::Sale { time, earned }),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(entry) = entry {
+            self.push(entry);
+        }
+    }
+}
+
+/// Sample of the player's progress, taken every minute of play
+/// * time: play time in seconds the sample was taken at
+/// * money: money the player had
+/// * grains: grains dropped so far, by hand and by the autoclicker
+/// * sales: conversions made so far
+/// * levels: levels of the owned upgrades, in the order of the upgrade table
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Sample {
+    pub(crate) time: u64,
+    pub(crate) money: Currency,
+    pub(crate) grains: u64,
+    pub(crate) sales: u64,
+    pub(crate) levels: Vec<(Upgrade, u32)>,
+}
+
+/// Samples of the player's progress over the last play time
+/// * samples: the last samples, oldest first
+#[derive(Debug, Default)]
+pub(crate) struct Timeline {
+    pub(crate) samples: VecDeque<Sample>,
+}
+
+/// Implementation of methods for the Timeline struct
+/// * push: adds a sample, dropping the oldest one when full
+/// * to_save: returns the timeline in the save file format
+/// * load_line: reads a sample from the save file format
+/// * to_csv: returns the timeline as a CSV table, one row per sample
+impl Timeline {
+    /// adds a sample, dropping the oldest one when full
+    pub(crate) fn push(&mut self, sample: Sample) {
+        if self.samples.len() >= TIMELINE_SIZE {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// returns the timeline in the save file format
+    /// each sample is a `sample=` line with comma separated fields,
+    /// ending with the `upgrade:level` pairs
+    pub(crate) fn to_save(&self) -> Vec<String> {
+        self.samples
+            .iter()
+            .map(|sample| {
+                let mut fields = vec![
+                    sample.time.to_string(),
+                    sample.money.to_string(),
+                    sample.grains.to_string(),
+                    sample.sales.to_string(),
+                ];
+                for (upgrade, level) in &sample.levels {
+                    fields.push(format!("{:?}:{}", upgrade, level));
+                }
+                format!("sample={}", fields.join(","))
+            })
+            .collect()
+    }
+
+    /// reads a sample from the save file format
+    /// broken samples are skipped, and so are upgrades the table doesn't have
+    pub(crate) fn load_line(&mut self, value: &str) {
+        let mut fields = value.split(',');
+        let mut number = || fields.next().and_then(|field| field.parse::<u128>().ok());
+        let (Some(time), Some(money), Some(grains), Some(sales)) =
+            (number(), number(), number(), number())
+        else {
+            return;
+        };
+        let levels = fields
+            .filter_map(|field| {
+                let (upgrade, level) = field.split_once(':')?;
+                Some((Upgrade::from_id(upgrade)?, level.parse().ok()?))
+            })
+            .collect();
+        self.push(Sample {
+            time: time as u64,
+            money: Currency(money),
+            grains: grains as u64,
+            sales: sales as u64,
+            levels,
+        });
+    }
+
+    /// returns the timeline as a CSV table, one row per sample
+    /// the upgrades get a column each, with the level they had
+    pub(crate) fn to_csv(&self, upgrades: &[Upgrade]) -> String {
+        let mut header = vec![
+            "minute".to_string(),
+            "money".to_string(),
+            "grains_dropped".to_string(),
+            "conversions".to_string(),
+        ];
+        header.extend(upgrades.iter().map(|upgrade| format!("{:?}", upgrade)));
+        let mut lines = vec![header.join(",")];
+        for sample in &self.samples {
+            let mut row = vec![
+                (sample.time / 60).to_string(),
+                sample.money.to_string(),
+                sample.grains.to_string(),
+                sample.sales.to_string(),
+            ];
+            row.extend(upgrades.iter().map(|upgrade| {
+                sample
+                    .levels
+                    .iter()
+                    .find(|(owned, _)| owned == upgrade)
+                    .map_or(0, |(_, level)| *level)
+                    .to_string()
+            }));
+            lines.push(row.join(","));
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Where the player dropped sand by hand this session, in a coarse grid over the play area
+/// * counts: clicks in each cell, row by row
+#[derive(Debug)]
+pub(crate) struct Heatmap {
+    pub(crate) counts: Vec<u32>,
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self {
+            counts: vec![0; HEATMAP_GRID.0 * HEATMAP_GRID.1],
+        }
+    }
+}
+
+/// Implementation of methods for the Heatmap struct
+/// * record: counts a click at a position of the play area
+/// * reset: forgets all the clicks
+/// * cells: returns the clicked cells with how hot they are
+impl Heatmap {
+    /// counts a click at a position of the play area
+    /// clicks outside of it count for the nearest cell
+    pub(crate) fn record(&mut self, x: f32, y: f32, size: (f32, f32)) {
+        let (columns, rows) = HEATMAP_GRID;
+        let cell = |pos: f32, length: f32, cells: usize| {
+            ((pos / length * cells as f32).max(0.0) as usize).min(cells - 1)
+        };
+        let column = cell(x, size.0, columns);
+        let row = cell(y, size.1, rows);
+        self.counts[row * columns + column] += 1;
+    }
+
+    /// forgets all the clicks
+    pub(crate) fn reset(&mut self) {
+        self.counts.fill(0);
+    }
+
+    /// returns the clicked cells with how hot they are
+    /// the most clicked cell has a heat of 1
+    pub(crate) fn cells(&self, size: (f32, f32)) -> Vec<(Rect, f32)> {
+        let (columns, rows) = HEATMAP_GRID;
+        let most = self.counts.iter().copied().max().unwrap_or(0);
+        let (width, height) = (size.0 / columns as f32, size.1 / rows as f32);
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(index, count)| {
+                let (column, row) = (index % columns, index / columns);
+                let rect = Rect::new(column as f32 * width, row as f32 * height, width, height);
+                (rect, *count as f32 / most as f32)
+            })
+            .collect()
+    }
+}
+
+/// Tests for the statistics
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::format_time;
+
+    // Stats tests
+    #[test]
+    fn test_stats_sales() {
+        let mut stats = Stats::default();
+        assert_eq!(stats.average_fill(), None);
+        // the first conversion isn't a record
+        assert!(!stats.record_sale(0.0, Currency(100), 1.0));
+        assert!(!stats.record_sale(30.0, Currency(50), 0.5));
+        assert_eq!(stats.best_sale, Currency(100));
+        assert_eq!(stats.average_sale(), Currency(75));
+        assert_eq!(stats.average_fill(), Some(75.0));
+        assert_eq!(stats.records, 0);
+        assert_eq!(stats.money_per_minute(30.0), Currency(150));
+        // the first sale drops out of the last minute
+        assert_eq!(stats.money_per_minute(61.0), Currency(50));
+        stats.record_sale(100.0, Currency(10), 0.0);
+        assert_eq!(stats.recent_sales.len(), 1);
+        assert!(stats.record_sale(110.0, Currency(101), 0.0));
+        assert_eq!(stats.records, 1);
+        assert_eq!(stats.best_sale, Currency(101));
+        // only the last conversions are listed
+        for i in 0..LAST_SALES as u128 {
+            stats.record_sale(200.0, Currency(i), 0.0);
+        }
+        assert_eq!(stats.last_sales.len(), LAST_SALES);
+        assert_eq!(stats.last_sales[0], (200, Currency(0)));
+    }
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0), "0:00:00");
+        assert_eq!(format_time(3723), "1:02:03");
+    }
+
+    // History tests
+    #[test]
+    fn test_history_bounded() {
+        let mut history = History::default();
+        for earned in 0..HISTORY_SIZE as u128 + 10 {
+            history.push(HistoryEntry::Sale {
+                time: 0,
+                earned: Currency(earned),
+            });
+        }
+        assert_eq!(history.entries.len(), HISTORY_SIZE);
+        assert_eq!(
+            history.entries[0],
+            HistoryEntry::Sale {
+                time: 0,
+                earned: Currency(10)
+            }
+        );
+        // broken lines are skipped
+        history.load_line("Sale,abc,5");
+        history.load_line("Purchase,1,Nothing,1,5");
+        assert_eq!(history.entries.len(), HISTORY_SIZE);
+    }
+
+    // Income tests
+    #[test]
+    fn test_income_decay() {
+        let mut income = Income::default();
+        income.record(Currency(600));
+        assert_eq!(income.per_second(), Currency(600));
+        income.update(10.0);
+        assert_eq!(income.per_second(), Currency(60));
+        // the sale drops out of the window once a minute has passed
+        income.update(INCOME_WINDOW as f32);
+        assert_eq!(income.buckets.len(), INCOME_WINDOW);
+        assert_eq!(income.per_second(), Currency(0));
+    }
+
+    // Counters tests
+    #[test]
+    fn test_counters_since() {
+        let start = Counters {
+            time: Duration::from_secs(100),
+            clicks: 40,
+            money_earned: Currency(500),
+            sales: 3,
+            ..Counters::default()
+        };
+        let now = Counters {
+            time: Duration::from_secs(160),
+            clicks: 45,
+            money_earned: Currency(800),
+            manual_grains: 12,
+            sales: 5,
+            ..Counters::default()
+        };
+        let session = now.since(&start);
+        assert_eq!(session.time, Duration::from_secs(60));
+        assert_eq!(session.clicks, 5);
+        assert_eq!(session.money_earned, Currency(300));
+        assert_eq!(session.manual_grains, 12);
+        assert_eq!(session.sales, 2);
+        // nothing is counted when the counters went down
+        assert_eq!(start.since(&now).clicks, 0);
+    }
+
+    // IncomeGraph tests
+    #[test]
+    fn test_income_graph_update() {
+        let mut graph = IncomeGraph::default();
+        graph.update(GRAPH_INTERVAL / 2.0, 2, Currency(100));
+        assert!(graph.points.is_empty());
+        graph.update(GRAPH_INTERVAL / 2.0, 5, Currency(300));
+        assert_eq!(graph.points, [(5, Currency(300))]);
+        // the graph only keeps the last points
+        for i in 0..GRAPH_POINTS as u128 {
+            graph.update(GRAPH_INTERVAL, 10 + i as u64, Currency(i));
+        }
+        assert_eq!(graph.points.len(), GRAPH_POINTS);
+        assert_eq!(graph.points[0], (10, Currency(0)));
+        assert_eq!(graph.peak(), Currency(GRAPH_POINTS as u128 - 1));
+    }
+
+    // Heatmap tests
+    #[test]
+    fn test_heatmap_cells() {
+        let mut heatmap = Heatmap::default();
+        let size = (320.0, 240.0);
+        heatmap.record(5.0, 5.0, size);
+        heatmap.record(5.0, 5.0, size);
+        heatmap.record(315.0, 235.0, size);
+        // clicks outside the play area count for the edge
+        heatmap.record(-50.0, 1000.0, size);
+        let cells = heatmap.cells(size);
+        assert_eq!(cells.len(), 3);
+        assert_eq!(cells[0], (Rect::new(0.0, 0.0, 10.0, 10.0), 1.0));
+        assert_eq!(cells[1], (Rect::new(0.0, 230.0, 10.0, 10.0), 0.5));
+        assert_eq!(cells[2], (Rect::new(310.0, 230.0, 10.0, 10.0), 0.5));
+        heatmap.reset();
+        assert!(heatmap.cells(size).is_empty());
+    }
+
+    // Timeline tests
+    #[test]
+    fn test_timeline_save() {
+        let mut timeline = Timeline::default();
+        timeline.push(Sample {
+            time: 60,
+            money: Currency(1500),
+            grains: 42,
+            sales: 3,
+            levels: vec![(Upgrade::PARTICLE_TIER, 1), (Upgrade::AUTO_CLICKER, 2)],
+        });
+        let lines = timeline.to_save();
+        assert_eq!(lines, ["sample=60,1500,42,3,ParticleTier:1,AutoClicker:2"]);
+        let mut loaded = Timeline::default();
+        let value = lines[0].strip_prefix("sample=").unwrap();
+        loaded.load_line(value);
+        assert_eq!(loaded.samples, timeline.samples);
+        // broken samples are skipped, and so are unknown upgrades
+        loaded.load_line("120,abc,1,1");
+        loaded.load_line("120,5,1");
+        loaded.load_line("120,5,1,1,Nothing:3");
+        assert_eq!(loaded.samples.len(), 2);
+        assert!(loaded.samples[1].levels.is_empty());
+    }
+    #[test]
+    fn test_timeline_csv() {
+        let mut timeline = Timeline::default();
+        for minute in 1..=TIMELINE_SIZE as u64 + 5 {
+            timeline.push(Sample {
+                time: minute * 60,
+                money: Currency(minute as u128 * 10),
+                grains: minute,
+                sales: 0,
+                levels: vec![(Upgrade::AUTO_CLICKER, 2)],
+            });
+        }
+        assert_eq!(timeline.samples.len(), TIMELINE_SIZE);
+        let csv = timeline.to_csv(&[Upgrade::PARTICLE_TIER, Upgrade::AUTO_CLICKER]);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("minute,money,grains_dropped,conversions,ParticleTier,AutoClicker")
+        );
+        // the oldest samples were dropped
+        assert_eq!(lines.next(), Some("6,60,6,0,0,2"));
+        assert_eq!(csv.lines().count(), TIMELINE_SIZE + 1);
+    }
+}
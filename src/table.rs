@@ -0,0 +1,130 @@
+//! Data tables of the game, written in a small subset of TOML
+
+use std::collections::HashMap;
+
+// Fields of a table section by key, with their line number and text
+pub(crate) type TableFields<'a> = HashMap<&'a str, (usize, &'a str)>;
+
+/// Section of a data table
+/// * name: name of the section, between the brackets
+/// * line: line number of the section header
+/// * fields: fields of the section by key
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TableSection<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) line: usize,
+    pub(crate) fields: TableFields<'a>,
+}
+
+/// Implementation of methods for the TableSection struct
+/// * field: reads a field of the section with its parser
+impl TableSection<'_> {
+    /// reads a field of the section with its parser
+    /// returns None if the section doesn't have the field
+    pub(crate) fn field<T>(
+        &self,
+        key: &str,
+        parse: impl Fn(&str) -> Option<T>,
+    ) -> Result<Option<T>, String> {
+        match self.fields.get(key) {
+            Some((number, text)) => parse(text).map(Some).ok_or_else(|| {
+                format!(
+                    "line {}: `{}` of [{}] isn't valid: {}",
+                    number, key, self.name, text
+                )
+            }),
+            None => Ok(None),
+        }
+    }
+}
+
+/// reads the sections of a data table, or the reason it is broken
+/// each `[Name]` section holds `key = value` lines, # starts a comment
+/// only the given keys are allowed
+pub(crate) fn parse_sections<'a>(
+    text: &'a str,
+    keys: &[&str],
+) -> Result<Vec<TableSection<'a>>, String> {
+    let mut sections: Vec<TableSection> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            let name = name.trim();
+            if sections.iter().any(|other| other.name == name) {
+                return Err(format!("line {}: [{}] is defined twice", number, name));
+            }
+            sections.push(TableSection {
+                name,
+                line: number,
+                fields: HashMap::new(),
+            });
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected `key = value`", number));
+        };
+        let key = key.trim();
+        let Some(section) = sections.last_mut() else {
+            return Err(format!(
+                "line {}: `{}` is outside of a section",
+                number, key
+            ));
+        };
+        if !keys.contains(&key) {
+            return Err(format!(
+                "line {}: unknown key `{}` in [{}]",
+                number, key, section.name
+            ));
+        }
+        section.fields.insert(key, (number, value.trim()));
+    }
+    Ok(sections)
+}
+
+/// reads a text written in double quotes
+pub(crate) fn parse_text(text: &str) -> Option<String> {
+    let inner = text.strip_prefix('"')?.strip_suffix('"')?;
+    (!inner.contains('"')).then(|| inner.to_string())
+}
+
+/// Tests for the data tables
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Table tests
+    #[test]
+    fn test_table_parse_sections() {
+        let text = "# comment\n[First]\nsize = 2\n\n[Second]\nname = \"two\"\n";
+        let sections = parse_sections(text, &["size", "name"]).unwrap();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].name, "First");
+        assert_eq!(sections[1].line, 5);
+        assert_eq!(
+            sections[0].field("size", |text| text.parse().ok()),
+            Ok(Some(2))
+        );
+        assert_eq!(sections[0].field("name", parse_text), Ok(None));
+        assert_eq!(
+            sections[1].field("name", parse_text),
+            Ok(Some("two".to_string()))
+        );
+        assert_eq!(
+            sections[1].field("name", |text| text.parse::<u32>().ok()),
+            Err("line 6: `name` of [Second] isn't valid: \"two\"".to_string())
+        );
+    }
+    #[test]
+    fn test_table_parse_text() {
+        assert_eq!(parse_text("\"Sand\""), Some("Sand".to_string()));
+        assert_eq!(parse_text("Sand"), None);
+        assert_eq!(parse_text("\"Sa\"nd\""), None);
+    }
+}
@@ -0,0 +1,249 @@
+//! Background themes and the day/night cycle
+
+use crate::{DAY_LENGTH, currency::Currency, lang::tr};
+use ggez::graphics::Color;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Different background themes available in the game
+/// * Classic: Plain black background.
+/// * Beach: Sky to sea gradient.
+/// * NightSky: Dark blue to purple gradient.
+/// * Volcanic: Dark red to lava gradient.
+#[derive(Hash, Eq, PartialEq, Debug, EnumIter, Clone, Copy)]
+pub(crate) enum Theme {
+    Classic,
+    Beach,
+    NightSky,
+    Volcanic,
+}
+
+/// Implementation of methods for the Theme enum
+/// * name: returns the display name of the theme
+/// * cost: returns the cost of the theme
+/// * clear_color: returns the color the screen is cleared with
+/// * gradient: returns the top and bottom colors of the background, if any
+/// * text_color: returns a text color that is readable on the theme
+impl Theme {
+    /// returns the display name of the theme
+    pub(crate) fn name(&self) -> &'static str {
+        tr(match self {
+            Theme::Classic => "theme-classic",
+            Theme::Beach => "theme-beach",
+            Theme::NightSky => "theme-night-sky",
+            Theme::Volcanic => "theme-volcanic",
+        })
+    }
+
+    /// returns the cost of the theme
+    pub(crate) fn cost(&self) -> Currency {
+        Currency(match self {
+            Theme::Classic => 0,
+            Theme::Beach => 5000,
+            Theme::NightSky => 25000,
+            Theme::Volcanic => 100000,
+        })
+    }
+
+    /// returns the color the screen is cleared with
+    pub(crate) fn clear_color(&self) -> Color {
+        match self.gradient() {
+            Some((top, _)) => top,
+            None => Color::BLACK,
+        }
+    }
+
+    /// returns the top and bottom colors of the background, if any
+    pub(crate) fn gradient(&self) -> Option<(Color, Color)> {
+        match self {
+            Theme::Classic => None,
+            Theme::Beach => Some((
+                Color::from_rgb(135, 206, 235),
+                Color::from_rgb(64, 164, 223),
+            )),
+            Theme::NightSky => Some((Color::from_rgb(8, 8, 32), Color::from_rgb(48, 32, 88))),
+            Theme::Volcanic => Some((Color::from_rgb(40, 0, 0), Color::from_rgb(130, 30, 10))),
+        }
+    }
+
+    /// returns a text color that is readable on the theme
+    pub(crate) fn text_color(&self) -> Color {
+        match self {
+            Theme::Beach => Color::BLACK,
+            _ => Color::WHITE,
+        }
+    }
+}
+
+/// Enum representing the times of day of the day/night cycle
+/// * Dawn: Warm orange sky.
+/// * Day: Light blue sky.
+/// * Dusk: Purple sky.
+/// * Night: Dark blue sky.
+#[derive(PartialEq, Debug, EnumIter, Clone, Copy)]
+pub(crate) enum TimeOfDay {
+    Dawn,
+    Day,
+    Dusk,
+    Night,
+}
+
+/// Implementation of methods for the TimeOfDay enum
+/// * name: returns the display name of the time of day
+/// * seconds: returns the seconds into the cycle the time of day starts at
+/// * color: returns the sky color at the time of day
+impl TimeOfDay {
+    /// returns the display name of the time of day
+    pub(crate) fn name(&self) -> &'static str {
+        tr(match self {
+            TimeOfDay::Dawn => "time-dawn",
+            TimeOfDay::Day => "time-day",
+            TimeOfDay::Dusk => "time-dusk",
+            TimeOfDay::Night => "time-night",
+        })
+    }
+
+    /// returns the seconds into the cycle the time of day starts at
+    pub(crate) fn seconds(&self) -> f32 {
+        *self as u32 as f32 * DAY_LENGTH / 4.0
+    }
+
+    /// returns the sky color at the time of day
+    pub(crate) fn color(&self) -> Color {
+        match self {
+            TimeOfDay::Dawn => Color::from_rgb(240, 160, 110),
+            TimeOfDay::Day => Color::from_rgb(140, 195, 240),
+            TimeOfDay::Dusk => Color::from_rgb(110, 60, 100),
+            TimeOfDay::Night => Color::from_rgb(10, 10, 30),
+        }
+    }
+}
+
+/// returns the sky color the given seconds into the day/night cycle
+/// blends between the colors of the surrounding times of day
+pub(crate) fn sky_color(seconds: f32) -> Color {
+    let times: Vec<TimeOfDay> = TimeOfDay::iter().collect();
+    let phase = seconds.rem_euclid(DAY_LENGTH) / DAY_LENGTH * times.len() as f32;
+    let index = (phase.floor() as usize).min(times.len() - 1);
+    let from = times[index].color();
+    let to = times[(index + 1) % times.len()].color();
+    let t = phase - index as f32;
+    Color::new(
+        from.r + (to.r - from.r) * t,
+        from.g + (to.g - from.g) * t,
+        from.b + (to.b - from.b) * t,
+        1.0,
+    )
+}
+
+/// returns the color of the container gauge for how full it is
+/// goes from green when empty over yellow to red when full
+pub(crate) fn gauge_color(fill: f32) -> Color {
+    let fill = fill.clamp(0.0, 1.0);
+    if fill < 0.5 {
+        Color::new(fill * 2.0, 1.0, 0.0, 1.0)
+    } else {
+        Color::new(1.0, 2.0 - fill * 2.0, 0.0, 1.0)
+    }
+}
+
+/// returns the color of a decaying grain, faded toward gray by how far it decayed
+/// decay goes from 0 for the full color to 1 for plain gray of the same brightness
+pub(crate) fn decay_color(color: Color, decay: f32) -> Color {
+    let decay = decay.clamp(0.0, 1.0);
+    let gray = 0.299 * color.r + 0.587 * color.g + 0.114 * color.b;
+    Color::new(
+        color.r + (gray - color.r) * decay,
+        color.g + (gray - color.g) * decay,
+        color.b + (gray - color.b) * decay,
+        color.a,
+    )
+}
+
+/// returns black or white, whichever is readable on the given color
+pub(crate) fn contrast_color(color: Color) -> Color {
+    let luminance = 0.299 * color.r + 0.587 * color.g + 0.114 * color.b;
+    if luminance > 0.5 {
+        Color::BLACK
+    } else {
+        Color::WHITE
+    }
+}
+
+/// Tests for the themes
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{game::SandDropClicker, settings::parse_enum, upgrade::UpgradeCategory};
+    use std::time::Duration;
+
+    // day/night cycle tests
+    #[test]
+    fn test_sky_color() {
+        // the cycle passes through each time of day and loops around
+        for time in TimeOfDay::iter() {
+            assert_eq!(sky_color(time.seconds()), time.color());
+        }
+        assert_eq!(sky_color(DAY_LENGTH), sky_color(0.0));
+        // halfway between dawn and day is a blend of both
+        let half = sky_color(TimeOfDay::Day.seconds() / 2.0);
+        let (dawn, day) = (TimeOfDay::Dawn.color(), TimeOfDay::Day.color());
+        assert!((half.r - (dawn.r + day.r) / 2.0).abs() < 0.001);
+        assert!((half.b - (dawn.b + day.b) / 2.0).abs() < 0.001);
+    }
+    #[test]
+    fn test_contrast_color() {
+        assert_eq!(contrast_color(TimeOfDay::Day.color()), Color::BLACK);
+        assert_eq!(contrast_color(TimeOfDay::Night.color()), Color::WHITE);
+    }
+    #[test]
+    fn test_game_fixed_time() {
+        let mut game = SandDropClicker::_test_state();
+        game.settings.fixed_time = Some(TimeOfDay::Night);
+        game.sim.total_time = Duration::from_secs(150);
+        assert_eq!(game.clear_color(), TimeOfDay::Night.color());
+        assert_eq!(game.text_color(), Color::WHITE);
+        // bought themes keep their own background
+        game.theme = Theme::Beach;
+        assert_eq!(game.clear_color(), Theme::Beach.clear_color());
+    }
+
+    // gauge tests
+    #[test]
+    fn test_gauge_color() {
+        assert_eq!(gauge_color(0.0), Color::new(0.0, 1.0, 0.0, 1.0));
+        assert_eq!(gauge_color(0.5), Color::new(1.0, 1.0, 0.0, 1.0));
+        assert_eq!(gauge_color(1.0), Color::new(1.0, 0.0, 0.0, 1.0));
+    }
+
+    // decay tests
+    #[test]
+    fn test_decay_color() {
+        let color = Color::new(1.0, 0.5, 0.0, 1.0);
+        assert_eq!(decay_color(color, 0.0), color);
+        let gray = decay_color(color, 1.0);
+        assert!((gray.r - gray.g).abs() < 0.001 && (gray.g - gray.b).abs() < 0.001);
+        assert!((gray.r - (0.299 + 0.587 * 0.5)).abs() < 0.001);
+        // halfway keeps half of the color
+        let half = decay_color(color, 0.5);
+        assert!((half.r - (1.0 + gray.r) / 2.0).abs() < 0.001);
+        assert_eq!(half.a, 1.0);
+    }
+
+    // Theme tests
+    #[test]
+    fn test_theme_text_color() {
+        assert_eq!(Theme::Classic.text_color(), Color::WHITE);
+        assert_eq!(Theme::Beach.text_color(), Color::BLACK);
+        assert_eq!(Theme::Classic.clear_color(), Color::BLACK);
+    }
+    #[test]
+    fn test_parse_enum() {
+        assert_eq!(parse_enum("NightSky"), Some(Theme::NightSky));
+        assert_eq!(
+            parse_enum::<UpgradeCategory>("Economy"),
+            Some(UpgradeCategory::Economy)
+        );
+        assert_eq!(parse_enum::<Theme>("Nope"), None);
+    }
+}
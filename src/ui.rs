@@ -0,0 +1,2489 @@
+//! GUI windows and on-screen displays of the game
+
+use crate::{
+    BULK_BUY, CHALLENGE_REWARD, FPS, FPS_CAPS, FULL_FLASH_TIME, GRAIN_CAPS, GRAPH_INTERVAL,
+    GRAPH_POINTS, MAX_NOTIFICATIONS, MIN_INSTANCES, NOTIFY_TIME, SANDBOX_GRAIN_SIZE,
+    SANDBOX_GRAVITY, SCIENTIFIC_MONEY, SPRITE_SIZE, TEXT_SIZE, TRAIL_LENGTH,
+    blitz::{format_date, today},
+    challenge::Challenge,
+    currency::Currency,
+    daily::{DailyRules, calendar},
+    game::{GameAction, SandDropClicker, TutorialStep},
+    grain::Grain,
+    input::{Action, Keybinds, MOUSE_BUTTONS, MouseAction, QUICK_BUY_KEYS, mouse_button_name},
+    lang::{Lang, set_lang, tr, tr_args},
+    particle::SandParticle,
+    profile::{Profile, SandboxTools},
+    replay::ReplaySpeed,
+    settings::BackgroundMode,
+    speedrun::{Split, format_delta, format_split},
+    stats::{Achievement, Counters, HistoryEntry},
+    theme::{Theme, TimeOfDay, contrast_color, decay_color, gauge_color, sky_color},
+    upgrade::{Upgrade, UpgradeCategory, UpgradeEffect, upgrade_table},
+};
+use ggegui::egui::{self, Button};
+use ggez::graphics::{self, Color, DrawParam, Rect, Text};
+use log::warn;
+use std::{
+    collections::VecDeque,
+    fmt::{self, Write},
+};
+use strum::IntoEnumIterator;
+
+pub(crate) const TUTORIAL_COLOR: egui::Color32 = egui::Color32::from_rgb(200, 140, 30); // Highlight of the tutorial
+
+/// GUI windows and on-screen displays of the game
+impl SandDropClicker {
+    /// updates the options GUI
+    /// displays money, upgrades, and instructions
+    pub(crate) fn options_gui(&mut self) {
+        // a closed window isn't built at all
+        if !self.show_options {
+            return;
+        }
+        let mut open = true;
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            // create the options window, it can be closed and reopened with a key
+            egui::Window::new(tr("options-title"))
+                .id(egui::Id::new("options"))
+                .open(&mut open)
+                .resizable(false)
+                .default_size([250.0, 100.0])
+                .default_pos([10.0, 100.0])
+                .show(&gui_ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("options-settings")).clicked() {
+                            self.show_settings = !self.show_settings;
+                        }
+                        if ui.button(tr("options-guide")).clicked() {
+                            self.show_guide = !self.show_guide;
+                        }
+                        if ui.button(tr("options-history")).clicked() {
+                            self.show_history = !self.show_history;
+                        }
+                    });
+                    // a hardcore or sandbox game wears its badge
+                    if let Some(badge) = self.profile.badge() {
+                        let color = match self.profile {
+                            Profile::Sandbox => egui::Color32::from_rgb(80, 170, 240),
+                            _ => egui::Color32::from_rgb(220, 60, 60),
+                        };
+                        ui.colored_label(color, badge)
+                            .on_hover_text(self.profile.desc());
+                    }
+                    // a game with every upgrade maxed can go again, for more
+                    if self.can_new_game_plus()
+                        && ui
+                            .button(tr_args("ng-plus", &[("level", &(self.ng_plus + 1))]))
+                            .on_hover_text(tr("ng-plus-tooltip"))
+                            .clicked()
+                    {
+                        self.queue(GameAction::NewGamePlus);
+                    }
+                    // Display instructions
+                    ui.label(tr("options-hint"));
+                    let key = self.settings.keybinds.label(Action::Convert);
+                    let mut convert = Button::new(tr_args("options-convert", &[("key", &key)]));
+                    if self.tutorial == Some(TutorialStep::Convert) {
+                        convert = convert.fill(TUTORIAL_COLOR);
+                    }
+                    if ui.add(convert).clicked() {
+                        self.queue(GameAction::Convert);
+                    }
+                    // display money, the exact amount is in the tooltip
+                    // the money of a sandbox game never runs out
+                    let money = if self.sim.modifiers.sandbox {
+                        "∞".to_string()
+                    } else {
+                        self.money_text(self.sim.money)
+                    };
+                    let income = self.money_text(self.sim.income.per_second());
+                    // small grain values keep a decimal, 1.5$ isn't 2$
+                    let value = self.sim.rates().grain_value;
+                    let value = if value < 100.0 {
+                        format!("{:.1}", value)
+                    } else {
+                        self.money_text(Currency::from_f64(value))
+                    };
+                    ui.horizontal(|ui| {
+                        ui.label(tr_args("options-money", &[("money", &money)]))
+                            .on_hover_text(format!("{}$", self.sim.money));
+                        ui.small(tr_args("income-per-second", &[("money", &income)]));
+                    });
+                    ui.small(tr_args("grain-value", &[("money", &value)]))
+                        .on_hover_text(tr("grain-value-tooltip"));
+                    // show what the container would sell for
+                    ui.collapsing(tr("contents-title"), |ui| {
+                        let contents = self.sim.contents();
+                        if contents.is_empty() {
+                            ui.label(tr("contents-empty"));
+                            return;
+                        }
+                        egui::Grid::new("container_contents")
+                            .striped(true)
+                            .num_columns(4)
+                            .show(ui, |ui| {
+                                ui.label(tr("column-sand"));
+                                ui.label(tr("column-count"));
+                                ui.label(tr("column-value"));
+                                ui.label(tr("column-subtotal"));
+                                ui.end_row();
+                                for (sand, count, value) in &contents {
+                                    ui.label(sand.name());
+                                    ui.label(count.to_string());
+                                    ui.label(format!("{}$", self.money_text(*value)));
+                                    let subtotal = *value * *count;
+                                    ui.label(format!("{}$", self.money_text(subtotal)));
+                                    ui.end_row();
+                                }
+                            });
+                        let total = self.money_text(self.sim.unsold_value());
+                        ui.label(tr_args("contents-total", &[("money", &total)]));
+                    });
+
+                    // show available upgrades
+                    ui.separator();
+                    if self.sim.unlock.is_empty() {
+                        ui.label(tr("upgrades-none"));
+                    } else {
+                        ui.label(tr("upgrades-available"));
+                    }
+                    // the simulation unlocks the upgrades, unaffordable ones are disabled
+                    for category in UpgradeCategory::iter() {
+                        let upgrades: Vec<Upgrade> = upgrade_table()
+                            .iter()
+                            .filter(|upgrade| {
+                                upgrade.category() == category && self.sim.unlock.contains(upgrade)
+                            })
+                            .collect();
+                        if upgrades.is_empty() {
+                            continue;
+                        }
+                        // a dot on a closed category tells that something can be bought
+                        let open = !self.settings.closed_categories.contains(&category);
+                        let affordable = upgrades.iter().any(|upgrade| {
+                            self.sim
+                                .upgrade_cost(*upgrade)
+                                .is_some_and(|cost| self.sim.money >= cost)
+                        });
+                        let title = if !open && affordable {
+                            format!("{} ●", category.name())
+                        } else {
+                            category.name().to_string()
+                        };
+                        let response = egui::CollapsingHeader::new(title)
+                            .id_source(category)
+                            .open(Some(open))
+                            .show(ui, |ui| {
+                                for upgrade in upgrades {
+                                    self.upgrade_gui(ui, upgrade);
+                                }
+                            });
+                        if response.header_response.clicked() {
+                            self.toggle_category(category);
+                        }
+                    }
+
+                    // show the background themes
+                    ui.separator();
+                    ui.collapsing(tr("themes-title"), |ui| {
+                        for theme in Theme::iter() {
+                            if self.owned_themes.contains(&theme) {
+                                let btn_txt = tr_args("themes-use", &[("theme", &theme.name())]);
+                                let enabled = self.theme != theme;
+                                if ui.add_enabled(enabled, Button::new(btn_txt)).clicked() {
+                                    self.theme = theme;
+                                }
+                            } else {
+                                let cost = theme.cost();
+                                let enabled = self.sim.money >= cost;
+                                let btn_txt = tr_args(
+                                    "themes-buy",
+                                    &[("theme", &theme.name()), ("cost", &self.money_text(cost))],
+                                );
+                                if ui.add_enabled(enabled, Button::new(btn_txt)).clicked() {
+                                    self.queue(GameAction::BuyTheme(theme));
+                                }
+                            }
+                        }
+                    });
+                });
+        }
+        self.show_options = open;
+    }
+
+    /// draws the description and buy button of an upgrade
+    /// the tooltip is only built while the button is hovered
+    pub(crate) fn upgrade_gui(&mut self, ui: &mut egui::Ui, upgrade: Upgrade) {
+        ui.label(upgrade.desc());
+        let amount = *self.sim.upgrades.get(&upgrade).unwrap_or(&0);
+        let btn_txt = self.upgrade_label(upgrade);
+        // a maxed upgrade has no next level to price
+        if let Some(cost) = self.sim.upgrade_cost(upgrade) {
+            let enabled: bool = self.sim.money >= cost;
+            let mut button = Button::new(btn_txt);
+            if enabled && self.tutorial == Some(TutorialStep::Upgrade) {
+                button = button.fill(TUTORIAL_COLOR);
+            }
+            // mark the upgrade the gamepad would buy
+            if self.gamepad_cursor.is_some() && self.gamepad_upgrade() == Some(upgrade) {
+                button = button.selected(true);
+            }
+            if ui
+                .add_enabled(enabled, button)
+                .on_hover_ui(|ui| {
+                    ui.label(self.upgrade_tooltip(upgrade));
+                })
+                .on_disabled_hover_ui(|ui| {
+                    ui.label(self.upgrade_tooltip(upgrade));
+                })
+                .clicked()
+            {
+                self.queue(GameAction::Buy {
+                    upgrade,
+                    count: self.buy_count,
+                });
+            }
+            // show how close the player is to affording it
+            if !enabled {
+                let fraction = (self.sim.money.as_f64() / cost.as_f64().max(1.0)).min(1.0);
+                let eta = self.time_to_afford(cost).map_or(String::new(), |secs| {
+                    tr_args("upgrade-eta", &[("time", &format_time(secs))])
+                });
+                ui.add(egui::ProgressBar::new(fraction as f32).text(eta));
+            }
+        } else {
+            ui.add_enabled(false, Button::new(btn_txt))
+                .on_disabled_hover_text(upgrade.effect_summary(amount));
+        }
+    }
+
+    /// returns the text of the buy button of an upgrade
+    /// it is only formatted again after a purchase, an unlock, a change of the cost,
+    /// or when the money crosses the cost
+    pub(crate) fn upgrade_label(&mut self, upgrade: Upgrade) -> String {
+        let level = *self.sim.upgrades.get(&upgrade).unwrap_or(&0);
+        let cost = self.sim.upgrade_cost(upgrade);
+        let key = UpgradeLabel {
+            level,
+            cost,
+            affordable: cost.is_some_and(|cost| self.sim.money >= cost),
+            unlocked: self.sim.unlock.len(),
+            lang: self.settings.lang,
+            full_numbers: self.settings.full_numbers,
+        };
+        if let Some((cached, label)) = self.upgrade_labels.get(&upgrade)
+            && *cached == key
+        {
+            return label.clone();
+        }
+        // the number key that buys the upgrade
+        let shortcut = match self.listed_upgrades().iter().position(|u| *u == upgrade) {
+            Some(slot) if slot < QUICK_BUY_KEYS.len() => format!("[{}] ", slot + 1),
+            _ => String::new(),
+        };
+        let label = match cost {
+            Some(cost) => {
+                shortcut
+                    + &tr_args(
+                        "upgrade-button",
+                        &[
+                            ("upgrade", &upgrade.btn_txt()),
+                            ("level", &level),
+                            ("cost", &self.money_text(cost)),
+                        ],
+                    )
+            }
+            None => {
+                shortcut
+                    + &tr_args(
+                        "upgrade-button-max",
+                        &[("upgrade", &upgrade.btn_txt()), ("level", &level)],
+                    )
+            }
+        };
+        self.upgrade_labels.insert(upgrade, (key, label.clone()));
+        label
+    }
+
+    /// returns the tooltip of the buy button of an upgrade
+    /// the exact cost, what the next level does, what a Shift-click costs,
+    /// and how long the next level takes to earn its cost back
+    /// a maxed upgrade only tells what it does
+    pub(crate) fn upgrade_tooltip(&self, upgrade: Upgrade) -> String {
+        let amount = *self.sim.upgrades.get(&upgrade).unwrap_or(&0);
+        let Some(cost) = self.sim.upgrade_cost(upgrade) else {
+            return upgrade.effect_summary(amount);
+        };
+        let (bulk, bulk_cost) = self.sim.levels_cost(upgrade, BULK_BUY);
+        let mut tooltip = format!(
+            "{}\n{}\n{}",
+            upgrade.effect_summary(amount),
+            tr_args("upgrade-cost", &[("cost", &cost)]),
+            tr_args(
+                "upgrade-shortcuts",
+                &[("count", &bulk), ("cost", &self.money_text(bulk_cost))]
+            )
+        );
+        match self.sim.payback_time(upgrade) {
+            Some(Some(secs)) => {
+                let time = format_time(secs);
+                tooltip += &format!("\n{}", tr_args("upgrade-payback", &[("time", &time)]));
+            }
+            Some(None) => tooltip += &format!("\n{}", tr("upgrade-payback-unknown")),
+            None => {}
+        }
+        tooltip
+    }
+
+    /// updates the pause menu GUI
+    /// returns true if the player chose to quit
+    pub(crate) fn pause_gui(&mut self) -> bool {
+        if !self.paused {
+            return false;
+        }
+        let mut quit = false;
+        let mut save = false;
+        let mut blitz = false;
+        let mut daily = false;
+        // a sandbox game plays no blitzes or daily runs
+        let idle = self.challenge.is_none() && self.blitz.is_none() && self.profile.scored();
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            // create the pause menu in the middle of the screen
+            egui::Window::new(tr("pause-title"))
+                .id(egui::Id::new("pause"))
+                .resizable(false)
+                .collapsible(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(&gui_ctx, |ui| {
+                    if ui.button(tr("pause-resume")).clicked() {
+                        self.paused = false;
+                    }
+                    if ui.button(tr("pause-settings")).clicked() {
+                        self.show_settings = true;
+                    }
+                    if ui.button(tr("pause-challenges")).clicked() {
+                        self.show_challenges = true;
+                    }
+                    blitz = ui
+                        .add_enabled(idle, Button::new(tr("pause-blitz")))
+                        .on_hover_text(tr("pause-blitz-tooltip"))
+                        .clicked();
+                    daily = ui
+                        .add_enabled(idle, Button::new(tr("pause-daily")))
+                        .on_hover_text(tr("pause-daily-tooltip"))
+                        .clicked();
+                    save = ui.button(tr("pause-save")).clicked();
+                    quit = ui.button(tr("pause-quit")).clicked();
+                });
+        }
+        if blitz {
+            self.queue(GameAction::StartBlitz);
+            self.paused = false;
+        }
+        if daily {
+            self.queue(GameAction::StartDaily(today()));
+            self.paused = false;
+        }
+        if save {
+            let message = match self.save() {
+                Ok(()) => tr("pause-saved").to_string(),
+                Err(err) => {
+                    warn!("couldn't save the game: {}", err);
+                    tr_args("pause-save-failed", &[("error", &err)])
+                }
+            };
+            self.notify(message);
+        }
+        quit
+    }
+
+    /// updates the quit confirmation GUI
+    /// returns true if the player chose to quit
+    pub(crate) fn quit_gui(&mut self) -> bool {
+        if !self.quit_dialog {
+            return false;
+        }
+        let amount = self.sim.get_amount();
+        let value = self.money_text(self.sim.unsold_value());
+        let mut sell = false;
+        let mut quit = false;
+        let mut cancel = false;
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            // create the confirmation in the middle of the screen
+            egui::Window::new(tr("quit-title"))
+                .id(egui::Id::new("quit"))
+                .resizable(false)
+                .collapsible(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(&gui_ctx, |ui| {
+                    ui.label(tr_args(
+                        "quit-unsold",
+                        &[("amount", &amount), ("money", &value)],
+                    ));
+                    ui.horizontal(|ui| {
+                        sell = ui.button(tr("quit-sell")).clicked();
+                        quit = ui.button(tr("quit-quit")).clicked();
+                        cancel = ui.button(tr("quit-cancel")).clicked();
+                    });
+                });
+        }
+        // the sale has to happen before the game quits
+        if sell {
+            self.apply(GameAction::Convert);
+        }
+        if sell || quit {
+            self.quit_confirmed = true;
+        }
+        if sell || quit || cancel {
+            self.quit_dialog = false;
+        }
+        self.quit_confirmed
+    }
+
+    /// updates the settings GUI
+    /// displays the player settings
+    pub(crate) fn settings_gui(&mut self) {
+        if !self.show_settings {
+            return;
+        }
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            let mut changed = false;
+            let mut theme_changed = false;
+            let mut speedrun_changed = false;
+            let mut reset = false;
+            // create the settings window
+            egui::Window::new(tr("settings-title"))
+                .id(egui::Id::new("settings"))
+                .resizable(false)
+                .default_pos([300.0, 100.0])
+                .open(&mut self.show_settings)
+                .show(&gui_ctx, |ui| {
+                    let settings = &mut self.settings;
+                    ui.heading(tr("settings-display"));
+                    let info_key = settings.keybinds.label(Action::ToggleInfo);
+                    changed |= ui
+                        .checkbox(
+                            &mut settings.show_info,
+                            tr_args("settings-player-info", &[("key", &info_key)]),
+                        )
+                        .changed();
+                    changed |= ui
+                        .checkbox(&mut settings.trails, tr("settings-trails"))
+                        .changed();
+                    changed |= ui
+                        .checkbox(&mut settings.colorblind, tr("settings-colorblind"))
+                        .changed();
+                    changed |= ui
+                        .checkbox(&mut settings.floating_text, tr("settings-floating-text"))
+                        .changed();
+                    changed |= ui
+                        .checkbox(&mut settings.full_numbers, tr("settings-full-numbers"))
+                        .changed();
+                    changed |= ui
+                        .checkbox(&mut settings.gauge, tr("settings-gauge"))
+                        .changed();
+                    changed |= ui
+                        .checkbox(&mut settings.screen_shake, tr("settings-screen-shake"))
+                        .changed();
+                    changed |= ui
+                        .checkbox(&mut settings.flash, tr("settings-flash"))
+                        .changed();
+                    let mute_key = settings.keybinds.label(Action::ToggleMute);
+                    changed |= ui
+                        .checkbox(
+                            &mut settings.muted,
+                            tr_args("settings-muted", &[("key", &mute_key)]),
+                        )
+                        .changed();
+                    changed |= ui
+                        .checkbox(&mut settings.music_muted, tr("settings-music-muted"))
+                        .changed();
+                    changed |= ui
+                        .add_enabled(
+                            !settings.music_muted,
+                            egui::Slider::new(&mut settings.music_volume, 0.0..=1.0)
+                                .text(tr("settings-music-volume")),
+                        )
+                        .changed();
+                    ui.separator();
+                    ui.heading(tr("settings-interface"));
+                    // switch the language of all texts right away
+                    egui::ComboBox::from_label(tr("settings-language"))
+                        .selected_text(settings.lang.name())
+                        .show_ui(ui, |ui| {
+                            for lang in Lang::iter() {
+                                changed |= ui
+                                    .selectable_value(&mut settings.lang, lang, lang.name())
+                                    .changed();
+                            }
+                        });
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut settings.ui_scale, 1.0..=2.0)
+                                .text(tr("settings-ui-scale")),
+                        )
+                        .changed();
+                    changed |= ui
+                        .checkbox(&mut settings.scale_grains, tr("settings-scale-grains"))
+                        .changed();
+                    speedrun_changed = ui
+                        .checkbox(&mut settings.speedrun, tr("settings-speedrun"))
+                        .on_hover_text(tr("settings-speedrun-hint"))
+                        .changed();
+                    changed |= speedrun_changed;
+                    // what the game does while the window is unfocused or minimized
+                    egui::ComboBox::from_label(tr("settings-background"))
+                        .selected_text(settings.background.name())
+                        .show_ui(ui, |ui| {
+                            for mode in BackgroundMode::iter() {
+                                changed |= ui
+                                    .selectable_value(&mut settings.background, mode, mode.name())
+                                    .on_hover_text(mode.hint())
+                                    .changed();
+                            }
+                        });
+                    // cap the frame rate, the physics always runs at FPS
+                    let cap_text = |cap: u32| match cap {
+                        0 => tr("settings-fps-unlimited").to_string(),
+                        cap => tr_args("settings-fps", &[("fps", &cap)]),
+                    };
+                    egui::ComboBox::from_label(tr("settings-fps-cap"))
+                        .selected_text(cap_text(settings.fps_cap))
+                        .show_ui(ui, |ui| {
+                            for cap in FPS_CAPS {
+                                changed |= ui
+                                    .selectable_value(&mut settings.fps_cap, cap, cap_text(cap))
+                                    .changed();
+                            }
+                        });
+                    // weak computers compact the oldest settled grains sooner
+                    let grains_text = |cap: u32| match cap {
+                        0 => tr("settings-max-grains-unlimited").to_string(),
+                        cap => cap.to_string(),
+                    };
+                    egui::ComboBox::from_label(tr("settings-max-grains"))
+                        .selected_text(grains_text(settings.max_grains))
+                        .show_ui(ui, |ui| {
+                            for cap in GRAIN_CAPS {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut settings.max_grains,
+                                        cap,
+                                        grains_text(cap),
+                                    )
+                                    .changed();
+                            }
+                        })
+                        .response
+                        .on_hover_text(tr("settings-max-grains-hint"));
+                    // pick one of the owned background themes
+                    egui::ComboBox::from_label(tr("settings-theme"))
+                        .selected_text(self.theme.name())
+                        .show_ui(ui, |ui| {
+                            for theme in Theme::iter() {
+                                if self.owned_themes.contains(&theme) {
+                                    theme_changed |= ui
+                                        .selectable_value(&mut self.theme, theme, theme.name())
+                                        .changed();
+                                }
+                            }
+                        });
+                    // lock the day/night cycle to a fixed time of day
+                    let selected = settings
+                        .fixed_time
+                        .map_or(tr("settings-time-cycle"), |time| time.name());
+                    egui::ComboBox::from_label(tr("settings-time-of-day"))
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut settings.fixed_time,
+                                    None,
+                                    tr("settings-time-cycle"),
+                                )
+                                .changed();
+                            for time in TimeOfDay::iter() {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut settings.fixed_time,
+                                        Some(time),
+                                        time.name(),
+                                    )
+                                    .changed();
+                            }
+                        });
+
+                    // click an action, then press the new key for it
+                    ui.separator();
+                    ui.heading(tr("settings-controls"));
+                    egui::Grid::new("controls").num_columns(3).show(ui, |ui| {
+                        for action in Action::iter() {
+                            ui.label(action.name());
+                            let text = if self.rebinding == Some(action) {
+                                tr("settings-press-key").to_string()
+                            } else {
+                                settings.keybinds.label(action)
+                            };
+                            if ui.button(text).clicked() {
+                                self.rebinding = Some(action);
+                            }
+                            if settings.keybinds.conflicts(action) {
+                                ui.colored_label(egui::Color32::RED, tr("settings-conflict"))
+                                    .on_hover_text(tr("settings-conflict-hint"));
+                            }
+                            ui.end_row();
+                        }
+                    });
+                    if ui.button(tr("settings-default-keys")).clicked() {
+                        settings.keybinds = Keybinds::default();
+                        self.rebinding = None;
+                        changed = true;
+                    }
+
+                    // pick what each mouse button does
+                    ui.separator();
+                    ui.heading(tr("settings-mouse"));
+                    egui::Grid::new("mouse").num_columns(2).show(ui, |ui| {
+                        for button in MOUSE_BUTTONS {
+                            ui.label(mouse_button_name(button));
+                            let current = settings.mouse.action(button);
+                            egui::ComboBox::from_id_source(format!("mouse.{:?}", button))
+                                .selected_text(current.name())
+                                .show_ui(ui, |ui| {
+                                    for action in MouseAction::iter() {
+                                        if ui
+                                            .selectable_label(current == action, action.name())
+                                            .clicked()
+                                        {
+                                            changed |= settings.mouse.bind(button, action);
+                                        }
+                                    }
+                                });
+                            ui.end_row();
+                        }
+                    });
+                    ui.label(tr("settings-mouse-hint"));
+                    if ui.button(tr("settings-swap-buttons")).clicked() {
+                        settings.mouse.swap();
+                        changed = true;
+                    }
+
+                    // resetting needs the player to type RESET first
+                    ui.separator();
+                    ui.heading(tr("settings-reset"));
+                    ui.label(tr("settings-reset-hint"));
+                    ui.text_edit_singleline(&mut self.reset_confirm);
+                    ui.horizontal(|ui| {
+                        ui.label(tr("settings-reset-profile"));
+                        for profile in Profile::iter() {
+                            ui.radio_value(&mut self.reset_profile, profile, profile.name())
+                                .on_hover_text(profile.desc());
+                        }
+                    });
+                    let confirmed = self.reset_confirm == "RESET";
+                    reset = ui
+                        .add_enabled(confirmed, Button::new(tr("settings-reset-button")))
+                        .clicked();
+                });
+            // apply and remember the new settings
+            if changed {
+                set_lang(self.settings.lang);
+                self.apply_palette();
+                self.try_save_settings();
+            }
+            if theme_changed {
+                self.try_save();
+            }
+            // turning the speedrun timer on starts a new run
+            if speedrun_changed && self.settings.speedrun {
+                self.speedrun.start(&self.sim);
+            }
+            if reset {
+                self.reset_confirm.clear();
+                self.apply(GameAction::Reset(self.reset_profile));
+                self.try_save();
+            }
+        }
+    }
+
+    /// updates the sand guide GUI
+    /// lists the unlocked sand particles with their color, value, and count,
+    /// and how many of them were ever dropped and sold
+    pub(crate) fn guide_gui(&mut self) {
+        if !self.show_guide {
+            return;
+        }
+        // gather the rows before borrowing the window state
+        let stats = &self.sim.stats;
+        let rows: Vec<(SandParticle, bool, String, u32, u64, u64)> = SandParticle::iter()
+            .map(|sand| {
+                let value = self.money_text(self.sim.sale_value(sand));
+                let count = self.sim.container.count(sand);
+                let dropped = *stats.tier_drops.get(&sand).unwrap_or(&0);
+                let sold = *stats.tier_sold.get(&sand).unwrap_or(&0);
+                (
+                    sand,
+                    self.sim.is_unlocked(sand),
+                    value,
+                    count,
+                    dropped,
+                    sold,
+                )
+            })
+            .collect();
+        let colorblind = self.settings.colorblind;
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            // create the sand guide window
+            egui::Window::new(tr("guide-title"))
+                .id(egui::Id::new("guide"))
+                .resizable(false)
+                .default_pos([550.0, 100.0])
+                .open(&mut self.show_guide)
+                .show(&gui_ctx, |ui| {
+                    egui::Grid::new("sand_guide")
+                        .striped(true)
+                        .num_columns(6)
+                        .show(ui, |ui| {
+                            ui.label("");
+                            ui.label(tr("column-sand"));
+                            ui.label(tr("column-value"));
+                            ui.label(tr("column-in-container"));
+                            ui.label(tr("column-dropped"));
+                            ui.label(tr("column-sold"));
+                            ui.end_row();
+                            for (sand, unlocked, value, count, dropped, sold) in rows {
+                                if unlocked {
+                                    let (r, g, b) = self
+                                        .sim
+                                        .particle_table
+                                        .palette_color(sand, colorblind)
+                                        .to_rgb();
+                                    ui.colored_label(egui::Color32::from_rgb(r, g, b), "■");
+                                    ui.label(sand.name());
+                                    ui.label(format!("{}$", value));
+                                    ui.label(count.to_string());
+                                    ui.label(dropped.to_string());
+                                    ui.label(sold.to_string());
+                                } else {
+                                    ui.label("");
+                                    ui.label("???");
+                                    ui.label("???");
+                                    ui.label("");
+                                    ui.label("");
+                                    ui.label("");
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+        }
+    }
+
+    /// updates the statistics GUI
+    /// displays the statistics of this session next to the lifetime ones
+    pub(crate) fn stats_gui(&mut self) {
+        if !self.show_stats {
+            return;
+        }
+        // gather the rows before borrowing the window state
+        let stats = &self.sim.stats;
+        let now = self.sim.total_time.as_secs_f32();
+        let money = |amount: Currency| format!("{}$", self.money_text(amount));
+        let per_minute = self.money_text(stats.money_per_minute(now));
+        let (session, lifetime) = (self.sim.session(), self.sim.lifetime());
+        // the counters have a session value, the rest only a lifetime one
+        let counter = |key: &str, value: &dyn Fn(&Counters) -> String| {
+            (tr(key).to_string(), value(&session), value(&lifetime))
+        };
+        let mut rows = vec![
+            counter("stats-time-played", &|counters| {
+                format_time(counters.time.as_secs())
+            }),
+            counter("stats-clicks", &|counters| counters.clicks.to_string()),
+            counter("stats-money-earned", &|counters| {
+                money(counters.money_earned)
+            }),
+            counter("stats-money-spent", &|counters| money(counters.money_spent)),
+            counter("stats-grains-clicked", &|counters| {
+                counters.manual_grains.to_string()
+            }),
+            counter("stats-grains-autoclicked", &|counters| {
+                counters.auto_grains.to_string()
+            }),
+        ];
+        rows.extend([
+            (
+                tr("stats-best-sale").to_string(),
+                String::new(),
+                money(stats.best_sale),
+            ),
+            (
+                tr("stats-average-sale").to_string(),
+                String::new(),
+                money(stats.average_sale()),
+            ),
+            (
+                tr("stats-average-fill").to_string(),
+                String::new(),
+                stats
+                    .average_fill()
+                    .map_or(String::new(), |fill| format!("{:.0}%", fill)),
+            ),
+            (
+                tr("stats-last-minute").to_string(),
+                tr_args("stats-per-minute", &[("money", &per_minute)]),
+                String::new(),
+            ),
+        ]);
+        if self.profile == Profile::Hardcore {
+            rows.push((
+                tr("stats-decayed").to_string(),
+                String::new(),
+                stats.decayed.to_string(),
+            ));
+        }
+        for sand in SandParticle::iter() {
+            if let Some(count) = stats.tier_drops.get(&sand) {
+                let name = tr_args("stats-dropped", &[("sand", &sand.name())]);
+                rows.push((name, String::new(), count.to_string()));
+            }
+            if let Some(count) = stats.tier_sold.get(&sand) {
+                let name = tr_args("stats-sold", &[("sand", &sand.name())]);
+                rows.push((name, String::new(), count.to_string()));
+            }
+        }
+        // the graph spans the last minutes, with its points and markers placed from 0 to 1
+        let graph = &self.sim.income_graph;
+        let span = GRAPH_POINTS as f32 * GRAPH_INTERVAL;
+        let end = graph.points.back().map_or(0, |(time, _)| *time);
+        let start = end.saturating_sub(span as u64);
+        let peak = graph.peak().as_f64().max(1.0);
+        let points: Vec<(f32, f32, String)> = graph
+            .points
+            .iter()
+            .map(|(time, money)| {
+                let text = tr_args(
+                    "stats-graph-point",
+                    &[
+                        ("time", &format_time(*time)),
+                        ("money", &self.money_text(*money)),
+                    ],
+                );
+                let height = (money.as_f64() / peak) as f32;
+                ((time - start) as f32 / span, height, text)
+            })
+            .collect();
+        // the purchases and conversions while the graph was sampled
+        let markers: Vec<(f32, bool)> = self
+            .sim
+            .history
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let (time, purchase) = match entry {
+                    HistoryEntry::Purchase { time, .. } => (*time, true),
+                    HistoryEntry::Sale { time, .. } => (*time, false),
+                };
+                (!points.is_empty() && (start..=end).contains(&time))
+                    .then(|| ((time - start) as f32 / span, purchase))
+            })
+            .collect();
+        // the last conversions, newest first
+        let last_sales: Vec<(String, String)> = self
+            .sim
+            .stats
+            .last_sales
+            .iter()
+            .rev()
+            .map(|(time, earned)| (format_time(*time), money(*earned)))
+            .collect();
+        let scores = self.score_rows();
+        let days = self.calendar_days();
+        let paused = self.paused;
+        let mut export = false;
+        let mut reset_session = false;
+        let mut reset_heatmap = false;
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            // create the statistics window
+            egui::Window::new(tr("stats-title"))
+                .id(egui::Id::new("statistics"))
+                .resizable(false)
+                .default_pos([300.0, 300.0])
+                .open(&mut self.show_stats)
+                .show(&gui_ctx, |ui| {
+                    egui::Grid::new("statistics")
+                        .striped(true)
+                        .num_columns(3)
+                        .show(ui, |ui| {
+                            ui.label("");
+                            ui.label(egui::RichText::new(tr("stats-session")).strong());
+                            ui.label(egui::RichText::new(tr("stats-lifetime")).strong());
+                            ui.end_row();
+                            for (name, session, lifetime) in rows {
+                                ui.label(name);
+                                ui.label(session);
+                                ui.label(lifetime);
+                                ui.end_row();
+                            }
+                        });
+                    ui.collapsing(tr("stats-last-sales"), |ui| {
+                        if last_sales.is_empty() {
+                            ui.label(tr("stats-no-sales"));
+                        }
+                        egui::Grid::new("last_sales")
+                            .striped(true)
+                            .num_columns(2)
+                            .show(ui, |ui| {
+                                for (time, earned) in &last_sales {
+                                    ui.label(time);
+                                    ui.label(earned);
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                    ui.collapsing(tr("stats-blitz-scores"), |ui| {
+                        score_grid(ui, &scores, None);
+                    });
+                    ui.collapsing(tr("stats-daily"), |ui| {
+                        daily_calendar(ui, &days);
+                    });
+                    reset_session = ui
+                        .button(tr("stats-session-reset"))
+                        .on_hover_text(tr("stats-session-reset-tooltip"))
+                        .clicked();
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label(tr("stats-income-graph"));
+                        if paused {
+                            ui.colored_label(egui::Color32::GRAY, tr("stats-graph-paused"));
+                        }
+                    });
+                    let (response, painter) = ui.allocate_painter(
+                        egui::vec2(ui.available_width(), 80.0),
+                        egui::Sense::hover(),
+                    );
+                    let rect = response.rect;
+                    let to_screen = |x: f32, y: f32| {
+                        egui::pos2(
+                            rect.left() + x * rect.width(),
+                            rect.bottom() - y * rect.height(),
+                        )
+                    };
+                    painter.rect_filled(rect, 2.0, egui::Color32::from_black_alpha(60));
+                    // purchases are gold and conversions are green
+                    for (x, purchase) in &markers {
+                        let color = if *purchase {
+                            egui::Color32::GOLD
+                        } else {
+                            egui::Color32::LIGHT_GREEN
+                        };
+                        painter.line_segment(
+                            [to_screen(*x, 0.0), to_screen(*x, 1.0)],
+                            egui::Stroke::new(1.0, color.gamma_multiply(0.5)),
+                        );
+                    }
+                    let line = egui::Stroke::new(1.5, egui::Color32::WHITE);
+                    for pair in points.windows(2) {
+                        painter.line_segment(
+                            [
+                                to_screen(pair[0].0, pair[0].1),
+                                to_screen(pair[1].0, pair[1].1),
+                            ],
+                            line,
+                        );
+                    }
+                    // the point nearest to the mouse shows its exact value
+                    let hovered = response.hover_pos().and_then(|pos| {
+                        let x = (pos.x - rect.left()) / rect.width();
+                        points
+                            .iter()
+                            .min_by(|a, b| (a.0 - x).abs().total_cmp(&(b.0 - x).abs()))
+                    });
+                    if let Some((x, y, text)) = hovered {
+                        painter.circle_filled(to_screen(*x, *y), 3.0, egui::Color32::WHITE);
+                        response.on_hover_text(text.as_str());
+                    }
+                    // where the player clicks, for this session only
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.show_heatmap, tr("stats-heatmap"));
+                        reset_heatmap = ui.button(tr("stats-heatmap-reset")).clicked();
+                    });
+                    // the per-minute samples go to a spreadsheet
+                    export = ui
+                        .button(tr("stats-export"))
+                        .on_hover_text(tr("stats-export-tooltip"))
+                        .clicked();
+                });
+        }
+        if export {
+            self.try_export_stats();
+        }
+        if reset_session {
+            self.sim.reset_session();
+        }
+        if reset_heatmap {
+            self.heatmap.reset();
+        }
+    }
+
+    /// updates the achievements GUI
+    /// lists the achievements with their progress, earned ones first
+    pub(crate) fn achievements_gui(&mut self) {
+        if !self.show_achievements {
+            return;
+        }
+        // gather the rows before borrowing the window state
+        let mut rows: Vec<(Achievement, Option<u64>, u128, u128)> = Achievement::iter()
+            .map(|achievement| {
+                let (current, goal) = self.sim.achievement_progress(achievement);
+                let earned = self.sim.achievements.get(&achievement).copied();
+                (achievement, earned, current, goal)
+            })
+            .collect();
+        // the earned achievements are pinned to the top, newest first
+        rows.sort_by_key(|(_, earned, _, _)| std::cmp::Reverse(*earned));
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            // create the achievements window
+            egui::Window::new(tr("achievements-title"))
+                .id(egui::Id::new("achievements"))
+                .resizable(false)
+                .default_pos([300.0, 150.0])
+                .open(&mut self.show_achievements)
+                .show(&gui_ctx, |ui| {
+                    egui::ScrollArea::vertical()
+                        .max_height(300.0)
+                        .show(ui, |ui| {
+                            for (achievement, earned, current, goal) in rows {
+                                ui.label(format!("{} {}", achievement.icon(), achievement.name()));
+                                ui.small(achievement.desc());
+                                match earned {
+                                    Some(time) => {
+                                        ui.add(egui::ProgressBar::new(1.0).text(tr_args(
+                                            "achievements-earned-at",
+                                            &[("time", &format_time(time))],
+                                        )));
+                                    }
+                                    None => {
+                                        let fraction = current.min(goal) as f32 / goal as f32;
+                                        ui.add(egui::ProgressBar::new(fraction).text(format!(
+                                            "{}/{}",
+                                            current.min(goal),
+                                            goal
+                                        )));
+                                    }
+                                }
+                                ui.separator();
+                            }
+                        });
+                });
+        }
+    }
+
+    /// updates the challenges GUI
+    /// lists the challenges with their rules and goals, starts one,
+    /// or gives up the one being played
+    pub(crate) fn challenges_gui(&mut self) {
+        if !self.show_challenges {
+            return;
+        }
+        // gather the rows before borrowing the window state
+        let rows: Vec<(Challenge, bool, String)> = Challenge::iter()
+            .map(|challenge| {
+                let done = self.completed_challenges.contains(&challenge);
+                (challenge, done, self.money_text(challenge.goal()))
+            })
+            .collect();
+        let playing = self.challenge.as_ref().map(|run| run.challenge);
+        // a sandbox game plays no challenges
+        let startable = playing.is_none() && self.profile.scored();
+        let earned = self.sim.lifetime().money_earned;
+        let percent = (CHALLENGE_REWARD * 100.0).round();
+        let mut start = None;
+        let mut give_up = false;
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            // create the challenges window
+            egui::Window::new(tr("challenges-title"))
+                .id(egui::Id::new("challenges"))
+                .resizable(false)
+                .default_pos([300.0, 150.0])
+                .open(&mut self.show_challenges)
+                .show(&gui_ctx, |ui| {
+                    ui.label(tr_args("challenges-intro", &[("percent", &percent)]));
+                    for (challenge, done, goal) in rows {
+                        ui.separator();
+                        if done {
+                            ui.strong(format!("{} ✔", challenge.name()));
+                        } else {
+                            ui.strong(challenge.name());
+                        }
+                        ui.label(challenge.desc());
+                        ui.small(tr_args("challenge-goal", &[("goal", &goal)]));
+                        if playing == Some(challenge) {
+                            let fraction = earned.as_f64() / challenge.goal().as_f64().max(1.0);
+                            ui.add(
+                                egui::ProgressBar::new(fraction.min(1.0) as f32).show_percentage(),
+                            );
+                            give_up = ui.button(tr("challenge-give-up")).clicked();
+                        } else if ui
+                            .add_enabled(startable, Button::new(tr("challenge-start")))
+                            .clicked()
+                        {
+                            start = Some(challenge);
+                        }
+                    }
+                });
+        }
+        if let Some(challenge) = start {
+            self.queue(GameAction::StartChallenge(challenge));
+            self.show_challenges = false;
+            self.paused = false;
+        }
+        if give_up {
+            self.queue(GameAction::EndChallenge);
+        }
+    }
+
+    /// updates the summary GUI of a completed challenge
+    /// shows what the run took and the reward, until it is closed
+    pub(crate) fn challenge_summary_gui(&mut self) {
+        let Some(summary) = self.challenge_summary else {
+            return;
+        };
+        let money = self.money_text(summary.counters.money_earned);
+        let percent = (CHALLENGE_REWARD * 100.0 * self.completed_challenges.len() as f64).round();
+        let mut close = false;
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            // create the summary in the middle of the screen
+            egui::Window::new(tr("challenge-complete-title"))
+                .id(egui::Id::new("challenge_summary"))
+                .resizable(false)
+                .collapsible(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(&gui_ctx, |ui| {
+                    ui.heading(summary.challenge.name());
+                    ui.label(tr_args(
+                        "challenge-complete-time",
+                        &[("time", &format_time(summary.counters.time.as_secs()))],
+                    ));
+                    ui.label(tr_args(
+                        "challenge-complete-clicks",
+                        &[("clicks", &summary.counters.clicks)],
+                    ));
+                    ui.label(tr_args("challenge-complete-money", &[("money", &money)]));
+                    ui.label(tr_args(
+                        "challenge-complete-reward",
+                        &[("percent", &percent)],
+                    ));
+                    close = ui.button(tr("challenge-complete-close")).clicked();
+                });
+        }
+        if close {
+            self.challenge_summary = None;
+        }
+    }
+
+    /// updates the countdown GUI of the blitz being played
+    /// shows the time left and the money earned, and gives up the blitz
+    pub(crate) fn blitz_gui(&mut self) {
+        let Some(blitz) = &self.blitz else {
+            return;
+        };
+        let time = format_time(blitz.time_left.ceil() as u64);
+        let rules = blitz.daily_rules().map(|rules| rules.desc());
+        let money = self.money_text(self.sim.lifetime().money_earned);
+        let mut give_up = false;
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            egui::Window::new(tr("blitz-title"))
+                .id(egui::Id::new("blitz"))
+                .resizable(false)
+                .collapsible(false)
+                .title_bar(false)
+                .anchor(egui::Align2::CENTER_TOP, [0.0, 10.0])
+                .show(&gui_ctx, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.heading(time);
+                        if let Some(rules) = rules {
+                            ui.small(rules);
+                        }
+                        ui.label(tr_args("blitz-earned", &[("money", &money)]));
+                        give_up = ui.small_button(tr("blitz-give-up")).clicked();
+                    });
+                });
+        }
+        if give_up {
+            self.queue(GameAction::EndBlitz);
+        }
+    }
+
+    /// updates the summary GUI of a finished blitz
+    /// shows the score and the high-score table, until it is closed
+    pub(crate) fn blitz_summary_gui(&mut self) {
+        let Some(summary) = self.blitz_summary else {
+            return;
+        };
+        let money = self.money_text(summary.money);
+        let rank = match (summary.daily, summary.rank) {
+            (Some(_), Some(_)) => tr("daily-over-best").to_string(),
+            (Some(day), None) => {
+                let best = self.daily_scores.get(day).unwrap_or_default();
+                tr_args(
+                    "daily-over-not-best",
+                    &[("best", &format!("{}$", self.money_text(best)))],
+                )
+            }
+            (None, Some(rank)) => tr_args("blitz-over-rank", &[("rank", &(rank + 1))]),
+            (None, None) => tr("blitz-over-unranked").to_string(),
+        };
+        let scores = self.score_rows();
+        let mut close = false;
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            // create the summary in the middle of the screen
+            egui::Window::new(tr("blitz-over-title"))
+                .id(egui::Id::new("blitz_summary"))
+                .resizable(false)
+                .collapsible(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(&gui_ctx, |ui| {
+                    ui.heading(tr_args("blitz-over-money", &[("money", &money)]));
+                    ui.label(rank);
+                    // a daily run has a calendar in the statistics window instead of the table
+                    if summary.daily.is_none() {
+                        ui.separator();
+                        score_grid(ui, &scores, summary.rank);
+                    }
+                    close = ui.button(tr("blitz-over-close")).clicked();
+                });
+        }
+        if close {
+            self.blitz_summary = None;
+        }
+    }
+
+    /// updates the sandbox GUI, only there in a sandbox game
+    /// picks the sand particle of the next drops, and sets the gravity and the grain size
+    pub(crate) fn sandbox_gui(&mut self) {
+        if self.profile != Profile::Sandbox {
+            return;
+        }
+        // gather the palette before borrowing the window state
+        let colorblind = self.settings.colorblind;
+        let palette: Vec<(SandParticle, egui::Color32)> = SandParticle::iter()
+            .take(self.sim.particle_table.len() as usize)
+            .map(|sand| {
+                let color = self.sim.particle_table.palette_color(sand, colorblind);
+                let (r, g, b) = color.to_rgb();
+                (sand, egui::Color32::from_rgb(r, g, b))
+            })
+            .collect();
+        let mut tools = self.sandbox;
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            egui::Window::new(tr("sandbox-title"))
+                .id(egui::Id::new("sandbox"))
+                .resizable(false)
+                .default_pos([10.0, 400.0])
+                .show(&gui_ctx, |ui| {
+                    ui.label(tr("sandbox-palette"));
+                    ui.horizontal_wrapped(|ui| {
+                        ui.selectable_value(&mut tools.palette, None, tr("sandbox-random"));
+                        for (sand, color) in palette {
+                            let label =
+                                egui::RichText::new(format!("■ {}", sand.name())).color(color);
+                            ui.selectable_value(&mut tools.palette, Some(sand), label);
+                        }
+                    });
+                    ui.add(
+                        egui::Slider::new(
+                            &mut tools.gravity,
+                            SANDBOX_GRAVITY.0..=SANDBOX_GRAVITY.1,
+                        )
+                        .text(tr("sandbox-gravity")),
+                    );
+                    ui.add(
+                        egui::Slider::new(
+                            &mut tools.grain_size,
+                            SANDBOX_GRAIN_SIZE.0..=SANDBOX_GRAIN_SIZE.1,
+                        )
+                        .text(tr("sandbox-grain-size")),
+                    );
+                    if ui.button(tr("sandbox-defaults")).clicked() {
+                        tools = SandboxTools::default();
+                    }
+                });
+        }
+        // the tools change the simulation, so they go through the actions
+        if tools != self.sandbox {
+            self.queue(GameAction::SetSandbox(tools));
+        }
+    }
+
+    /// returns the rows of the blitz high-score table: the money and the date of each score
+    pub(crate) fn score_rows(&self) -> Vec<(String, String)> {
+        self.blitz_scores
+            .scores
+            .iter()
+            .map(|score| {
+                (
+                    format!("{}$", self.money_text(score.money)),
+                    score.date.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// returns the days of the daily run calendar, None for the days after today
+    pub(crate) fn calendar_days(&self) -> Vec<Option<CalendarDay>> {
+        let today = today();
+        calendar(today)
+            .into_iter()
+            .map(|day| {
+                (day <= today).then(|| {
+                    let date = format_date(day);
+                    let best = self.daily_scores.get(day).map(|best| {
+                        let rules = DailyRules::for_date(&date).desc();
+                        format!("{} {}$\n{}", date, self.money_text(best), rules)
+                    });
+                    CalendarDay {
+                        number: date[8..].to_string(),
+                        best,
+                        today: day == today,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// updates the history GUI
+    /// lists the recent purchases and sales, newest first
+    pub(crate) fn history_gui(&mut self) {
+        if !self.show_history {
+            return;
+        }
+        // gather the rows before borrowing the window state
+        let rows: Vec<(String, String)> = self
+            .sim
+            .history
+            .entries
+            .iter()
+            .rev()
+            .map(|entry| match entry {
+                HistoryEntry::Purchase {
+                    time,
+                    upgrade,
+                    level,
+                    cost,
+                } => (
+                    format_time(*time),
+                    tr_args(
+                        "history-purchase",
+                        &[
+                            ("upgrade", &upgrade.name()),
+                            ("level", level),
+                            ("cost", &self.money_text(*cost)),
+                        ],
+                    ),
+                ),
+                HistoryEntry::Sale { time, earned } => (
+                    format_time(*time),
+                    tr_args("history-sale", &[("money", &self.money_text(*earned))]),
+                ),
+            })
+            .collect();
+        let mut clear = false;
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            // create the history window
+            egui::Window::new(tr("history-title"))
+                .id(egui::Id::new("history"))
+                .resizable(false)
+                .default_pos([300.0, 200.0])
+                .open(&mut self.show_history)
+                .show(&gui_ctx, |ui| {
+                    if rows.is_empty() {
+                        ui.label(tr("history-empty"));
+                        return;
+                    }
+                    clear = ui.button(tr("history-clear")).clicked();
+                    ui.separator();
+                    egui::ScrollArea::vertical()
+                        .max_height(300.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new("history")
+                                .striped(true)
+                                .num_columns(2)
+                                .show(ui, |ui| {
+                                    for (time, text) in rows {
+                                        ui.label(time);
+                                        ui.label(text);
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                });
+        }
+        if clear {
+            self.sim.history.entries.clear();
+        }
+    }
+
+    /// returns the money amount as text
+    /// abbreviated unless the player prefers full numbers
+    pub(crate) fn money_text(&self, money: Currency) -> String {
+        if self.settings.full_numbers {
+            money.to_string()
+        } else {
+            format_money(money)
+        }
+    }
+
+    /// draws the background gradient of the theme, if it has one
+    pub(crate) fn background(&self, canvas: &mut graphics::Canvas) {
+        if let Some((top, bottom)) = self.theme.gradient() {
+            // draw the gradient as thin horizontal bands
+            let bands = 32;
+            let height = self.sim.screen_size.1 / bands as f32;
+            for i in 0..bands {
+                let t = i as f32 / (bands - 1) as f32;
+                let color = Color::new(
+                    top.r + (bottom.r - top.r) * t,
+                    top.g + (bottom.g - top.g) * t,
+                    top.b + (bottom.b - top.b) * t,
+                    1.0,
+                );
+                let band = Rect::new(0.0, i as f32 * height, self.sim.screen_size.0, height + 1.0);
+                canvas.draw(
+                    &graphics::Quad,
+                    DrawParam::default().dest_rect(band).color(color),
+                );
+            }
+        }
+    }
+
+    /// returns the color the screen is cleared with
+    /// themes without a gradient follow the day/night cycle
+    pub(crate) fn clear_color(&self) -> Color {
+        match self.theme.gradient() {
+            Some(_) => self.theme.clear_color(),
+            None => sky_color(self.time_of_day()),
+        }
+    }
+
+    /// returns a text color that is readable on the background
+    pub(crate) fn text_color(&self) -> Color {
+        match self.theme.gradient() {
+            Some(_) => self.theme.text_color(),
+            None => contrast_color(self.clear_color()),
+        }
+    }
+
+    /// returns true if the mouse is over the GUI
+    pub(crate) fn over_gui(&mut self) -> bool {
+        match &mut self.gui {
+            Some(gui) => gui.ctx().wants_pointer_input(),
+            None => false,
+        }
+    }
+
+    /// returns true if the screen position (x, y) is over a GUI window
+    pub(crate) fn gui_at(&mut self, x: f32, y: f32) -> bool {
+        match &mut self.gui {
+            Some(gui) => {
+                let scale = gui.ctx().pixels_per_point();
+                gui.ctx()
+                    .layer_id_at(egui::pos2(x / scale, y / scale))
+                    .is_some()
+            }
+            None => false,
+        }
+    }
+
+    /// returns the colors of the sand tiers a click can drop
+    pub(crate) fn tier_colors(&self) -> Vec<Color> {
+        let level = self.sim.effect_level(UpgradeEffect::ParticleTier);
+        (0..level)
+            .filter_map(SandParticle::from_u32)
+            .map(|sand| {
+                self.sim
+                    .particle_table
+                    .palette_color(sand, self.settings.colorblind)
+            })
+            .collect()
+    }
+
+    /// draws the click heatmap over the play area, from cold blue to hot red
+    pub(crate) fn heatmap_overlay(&self, canvas: &mut graphics::Canvas) {
+        for (rect, heat) in self.heatmap.cells(self.sim.screen_size) {
+            let (left, top) = self.camera.to_screen(rect.x, rect.y);
+            let (right, bottom) = self.camera.to_screen(rect.right(), rect.bottom());
+            let color = Color::new(heat, 0.2, 1.0 - heat, 0.15 + 0.4 * heat);
+            canvas.draw(
+                &graphics::Quad,
+                DrawParam::default()
+                    .dest_rect(Rect::new(left, top, right - left, bottom - top))
+                    .color(color),
+            );
+        }
+    }
+
+    /// draws the compacted sand as a solid layer at the bottom of the pile
+    /// in the color of the sand most of it is made of
+    pub(crate) fn compacted_layer(&self, canvas: &mut graphics::Canvas) {
+        let Some(sand) = self
+            .sim
+            .container
+            .compacted()
+            .iter()
+            .max_by_key(|(sand, count)| (**count, sand.level()))
+            .map(|(sand, _)| *sand)
+        else {
+            return;
+        };
+        let (width, floor) = self.sim.screen_size;
+        let area = self.sim.compacted_amount() as f32 * self.sim.grain_size.powi(2);
+        let height = (area / width).min(floor);
+        let (left, top) = self.camera.to_screen(0.0, floor - height);
+        let (right, bottom) = self.camera.to_screen(width, floor);
+        let color = self
+            .sim
+            .particle_table
+            .palette_color(sand, self.settings.colorblind);
+        canvas.draw(
+            &graphics::Quad,
+            DrawParam::default()
+                .dest_rect(Rect::new(left, top, right - left, bottom - top))
+                .color(color),
+        );
+    }
+
+    /// draws the drop indicator at the mouse
+    /// a ring of the possible tier colors and the amount of grains per click
+    pub(crate) fn drop_indicator(&self, canvas: &mut graphics::Canvas) {
+        let (x, y) = self.pointer;
+        let color = self.text_color();
+        let dot = Rect::new(x - 1.5, y - 1.5, 3.0, 3.0);
+        canvas.draw(
+            &graphics::Quad,
+            DrawParam::default().dest_rect(dot).color(color),
+        );
+        let colors = self.tier_colors();
+        for (i, tier_color) in colors.iter().enumerate() {
+            let angle = i as f32 / colors.len() as f32 * std::f32::consts::TAU;
+            let (dx, dy) = (angle.cos() * 10.0, angle.sin() * 10.0);
+            let dot = Rect::new(x + dx - 2.0, y + dy - 2.0, 4.0, 4.0);
+            canvas.draw(
+                &graphics::Quad,
+                DrawParam::default().dest_rect(dot).color(*tier_color),
+            );
+        }
+        let txt = Text::new(format!("x{}", self.click_amount()));
+        canvas.draw(&txt, DrawParam::from([x + 14.0, y + 8.0]).color(color));
+        // the combo pulses above the cursor
+        if self.sim.combo.count > 0 {
+            let bonus = ((self.sim.combo.multiplier() - 1.0) * 100.0).round();
+            let mut txt = Text::new(tr_args(
+                "combo",
+                &[("count", &self.sim.combo.count), ("bonus", &bonus)],
+            ));
+            let pulse = 1.0 + 0.15 * (self.sim.total_time.as_secs_f32() * 12.0).sin();
+            txt.set_scale(TEXT_SIZE * self.settings.ui_scale * pulse);
+            canvas.draw(&txt, DrawParam::from([x + 14.0, y - 24.0]).color(color));
+        }
+    }
+
+    /// draws the gamepad cursor as a cross at the world position (x, y)
+    pub(crate) fn gamepad_indicator(&self, canvas: &mut graphics::Canvas, x: f32, y: f32) {
+        let (x, y) = self.camera.to_screen(x, y);
+        let color = self.text_color();
+        for bar in [
+            Rect::new(x - 8.0, y - 1.0, 16.0, 2.0),
+            Rect::new(x - 1.0, y - 8.0, 2.0, 16.0),
+        ] {
+            canvas.draw(
+                &graphics::Quad,
+                DrawParam::default().dest_rect(bar).color(color),
+            );
+        }
+    }
+
+    /// draws the game info on the screen
+    /// the text is only formatted again when one of its values changed
+    /// it flashes red after a click into the full container
+    pub(crate) fn game_info(&mut self, canvas: &mut graphics::Canvas) {
+        let money = self.sim.money;
+        let size = self.sim.get_size();
+        let amount = self.sim.get_amount();
+        let per_second = self.sim.income.per_second();
+        let scale = TEXT_SIZE * self.settings.ui_scale;
+        let key = (
+            amount,
+            size,
+            money,
+            per_second,
+            self.settings.full_numbers,
+            self.settings.lang,
+            scale,
+        );
+        if !self.game_text.is_current(&key) {
+            let income = tr_args(
+                "income-per-second",
+                &[("money", &self.money_text(per_second))],
+            );
+            let money = self.money_text(money);
+            self.game_text.set(
+                key,
+                scale,
+                format_args!("{}/{}\n{}$\n{}", amount, size, money, income),
+            );
+        }
+        let mut color = self.text_color();
+        if self.full_flash_timer > 0.0 {
+            let flash = self.full_flash_timer / FULL_FLASH_TIME;
+            color.r += (1.0 - color.r) * flash;
+            color.g -= color.g * 0.8 * flash;
+            color.b -= color.b * 0.8 * flash;
+        }
+        canvas.draw(
+            self.game_text.text(),
+            DrawParam::from([10.0, 10.0]).color(color),
+        );
+    }
+
+    /// draws the container gauge on the right edge of the screen
+    /// the border pulses when the container is full
+    pub(crate) fn fill_gauge(&self, canvas: &mut graphics::Canvas) {
+        let fill = (self.sim.get_amount() as f32 / self.sim.get_size().max(1) as f32).min(1.0);
+        let (width, height) = self.sim.screen_size;
+        let gauge = Rect::new(width - 20.0, 120.0, 10.0, (height - 160.0).max(0.0));
+        // border, drawn as a slightly bigger rect behind the gauge
+        let border_color = if self.sim.is_full() {
+            let pulse = (self.sim.total_time.as_secs_f32() * 6.0).sin() * 0.5 + 0.5;
+            Color::new(1.0, 0.2, 0.2, 0.4 + 0.6 * pulse)
+        } else {
+            let mut color = self.text_color();
+            color.a = 0.5;
+            color
+        };
+        let border = Rect::new(gauge.x - 2.0, gauge.y - 2.0, gauge.w + 4.0, gauge.h + 4.0);
+        canvas.draw(
+            &graphics::Quad,
+            DrawParam::default().dest_rect(border).color(border_color),
+        );
+        canvas.draw(
+            &graphics::Quad,
+            DrawParam::default()
+                .dest_rect(gauge)
+                .color(Color::new(0.0, 0.0, 0.0, 0.6)),
+        );
+        // the fill grows from the bottom
+        let filled = Rect::new(
+            gauge.x,
+            gauge.bottom() - gauge.h * fill,
+            gauge.w,
+            gauge.h * fill,
+        );
+        canvas.draw(
+            &graphics::Quad,
+            DrawParam::default()
+                .dest_rect(filled)
+                .color(gauge_color(fill)),
+        );
+    }
+
+    /// draws the low gravity banner with a countdown
+    pub(crate) fn low_gravity_banner(&self, canvas: &mut graphics::Canvas) {
+        let seconds = self.sim.low_gravity_timer.ceil() as u32;
+        let txt = Text::new(tr_args("low-gravity", &[("seconds", &seconds)]));
+        canvas.draw(
+            &txt,
+            DrawParam::from([self.sim.screen_size.0 / 2.0 - 60.0, 10.0]).color(self.text_color()),
+        );
+    }
+
+    /// draws the player info on the screen
+    /// the text is only formatted again when one of its values changed
+    pub(crate) fn player_info(&mut self, canvas: &mut graphics::Canvas) {
+        let total_time = self.sim.total_time.as_secs();
+        let total_clicks = self.sim.total_clicks;
+        let scale = self.settings.ui_scale;
+        let key = (
+            total_time,
+            total_clicks,
+            self.settings.muted,
+            self.settings.music_muted,
+            self.settings.music_volume,
+            self.settings.lang,
+            scale,
+            self.ng_plus,
+        );
+        if !self.player_text.is_current(&key) {
+            let mut info = tr_args(
+                "player-info",
+                &[
+                    ("time", &format_time(total_time)),
+                    ("clicks", &total_clicks),
+                ],
+            );
+            if self.ng_plus > 0 {
+                info.push('\n');
+                info.push_str(&tr_args("player-ng-plus", &[("level", &self.ng_plus)]));
+            }
+            let audio = self.audio_state();
+            self.player_text
+                .set(key, TEXT_SIZE * scale, format_args!("{}\n{}", info, audio));
+        }
+        // keep the info below the scaled game info
+        let y = 10.0 + 60.0 * scale;
+        let color = self.text_color();
+        canvas.draw(
+            self.player_text.text(),
+            DrawParam::from([10.0, y]).color(color),
+        );
+    }
+
+    /// draws the debug overlay in the top-right corner
+    pub(crate) fn debug_info(&self, canvas: &mut graphics::Canvas, fps: f64) {
+        let settled = self
+            .sim
+            .container
+            .iter()
+            .filter(|grain| grain.is_done())
+            .count();
+        let active = self.sim.container.len() - settled;
+        let capacity = self.batch.as_ref().map_or(0, |batch| batch.capacity());
+        let txt = Text::new(format!(
+            "FPS: {:.0}\nActive: {}\nSettled: {}\nCompacted: {}\nBuffer: {}\nPhysics: {:.2}ms\nGUI: {:.2}ms",
+            fps,
+            active,
+            settled,
+            self.sim.compacted_amount(),
+            capacity,
+            self.physics_time.as_secs_f64() * 1000.0,
+            self.gui_time.as_secs_f64() * 1000.0
+        ));
+        let color = self.text_color();
+        canvas.draw(
+            &txt,
+            DrawParam::from([self.sim.screen_size.0 - 160.0, 10.0]).color(color),
+        );
+    }
+
+    /// draws the current tutorial step at the top of the screen
+    pub(crate) fn tutorial_gui(&mut self) {
+        let Some(step) = self.tutorial else {
+            return;
+        };
+        let info_key = self.settings.keybinds.label(Action::ToggleInfo);
+        let mut skip = false;
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            egui::Window::new(tr("tutorial-title"))
+                .id(egui::Id::new("tutorial"))
+                .resizable(false)
+                .collapsible(false)
+                .anchor(egui::Align2::CENTER_TOP, [0.0, 10.0])
+                .show(&gui_ctx, |ui| {
+                    ui.label(tr(step.text_key()));
+                    if step == TutorialStep::Upgrade {
+                        ui.small(tr_args("tutorial-info", &[("key", &info_key)]));
+                    }
+                    skip = ui.button(tr("tutorial-skip")).clicked();
+                });
+        }
+        if skip {
+            self.skip_tutorial();
+        }
+    }
+
+    /// draws the replay banner at the top of the screen
+    /// with the playback speed buttons and, at the end, whether the replay matched
+    pub(crate) fn replay_gui(&mut self) {
+        let Some(playback) = &mut self.playback else {
+            return;
+        };
+        let time = format_time(self.ticks / FPS as u64);
+        let total = format_time(playback.replay.end / FPS as u64);
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            egui::Window::new(tr("replay-title"))
+                .id(egui::Id::new("replay"))
+                .resizable(false)
+                .collapsible(false)
+                .anchor(egui::Align2::CENTER_TOP, [0.0, 10.0])
+                .show(&gui_ctx, |ui| {
+                    ui.label(tr_args(
+                        "replay-progress",
+                        &[("time", &time), ("total", &total)],
+                    ));
+                    ui.horizontal(|ui| {
+                        for speed in [ReplaySpeed::Paused, ReplaySpeed::Normal, ReplaySpeed::Fast] {
+                            ui.selectable_value(&mut playback.speed, speed, speed.label());
+                        }
+                    });
+                    match playback.matched {
+                        Some(true) => {
+                            ui.label(tr("replay-matched"));
+                        }
+                        Some(false) => {
+                            ui.colored_label(egui::Color32::RED, tr("replay-diverged"));
+                        }
+                        None => {}
+                    }
+                });
+        }
+    }
+
+    /// draws the speedrun timer with the splits in the top right corner
+    /// each split shows how far ahead or behind the personal best it was
+    pub(crate) fn speedrun_gui(&mut self) {
+        if !self.settings.speedrun {
+            return;
+        }
+        let speedrun = &self.speedrun;
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            egui::Window::new(tr("speedrun-title"))
+                .id(egui::Id::new("speedrun"))
+                .resizable(false)
+                .collapsible(false)
+                .title_bar(false)
+                .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+                .show(&gui_ctx, |ui| {
+                    ui.heading(format_split(speedrun.elapsed));
+                    egui::Grid::new("splits").num_columns(3).show(ui, |ui| {
+                        for split in Split::iter() {
+                            ui.label(split.name());
+                            match speedrun.splits.get(&split) {
+                                Some(Some(time)) => {
+                                    ui.label(format_split(*time));
+                                }
+                                // the goal was reached before the run started
+                                Some(None) => {
+                                    ui.label("-").on_hover_text(tr("speedrun-skipped"));
+                                }
+                                None => {
+                                    ui.label("");
+                                }
+                            }
+                            match (speedrun.delta(split), speedrun.bests.get(&split)) {
+                                (Some(delta), _) => {
+                                    let color = if delta < 0.0 {
+                                        egui::Color32::GREEN
+                                    } else {
+                                        egui::Color32::RED
+                                    };
+                                    ui.colored_label(color, format_delta(delta));
+                                }
+                                (None, Some(best)) => {
+                                    ui.colored_label(egui::Color32::GRAY, format_split(*best))
+                                        .on_hover_text(tr("speedrun-best"));
+                                }
+                                (None, None) => {
+                                    ui.label("");
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+        }
+    }
+
+    /// draws the developer console dropped down from the top of the screen
+    /// Enter runs the typed command, the arrow keys go through the history
+    pub(crate) fn console_gui(&mut self) {
+        let Some(console) = &mut self.console else {
+            return;
+        };
+        if !console.open {
+            return;
+        }
+        let mut action = None;
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            egui::Window::new(tr("console-title"))
+                .id(egui::Id::new("console"))
+                .resizable(false)
+                .collapsible(false)
+                .title_bar(false)
+                .fixed_size([self.sim.screen_size.0 - 40.0, 0.0])
+                .anchor(egui::Align2::CENTER_TOP, [0.0, 0.0])
+                .show(&gui_ctx, |ui| {
+                    egui::ScrollArea::vertical()
+                        .max_height(200.0)
+                        .stick_to_bottom(true)
+                        .show(ui, |ui| {
+                            for line in &console.output {
+                                ui.monospace(line.as_str());
+                            }
+                        });
+                    let input = ui.add(
+                        egui::TextEdit::singleline(&mut console.input)
+                            .desired_width(f32::INFINITY)
+                            .font(egui::TextStyle::Monospace),
+                    );
+                    // the toggle key isn't part of any command
+                    console.input.retain(|c| c != '`' && c != '~');
+                    if input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        action = console.submit();
+                    } else if input.has_focus() && ui.input(|i| i.key_pressed(egui::Key::ArrowUp)) {
+                        console.previous();
+                    } else if input.has_focus() && ui.input(|i| i.key_pressed(egui::Key::ArrowDown))
+                    {
+                        console.next();
+                    }
+                    input.request_focus();
+                });
+        }
+        if let Some(action) = action {
+            self.apply(action);
+        }
+    }
+
+    /// draws a pulsing frame around the play area for the tutorial
+    pub(crate) fn tutorial_highlight(&self, canvas: &mut graphics::Canvas) {
+        let (width, height) = self.sim.screen_size;
+        let pulse = (self.sim.total_time.as_secs_f32() * 4.0).sin() * 0.5 + 0.5;
+        let alpha = (100.0 + 155.0 * pulse) as u8;
+        let color = Color::from_rgba(
+            TUTORIAL_COLOR.r(),
+            TUTORIAL_COLOR.g(),
+            TUTORIAL_COLOR.b(),
+            alpha,
+        );
+        let edges = [
+            Rect::new(0.0, 0.0, width, 4.0),
+            Rect::new(0.0, height - 4.0, width, 4.0),
+            Rect::new(0.0, 0.0, 4.0, height),
+            Rect::new(width - 4.0, 0.0, 4.0, height),
+        ];
+        for edge in edges {
+            canvas.draw(
+                &graphics::Quad,
+                DrawParam::default().dest_rect(edge).color(color),
+            );
+        }
+    }
+
+    /// draws the notifications as stacked panels in the top-right corner
+    pub(crate) fn notifications_gui(&mut self) {
+        if let Some(gui) = &mut self.gui {
+            // get the GUI context
+            let gui_ctx = gui.ctx();
+            let visible = self.notifications.visible();
+            let hidden = self.notifications.hidden();
+            let rows = visible
+                .iter()
+                .map(|(text, alpha)| (text.to_string(), *alpha))
+                .chain(
+                    (hidden > 0)
+                        .then(|| (tr_args("notifications-more", &[("count", &hidden)]), 1.0)),
+                );
+            for (i, (text, alpha)) in rows.enumerate() {
+                let alpha = (alpha * 255.0) as u8;
+                egui::Area::new(egui::Id::new("notification").with(i))
+                    .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0 + 36.0 * i as f32])
+                    .interactable(false)
+                    .show(&gui_ctx, |ui| {
+                        egui::Frame::none()
+                            .fill(egui::Color32::from_rgba_unmultiplied(
+                                0,
+                                0,
+                                0,
+                                alpha / 4 * 3,
+                            ))
+                            .inner_margin(8.0)
+                            .rounding(4.0)
+                            .show(ui, |ui| {
+                                let color =
+                                    egui::Color32::from_rgba_unmultiplied(255, 255, 255, alpha);
+                                ui.label(egui::RichText::new(text).color(color));
+                            });
+                    });
+            }
+        }
+    }
+}
+
+/// Queue of short messages for the player
+/// * messages: texts and the time they have left, oldest first
+#[derive(Debug, Default)]
+pub(crate) struct Notifications {
+    pub(crate) messages: VecDeque<(String, f32)>,
+}
+
+/// Implementation of methods for the Notifications struct
+/// * push: adds a message to the queue
+/// * update: ages the shown messages and removes the expired ones
+/// * visible: returns the shown messages and how opaque they are
+/// * hidden: returns the amount of messages waiting to be shown
+impl Notifications {
+    /// adds a message to the queue
+    pub(crate) fn push(&mut self, text: String) {
+        self.messages.push_back((text, NOTIFY_TIME));
+    }
+
+    /// ages the shown messages and removes the expired ones
+    /// the waiting messages keep their full time
+    pub(crate) fn update(&mut self, dt: f32) {
+        for (_, life) in self.messages.iter_mut().take(MAX_NOTIFICATIONS) {
+            *life -= dt;
+        }
+        self.messages.retain(|(_, life)| *life > 0.0);
+    }
+
+    /// returns the shown messages and how opaque they are
+    /// messages fade out during their last second
+    pub(crate) fn visible(&self) -> Vec<(&str, f32)> {
+        self.messages
+            .iter()
+            .take(MAX_NOTIFICATIONS)
+            .map(|(text, life)| (text.as_str(), life.min(1.0)))
+            .collect()
+    }
+
+    /// returns the amount of messages waiting to be shown
+    pub(crate) fn hidden(&self) -> usize {
+        self.messages.len().saturating_sub(MAX_NOTIFICATIONS)
+    }
+}
+
+/// Values the buy button of an upgrade was last labeled with
+/// * level: level of the upgrade
+/// * cost: cost of the next level, None once maxed out
+/// * affordable: flag for whether the next level can be bought
+/// * unlocked: amount of unlocked upgrades, which moves the number key shortcuts
+/// * lang: language of the label
+/// * full_numbers: flag for whether the cost is shown exactly
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct UpgradeLabel {
+    pub(crate) level: u32,
+    pub(crate) cost: Option<Currency>,
+    pub(crate) affordable: bool,
+    pub(crate) unlocked: usize,
+    pub(crate) lang: Lang,
+    pub(crate) full_numbers: bool,
+}
+
+/// Day of the daily run calendar in the statistics window
+/// * number: day of the month
+/// * best: date, best score, and rules of the day, if the daily run was played that day
+/// * today: flag for whether the day is today
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CalendarDay {
+    pub(crate) number: String,
+    pub(crate) best: Option<String>,
+    pub(crate) today: bool,
+}
+
+/// Values the player info is built from: play time, clicks, the audio state, language, UI scale, and New Game+ count
+pub(crate) type PlayerInfoKey = (u64, u32, bool, bool, f32, Lang, f32, u32);
+
+/// Text of an on-screen display that is only rebuilt when its values change
+/// * key: values the text was last built from
+/// * buffer: string the text is formatted into, kept between rebuilds
+/// * text: the text drawn every frame
+#[derive(Debug)]
+pub struct HudText<K> {
+    pub(crate) key: Option<K>,
+    pub(crate) buffer: String,
+    pub(crate) text: Text,
+}
+
+impl<K> Default for HudText<K> {
+    fn default() -> Self {
+        Self {
+            key: None,
+            buffer: String::new(),
+            text: Text::new(""),
+        }
+    }
+}
+
+/// Implementation of methods for the HudText struct
+/// * is_current: checks if the text was built from the values
+/// * set: rebuilds the text from the values
+/// * text: returns the text to draw
+impl<K: PartialEq> HudText<K> {
+    /// checks if the text was built from the values, so it can be drawn as it is
+    pub fn is_current(&self, key: &K) -> bool {
+        self.key.as_ref() == Some(key)
+    }
+
+    /// rebuilds the text from the values, formatting it into the kept buffer
+    pub fn set(&mut self, key: K, scale: f32, args: fmt::Arguments) {
+        self.buffer.clear();
+        // writing into a String can't fail
+        let _ = self.buffer.write_fmt(args);
+        self.text = Text::new(self.buffer.as_str());
+        self.text.set_scale(scale);
+        self.key = Some(key);
+    }
+
+    /// returns the text to draw
+    pub fn text(&self) -> &Text {
+        &self.text
+    }
+}
+
+/// returns the draw parameters for the grains of the instance array
+/// the grains that are done falling are skipped, unless the sand decays,
+/// then they are kept and fade toward gray by how far the decay of their age got
+pub fn grain_instances<'a>(
+    grains: impl IntoIterator<Item = &'a Grain, IntoIter: 'a>,
+    textured: bool,
+    alpha: f32,
+    decay: Option<&'a dyn Fn(f32) -> f32>,
+) -> impl Iterator<Item = DrawParam> + 'a {
+    grains
+        .into_iter()
+        .filter(move |grain| !grain.is_done() || decay.is_some())
+        .map(move |grain| {
+            let params = grain_params(grain, textured, alpha);
+            match decay {
+                Some(decay) if grain.is_done() => {
+                    params.color(decay_color(grain.color, decay(grain.age)))
+                }
+                _ => params,
+            }
+        })
+}
+
+/// returns the capacity an instance array needs for an amount of instances
+/// None if they already fit, as the capacity only grows,
+/// doubling so a changing amount doesn't reallocate the array every frame
+pub fn grown_capacity(capacity: usize, amount: usize) -> Option<usize> {
+    (amount > capacity).then(|| amount.next_power_of_two().max(MIN_INSTANCES))
+}
+
+/// returns the draw parameters for a grain
+/// textured grains use their sprite from the particle atlas
+/// the grain is drawn alpha of the way from its last position
+pub fn grain_params(grain: &Grain, textured: bool, alpha: f32) -> DrawParam {
+    let (x, y) = grain.position(alpha);
+    let params = DrawParam::default()
+        .dest([x + grain.rect.w / 2.0, y + grain.rect.h / 2.0])
+        .rotation(grain.rotation)
+        .offset([0.5, 0.5])
+        .color(grain.color);
+    if textured {
+        // the sprite is SPRITE_SIZE pixels, scale it to the grain size
+        params
+            .src(grain.particle.sprite_rect())
+            .scale([grain.rect.w / SPRITE_SIZE, grain.rect.h / SPRITE_SIZE])
+    } else {
+        params.scale(grain.rect.size())
+    }
+}
+
+/// returns the draw parameters for a grain's trail
+/// the oldest positions are the most transparent
+pub(crate) fn trail_params(grain: &Grain, textured: bool) -> Vec<DrawParam> {
+    let base = grain_params(grain, textured, 1.0);
+    let mut params = Vec::with_capacity(grain.trail_count);
+    for i in 0..grain.trail_count {
+        // walk the ring buffer from the oldest position to the newest
+        let index = (grain.trail_next + TRAIL_LENGTH - grain.trail_count + i) % TRAIL_LENGTH;
+        let alpha = 0.5 * (i + 1) as f32 / (TRAIL_LENGTH + 1) as f32;
+        let mut color = grain.color;
+        color.a = alpha;
+        params.push(
+            base.dest([grain.trail[index].0, grain.trail[index].1])
+                .color(color),
+        );
+    }
+    params
+}
+
+/// shows the blitz high-score table, with its place, money, and date columns
+/// the highlighted score is the one that was just set
+pub(crate) fn score_grid(ui: &mut egui::Ui, rows: &[(String, String)], highlight: Option<usize>) {
+    if rows.is_empty() {
+        ui.label(tr("blitz-no-scores"));
+        return;
+    }
+    egui::Grid::new("blitz_scores")
+        .striped(true)
+        .num_columns(3)
+        .show(ui, |ui| {
+            for (i, (money, date)) in rows.iter().enumerate() {
+                let place = format!("{}.", i + 1);
+                if highlight == Some(i) {
+                    ui.colored_label(egui::Color32::GOLD, place);
+                    ui.colored_label(egui::Color32::GOLD, money);
+                } else {
+                    ui.label(place);
+                    ui.label(money);
+                }
+                ui.label(date);
+                ui.end_row();
+            }
+        });
+}
+
+/// shows the daily run calendar, a week in each row from Monday to Sunday
+/// the days with a score are gold and show it when hovered, and today is underlined
+pub(crate) fn daily_calendar(ui: &mut egui::Ui, days: &[Option<CalendarDay>]) {
+    egui::Grid::new("daily_calendar")
+        .num_columns(7)
+        .show(ui, |ui| {
+            for weekday in [
+                "daily-mon",
+                "daily-tue",
+                "daily-wed",
+                "daily-thu",
+                "daily-fri",
+                "daily-sat",
+                "daily-sun",
+            ] {
+                ui.small(tr(weekday));
+            }
+            ui.end_row();
+            for week in days.chunks(7) {
+                for day in week {
+                    let Some(day) = day else {
+                        ui.label("");
+                        continue;
+                    };
+                    let mut text = egui::RichText::new(&day.number);
+                    if day.best.is_some() {
+                        text = text.color(egui::Color32::GOLD).strong();
+                    }
+                    if day.today {
+                        text = text.underline();
+                    }
+                    let label = ui.label(text);
+                    if let Some(best) = &day.best {
+                        label.on_hover_text(best);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// returns the seconds as hours, minutes, and seconds, e.g. 1:02:03
+pub fn format_time(secs: u64) -> String {
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// returns the money amount in a short form
+/// large amounts are rounded to three digits, e.g. 1.23K, 45.6M, or 789B
+/// amounts past the units use scientific notation, e.g. 1.70e19
+pub fn format_money(money: Currency) -> String {
+    let units = ["", "K", "M", "B", "T", "Qa", "Qi"];
+    if money.0 < 1000 {
+        return money.to_string();
+    }
+    if money.0 >= SCIENTIFIC_MONEY {
+        return format_scientific(money.as_f64());
+    }
+    // find the biggest unit that fits
+    let mut value = money.as_f64();
+    let mut unit = 0;
+    while value >= 1000.0 && unit < units.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    // round to three significant digits, which can carry into the next unit
+    let mut decimals = significant_decimals(value);
+    let mut rounded = round_to(value, decimals);
+    if rounded >= 1000.0 && unit < units.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+        decimals = significant_decimals(value);
+        rounded = round_to(value, decimals);
+    }
+    // rounding 9.999 up to 10.0 needs one less decimal
+    let decimals = significant_decimals(rounded).min(decimals);
+    format!("{:.*}{}", decimals, rounded, units[unit])
+}
+
+/// returns the value in scientific notation with three digits, e.g. 1.70e19
+pub(crate) fn format_scientific(value: f64) -> String {
+    let mut exponent = value.log10().floor() as i32;
+    let mut mantissa = round_to(value / 10f64.powi(exponent), 2);
+    // rounding 9.999 up carries into the exponent
+    if mantissa >= 10.0 {
+        mantissa /= 10.0;
+        exponent += 1;
+    }
+    format!("{:.2}e{}", mantissa, exponent)
+}
+
+/// returns the number of decimals that keeps three significant digits
+pub(crate) fn significant_decimals(value: f64) -> usize {
+    if value < 10.0 {
+        2
+    } else if value < 100.0 {
+        1
+    } else {
+        0
+    }
+}
+
+/// rounds the value to the given number of decimals
+pub(crate) fn round_to(value: f64, decimals: usize) -> f64 {
+    let scale = 10f64.powi(decimals as i32);
+    (value * scale).round() / scale
+}
+
+/// Tests for the GUI helpers
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GRAIN_SIZE, GRAVITY, SCREEN_SIZE};
+
+    // grain drawing tests
+    #[test]
+    fn test_grain_params() {
+        let mut grain = Grain::new(100.0, 200.0, GRAIN_SIZE, Color::WHITE);
+        grain.particle = SandParticle::Gold;
+        // plain squares are scaled straight to the grain size
+        let plain = grain_params(&grain, false, 1.0);
+        match plain.transform {
+            graphics::Transform::Values { scale, .. } => assert_eq!(scale.x, GRAIN_SIZE),
+            _ => panic!("Grain should not use a matrix transform!"),
+        }
+        // sprites are scaled from the sprite size to the grain size
+        let textured = grain_params(&grain, true, 1.0);
+        match textured.transform {
+            graphics::Transform::Values { scale, .. } => {
+                assert_eq!(scale.x * SPRITE_SIZE, GRAIN_SIZE)
+            }
+            _ => panic!("Grain should not use a matrix transform!"),
+        }
+        assert_eq!(textured.src, SandParticle::Gold.sprite_rect());
+    }
+    #[test]
+    fn test_trail_params() {
+        let mut grain = Grain::new(100.0, 0.0, GRAIN_SIZE, Color::WHITE);
+        assert!(trail_params(&grain, false).is_empty());
+        // the trail never holds more than TRAIL_LENGTH positions
+        for _ in 0..10 {
+            grain.update(1.0 / 30.0, GRAVITY, SCREEN_SIZE);
+        }
+        let trail = trail_params(&grain, false);
+        assert_eq!(trail.len(), TRAIL_LENGTH);
+        // older positions fade out more
+        assert!(trail[0].color.a < trail[TRAIL_LENGTH - 1].color.a);
+        // settled grains drop their trail
+        for _ in 0..300 {
+            grain.update(1.0 / 30.0, GRAVITY, SCREEN_SIZE);
+        }
+        assert!(grain.is_done());
+        assert!(trail_params(&grain, false).is_empty());
+    }
+    #[test]
+    fn test_grown_capacity() {
+        // instances that fit keep the array as it is
+        assert_eq!(grown_capacity(512, 512), None);
+        assert_eq!(grown_capacity(512, 10), None);
+        // small arrays start at the minimum capacity
+        assert_eq!(grown_capacity(0, 1), Some(MIN_INSTANCES));
+        // larger arrays double past the amount
+        assert_eq!(grown_capacity(512, 513), Some(1024));
+        assert_eq!(grown_capacity(1024, 3000), Some(4096));
+    }
+    // format_money tests
+    #[test]
+    fn test_format_money() {
+        assert_eq!(format_money(Currency(0)), "0");
+        assert_eq!(format_money(Currency(999)), "999");
+        assert_eq!(format_money(Currency(1000)), "1.00K");
+        assert_eq!(format_money(Currency(1234)), "1.23K");
+        assert_eq!(format_money(Currency(45_600_000)), "45.6M");
+        assert_eq!(format_money(Currency(7_890_000_000)), "7.89B");
+        assert_eq!(format_money(Currency(456_789)), "457K");
+    }
+    #[test]
+    fn test_format_money_rounding() {
+        assert_eq!(format_money(Currency(9_999)), "10.0K");
+        assert_eq!(format_money(Currency(999_499)), "999K");
+        assert_eq!(format_money(Currency(999_999)), "1.00M");
+    }
+    #[test]
+    fn test_format_money_extremes() {
+        let old_max = i64::MAX as u128;
+        assert_eq!(format_money(Currency(old_max)), "9.22Qi");
+        // past the old 64-bit limit the money keeps counting up
+        assert_eq!(format_money(Currency(old_max + 1)), "9.22Qi");
+        assert_eq!(
+            format_money(Currency(17_000_000_000_000_000_000)),
+            "1.70e19"
+        );
+        assert_eq!(format_money(Currency(old_max * 2)), "1.84e19");
+        assert_eq!(
+            format_money(Currency(9_999_000_000_000_000_000_000)),
+            "1.00e22"
+        );
+        assert_eq!(format_money(Currency::MAX), "3.40e38");
+    }
+
+    // Notifications tests
+    #[test]
+    fn test_notifications_fade() {
+        let mut notifications = Notifications::default();
+        notifications.push("Hello".to_string());
+        notifications.update(NOTIFY_TIME - 0.5);
+        assert_eq!(notifications.visible(), vec![("Hello", 0.5)]);
+        notifications.update(0.5);
+        assert!(notifications.visible().is_empty());
+    }
+    #[test]
+    fn test_hud_text() {
+        let mut hud = HudText::default();
+        assert!(!hud.is_current(&(1, 2)));
+        hud.set((1, 2), TEXT_SIZE, format_args!("{}/{}", 1, 2));
+        assert_eq!(hud.buffer, "1/2");
+        // the same values keep the text, new ones rebuild it in the same buffer
+        assert!(hud.is_current(&(1, 2)));
+        let capacity = hud.buffer.capacity();
+        hud.set((3, 4), TEXT_SIZE, format_args!("{}/{}", 3, 4));
+        assert_eq!(hud.buffer, "3/4");
+        assert_eq!(hud.buffer.capacity(), capacity);
+        assert!(!hud.is_current(&(1, 2)));
+    }
+    #[test]
+    fn test_notifications_overflow() {
+        let mut notifications = Notifications::default();
+        for i in 0..MAX_NOTIFICATIONS + 2 {
+            notifications.push(i.to_string());
+        }
+        assert_eq!(notifications.visible().len(), MAX_NOTIFICATIONS);
+        assert_eq!(notifications.hidden(), 2);
+        // the waiting messages show up once the first ones are gone
+        notifications.update(NOTIFY_TIME);
+        assert_eq!(notifications.visible().len(), 2);
+        assert_eq!(notifications.visible()[0].1, 1.0);
+        assert_eq!(notifications.hidden(), 0);
+    }
+}
@@ -0,0 +1,520 @@
+//! Upgrades the player can buy
+
+use crate::{
+    AUTOCLICK_PERIOD, SCULPT_RADIUS,
+    currency::Currency,
+    lang::{tr, tr_args, tr_or_text},
+    particle::SandParticle,
+    settings::parse_enum,
+    table::{parse_sections, parse_text},
+};
+use std::{fmt, sync::OnceLock};
+use strum_macros::EnumIter;
+
+// Built-in upgrade table, replaced by an upgrades.toml in the save folder
+pub(crate) const UPGRADE_TABLE: &str = include_str!("../assets/upgrades.toml");
+
+// Keys an upgrade can have in the upgrade table
+pub(crate) const UPGRADE_FIELDS: [&str; 11] = [
+    "name",
+    "button",
+    "desc",
+    "category",
+    "base_cost",
+    "growth",
+    "max_level",
+    "start_level",
+    "unlock_earned",
+    "requires",
+    "effect",
+];
+
+// Upgrade table the game is played with, set once at the start
+static UPGRADES: OnceLock<UpgradeTable> = OnceLock::new();
+
+/// What the levels of an upgrade do, interpreted by the game
+/// upgrades with the same effect add up their levels
+/// * ContainerMultiplier: Makes room for more sand.
+/// * ParticleTier: Unlocks better sand particles, priced by the particle table.
+/// * AutoclickInterval: Automatically drops sand particles, more often with each level.
+/// * DropCount: Increases number of particles dropped per click.
+/// * SculptRadius: Improves the Shift/Ctrl sculpting tool.
+#[derive(Hash, Eq, PartialEq, Debug, EnumIter, Clone, Copy)]
+pub(crate) enum UpgradeEffect {
+    ContainerMultiplier,
+    ParticleTier,
+    AutoclickInterval,
+    DropCount,
+    SculptRadius,
+}
+
+/// Implementation of methods for the UpgradeEffect enum
+/// * describe: returns the name and value of the effect at a level
+impl UpgradeEffect {
+    /// returns the name and value of the effect at a level
+    pub(crate) fn describe(&self, level: u32) -> (&'static str, String) {
+        match self {
+            UpgradeEffect::ContainerMultiplier => {
+                (tr("effect-container"), (25 * (1 + level)).to_string())
+            }
+            UpgradeEffect::ParticleTier => {
+                let best = level
+                    .checked_sub(1)
+                    .and_then(SandParticle::from_u32)
+                    .map_or(tr("effect-none"), |sand| sand.name());
+                (tr("effect-best-sand"), best.to_string())
+            }
+            UpgradeEffect::AutoclickInterval => match level {
+                0 => (tr("effect-autoclicker"), tr("effect-off").to_string()),
+                _ => {
+                    let seconds = format!("{:.2}", AUTOCLICK_PERIOD / level as f32);
+                    let every = tr_args("effect-every", &[("seconds", &seconds)]);
+                    (tr("effect-autoclicker"), every)
+                }
+            },
+            UpgradeEffect::DropCount => (tr("effect-grains"), (1 + level).to_string()),
+            UpgradeEffect::SculptRadius => (
+                tr("effect-sculpt-radius"),
+                (SCULPT_RADIUS + 10.0 * level as f32).to_string(),
+            ),
+        }
+    }
+}
+
+/// Data of an upgrade, read from the upgrade table
+/// * id: name of the upgrade in the save file
+/// * name: display name, a text key or plain text
+/// * button: button text, a text key or plain text
+/// * desc: description, a text key or plain text
+/// * category: group the upgrade is listed in
+/// * base_cost: cost of the first level
+/// * growth: factor the cost grows by with each level
+/// * max_level: highest level, if any
+/// * start_level: level of a new game
+/// * unlock_earned: money earned in the game that lists the upgrade in the shop
+/// * requires: id of an upgrade that needs a level before this one is listed, if any
+/// * effect: what the levels of the upgrade do
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct UpgradeDef {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) button: String,
+    pub(crate) desc: String,
+    pub(crate) category: UpgradeCategory,
+    pub(crate) base_cost: f64,
+    pub(crate) growth: f64,
+    pub(crate) max_level: Option<u32>,
+    pub(crate) start_level: u32,
+    pub(crate) unlock_earned: Currency,
+    pub(crate) requires: Option<String>,
+    pub(crate) effect: UpgradeEffect,
+}
+
+/// Data of all the upgrades, in the order they are listed
+/// * defs: data of each upgrade
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct UpgradeTable {
+    pub(crate) defs: Vec<UpgradeDef>,
+}
+
+/// Built-in upgrade table of the game
+impl Default for UpgradeTable {
+    fn default() -> Self {
+        // the built-in table is checked by the tests
+        UpgradeTable::parse(UPGRADE_TABLE).expect("the built-in upgrade table is broken")
+    }
+}
+
+/// Implementation of methods for the UpgradeTable struct
+/// * parse: reads an upgrade table, or the reason it is broken
+/// * iter: returns the upgrades of the table in order
+/// * find: returns the upgrade with an id, if the table has it
+impl UpgradeTable {
+    /// reads an upgrade table, or the reason it is broken
+    /// each upgrade has an `[Id]` section
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let mut defs = Vec::new();
+        for section in parse_sections(text, &UPGRADE_FIELDS)? {
+            let required = |key: &str| format!("[{}] is missing `{}`", section.name, key);
+            let number = |text: &str| text.parse().ok();
+            let effect = section
+                .field("effect", |text| {
+                    parse_text(text).and_then(|text| parse_enum(&text))
+                })?
+                .ok_or_else(|| required("effect"))?;
+            let mut max_level = section.field("max_level", number)?;
+            if effect == UpgradeEffect::ParticleTier {
+                // there is nothing to unlock past the last sand particle
+                let last = SandParticle::max_level();
+                max_level = Some(max_level.map_or(last, |max: u32| max.min(last)));
+            }
+            let base_cost = section
+                .field("base_cost", |text| text.parse().ok())?
+                .ok_or_else(|| required("base_cost"))?;
+            defs.push(UpgradeDef {
+                id: section.name.to_string(),
+                name: section
+                    .field("name", parse_text)?
+                    .ok_or_else(|| required("name"))?,
+                button: section
+                    .field("button", parse_text)?
+                    .ok_or_else(|| required("button"))?,
+                desc: section
+                    .field("desc", parse_text)?
+                    .ok_or_else(|| required("desc"))?,
+                category: section
+                    .field("category", |text| {
+                        parse_text(text).and_then(|text| parse_enum(&text))
+                    })?
+                    .ok_or_else(|| required("category"))?,
+                base_cost,
+                growth: section
+                    .field("growth", |text| text.parse().ok())?
+                    .unwrap_or(1.1),
+                max_level,
+                start_level: section.field("start_level", number)?.unwrap_or(0),
+                unlock_earned: section
+                    .field("unlock_earned", |text| text.parse().ok())?
+                    .unwrap_or(Currency::from_f64(base_cost)),
+                requires: section.field("requires", parse_text)?,
+                effect,
+            });
+        }
+        if defs.is_empty() {
+            return Err("the table has no upgrades".to_string());
+        }
+        // a required upgrade has to be in the table
+        for def in &defs {
+            if let Some(id) = &def.requires
+                && !defs.iter().any(|other| other.id == *id)
+            {
+                return Err(format!("[{}] requires unknown upgrade [{}]", def.id, id));
+            }
+        }
+        Ok(Self { defs })
+    }
+
+    /// returns the upgrades of the table in order
+    pub(crate) fn iter(&self) -> impl Iterator<Item = Upgrade> + use<> {
+        (0..self.defs.len()).map(Upgrade)
+    }
+
+    /// returns the upgrade with an id, if the table has it
+    pub(crate) fn find(&self, id: &str) -> Option<Upgrade> {
+        self.defs.iter().position(|def| def.id == id).map(Upgrade)
+    }
+}
+
+/// returns the upgrade table the game is played with
+/// the built-in table, unless a modded one was set first
+pub(crate) fn upgrade_table() -> &'static UpgradeTable {
+    UPGRADES.get_or_init(UpgradeTable::default)
+}
+
+/// sets the upgrade table the game is played with
+/// returns false if a table was already in use
+pub(crate) fn set_upgrade_table(table: UpgradeTable) -> bool {
+    UPGRADES.set(table).is_ok()
+}
+
+/// Upgrade the player can buy, an entry of the upgrade table
+/// * 0: position of the upgrade in the upgrade table
+#[derive(Hash, Eq, PartialEq, Clone, Copy)]
+pub struct Upgrade(usize);
+
+/// Implementation of methods for the Upgrade struct
+/// * def: returns the data of the upgrade in the upgrade table
+/// * from_id: returns the upgrade with an id, None for upgrades of other tables
+/// * name: returns the display name of the upgrade
+/// * category: returns the group the upgrade is listed in
+/// * effect: returns what the levels of the upgrade do
+/// * effect_summary: returns the current and next effect of the upgrade
+/// * btn_txt: returns the button text for the upgrade
+/// * desc: returns the description of the upgrade
+/// * cost: returns the cost of the upgrade based on its current level
+/// * max_level: returns the maximum level of the upgrade, if any
+/// * requires: returns the upgrade that needs a level before this one is listed, if any
+impl Upgrade {
+    /// returns the data of the upgrade in the upgrade table
+    pub(crate) fn def(&self) -> &'static UpgradeDef {
+        &upgrade_table().defs[self.0]
+    }
+
+    /// returns the upgrade with an id, None for upgrades of other tables
+    /// a save can still refer to an upgrade a mod took out
+    pub fn from_id(id: &str) -> Option<Upgrade> {
+        upgrade_table().find(id)
+    }
+
+    /// returns the display name of the upgrade
+    pub(crate) fn name(&self) -> &'static str {
+        tr_or_text(&self.def().name)
+    }
+
+    /// returns the group the upgrade is listed in
+    pub(crate) fn category(&self) -> UpgradeCategory {
+        self.def().category
+    }
+
+    /// returns what the levels of the upgrade do
+    pub(crate) fn effect(&self) -> UpgradeEffect {
+        self.def().effect
+    }
+
+    /// returns the button text for the upgrade
+    pub(crate) fn btn_txt(&self) -> &'static str {
+        tr_or_text(&self.def().button)
+    }
+
+    /// returns the description of the upgrade
+    pub(crate) fn desc(&self) -> &'static str {
+        tr_or_text(&self.def().desc)
+    }
+
+    /// returns the current and next effect of the upgrade
+    /// e.g. "Container: 250 → 275", or the final effect at the max level
+    pub(crate) fn effect_summary(&self, level: u32) -> String {
+        let (name, current) = self.effect().describe(level);
+        match self.max_level() {
+            Some(max) if level >= max => tr_args(
+                "effect-summary-max",
+                &[("effect", &name), ("current", &current)],
+            ),
+            _ => tr_args(
+                "effect-summary",
+                &[
+                    ("effect", &name),
+                    ("current", &current),
+                    ("next", &self.effect().describe(level + 1).1),
+                ],
+            ),
+        }
+    }
+
+    /// returns the cost of the upgrade based on its current level
+    /// the ParticleTier effect is priced by the particle table instead
+    pub(crate) fn cost(&self, n: u32) -> f64 {
+        // formula: base_cost * growth^n
+        let def = self.def();
+        def.base_cost * def.growth.powf(n as f64)
+    }
+
+    /// returns the maximum level of the upgrade, if any
+    pub fn max_level(&self) -> Option<u32> {
+        self.def().max_level
+    }
+
+    /// returns the upgrade that needs a level before this one is listed, if any
+    pub(crate) fn requires(&self) -> Option<Upgrade> {
+        self.def()
+            .requires
+            .as_ref()
+            .and_then(|id| upgrade_table().find(id))
+    }
+}
+
+/// Shows the id of the upgrade, as written in the save file
+impl fmt::Debug for Upgrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.def().id)
+    }
+}
+
+/// Built-in upgrades by their place in the built-in upgrade table, for the tests
+#[cfg(test)]
+impl Upgrade {
+    pub(crate) const BIGGER_CONTAINER: Upgrade = Upgrade(0);
+    pub(crate) const PARTICLE_TIER: Upgrade = Upgrade(1);
+    pub(crate) const AUTO_CLICKER: Upgrade = Upgrade(2);
+    pub(crate) const MORE_PARTICLES: Upgrade = Upgrade(3);
+    pub(crate) const SCULPTOR: Upgrade = Upgrade(4);
+}
+
+/// Groups of upgrades in the options window
+/// * Production: Upgrades that drop more sand.
+/// * Economy: Upgrades that make the sand worth more.
+/// * Container: Upgrades that make room for more sand.
+#[derive(Hash, Eq, PartialEq, Debug, EnumIter, Clone, Copy)]
+pub(crate) enum UpgradeCategory {
+    Production,
+    Economy,
+    Container,
+}
+
+/// Implementation of methods for the UpgradeCategory enum
+/// * name: returns the display name of the category
+impl UpgradeCategory {
+    /// returns the display name of the category
+    pub(crate) fn name(&self) -> &'static str {
+        tr(match self {
+            UpgradeCategory::Production => "category-production",
+            UpgradeCategory::Economy => "category-economy",
+            UpgradeCategory::Container => "category-container",
+        })
+    }
+}
+
+/// Tests for the upgrades
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    // Upgrade tests
+    #[test]
+    fn test_upgrade_category() {
+        // every category has at least one upgrade
+        for category in UpgradeCategory::iter() {
+            assert!(
+                upgrade_table()
+                    .iter()
+                    .any(|upgrade| upgrade.category() == category)
+            );
+        }
+        assert_eq!(
+            Upgrade::BIGGER_CONTAINER.category(),
+            UpgradeCategory::Container
+        );
+    }
+    #[test]
+    fn test_upgrade_desc() {
+        let upgrade = Upgrade::MORE_PARTICLES;
+        assert_eq!(
+            upgrade.desc(),
+            "This will allow you to drop more sand per click:"
+        );
+    }
+    #[test]
+    fn test_upgrade_btn_txt() {
+        let upgrade = Upgrade::AUTO_CLICKER;
+        assert_eq!(upgrade.btn_txt(), "Buy Auto Clicker");
+    }
+    #[test]
+    fn test_upgrade_cost() {
+        let upgrade = Upgrade::BIGGER_CONTAINER;
+        let base_m: f64 = 1.1;
+        let base_cost: f64 = 50.0;
+        let m: f64 = 100.0;
+        let cost_level_100 = base_cost * base_m.powf(m);
+        assert_eq!(upgrade.cost(0), 50.0);
+        assert_eq!(upgrade.cost(100), cost_level_100);
+    }
+    #[test]
+    fn test_upgrade_effect_summary() {
+        assert_eq!(
+            Upgrade::BIGGER_CONTAINER.effect_summary(9),
+            "Container: 250 → 275"
+        );
+        assert_eq!(
+            Upgrade::PARTICLE_TIER.effect_summary(1),
+            "Best sand: Sand → Quartz"
+        );
+        assert_eq!(
+            Upgrade::PARTICLE_TIER.effect_summary(SandParticle::max_level()),
+            "Best sand: Diamond (max)"
+        );
+        assert_eq!(
+            Upgrade::AUTO_CLICKER.effect_summary(5),
+            "Autoclicker: every 1.00s → every 0.83s"
+        );
+        assert_eq!(
+            Upgrade::AUTO_CLICKER.effect_summary(0),
+            "Autoclicker: off → every 5.00s"
+        );
+        assert_eq!(
+            Upgrade::MORE_PARTICLES.effect_summary(2),
+            "Grains per click: 3 → 4"
+        );
+        assert_eq!(
+            Upgrade::SCULPTOR.effect_summary(20),
+            "Sculpting radius: 240 (max)"
+        );
+    }
+    #[test]
+    fn test_upgrade_max_level() {
+        let upgrade = Upgrade::PARTICLE_TIER;
+        assert_eq!(upgrade.max_level(), Some(SandParticle::max_level()));
+    }
+
+    // UpgradeTable tests
+    #[test]
+    fn test_upgrade_table_default() {
+        let table = UpgradeTable::default();
+        let ids: Vec<String> = table
+            .iter()
+            .map(|upgrade| format!("{:?}", upgrade))
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                "BiggerContainer",
+                "ParticleTier",
+                "AutoClicker",
+                "MoreParticles",
+                "Sculptor"
+            ]
+        );
+        assert_eq!(table.find("Sculptor"), Some(Upgrade::SCULPTOR));
+        assert_eq!(table.find("Unknown"), None);
+        assert_eq!(Upgrade::PARTICLE_TIER.def().start_level, 1);
+        assert_eq!(
+            Upgrade::AUTO_CLICKER.effect(),
+            UpgradeEffect::AutoclickInterval
+        );
+        // every effect is used by an upgrade
+        for effect in UpgradeEffect::iter() {
+            assert!(table.iter().any(|upgrade| upgrade.effect() == effect));
+        }
+    }
+    #[test]
+    fn test_upgrade_table_override() {
+        // a modded upgrade can use plain text and leave out the optional keys
+        let text = format!(
+            "{}\n[Funnel]\nname = \"Funnel\"\nbutton = \"Buy Funnel\"\ndesc = \"More grains:\"\n\
+             category = \"Production\"\nbase_cost = 20\neffect = \"DropCount\"\n",
+            UPGRADE_TABLE
+        );
+        let table = UpgradeTable::parse(&text).unwrap();
+        let def = &table.defs[5];
+        assert_eq!(def.id, "Funnel");
+        assert_eq!(tr_or_text("Buy Funnel"), "Buy Funnel");
+        assert_eq!(def.growth, 1.1);
+        assert_eq!(def.max_level, None);
+        assert_eq!(def.start_level, 0);
+        assert_eq!(def.unlock_earned, Currency(20));
+        assert_eq!(def.requires, None);
+        assert_eq!(def.effect, UpgradeEffect::DropCount);
+        // the sand particles run out before a bigger ParticleTier max level
+        let text = UPGRADE_TABLE.replace("start_level = 1\n", "max_level = 99\n");
+        let table = UpgradeTable::parse(&text).unwrap();
+        assert_eq!(table.defs[1].max_level, Some(SandParticle::max_level()));
+    }
+    #[test]
+    fn test_upgrade_table_errors() {
+        let broken =
+            |from: &str, to: &str| UpgradeTable::parse(&UPGRADE_TABLE.replacen(from, to, 1));
+        assert_eq!(
+            broken("[ParticleTier]", "[BiggerContainer]"),
+            Err("line 31: [BiggerContainer] is defined twice".to_string())
+        );
+        assert_eq!(
+            broken("effect = \"ContainerMultiplier\"", "effect = \"Magic\""),
+            Err("line 29: `effect` of [BiggerContainer] isn't valid: \"Magic\"".to_string())
+        );
+        assert_eq!(
+            broken("category = \"Container\"\n", ""),
+            Err("[BiggerContainer] is missing `category`".to_string())
+        );
+        assert_eq!(
+            broken(
+                "category = \"Production\"\n",
+                "requires = \"Funnel\"\ncategory = \"Production\"\n"
+            ),
+            Err("[AutoClicker] requires unknown upgrade [Funnel]".to_string())
+        );
+        assert_eq!(
+            UpgradeTable::parse("# nothing"),
+            Err("the table has no upgrades".to_string())
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Scripted play sessions against the headless simulation
+//! each step of a session plays out right away, so a script reads top to bottom
+
+use sand_drop_clicker::{
+    FPS, currency::Currency, particle::SandParticle, sim::SimState, upgrade::Upgrade,
+};
+
+/// Play session driven by a script of player actions
+/// * sim: simulation the session plays, without a window or GPU
+pub struct Session {
+    pub sim: SimState,
+}
+
+/// Implementation of methods for the Session struct
+/// * new: starts a new game whose random numbers come from the seed
+/// * give_money: adds money, as a head start for the later upgrades
+/// * click: clicks once at a position, like the player would
+/// * clicks: clicks a number of times at a position
+/// * wait_secs: lets the simulation run for a while
+/// * convert: sells all the sand in the container
+/// * buy: buys an upgrade, failing the test if it can't be bought
+/// * cant_buy: fails the test if an upgrade can be bought
+/// * money: returns the money of the player
+/// * contents: returns the amount of grains of each sand particle in the container
+#[allow(dead_code)]
+impl Session {
+    /// starts a new game whose random numbers come from the seed
+    pub fn new(seed: u64) -> Self {
+        Self {
+            sim: SimState::with_seed(seed),
+        }
+    }
+
+    /// adds money, as a head start for the later upgrades
+    pub fn give_money(mut self, amount: u128) -> Self {
+        self.sim.money += Currency(amount);
+        self
+    }
+
+    /// clicks once at a position, like the player would
+    pub fn click(mut self, x: f32, y: f32) -> Self {
+        let amount = self.sim.drop_amount();
+        self.sim.click(x, y, amount);
+        self
+    }
+
+    /// clicks a number of times at a position
+    pub fn clicks(self, count: u32, x: f32, y: f32) -> Self {
+        (0..count).fold(self, |session, _| session.click(x, y))
+    }
+
+    /// lets the simulation run for a while, a frame at a time
+    pub fn wait_secs(mut self, seconds: f32) -> Self {
+        let frames = (seconds * FPS as f32).round() as u32;
+        for _ in 0..frames {
+            self.sim.tick(1.0 / FPS as f32);
+        }
+        self
+    }
+
+    /// sells all the sand in the container
+    pub fn convert(mut self) -> Self {
+        self.sim.sell();
+        self
+    }
+
+    /// buys an upgrade by its id, failing the test if it can't be bought
+    pub fn buy(mut self, id: &str) -> Self {
+        let upgrade = upgrade(id);
+        assert!(
+            self.sim.buy(upgrade),
+            "couldn't buy {} with {}$",
+            id,
+            self.sim.money
+        );
+        self
+    }
+
+    /// fails the test if an upgrade by its id can be bought
+    pub fn cant_buy(mut self, id: &str) -> Self {
+        let upgrade = upgrade(id);
+        let money = self.sim.money;
+        assert!(!self.sim.buy(upgrade), "{} shouldn't be for sale", id);
+        assert_eq!(self.sim.money, money, "a failed purchase cost money");
+        self
+    }
+
+    /// returns the money of the player
+    pub fn money(&self) -> u128 {
+        self.sim.money.0
+    }
+
+    /// returns the amount of grains of each sand particle in the container
+    pub fn contents(&self) -> Vec<(SandParticle, u32)> {
+        self.sim
+            .contents()
+            .into_iter()
+            .map(|(particle, count, _)| (particle, count))
+            .collect()
+    }
+}
+
+/// returns the built-in upgrade with an id, failing the test if there is none
+pub fn upgrade(id: &str) -> Upgrade {
+    Upgrade::from_id(id).unwrap_or_else(|| panic!("there is no upgrade {}", id))
+}
@@ -0,0 +1,57 @@
+//! Scripted play sessions, run headless without a window or GPU
+
+mod common;
+
+use common::{Session, upgrade};
+use sand_drop_clicker::particle::SandParticle;
+
+// Session tests
+#[test]
+fn test_session_full_container_ignores_clicks() {
+    // the container starts with room for 25 grains
+    let session = Session::new(1).clicks(25, 400.0, 0.0).wait_secs(5.0);
+    assert!(session.sim.is_full());
+    assert_eq!(session.contents(), [(SandParticle::Sand, 25)]);
+    // further clicks drop nothing and aren't counted
+    let session = session.clicks(10, 400.0, 0.0).wait_secs(1.0);
+    assert_eq!(session.sim.get_amount(), 25);
+    assert_eq!(session.sim.total_clicks, 25);
+    assert_eq!(session.sim.stats.manual_grains, 25);
+    // selling makes room again
+    let session = session.convert().click(400.0, 0.0);
+    assert_eq!(session.sim.get_amount(), 1);
+    assert_eq!(session.sim.stats.sales, 1);
+}
+#[test]
+fn test_session_autoclicker_earns_over_five_minutes() {
+    let mut session = Session::new(2).give_money(700).buy("AutoClicker");
+    assert_eq!(session.money(), 0);
+    // sell every half minute, before the container fills up
+    for _ in 0..10 {
+        session = session.wait_secs(30.0).convert();
+    }
+    // about a grain of basic sand every five seconds, each worth 1$
+    // the time past a drop carries over to the next one, so no drop falls behind,
+    // only the rounding of the frame times can move the last one past the end
+    let grains = session.sim.stats.auto_grains;
+    assert!(grains.abs_diff(60) <= 1, "{} grains", grains);
+    assert_eq!(session.money(), grains as u128);
+    assert_eq!(session.sim.stats.money_earned.0, grains as u128);
+    assert_eq!(session.sim.stats.manual_grains, 0);
+    assert!(session.contents().is_empty());
+}
+#[test]
+fn test_session_maxed_upgrade_cant_be_bought() {
+    let sculptor = upgrade("Sculptor");
+    let max = sculptor.max_level().unwrap();
+    let mut session = Session::new(3).give_money(1_000_000);
+    for _ in 0..max {
+        session = session.buy("Sculptor");
+    }
+    assert!(session.sim.is_maxed(sculptor));
+    assert_eq!(session.sim.upgrades[&sculptor], max);
+    // plenty of money left, but no more levels
+    assert!(session.money() > 0);
+    let session = session.cant_buy("Sculptor");
+    assert_eq!(session.sim.upgrades[&sculptor], max);
+}